@@ -0,0 +1,146 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::crawlers::feed_crawler::{FeedSource, capture_project_id, child, feed_item, text_of};
+use crate::models::channel::PublisherChannel;
+use crate::models::types::{CrawlItem, MetadataItem};
+use crate::traits::cache_manager::CacheManager;
+use crate::traits::crawler::Crawler;
+use async_trait::async_trait;
+use bon::bon;
+use regex::Regex;
+use reqwest::Client;
+use roxmltree::{Document, Node};
+use tokio::sync::mpsc;
+use tracing::{error, info};
+
+/// Crawler для лент Atom 1.0: элементы `<entry>` с `<id>`, `<link href>`, `<title>` и `<summary>`/`<content>`
+pub struct AtomCrawler {
+    feed: FeedSource,
+}
+
+#[bon]
+impl AtomCrawler {
+    #[builder]
+    pub fn new(
+        url: String,
+        project_id_re: Option<Regex>,
+        source_label: Option<String>,
+        timeout: Duration,
+        /// Общий HTTP-клиент (секция http); без него создается собственный
+        client: Option<Client>,
+        cache_manager: Arc<dyn CacheManager>,
+        enabled_channels: Vec<PublisherChannel>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let feed = FeedSource::builder()
+            .kind("atom")
+            .url(url)
+            .maybe_project_id_re(project_id_re)
+            .maybe_source_label(source_label)
+            .timeout(timeout)
+            .maybe_client(client)
+            .cache_manager(cache_manager)
+            .enabled_channels(enabled_channels)
+            .build()?;
+        Ok(Self { feed })
+    }
+}
+
+#[async_trait]
+impl Crawler for AtomCrawler {
+    fn source_id(&self) -> String {
+        self.feed.source_id()
+    }
+
+    async fn fetch_stream(&self, sender: mpsc::Sender<CrawlItem>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let text = self.feed.fetch_text().await?;
+        let entries = parse_atom_entries(&text, self.feed.project_id_re.as_ref());
+        self.feed.send_unpublished(entries, sender).await
+    }
+}
+
+/// Разбирает ленту Atom. project_id извлекается первой группой `project_id_re` из ссылки записи,
+/// а если там нет совпадения — из `<id>`; без regex или без совпадения project_id не задан
+pub(crate) fn parse_atom_entries(text: &str, project_id_re: Option<&Regex>) -> Vec<CrawlItem> {
+    let doc = match Document::parse(text) {
+        Ok(doc) => doc,
+        Err(e) => {
+            error!(error = %e, "parse_atom_entries: XML parsing failed");
+            return Vec::new();
+        }
+    };
+    let mut out = Vec::new();
+    for entry in doc.descendants().filter(|n| n.has_tag_name("entry")) {
+        let id = text_of(entry, "id");
+        let link = entry_link(entry).or_else(|| id.clone().filter(|i| i.starts_with("http")));
+        let Some(url) = link else {
+            info!(id = ?id, "parse_atom_entries: skipping entry without link");
+            continue;
+        };
+        let project_id = capture_project_id(project_id_re, [Some(url.as_str()), id.as_deref()]);
+
+        let mut metadata = Vec::new();
+        if let Some(v) = text_of(entry, "published").or_else(|| text_of(entry, "updated")) {
+            metadata.push(MetadataItem::PublishDate(v));
+        }
+        if let Some(v) = child(entry, "author").and_then(|a| text_of(a, "name")) {
+            metadata.push(MetadataItem::Author(v));
+        }
+
+        out.push(feed_item(
+            text_of(entry, "title").unwrap_or_default(),
+            text_of(entry, "summary").or_else(|| text_of(entry, "content")).unwrap_or_default(),
+            url,
+            project_id,
+            metadata,
+        ));
+    }
+    out
+}
+
+/// Ссылка записи: `<link rel="alternate">` или `<link>` без rel
+fn entry_link(entry: Node) -> Option<String> {
+    entry
+        .children()
+        .filter(|n| n.has_tag_name("link"))
+        .find(|n| n.attribute("rel").is_none_or(|rel| rel == "alternate"))
+        .and_then(|n| n.attribute("href"))
+        .map(|href| href.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_entries_with_link_id_and_summary() {
+        let feed = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <entry>
+    <id>urn:npa:160532</id>
+    <link rel="edit" href="https://example.org/edit/1"/>
+    <link href="https://regulation.gov.ru/projects/160532"/>
+    <title>Проект</title>
+    <summary>Краткое описание</summary>
+    <updated>2025-09-20T17:07:27Z</updated>
+    <author><name>Минфин</name></author>
+  </entry>
+  <entry>
+    <id>https://example.org/news/7</id>
+    <title>Новость</title>
+    <content type="text">Текст новости</content>
+  </entry>
+</feed>"#;
+        let re = Regex::new(r"(\d{5,})").unwrap();
+        let items = parse_atom_entries(feed, Some(&re));
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].url, "https://regulation.gov.ru/projects/160532");
+        assert_eq!(items[0].project_id.as_deref(), Some("160532"));
+        assert_eq!(items[0].body, "Проект\nКраткое описание");
+        assert!(items[0].publish_date().is_some());
+        // Ссылки нет: URL берется из <id>, regex не совпал
+        assert_eq!(items[1].url, "https://example.org/news/7");
+        assert_eq!(items[1].project_id, None);
+        assert_eq!(items[1].body, "Новость\nТекст новости");
+    }
+}
@@ -0,0 +1,151 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::models::channel::PublisherChannel;
+use crate::models::types::{CrawlItem, MetadataItem, synthetic_project_id};
+use crate::traits::cache_manager::CacheManager;
+use bon::bon;
+use regex::Regex;
+use reqwest::Client;
+use roxmltree::Node;
+use tokio::sync::mpsc;
+use tracing::info;
+
+/// Общая часть краулеров лент (RSS, Atom): загрузка ленты и отправка в worker неопубликованных элементов
+pub(crate) struct FeedSource {
+    /// Вид ленты для логов и ошибок: rss, atom
+    kind: &'static str,
+    client: Client,
+    timeout: Duration,
+    url: String,
+    pub(crate) project_id_re: Option<Regex>,
+    source_label: Option<String>,
+    cache_manager: Arc<dyn CacheManager>,
+    enabled_channels: Vec<PublisherChannel>,
+}
+
+#[bon]
+impl FeedSource {
+    #[builder]
+    pub(crate) fn new(
+        kind: &'static str,
+        url: String,
+        project_id_re: Option<Regex>,
+        source_label: Option<String>,
+        timeout: Duration,
+        /// Общий HTTP-клиент (секция http); без него создается собственный
+        client: Option<Client>,
+        cache_manager: Arc<dyn CacheManager>,
+        enabled_channels: Vec<PublisherChannel>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let client = match client {
+            Some(client) => client,
+            None => Client::builder().timeout(timeout).build()?,
+        };
+        Ok(Self {
+            kind,
+            client,
+            timeout,
+            url,
+            project_id_re,
+            source_label,
+            cache_manager,
+            enabled_channels,
+        })
+    }
+
+    /// URL ленты; метка источника в ключ не входит, ее можно менять без потери состояния
+    pub(crate) fn source_id(&self) -> String {
+        self.url.clone()
+    }
+
+    /// Текст ленты; HTTP-статус не 2xx — ошибка
+    pub(crate) async fn fetch_text(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        info!(source = %self.source_id(), url = %self.url, "{}: fetch feed", self.kind);
+        let resp = self.client.get(&self.url).timeout(self.timeout).send().await?;
+        if !resp.status().is_success() {
+            return Err(Box::new(std::io::Error::other(format!(
+                "{}: http error on feed: {}",
+                self.kind,
+                resp.status()
+            ))));
+        }
+        Ok(resp.text().await?)
+    }
+
+    /// Помечает элементы источником и отправляет в `sender` те, что опубликованы не во всех каналах
+    pub(crate) async fn send_unpublished(
+        &self,
+        mut items: Vec<CrawlItem>,
+        sender: mpsc::Sender<CrawlItem>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        info!(source = %self.source_id(), count = items.len(), "{}: parsed feed items", self.kind);
+        for item in &mut items {
+            item.source_label = self.source_label.clone();
+            item.source_id = Some(self.source_id());
+        }
+
+        for it in items {
+            // Элементы без project_id проверяются по синтетическому id, который им назначит worker
+            let cache_key = it.project_id.clone().unwrap_or_else(|| synthetic_project_id(&it.url));
+            if self.cache_manager.is_fully_published(&cache_key, &self.enabled_channels).await? {
+                info!(project_id = %cache_key, "{}: item is fully published, skipping", self.kind);
+                continue;
+            }
+            info!(project_id = %cache_key, "{}: item not fully published, sending to worker", self.kind);
+            if sender.send(it).await.is_err() {
+                info!("{}: worker channel closed, stopping streaming", self.kind);
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn child<'a, 'i>(node: Node<'a, 'i>, name: &str) -> Option<Node<'a, 'i>> {
+    node.children().find(|n| n.has_tag_name(name))
+}
+
+/// Непустой текст дочернего элемента `name` без пробелов по краям
+pub(crate) fn text_of(node: Node, name: &str) -> Option<String> {
+    child(node, name)
+        .and_then(|n| n.text())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Первая группа `project_id_re` в первой из строк `candidates`, где regex совпал
+pub(crate) fn capture_project_id<'s>(
+    project_id_re: Option<&Regex>,
+    candidates: impl IntoIterator<Item = Option<&'s str>>,
+) -> Option<String> {
+    let re = project_id_re?;
+    candidates
+        .into_iter()
+        .flatten()
+        .find_map(|s| re.captures(s).and_then(|c| c.get(1)).map(|m| m.as_str().to_string()))
+}
+
+/// Элемент ленты: текст — заголовок и описание через перевод строки
+pub(crate) fn feed_item(
+    title: String,
+    description: String,
+    url: String,
+    project_id: Option<String>,
+    metadata: Vec<MetadataItem>,
+) -> CrawlItem {
+    let body = match (title.is_empty(), description.is_empty()) {
+        (_, true) => title.clone(),
+        (true, false) => description,
+        (false, false) => format!("{}\n{}", title, description),
+    };
+    CrawlItem {
+        title,
+        url,
+        body,
+        project_id,
+        metadata,
+        source_label: None,
+        source_id: None,
+    }
+}
@@ -1,4 +1,113 @@
+//! Краулеры источников. Их можно использовать отдельно от подсистем Luminis:
+//! `Crawler::fetch_stream` отправляет элементы в `mpsc::Sender`, а читатель забирает их из `Receiver`.
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//! use luminis::crawlers::{NpaListCrawler, crawl_to_vec};
+//! use luminis::services::cache_manager_impl::FileSystemCacheManager;
+//! use luminis::traits::crawler::Crawler;
+//! use tokio::sync::mpsc;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+//! let crawler = NpaListCrawler::builder()
+//!     .url_template("https://regulation.gov.ru/api/npalist/?limit={limit}&offset={offset}&sort=desc".to_string())
+//!     .timeout(Duration::from_secs(30))
+//!     .cache_manager(Arc::new(FileSystemCacheManager::builder().cache_dir("./cache".to_string()).build()))
+//!     .poll_delay(Duration::from_secs(0))
+//!     .enabled_channels(vec![])
+//!     .build()?;
+//!
+//! // Потоковое чтение: краулер и читатель работают одновременно
+//! let (tx, mut rx) = mpsc::channel(16);
+//! let (result, ()) = tokio::join!(crawler.fetch_stream(tx), async {
+//!     while let Some(item) = rx.recv().await {
+//!         println!("{} {}", item.title, item.url);
+//!     }
+//! });
+//! result?;
+//!
+//! // Или все элементы одного обхода сразу
+//! let items = crawl_to_vec(&crawler).await?;
+//! # let _ = items;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Краулер учитывает состояние кэша (опубликованные проекты и manifest.json) переданного `cache_manager`.
+
 pub mod npalist_crawler;
+pub mod atom_crawler;
+pub mod rss_crawler;
+mod feed_crawler;
 
-pub use npalist_crawler::{NpaListCrawler, FileIdScanner};
+pub use npalist_crawler::{NpaListCrawler, FileIdScanner, FileInfo};
+pub use atom_crawler::AtomCrawler;
+pub use rss_crawler::RssCrawler;
 pub use crate::models::types::{CrawlItem, MetadataItem, Manifest};
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::models::types::synthetic_project_id;
+use crate::traits::crawler::Crawler;
+
+/// Выполняет один обход краулера и собирает все отправленные элементы в вектор
+pub async fn crawl_to_vec(crawler: &dyn Crawler) -> Result<Vec<CrawlItem>, Box<dyn std::error::Error + Send + Sync>> {
+    let (tx, mut rx) = mpsc::channel(16);
+    let collect = async move {
+        let mut items = Vec::new();
+        while let Some(item) = rx.recv().await {
+            items.push(item);
+        }
+        items
+    };
+    let (result, items) = tokio::join!(crawler.fetch_stream(tx), collect);
+    result?;
+    Ok(items)
+}
+
+/// Обходит несколько краулеров одновременно и отправляет их элементы в один `sender`.
+/// Элемент с уже отправленным project_id (без него — с тем же URL) из другого источника пропускается.
+/// Ошибка одного источника не прерывает остальные и возвращается после завершения всех
+pub async fn fetch_merged(
+    crawlers: &[&dyn Crawler],
+    sender: mpsc::Sender<CrawlItem>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    fetch_merged_with_seen(crawlers, sender, &Mutex::new(HashSet::new())).await
+}
+
+/// Как `fetch_merged`, но ключи отправленных элементов хранятся в `seen` вызывающего:
+/// при повторе обхода после ошибки уже отправленные элементы не отправляются снова
+pub async fn fetch_merged_with_seen(
+    crawlers: &[&dyn Crawler],
+    sender: mpsc::Sender<CrawlItem>,
+    seen: &Mutex<HashSet<String>>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let (tx, mut rx) = mpsc::channel(16);
+    let crawl = futures_util::future::join_all(crawlers.iter().map(|c| c.fetch_stream(tx.clone())));
+    drop(tx);
+    let forward = async move {
+        while let Some(item) = rx.recv().await {
+            let key = item.project_id.clone().unwrap_or_else(|| synthetic_project_id(&item.url));
+            if !seen.lock().unwrap().insert(key.clone()) {
+                info!(project_id = %key, "crawler: item already sent by another source, skipping");
+                continue;
+            }
+            if sender.send(item).await.is_err() {
+                break;
+            }
+        }
+    };
+    let (results, ()) = tokio::join!(crawl, forward);
+    for (crawler, result) in crawlers.iter().zip(&results) {
+        if let Err(e) = result {
+            warn!(source = %crawler.source_id(), error = %e, "crawler: source failed");
+        }
+    }
+    results.into_iter().collect::<Result<Vec<()>, _>>()?;
+    Ok(())
+}
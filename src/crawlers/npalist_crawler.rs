@@ -6,22 +6,43 @@ use crate::traits::crawler::Crawler;
 use crate::models::channel::PublisherChannel;
 use crate::models::types::{CrawlItem, MetadataItem};
 use async_trait::async_trait;
+use backon::{ExponentialBuilder, Retryable};
 use bon::{Builder, bon};
 use regex::Regex;
 use reqwest::Client;
 use roxmltree::Document;
 use tracing::{info, error};
-use tokio::sync::mpsc;
+use tokio::sync::{Semaphore, mpsc};
+
+/// Шаблон URL страницы проекта по умолчанию (плейсхолдер {project_id})
+pub const DEFAULT_PROJECT_URL_TEMPLATE: &str = "https://regulation.gov.ru/projects/{project_id}";
+
+/// URL страницы проекта по шаблону с плейсхолдером {id} или {project_id}
+pub fn project_url(template: &str, project_id: &str) -> String {
+    template.replace("{project_id}", project_id).replace("{id}", project_id)
+}
+
+/// Порядок списка по умолчанию для плейсхолдера {sort}
+const DEFAULT_SORT: &str = "desc";
 
 /// Crawler для API списка НПА с пагинацией, состояние в manifest.json
 pub struct NpaListCrawler {
     client: Client,
+    timeout: Duration,
     url_template: String,
     limit: u32,
+    sort_param: Option<String>,
+    offset_override: Option<u32>,
     project_id_re: Option<Regex>,
+    project_url_template: String,
+    max_items_per_page: Option<usize>,
+    source_label: Option<String>,
     cache_manager: Arc<dyn CacheManager>,
     poll_delay: Duration,
     enabled_channels: Vec<PublisherChannel>,
+    detect_stage_updates: bool,
+    history_pages_per_run: Option<u32>,
+    dry_run: bool,
 }
 
 #[bon]
@@ -30,42 +51,174 @@ impl NpaListCrawler {
     pub fn new(
         url_template: String,
         limit_opt: Option<u32>,
+        sort_param: Option<String>,
+        offset_override: Option<u32>,
         project_id_re: Option<Regex>,
+        project_url_template: Option<String>,
+        max_items_per_page: Option<usize>,
+        source_label: Option<String>,
         timeout: Duration,
+        /// Общий HTTP-клиент (секция http); без него создается собственный
+        client: Option<Client>,
         cache_manager: Arc<dyn CacheManager>,
         poll_delay: Duration,
         enabled_channels: Vec<PublisherChannel>,
+        #[builder(default)]
+        detect_stage_updates: bool,
+        /// Сколько страниц истории читать за запуск (crawler.history_pages_per_run)
+        history_pages_per_run: Option<u32>,
+        /// --dry-run: manifest не обновляется
+        #[builder(default)]
+        dry_run: bool,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        let client = Client::builder().timeout(timeout).build()?;
+        let client = match client {
+            Some(client) => client,
+            None => Client::builder().timeout(timeout).build()?,
+        };
         Ok(Self {
             client,
+            timeout,
             url_template,
             limit: limit_opt.unwrap_or(50),
+            sort_param,
+            offset_override,
             project_id_re,
+            project_url_template: project_url_template.unwrap_or_else(|| DEFAULT_PROJECT_URL_TEMPLATE.to_string()),
+            max_items_per_page,
+            source_label,
             cache_manager,
             poll_delay,
             enabled_channels,
+            detect_stage_updates,
+            history_pages_per_run,
+            dry_run,
         })
     }
 }
 
+impl NpaListCrawler {
+    /// URL страницы списка: подставляет {limit}/{offset}/{sort} и применяет сортировку
+    /// (crawler.npalist.sort) к параметру sort
+    fn page_url(&self, limit: u32, offset: u32) -> String {
+        let url = self
+            .url_template
+            .replace("{limit}", &limit.to_string())
+            .replace("{offset}", &offset.to_string())
+            .replace("{sort}", self.sort_param.as_deref().unwrap_or(DEFAULT_SORT));
+        apply_sort_param(&url, self.sort_param.as_deref())
+    }
+
+    /// Разбирает страницу списка и помечает элементы меткой и идентификатором источника
+    fn parse_page(&self, text: &str) -> Vec<CrawlItem> {
+        let mut items = parse_npa_projects(text, self.project_id_re.as_ref(), &self.project_url_template);
+        for item in &mut items {
+            item.source_label = self.source_label.clone();
+            item.source_id = Some(self.source_id());
+        }
+        items
+    }
+
+    /// Проверяет, нужно ли отправлять элемент в worker: он не опубликован полностью
+    /// или (при включенном обнаружении) у опубликованного проекта сменилась стадия
+    async fn needs_processing(
+        &self,
+        pid: &str,
+        item: &CrawlItem,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        if !self.cache_manager.is_fully_published(pid, &self.enabled_channels).await? {
+            return Ok(true);
+        }
+        if !self.detect_stage_updates {
+            return Ok(false);
+        }
+        let cached_stage = self
+            .cache_manager
+            .load_metadata(pid)
+            .await?
+            .and_then(|m| m.stage().map(|s| s.to_string()));
+        let changed = matches!((cached_stage.as_deref(), item.stage()), (Some(old), Some(new)) if old != new);
+        if changed {
+            info!(project_id = %pid, old_stage = ?cached_stage, new_stage = ?item.stage(), "npalist: stage changed for published project");
+        }
+        Ok(changed)
+    }
+
+    /// Минимальный project_id непрерывного префикса опубликованных элементов истории.
+    /// Отправленные в worker элементы перепроверяются по кешу: учитываются только уже опубликованные
+    async fn confirmed_history_min_id(
+        &self,
+        scanned: &[(u32, bool)],
+    ) -> Result<Option<u32>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut confirmed_min: Option<u32> = None;
+        for &(pid_num, sent) in scanned {
+            if sent && !self.cache_manager.is_fully_published(&pid_num.to_string(), &self.enabled_channels).await? {
+                info!(project_id = pid_num, "npalist: history project is not confirmed as published yet");
+                break;
+            }
+            confirmed_min = Some(confirmed_min.map_or(pid_num, |min| min.min(pid_num)));
+        }
+        Ok(confirmed_min)
+    }
+
+    /// Разовый обход одной страницы с заданным offset (manifest не читается и не обновляется)
+    async fn fetch_fixed_offset(
+        &self,
+        offset: u32,
+        sender: mpsc::Sender<CrawlItem>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let url = self.page_url(self.limit, offset);
+        info!(%url, offset, "npalist: fetch page with overridden offset");
+
+        let resp = self.client.get(&url).timeout(self.timeout).send().await?;
+        if !resp.status().is_success() {
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!("npalist: http error on offset {}: {}", offset, resp.status()),
+            )));
+        }
+
+        let projects = self.parse_page(&resp.text().await?);
+        for it in projects.into_iter() {
+            if let Some(pid) = it.project_id.as_deref() {
+                if !self.needs_processing(pid, &it).await? {
+                    info!(project_id = %pid, "npalist: project is fully published, skipping");
+                    continue;
+                }
+                info!(project_id = %pid, "npalist: project not fully published, sending to worker");
+                if sender.send(it).await.is_err() {
+                    info!("npalist: worker channel closed, stopping streaming");
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl Crawler for NpaListCrawler {
+    /// Шаблон URL списка; метка источника в ключ не входит, ее можно менять без потери состояния
+    fn source_id(&self) -> String {
+        self.url_template.clone()
+    }
+
     async fn fetch_stream(&self, sender: mpsc::Sender<CrawlItem>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(offset) = self.offset_override {
+            return self.fetch_fixed_offset(offset, sender).await;
+        }
+
+        let source_id = self.source_id();
         let manifest = self.cache_manager.load_manifest().await?;
         let limit = self.limit;
-        let min_published_project_id = manifest.min_published_project_id;
+        let min_published_project_id = manifest.min_published_for(&source_id);
         
-        info!(min_published_project_id = min_published_project_id, "npalist: loaded manifest state for streaming");
+        info!(source = %source_id, min_published_project_id = min_published_project_id, "npalist: loaded manifest state for streaming");
 
         // 1. Всегда читаем offset=0 (новые записи)
-        let url_latest = self
-            .url_template
-            .replace("{limit}", &limit.to_string())
-            .replace("{offset}", &0.to_string());
+        let url_latest = self.page_url(limit, 0);
         info!(%url_latest, "npalist: fetch latest page (offset=0) for streaming");
         
-        let latest_projects = self.client.get(&url_latest).send().await?;
+        let latest_projects = self.client.get(&url_latest).timeout(self.timeout).send().await?;
         if !latest_projects.status().is_success() {
             return Err(Box::new(std::io::Error::new(
                 std::io::ErrorKind::Other,
@@ -77,7 +230,7 @@ impl Crawler for NpaListCrawler {
         }
         
         let latest_text = latest_projects.text().await?;
-        let latest = parse_npa_projects(&latest_text, self.project_id_re.as_ref());
+        let latest = self.parse_page(&latest_text);
         let total_items = latest.len();
         
         info!(total_items = total_items, "npalist: parsing latest projects for streaming");
@@ -91,7 +244,7 @@ impl Crawler for NpaListCrawler {
             if let Some(pid) = it.project_id.as_deref() {
                 if let Ok(pid_num) = pid.parse::<u32>() {
                     // Проверяем, полностью ли опубликован элемент
-                    let fully_published = self.cache_manager.is_fully_published(pid, &self.enabled_channels).await?;
+                    let fully_published = !self.needs_processing(pid, &it).await?;
                     // Обновляем min/max ID
                     current_max_id = Some(current_max_id.map_or(pid_num, |max| max.max(pid_num)));
                     current_min_id = Some(current_min_id.map_or(pid_num, |min| min.min(pid_num)));
@@ -120,8 +273,10 @@ impl Crawler for NpaListCrawler {
         );
 
         // Обновляем min_published_project_id в manifest после обработки элементов
-        if let Some(current_min_id) = current_min_id {
-            self.cache_manager.update_min_published_project_id(current_min_id).await?;
+        if self.dry_run {
+            info!(source = %source_id, ?current_min_id, "dry-run: npalist manifest not updated");
+        } else if let Some(current_min_id) = current_min_id {
+            self.cache_manager.update_min_published_project_id(&source_id, current_min_id).await?;
         } else {
             info!("npalist: current_min_id is None, skipping manifest update");
         }
@@ -169,18 +324,28 @@ impl Crawler for NpaListCrawler {
             limit
         };
 
+        // Незавершенный обход истории прошлого запуска (crawler.history_pages_per_run) продолжается с сохраненного offset
+        let history_offset = match manifest.history_offsets.get(&source_id) {
+            Some(&saved) => {
+                info!(source = %source_id, saved_offset = saved, calculated_offset = history_offset, "npalist: resuming history from offset saved by previous run");
+                saved
+            }
+            None => history_offset,
+        };
+
         // 3. Углубляемся в историю
         let mut current_offset = history_offset;
-        let mut processed_history_items: Vec<CrawlItem> = Vec::new();
+        let mut pages_scanned = 0u32;
+        // Offset, с которого продолжит следующий запуск, если обход остановлен лимитом страниц
+        let mut resume_offset: Option<u32> = None;
+        // Просмотренные элементы истории в порядке выдачи: (project_id, отправлен ли в worker)
+        let mut scanned_history: Vec<(u32, bool)> = Vec::new();
         
         loop {
-            let url_cont = self
-                .url_template
-                .replace("{limit}", &limit.to_string())
-                .replace("{offset}", &current_offset.to_string());
+            let url_cont = self.page_url(limit, current_offset);
             info!(%url_cont, current_offset, "npalist: deep dive into history for streaming");
 
-            let history_page = self.client.get(&url_cont).send().await?;
+            let history_page = self.client.get(&url_cont).timeout(self.timeout).send().await?;
             info!(status = %history_page.status(), "npalist: history page response status");
             
             if !history_page.status().is_success() {
@@ -192,7 +357,7 @@ impl Crawler for NpaListCrawler {
             
             let history_page_text = history_page.text().await?;
             info!(text_len = history_page_text.len(), "npalist: history page response text length");
-            let history_projects = parse_npa_projects(&history_page_text, self.project_id_re.as_ref());
+            let history_projects = self.parse_page(&history_page_text);
 
             // Если страница пустая, значит дошли до конца истории
             if history_projects.is_empty() {
@@ -201,20 +366,33 @@ impl Crawler for NpaListCrawler {
             }
 
             info!(count = history_projects.len(), "npalist: parsing history projects for streaming");
+            pages_scanned += 1;
             
             // Отправляем элементы по одному, если они не полностью опубликованы
             let mut found_new_items = false;
+            let mut sent_on_page = 0usize;
             for it in history_projects.into_iter() {
                 if let Some(pid) = it.project_id.as_deref() {
                     if let Ok(pid_num) = pid.parse::<u32>() {
                         // Проверяем, полностью ли опубликован элемент
-                        let fully_published = self.cache_manager.is_fully_published(pid, &self.enabled_channels).await?;
+                        let fully_published = !self.needs_processing(pid, &it).await?;
                         if fully_published {
                             info!(project_id = pid_num, "npalist: history project is fully published, skipping");
+                            scanned_history.push((pid_num, false));
                         } else {
+                            // Не отправляем со страницы больше, чем worker успеет опубликовать за запуск
+                            if self.max_items_per_page.is_some_and(|cap| sent_on_page >= cap) {
+                                info!(
+                                    project_id = pid_num,
+                                    max_items_per_page = ?self.max_items_per_page,
+                                    "npalist: per-page cap reached, leaving the rest of the page for the next run"
+                                );
+                                break;
+                            }
                             info!(project_id = pid_num, "npalist: history project not fully published, sending to worker");
                             found_new_items = true;
-                            processed_history_items.push(it.clone());
+                            sent_on_page += 1;
+                            scanned_history.push((pid_num, true));
                             // Отправляем элемент в канал (может зависнуть если канал полон)
                             if let Err(_) = sender.send(it).await {
                                 info!("npalist: worker channel closed, stopping streaming");
@@ -228,6 +406,16 @@ impl Crawler for NpaListCrawler {
             // Если новых элементов нет, продолжаем углубление
             if !found_new_items {
                 current_offset += limit;
+                if self.history_pages_per_run.is_some_and(|max| pages_scanned >= max) {
+                    info!(
+                        source = %source_id,
+                        pages_scanned,
+                        next_offset = current_offset,
+                        "npalist: history_pages_per_run reached, next run continues from saved offset"
+                    );
+                    resume_offset = Some(current_offset);
+                    break;
+                }
                 if self.poll_delay.as_millis() > 0 {
                     info!(
                         delay_ms = self.poll_delay.as_millis(),
@@ -242,28 +430,68 @@ impl Crawler for NpaListCrawler {
             }
         }
         
-        // Обновляем min_published_project_id в manifest после обработки истории
-        let history_min_id = processed_history_items.iter()
-            .filter_map(|item| item.project_id.as_deref())
-            .filter_map(|pid| pid.parse::<u32>().ok())
-            .min();
+        // Обновляем min_published_project_id в manifest после обработки истории:
+        // учитываем только непрерывный префикс подтвержденно опубликованных элементов,
+        // чтобы неопубликованные (не отправленные или еще не обработанные worker) не были пропущены
+        let history_min_id = self.confirmed_history_min_id(&scanned_history).await?;
             
-        if let Some(new_min_id) = [current_min_id, history_min_id]
-            .iter()
-            .filter_map(|&id| id)
-            .min() {
-            let mut updated_manifest = self.cache_manager.load_manifest().await?;
-            updated_manifest.min_published_project_id = Some(new_min_id);
-            info!(new_min_id = new_min_id, "npalist: updated min_published_project_id after history processing");
-            self.cache_manager.save_manifest(&updated_manifest).await?;
+        let new_min_id = [current_min_id, history_min_id].iter().filter_map(|&id| id).min();
+        // Сохраненный offset нужен, только пока обход истории прерывается лимитом страниц
+        let previous_offset = self.cache_manager.load_manifest().await?.history_offsets.get(&source_id).copied();
+        if self.dry_run {
+            info!(source = %source_id, ?new_min_id, ?resume_offset, "dry-run: npalist manifest not updated");
+        } else if new_min_id.is_some() || previous_offset != resume_offset {
+            let offset_source = source_id.clone();
+            self.cache_manager.update_manifest(Box::new(move |manifest| {
+                if let Some(min_id) = new_min_id {
+                    manifest.min_published_project_ids.insert(offset_source.clone(), min_id);
+                }
+                match resume_offset {
+                    Some(offset) => manifest.history_offsets.insert(offset_source, offset),
+                    None => manifest.history_offsets.remove(&offset_source),
+                };
+            })).await?;
+            if let Some(new_min_id) = new_min_id {
+                info!(new_min_id = new_min_id, "npalist: updated min_published_project_id after history processing");
+            }
         }
         
         Ok(())
     }
 }
 
+/// Применяет настройку сортировки к URL списка.
+/// `None` — URL не меняется, пустая строка — параметр `sort` удаляется
+/// (для источников, которые его не поддерживают), иначе `sort` заменяется заданным значением.
+fn apply_sort_param(url: &str, sort_param: Option<&str>) -> String {
+    let Some(sort) = sort_param else {
+        return url.to_string();
+    };
+    let Ok(mut parsed) = url::Url::parse(url) else {
+        return url.to_string();
+    };
+    let pairs: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(k, _)| k != "sort")
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    let sort = sort.trim();
+    if pairs.is_empty() && sort.is_empty() {
+        parsed.set_query(None);
+    } else {
+        let mut query = parsed.query_pairs_mut();
+        query.clear();
+        for (k, v) in &pairs {
+            query.append_pair(k, v);
+        }
+        if !sort.is_empty() {
+            query.append_pair("sort", sort);
+        }
+    }
+    parsed.to_string()
+}
 
-fn parse_npa_projects(text: &str, project_id_re: Option<&Regex>) -> Vec<CrawlItem> {
+fn parse_npa_projects(text: &str, project_id_re: Option<&Regex>, project_url_template: &str) -> Vec<CrawlItem> {
     let mut out = Vec::new();
     info!(text_len = text.len(), "parse_npa_projects: input text length");
     let preview: String = text.chars().take(200).collect();
@@ -310,13 +538,12 @@ fn parse_npa_projects(text: &str, project_id_re: Option<&Regex>) -> Vec<CrawlIte
                 continue;
             },
         };
-        let mut url = format!("https://regulation.gov.ru/projects/{}", project_attr_id);
+        let mut url = project_url(project_url_template, &project_attr_id);
         if let Some(re) = project_id_re {
             // Проверяем соответствие по regex: пытаемся извлечь id из полного URL
-            let full_url = format!("https://regulation.gov.ru/projects/{}", project_attr_id);
-            if let Some(cap) = re.captures(&full_url).and_then(|c| c.get(1)) {
+            if let Some(cap) = re.captures(&url).and_then(|c| c.get(1)) {
                 project_attr_id = cap.as_str().to_string();
-                url = format!("https://regulation.gov.ru/projects/{}", project_attr_id);
+                url = project_url(project_url_template, &project_attr_id);
             } else {
                 // Если regex не подтверждает id, пропускаем запись
                 continue;
@@ -480,6 +707,8 @@ fn parse_npa_projects(text: &str, project_id_re: Option<&Regex>) -> Vec<CrawlIte
             body,
             project_id: Some(project_attr_id.clone()),
             metadata,
+            source_label: None,
+            source_id: None,
         });
     }
     out
@@ -490,16 +719,74 @@ fn parse_npa_projects(text: &str, project_id_re: Option<&Regex>) -> Vec<CrawlIte
 pub struct FileIdScanner {
     #[builder(default)]
     client: Client,
+    /// Таймаут запроса stages поверх таймаута клиента
+    timeout: Option<Duration>,
+    /// Повторы запроса stages при сетевых ошибках и 5xx (0 = без повторов)
+    #[builder(default)]
+    max_retry_attempts: u64,
+    /// Общий лимит одновременных запросов stages (crawler.scan_concurrency)
+    permits: Option<Arc<Semaphore>>,
+}
+
+/// Файл проекта из ответа stages endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileInfo {
+    pub file_id: String,
+    /// Ожидаемый sha256 документа, если источник его публикует
+    pub checksum: Option<String>,
 }
 
 impl FileIdScanner {
+    /// GET с таймаутом запроса stages, если он задан
+    fn get(&self, url: &str) -> reqwest::RequestBuilder {
+        let request = self.client.get(url);
+        match self.timeout {
+            Some(timeout) => request.timeout(timeout),
+            None => request,
+        }
+    }
+
     pub async fn fetch_file_id(
         &self,
         url: &str,
     ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.fetch_file_info(url).await?.map(|info| info.file_id))
+    }
+
+    /// Возвращает fileId и (если есть) контрольную сумму документа.
+    /// Сетевые ошибки и 5xx повторяются с экспоненциальной задержкой
+    pub async fn fetch_file_info(
+        &self,
+        url: &str,
+    ) -> Result<Option<FileInfo>, Box<dyn std::error::Error + Send + Sync>> {
+        let builder = ExponentialBuilder::default()
+            .with_max_times(self.max_retry_attempts as usize)
+            .with_min_delay(Duration::from_millis(500));
+
+        (|| self.fetch_file_info_once(url))
+            .retry(builder)
+            .sleep(tokio::time::sleep)
+            .notify(|err: &Box<dyn std::error::Error + Send + Sync>, dur: Duration| {
+                info!(%url, "fileid: retrying after {:?} due to error: {}", dur, err);
+            })
+            .await
+    }
+
+    async fn fetch_file_info_once(
+        &self,
+        url: &str,
+    ) -> Result<Option<FileInfo>, Box<dyn std::error::Error + Send + Sync>> {
+        // Разрешение держится только на время запроса: ожидание перед повтором его не занимает
+        let _permit = match &self.permits {
+            Some(permits) => Some(permits.clone().acquire_owned().await?),
+            None => None,
+        };
         info!(%url, "fileid: fetch");
-        let response = self.client.get(url).send().await?;
+        let response = self.get(url).send().await?;
         info!(status = %response.status(), "fileid: response status");
+        if response.status().is_server_error() {
+            return Err(format!("fileid: http error on stages request: {}", response.status()).into());
+        }
         let body = response.text().await?;
         info!(body_len = body.len(), "fileid: response body length");
         let re = Regex::new(r#"fileId"\s*:\s*"([^"]+)"#).unwrap();
@@ -507,10 +794,88 @@ impl FileIdScanner {
             if let Some(m) = caps.get(1) {
                 let file_id = m.as_str().to_string();
                 info!(%file_id, "fileid: found fileId");
-                return Ok(Some(file_id));
+                let checksum = serde_json::from_str::<serde_json::Value>(&body)
+                    .ok()
+                    .and_then(|v| find_file_checksum(&v, &file_id));
+                return Ok(Some(FileInfo { file_id, checksum }));
             }
         }
         info!("fileid: no fileId found in response");
         Ok(None)
     }
+
+    /// Метаданные проекта, доступные в ответе stages endpoint: название текущей стадии.
+    /// Используется, когда элемент строится без списка НПА (backfill)
+    pub async fn fetch_stage_metadata(
+        &self,
+        url: &str,
+    ) -> Result<Vec<MetadataItem>, Box<dyn std::error::Error + Send + Sync>> {
+        info!(%url, "stages: fetch metadata");
+        let response = self.get(url).send().await?;
+        if !response.status().is_success() {
+            return Err(format!("stages: http error on stages request: {}", response.status()).into());
+        }
+        let stages: serde_json::Value = serde_json::from_str(&response.text().await?)?;
+        Ok(parse_stage_metadata(&stages))
+    }
+}
+
+/// Текущая стадия (`isCurrent: true`) из ответа stages endpoint
+fn parse_stage_metadata(stages: &serde_json::Value) -> Vec<MetadataItem> {
+    stages
+        .as_array()
+        .and_then(|list| list.iter().find(|s| s.get("isCurrent").and_then(|v| v.as_bool()) == Some(true)))
+        .and_then(|s| s.get("title").and_then(|v| v.as_str()))
+        .map(|title| vec![MetadataItem::Stage(title.to_string())])
+        .unwrap_or_default()
+}
+
+/// Ищет в JSON объект файла с заданным fileId и достает из него контрольную сумму (sha256/checksum/hash)
+fn find_file_checksum(value: &serde_json::Value, file_id: &str) -> Option<String> {
+    match value {
+        serde_json::Value::Object(map) => {
+            if map.get("fileId").and_then(|v| v.as_str()) == Some(file_id) {
+                return ["sha256", "checksum", "hash"]
+                    .iter()
+                    .find_map(|k| map.get(*k).and_then(|v| v.as_str()))
+                    .map(|s| s.to_lowercase());
+            }
+            map.values().find_map(|v| find_file_checksum(v, file_id))
+        }
+        serde_json::Value::Array(items) => items.iter().find_map(|v| find_file_checksum(v, file_id)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_checksum_of_matching_file() {
+        let v: serde_json::Value = serde_json::from_str(
+            r#"[{"file":null},{"file":{"fileId":"other","sha256":"AA"}},{"file":{"fileId":"abc","checksum":"BEEF"}}]"#,
+        )
+        .unwrap();
+        assert_eq!(find_file_checksum(&v, "abc"), Some("beef".to_string()));
+        assert_eq!(find_file_checksum(&v, "missing"), None);
+    }
+
+    #[test]
+    fn sort_param_is_kept_replaced_or_removed() {
+        let url = "http://localhost/api/npalist/?limit=50&offset=0&sort=desc";
+        assert_eq!(apply_sort_param(url, None), url);
+        assert_eq!(
+            apply_sort_param(url, Some("")),
+            "http://localhost/api/npalist/?limit=50&offset=0"
+        );
+        assert_eq!(
+            apply_sort_param(url, Some("asc")),
+            "http://localhost/api/npalist/?limit=50&offset=0&sort=asc"
+        );
+        assert_eq!(
+            apply_sort_param("http://localhost/api/npalist/?limit=50", Some("desc")),
+            "http://localhost/api/npalist/?limit=50&sort=desc"
+        );
+    }
 }
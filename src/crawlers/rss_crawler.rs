@@ -0,0 +1,169 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::crawlers::feed_crawler::{FeedSource, capture_project_id, feed_item, text_of};
+use crate::crawlers::npalist_crawler::project_url;
+use crate::models::channel::PublisherChannel;
+use crate::models::types::{CrawlItem, MetadataItem};
+use crate::traits::cache_manager::CacheManager;
+use crate::traits::crawler::Crawler;
+use async_trait::async_trait;
+use bon::bon;
+use regex::Regex;
+use reqwest::Client;
+use roxmltree::Document;
+use tokio::sync::mpsc;
+use tracing::{error, info};
+
+/// Crawler для лент RSS 2.0: элементы `<item>` с `<guid>`, `<link>`, `<title>` и `<description>`
+pub struct RssCrawler {
+    feed: FeedSource,
+    project_url_template: Option<String>,
+}
+
+#[bon]
+impl RssCrawler {
+    #[builder]
+    pub fn new(
+        url: String,
+        project_id_re: Option<Regex>,
+        /// URL элемента без `<link>` по project_id из `<guid>` (crawler.project_url_template)
+        project_url_template: Option<String>,
+        source_label: Option<String>,
+        timeout: Duration,
+        /// Общий HTTP-клиент (секция http); без него создается собственный
+        client: Option<Client>,
+        cache_manager: Arc<dyn CacheManager>,
+        enabled_channels: Vec<PublisherChannel>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let feed = FeedSource::builder()
+            .kind("rss")
+            .url(url)
+            .maybe_project_id_re(project_id_re)
+            .maybe_source_label(source_label)
+            .timeout(timeout)
+            .maybe_client(client)
+            .cache_manager(cache_manager)
+            .enabled_channels(enabled_channels)
+            .build()?;
+        Ok(Self { feed, project_url_template })
+    }
+}
+
+#[async_trait]
+impl Crawler for RssCrawler {
+    fn source_id(&self) -> String {
+        self.feed.source_id()
+    }
+
+    async fn fetch_stream(&self, sender: mpsc::Sender<CrawlItem>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let text = self.feed.fetch_text().await?;
+        let items = parse_rss_items(&text, self.feed.project_id_re.as_ref(), self.project_url_template.as_deref());
+        self.feed.send_unpublished(items, sender).await
+    }
+}
+
+/// Разбирает ленту RSS. project_id извлекается первой группой `project_id_re` из `<guid>`,
+/// а если там нет совпадения — из `<link>`; без regex или без совпадения project_id не задан.
+/// Элемент без ссылки получает URL по `project_url_template` и project_id из `<guid>`
+pub(crate) fn parse_rss_items(text: &str, project_id_re: Option<&Regex>, project_url_template: Option<&str>) -> Vec<CrawlItem> {
+    let doc = match Document::parse(text) {
+        Ok(doc) => doc,
+        Err(e) => {
+            error!(error = %e, "parse_rss_items: XML parsing failed");
+            return Vec::new();
+        }
+    };
+    let mut out = Vec::new();
+    for item in doc.descendants().filter(|n| n.has_tag_name("item")) {
+        let guid = text_of(item, "guid");
+        let link = text_of(item, "link")
+            .or_else(|| guid.clone().filter(|g| g.starts_with("http")))
+            .or_else(|| {
+                let id = capture_project_id(project_id_re, [guid.as_deref()])?;
+                project_url_template.map(|tpl| project_url(tpl, &id))
+            });
+        let Some(url) = link else {
+            info!(guid = ?guid, "parse_rss_items: skipping item without link");
+            continue;
+        };
+        let project_id = capture_project_id(project_id_re, [guid.as_deref(), Some(url.as_str())]);
+
+        let mut metadata = Vec::new();
+        if let Some(v) = text_of(item, "pubDate") {
+            metadata.push(MetadataItem::PublishDate(v));
+        }
+        if let Some(v) = text_of(item, "author") {
+            metadata.push(MetadataItem::Author(v));
+        }
+
+        out.push(feed_item(
+            text_of(item, "title").unwrap_or_default(),
+            text_of(item, "description").unwrap_or_default(),
+            url,
+            project_id,
+            metadata,
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_items_with_guid_and_link() {
+        let feed = r#"<?xml version="1.0" encoding="utf-8"?>
+<rss version="2.0">
+  <channel>
+    <item>
+      <guid isPermaLink="false">160532</guid>
+      <link>https://regulation.gov.ru/projects/160532</link>
+      <author>dev@example.org</author>
+      <title>Проект</title>
+      <description>Вид: "Проект федерального закона"</description>
+      <pubDate>Wed, 24 Sep 2025 09:00:00 +0300</pubDate>
+    </item>
+    <item>
+      <guid>https://example.org/news/7</guid>
+      <title>Новость</title>
+    </item>
+  </channel>
+</rss>"#;
+        let re = Regex::new(r"(\d{5,})").unwrap();
+        let items = parse_rss_items(feed, Some(&re), None);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].project_id.as_deref(), Some("160532"));
+        assert_eq!(items[0].body, "Проект\nВид: \"Проект федерального закона\"");
+        assert!(items[0].publish_date().is_some());
+        assert_eq!(items[1].url, "https://example.org/news/7");
+        assert_eq!(items[1].project_id, None);
+        assert_eq!(items[1].body, "Новость");
+    }
+
+    #[test]
+    fn builds_url_from_template_only_without_link() {
+        let feed = r#"<?xml version="1.0" encoding="utf-8"?>
+<rss version="2.0">
+  <channel>
+    <item>
+      <guid isPermaLink="false">160532</guid>
+      <link>https://regulation.gov.ru/projects/160532</link>
+      <title>Со ссылкой</title>
+    </item>
+    <item>
+      <guid isPermaLink="false">160533</guid>
+      <title>Без ссылки</title>
+    </item>
+  </channel>
+</rss>"#;
+        let re = Regex::new(r"(\d{5,})").unwrap();
+        let items = parse_rss_items(feed, Some(&re), Some("https://staging.example.org/p/{id}"));
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].url, "https://regulation.gov.ru/projects/160532");
+        assert_eq!(items[1].url, "https://staging.example.org/p/160533");
+        assert_eq!(items[1].project_id.as_deref(), Some("160533"));
+        assert_eq!(parse_rss_items(feed, Some(&re), None).len(), 1);
+    }
+}
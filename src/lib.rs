@@ -12,26 +12,61 @@ use tokio_graceful_shutdown::{SubsystemBuilder, Toplevel};
 
 use crate::traits::chat_api::ChatApi;
 use crate::services::chat_api_local::LocalChatApi;
-use crate::models::config::AppConfig;
+use crate::models::config::{AppConfig, CacheBackend, LogFormat, OnUnwritableCache, RunOptions};
+use crate::models::types::{CacheSelection, CrawlItem, RunOutcome};
 use crate::services::settings::load_config;
 use crate::services::summarizer::Summarizer;
+use crate::services::worker::Worker;
+use crate::crawlers::FileIdScanner;
+use crate::crawlers::npalist_crawler::{DEFAULT_PROJECT_URL_TEMPLATE, project_url};
 use crate::traits::telegram_api::TelegramApi;
 use crate::publishers::RealTelegramApi;
+use crate::publishers::utils::HttpRetryPolicy;
 use reqwest::Client;
 use crate::traits::cache_manager::CacheManager;
-use crate::services::cache_manager_impl::FileSystemCacheManager;
+use crate::services::cache_manager_impl::{CacheMigrationReport, FileSystemCacheManager};
+use crate::services::cache_manager_sqlite::{SqliteCacheManager, SQLITE_CACHE_FILE};
+use crate::services::channels::ChannelManager;
+use crate::models::channel::PublisherChannel;
 use crate::subsystems::scanner::ScannerSubsystem;
-use crate::subsystems::worker::WorkerSubsystem;
+use crate::subsystems::worker::{PublishedProjects, WorkerSubsystem};
+use crate::subsystems::watchdog::{InProgress, WatchdogSubsystem};
+use crate::subsystems::health::{HealthSubsystem, Liveness, Ready};
+
+/// Устанавливает глобальный subscriber; в json поля событий (project_id, channel, error, ...)
+/// выводятся ключами верхнего уровня, а не внутри message
+fn init_tracing<W>(format: LogFormat, log_spec: String, writer: W)
+where
+    W: for<'w> tracing_subscriber::fmt::MakeWriter<'w> + Send + Sync + 'static,
+{
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::new(log_spec))
+        .with_target(false)
+        .with_writer(writer);
+    let _ = match format {
+        LogFormat::Text => builder.compact().try_init(),
+        LogFormat::Json => builder.json().flatten_event(true).with_current_span(false).try_init(),
+    };
+}
 
 /// High-level entrypoint: load config, init logging, run worker
-pub async fn run_with_config_path(path: &str, log_file: Option<&str>) -> std::io::Result<()> {
+pub async fn run_with_config_path(path: &str, log_file: Option<&str>) -> std::io::Result<RunOutcome> {
+    run_with_options(path, log_file, RunOptions::default()).await
+}
+
+/// Same as `run_with_config_path`, with one-off overrides from the command line
+pub async fn run_with_options(path: &str, log_file: Option<&str>, options: RunOptions) -> std::io::Result<RunOutcome> {
     // Load YAML config
     let cfg: AppConfig = load_config(path)
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("Failed to load {}: {}", path, e)))?;
 
     // Initialize structured logging (default to info if RUST_LOG not set)
     let log_spec = std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string());
-    
+    let log_format = options
+        .log_format
+        .or_else(|| cfg.run.as_ref().and_then(|r| r.log_format))
+        .unwrap_or_default();
+
     // Проверяем, нужно ли логирование в файл
     if let Some(log_path) = log_file {
         // Логирование в файл и консоль
@@ -42,70 +77,68 @@ pub async fn run_with_config_path(path: &str, log_file: Option<&str>) -> std::io
         
         let (non_blocking, _guard) = tracing_appender::non_blocking(file_appender);
         
-        let _ = tracing_subscriber::fmt()
-            .with_env_filter(tracing_subscriber::EnvFilter::new(log_spec))
-            .with_target(false)
-            .compact()
-            .with_writer(non_blocking)
-            .try_init();
+        init_tracing(log_format, log_spec, non_blocking);
     } else {
         // Только консольное логирование
-        let _ = tracing_subscriber::fmt()
-            .with_env_filter(tracing_subscriber::EnvFilter::new(log_spec))
-            .with_target(false)
-            .compact()
-            .try_init();
+        init_tracing(log_format, log_spec, std::io::stdout);
     }
 
+    // Проверка конфигурации до обращения к сети: шаблон поста, каналы, mastodon.allowed_hosts
+    cfg.validate()
+        .and_then(|_| options.validate())
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+
     // Initialize shared services from config
-    let chat_api: Arc<dyn ChatApi> = Arc::new(LocalChatApi::from_config(&cfg.llm));
-    let summarizer = Arc::new(Summarizer::builder()
-        .chat_api(Arc::clone(&chat_api))
-        .hard_max_chars(600)
-        .sample_percent(0.05)
-        .max_retry_attempts(3)
-        .retry_delay_secs(2)
-        .build()
-        .with_config(&cfg));
-
-    let (telegram_api, target_chat_id) = if let Some(tg) = cfg.telegram.clone().filter(|t| t.enabled) {
-        let api: Arc<dyn TelegramApi> = Arc::new(RealTelegramApi {
-            client: Client::new(),
-            base_url: tg.api_base_url,
-            token: tg.bot_token,
-            chat_id: tg.target_chat_id,
-            max_chars: tg.max_chars,
-        });
-        (Some(api), Some(tg.target_chat_id))
-    } else {
-        (None, None)
-    };
+    let summarizer = build_summarizer(&cfg, options.print_prompt);
+    let http_client = build_http_client(&cfg)?;
 
-    // Ensure post template is provided
-    if cfg.run.as_ref().and_then(|r| r.post_template.as_ref()).is_none() {
-        return Err(std::io::Error::new(std::io::ErrorKind::Other, "run.post_template is required in config (no fallback post formatting)"));
+    // Проверка LLM до начала краулинга (summarizer.validate_on_start); в --print-prompt LLM не вызывается
+    let validate_on_start = cfg.summarizer.as_ref().and_then(|s| s.validate_on_start).unwrap_or(false);
+    if validate_on_start && !options.print_prompt {
+        summarizer.validate_connection().await.map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!("LLM validation failed at startup (check llm.provider, llm.base_url, llm.api_key, llm.model): {}", e),
+            )
+        })?;
     }
 
+    let (telegram_api, target_chat_id) = build_telegram_api(&cfg, &http_client);
+
     let req_timeout = Duration::from_secs(cfg.crawler.request_timeout_secs.unwrap_or(30));
 
     // Initialize cache manager
-    let cache_dir = cfg
-        .run
-        .as_ref()
-        .and_then(|r| r.cache_dir.as_ref())
-        .map(|s| s.clone())
-        .unwrap_or_else(|| "./cache".to_string());
-    let cache_manager: Arc<dyn CacheManager> = Arc::new(FileSystemCacheManager::builder().cache_dir(cache_dir).build());
+    check_cache_dir_writable(&cfg)?;
+    let cache_manager = build_cache_manager(&cfg).await?;
+    prune_expired_cache(&cfg, cache_manager.as_ref()).await;
 
-    // Channel between crawler and worker (single items)
+    // Channel between crawler and worker (single items and crawl cycle markers)
     let (tx, rx) = mpsc::channel(10);
 
+    // Текущий элемент worker, выводится в лог watchdog при превышении run.max_duration_secs
+    let in_progress = InProgress::default();
+    // Число опубликованных постов: итог запуска для кода выхода --once
+    let published_projects = PublishedProjects::default();
+
+    // --dry-run: worker только логирует посты, ничего не отмечая опубликованным
+    let dry_run = options.dry_run;
+
+    // Готовность для GET /readyz: сканер выставляет ее после первого успешного обхода
+    let ready = Ready::default();
+    // Работа и занятость сканера и worker для GET /healthz
+    let scanner_liveness = Liveness::default();
+    let worker_liveness = Liveness::default();
+
     // Build subsystems
     let npa_subsystem = ScannerSubsystem::builder()
         .config(cfg.clone())
         .req_timeout(req_timeout)
         .sender(tx)
         .cache_manager(Arc::clone(&cache_manager))
+        .client(http_client.clone())
+        .options(options)
+        .ready(Arc::clone(&ready))
+        .liveness(scanner_liveness.clone())
         .build();
 
     let worker_subsystem = if let (Some(api), Some(chat_id)) = (telegram_api.clone(), target_chat_id) {
@@ -116,6 +149,11 @@ pub async fn run_with_config_path(path: &str, log_file: Option<&str>) -> std::io
             .target_chat_id(chat_id)
             .cache_manager(Arc::clone(&cache_manager))
             .receiver(rx)
+            .client(http_client.clone())
+            .in_progress(Arc::clone(&in_progress))
+            .published_projects(Arc::clone(&published_projects))
+            .dry_run(dry_run)
+            .liveness(worker_liveness.clone())
             .build()
     } else if let Some(api) = telegram_api.clone() {
         WorkerSubsystem::builder()
@@ -124,6 +162,11 @@ pub async fn run_with_config_path(path: &str, log_file: Option<&str>) -> std::io
             .telegram_api(api)
             .cache_manager(Arc::clone(&cache_manager))
             .receiver(rx)
+            .client(http_client.clone())
+            .in_progress(Arc::clone(&in_progress))
+            .published_projects(Arc::clone(&published_projects))
+            .dry_run(dry_run)
+            .liveness(worker_liveness.clone())
             .build()
     } else if let Some(chat_id) = target_chat_id {
         WorkerSubsystem::builder()
@@ -132,6 +175,11 @@ pub async fn run_with_config_path(path: &str, log_file: Option<&str>) -> std::io
             .target_chat_id(chat_id)
             .cache_manager(Arc::clone(&cache_manager))
             .receiver(rx)
+            .client(http_client.clone())
+            .in_progress(Arc::clone(&in_progress))
+            .published_projects(Arc::clone(&published_projects))
+            .dry_run(dry_run)
+            .liveness(worker_liveness.clone())
             .build()
     } else {
         WorkerSubsystem::builder()
@@ -139,18 +187,373 @@ pub async fn run_with_config_path(path: &str, log_file: Option<&str>) -> std::io
             .summarizer(Arc::clone(&summarizer))
             .cache_manager(Arc::clone(&cache_manager))
             .receiver(rx)
+            .client(http_client.clone())
+            .in_progress(Arc::clone(&in_progress))
+            .published_projects(Arc::clone(&published_projects))
+            .dry_run(dry_run)
+            .liveness(worker_liveness.clone())
             .build()
     };
 
+    let watchdog_subsystem = cfg
+        .run
+        .as_ref()
+        .and_then(|r| r.max_duration_secs)
+        .filter(|secs| *secs > 0)
+        .map(|secs| {
+            WatchdogSubsystem::builder()
+                .max_duration(Duration::from_secs(secs))
+                .in_progress(Arc::clone(&in_progress))
+                .build()
+        });
+
+    let health_subsystem = cfg
+        .health
+        .as_ref()
+        .filter(|h| h.enabled.unwrap_or(true))
+        .map(|h| {
+            HealthSubsystem::builder()
+                .bind(h.bind.clone().unwrap_or_else(|| "0.0.0.0:8080".to_string()))
+                .ready(Arc::clone(&ready))
+                .subsystems(vec![("scanner", scanner_liveness.clone()), ("worker", worker_liveness.clone())])
+                .maybe_stall_timeout(h.stall_timeout_secs.map(Duration::from_secs))
+                .build()
+        });
+
     // Setup and execute subsystem tree
     Toplevel::new(|s| async move {
         s.start(SubsystemBuilder::new("NPAListCrawler", |h| npa_subsystem.run(h)));
         s.start(SubsystemBuilder::new("Worker", |h| worker_subsystem.run(h)));
+        if let Some(watchdog) = watchdog_subsystem {
+            s.start(SubsystemBuilder::new("Watchdog", |h| watchdog.run(h)));
+        }
+        if let Some(health) = health_subsystem {
+            s.start(SubsystemBuilder::new("Health", |h| health.run(h)));
+        }
     })
     .catch_signals()
     .handle_shutdown_requests(Duration::from_secs(5))
     .await
-    .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("shutdown error: {}", e)))
+    .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("shutdown error: {}", e)))?;
+
+    Ok(RunOutcome::from_published(published_projects.load(std::sync::atomic::Ordering::SeqCst)))
+}
+
+/// Суммаризатор с общими для запуска и backfill настройками
+fn build_summarizer(cfg: &AppConfig, print_prompt: bool) -> Arc<Summarizer> {
+    let chat_api: Arc<dyn ChatApi> = Arc::new(LocalChatApi::from_config(&cfg.llm).with_http_config(cfg.http.as_ref()));
+    Arc::new(Summarizer::builder()
+        .chat_api(chat_api)
+        .hard_max_chars(600)
+        .sample_percent(0.05)
+        .max_retry_attempts(3)
+        .retry_delay_secs(2)
+        .build()
+        .with_config(cfg)
+        .with_print_prompt(print_prompt))
+}
+
+/// Общий HTTP-клиент по секции http: прокси, User-Agent и таймаут запроса.
+/// Без секции — клиент по умолчанию
+pub fn build_http_client(cfg: &AppConfig) -> std::io::Result<Client> {
+    let to_io = |e: reqwest::Error| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("http: {}", e));
+    let mut builder = Client::builder();
+    if let Some(http) = cfg.http.as_ref() {
+        if let Some(proxy) = http.proxy.as_deref().filter(|p| !p.trim().is_empty()) {
+            builder = builder.proxy(reqwest::Proxy::all(proxy).map_err(to_io)?);
+        }
+        if let Some(user_agent) = http.user_agent.as_deref() {
+            builder = builder.user_agent(user_agent);
+        }
+        if let Some(secs) = http.timeout_secs.filter(|s| *s > 0) {
+            builder = builder.timeout(Duration::from_secs(secs));
+        }
+    }
+    builder.build().map_err(to_io)
+}
+
+/// Клиент Telegram и id чата, если канал telegram включен
+fn build_telegram_api(cfg: &AppConfig, client: &Client) -> (Option<Arc<dyn TelegramApi>>, Option<i64>) {
+    let Some(tg) = cfg.telegram.clone().filter(|t| t.enabled) else {
+        return (None, None);
+    };
+    let api: Arc<dyn TelegramApi> = Arc::new(RealTelegramApi {
+        client: client.clone(),
+        base_url: tg.api_base_url,
+        token: tg.bot_token,
+        chat_id: tg.target_chat_id,
+        max_chars: tg.max_chars,
+        message_thread_id: tg.message_thread_id,
+        trim_on_word_boundary: cfg.run.as_ref().and_then(|r| r.trim_on_word_boundary).unwrap_or(false),
+        retry: HttpRetryPolicy::from_config(cfg.run.as_ref().and_then(|r| r.publish_retry.as_ref())),
+        parse_mode: tg.parse_mode.unwrap_or_default(),
+        split_long_messages: tg.split_long_messages.unwrap_or(false),
+    });
+    (Some(api), Some(tg.target_chat_id))
+}
+
+/// Каталог кэша (run.cache_dir, по умолчанию ./cache)
+fn cache_dir(cfg: &AppConfig) -> String {
+    cfg
+        .run
+        .as_ref()
+        .and_then(|r| r.cache_dir.as_ref())
+        .map(|s| s.clone())
+        .unwrap_or_else(|| "./cache".to_string())
+}
+
+/// Проверяет при запуске, что в каталог кэша можно писать (пробный файл).
+/// По cache.on_unwritable: fail — ошибка запуска, warn — предупреждение
+fn check_cache_dir_writable(cfg: &AppConfig) -> std::io::Result<()> {
+    let dir = cache_dir(cfg);
+    let probe = std::path::Path::new(&dir).join(".write_probe");
+    let result = std::fs::create_dir_all(&dir)
+        .and_then(|_| std::fs::write(&probe, b"probe"))
+        .and_then(|_| std::fs::remove_file(&probe));
+    let Err(e) = result else {
+        return Ok(());
+    };
+    match cfg.cache.as_ref().and_then(|c| c.on_unwritable).unwrap_or_default() {
+        OnUnwritableCache::Fail => Err(std::io::Error::new(
+            e.kind(),
+            format!("cache directory {} is not writable (run.cache_dir, cache.on_unwritable: fail): {}", dir, e),
+        )),
+        OnUnwritableCache::Warn => {
+            tracing::warn!(cache_dir = %dir, error = %e, "cache directory is not writable, published items will not be recorded");
+            Ok(())
+        }
+    }
+}
+
+/// Кэш артефактов по настройкам run.cache_dir, run.cache_backend и cache.
+/// База SQLite при первом открытии заполняется существующим файловым кэшем run.cache_dir
+async fn build_cache_manager(cfg: &AppConfig) -> std::io::Result<Arc<dyn CacheManager>> {
+    let cache_dir = cache_dir(cfg);
+    let backend = cfg.run.as_ref().and_then(|r| r.cache_backend).unwrap_or_default();
+    if backend == CacheBackend::Sqlite {
+        let to_io = |e: Box<dyn std::error::Error + Send + Sync>| {
+            std::io::Error::new(std::io::ErrorKind::Other, format!("sqlite cache in {}: {}", cache_dir, e))
+        };
+        let db_path = std::path::Path::new(&cache_dir).join(SQLITE_CACHE_FILE);
+        let fresh = !db_path.exists();
+        let sqlite = SqliteCacheManager::builder()
+            .db_path(db_path)
+            .maybe_summary_model(cfg.llm.model.clone())
+            .build()
+            .map_err(to_io)?;
+        if fresh {
+            sqlite.import_filesystem_cache(&cache_dir).await.map_err(to_io)?;
+        }
+        return Ok(Arc::new(sqlite));
+    }
+
+    let compress_cache = cfg.cache.as_ref().and_then(|c| c.compress).unwrap_or(false);
+    let externalize_threshold = cfg.cache.as_ref()
+        .filter(|c| c.externalize_large_fields.unwrap_or(false))
+        .map(|c| c.externalize_threshold_bytes.unwrap_or(4096));
+    Ok(Arc::new(
+        FileSystemCacheManager::builder()
+            .cache_dir(cache_dir)
+            .compress(compress_cache)
+            .maybe_externalize_threshold(externalize_threshold)
+            .maybe_summary_model(cfg.llm.model.clone())
+            .layout(cfg.run.as_ref().and_then(|r| r.cache_layout).unwrap_or_default())
+            .build(),
+    ))
+}
+
+/// Удаляет из кэша опубликованные проекты старше run.cache_ttl_days.
+/// Ошибка очистки не мешает запуску: она только логируется
+async fn prune_expired_cache(cfg: &AppConfig, cache_manager: &dyn CacheManager) {
+    let Some(ttl_days) = cfg.run.as_ref().and_then(|r| r.cache_ttl_days).filter(|d| *d > 0) else {
+        return;
+    };
+    let enabled_channels: Vec<PublisherChannel> = ChannelManager::builder()
+        .config(cfg)
+        .build()
+        .get_enabled_channels()
+        .iter()
+        .map(|c| c.channel)
+        .collect();
+    let ttl = Duration::from_secs(ttl_days * 24 * 3600);
+    match cache_manager.prune_expired(ttl, &enabled_channels).await {
+        Ok(removed) => tracing::info!(cache_ttl_days = ttl_days, removed = removed, "cache: pruned expired projects"),
+        Err(e) => tracing::warn!(cache_ttl_days = ttl_days, error = %e, "cache: failed to prune expired projects"),
+    }
+}
+
+/// Clears cached channel summaries and posts for project ids in `from..=to`,
+/// keeping documents, so not yet published channels are summarized again with the current prompt.
+/// Returns the number of projects that were found in the cache.
+pub async fn invalidate_summaries(path: &str, from: u32, to: u32) -> std::io::Result<usize> {
+    let selection = CacheSelection { from: Some(from), to: Some(to), ..Default::default() };
+    invalidate_summaries_matching(path, &selection).await
+}
+
+/// Clears cached channel summaries and posts of cached projects matching `selection`
+/// (project id range and/or `PublishDate` bounds), keeping documents.
+/// Returns the number of cleared projects.
+pub async fn invalidate_summaries_matching(path: &str, selection: &CacheSelection) -> std::io::Result<usize> {
+    clear_matching_summaries(path, selection).await.map(|(cleared, _)| cleared)
+}
+
+/// Clears cached channel summaries and posts of cached projects matching `selection` like
+/// [`invalidate_summaries_matching`], then publishes every matched project again through [`backfill`]
+/// to all enabled channels, including the ones it was already published to, so the new summaries
+/// reach the channels. Projects without a cached title are skipped with a warning.
+/// Returns the number of republished projects.
+pub async fn invalidate_and_republish_matching(path: &str, selection: &CacheSelection) -> std::io::Result<usize> {
+    let (_, matched) = clear_matching_summaries(path, selection).await?;
+    let mut republished = 0;
+    for (project_id, title) in matched {
+        let Some(title) = title else {
+            tracing::warn!(project_id = %project_id, "invalidate: title is not in cache, project is not republished");
+            continue;
+        };
+        republished += backfill(path, &project_id, Some(&title), &[]).await?;
+    }
+    Ok(republished)
+}
+
+/// Очищает суммаризации проектов кэша из выборки; возвращает число очищенных проектов
+/// и id всех подошедших проектов с сохраненными заголовками
+async fn clear_matching_summaries(
+    path: &str,
+    selection: &CacheSelection,
+) -> std::io::Result<(usize, Vec<(String, Option<String>)>)> {
+    let cfg: AppConfig = load_config(path)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("Failed to load {}: {}", path, e)))?;
+    let cache_manager = build_cache_manager(&cfg).await?;
+    let to_io = |project_id: &str, e: Box<dyn std::error::Error + Send + Sync>| {
+        std::io::Error::new(std::io::ErrorKind::Other, format!("Failed to invalidate {}: {}", project_id, e))
+    };
+
+    let project_ids = cache_manager
+        .list_project_ids()
+        .await
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("Failed to list cached projects: {}", e)))?;
+    let mut cleared = 0;
+    let mut matched = Vec::new();
+    for project_id in project_ids {
+        let meta = cache_manager.load_metadata(&project_id).await.map_err(|e| to_io(&project_id, e))?;
+        let Some(meta) = meta.filter(|m| selection.matches(m)) else {
+            continue;
+        };
+        if cache_manager.clear_summaries(&project_id).await.map_err(|e| to_io(&project_id, e))? {
+            cleared += 1;
+        }
+        matched.push((project_id, meta.title));
+    }
+    Ok((cleared, matched))
+}
+
+/// Moves legacy flat cache files (`{id}_metadata.json`, `{id}_extracted.md`, `{id}_summary.txt`)
+/// in run.cache_dir into per-project directories with metadata.json, deleting the flat files
+/// of migrated projects when `delete_legacy` is set. Projects already in the new layout are skipped.
+/// Afterwards `run.cache_layout: v2` turns off the legacy lookups.
+pub async fn migrate_cache(path: &str, delete_legacy: bool) -> std::io::Result<CacheMigrationReport> {
+    let cfg: AppConfig = load_config(path)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("Failed to load {}: {}", path, e)))?;
+    let cache_dir = cache_dir(&cfg);
+    FileSystemCacheManager::builder()
+        .cache_dir(cache_dir.clone())
+        .compress(cfg.cache.as_ref().and_then(|c| c.compress).unwrap_or(false))
+        .build()
+        .migrate_legacy_layout(delete_legacy)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("Failed to migrate cache {}: {}", cache_dir, e)))
+}
+
+/// Publishes one project without the crawler: builds the item from the stages endpoint
+/// (or cached crawl metadata) and runs it through the worker for `channels` only
+/// (all enabled channels when empty), re-posting even where it was already published.
+/// The title is `title` or the one cached by a previous run; without either backfill fails,
+/// since the stages endpoint does not return it.
+/// Returns 1 when the project was published to at least one channel, otherwise 0.
+pub async fn backfill(
+    path: &str,
+    project_id: &str,
+    title: Option<&str>,
+    channels: &[PublisherChannel],
+) -> std::io::Result<usize> {
+    let cfg: AppConfig = load_config(path)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("Failed to load {}: {}", path, e)))?;
+    let log_spec = std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string());
+    init_tracing(cfg.run.as_ref().and_then(|r| r.log_format).unwrap_or_default(), log_spec, std::io::stdout);
+    cfg.validate()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+
+    let enabled: Vec<PublisherChannel> = ChannelManager::builder()
+        .config(&cfg)
+        .build()
+        .get_enabled_channels()
+        .into_iter()
+        .map(|c| c.channel)
+        .collect();
+    if let Some(channel) = channels.iter().find(|c| !enabled.contains(c)) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("backfill: channel {} is not enabled in config", channel.as_ref()),
+        ));
+    }
+    let targets = if channels.is_empty() { enabled } else { channels.to_vec() };
+
+    check_cache_dir_writable(&cfg)?;
+    let cache_manager = build_cache_manager(&cfg).await?;
+    let http_client = build_http_client(&cfg)?;
+
+    let cached = cache_manager.load_metadata(project_id).await.ok().flatten();
+    // Заголовка в stages нет: без --title берем сохраненный при прошлой обработке
+    let title = match title.map(str::to_string).or_else(|| cached.as_ref().and_then(|m| m.title.clone())) {
+        Some(title) => title,
+        None => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("backfill: title of project {} is not in cache, pass it with --title", project_id),
+            ));
+        }
+    };
+    // Метаданные списка НПА берем из кэша прошлой обработки, иначе — текущую стадию из stages
+    let cached_metadata = cached.map(|m| m.crawl_metadata).filter(|m| !m.is_empty());
+    let metadata = match (cached_metadata, cfg.crawler.file_id.as_ref()) {
+        (Some(metadata), _) => metadata,
+        (None, Some(file_id)) => {
+            let timeout = file_id.timeout_secs.or(cfg.crawler.request_timeout_secs).unwrap_or(30);
+            FileIdScanner::builder()
+                .client(http_client.clone())
+                .timeout(Duration::from_secs(timeout))
+                .max_retry_attempts(file_id.max_retry_attempts.unwrap_or(2))
+                .build()
+                .fetch_stage_metadata(&file_id.url.replace("{project_id}", project_id))
+                .await
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("backfill: stages request failed: {}", e)))?
+        }
+        (None, None) => Vec::new(),
+    };
+    let npalist = cfg.crawler.npalist.as_ref();
+    let url_template = cfg.crawler.npalist_project_url_template().unwrap_or(DEFAULT_PROJECT_URL_TEMPLATE);
+    let item = CrawlItem {
+        title,
+        url: project_url(url_template, project_id),
+        body: String::new(),
+        project_id: Some(project_id.to_string()),
+        metadata,
+        source_label: npalist.and_then(|n| n.label.clone()),
+        // Повтор старого проекта не сдвигает состояние источника в manifest
+        source_id: None,
+    };
+
+    let (telegram_api, target_chat_id) = build_telegram_api(&cfg, &http_client);
+    let worker = Worker::builder()
+        .config(cfg.clone())
+        .summarizer(build_summarizer(&cfg, false))
+        .maybe_telegram_api(telegram_api)
+        .maybe_target_chat_id(target_chat_id)
+        .cache_manager(cache_manager)
+        .client(http_client)
+        .force_channels(targets)
+        .build()
+        .await?;
+    worker.process_item(item).await
 }
 
 // run_worker оставлен в истории как документационный артефакт и заменён подсистемной моделью
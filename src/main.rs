@@ -1,6 +1,10 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use dotenv::dotenv;
-use luminis::run_with_config_path;
+use luminis::models::config::{LogFormat, RunOptions};
+use luminis::models::types::{CacheSelection, RunOutcome, parse_date};
+use luminis::models::channel::PublisherChannel;
+use luminis::{backfill, invalidate_and_republish_matching, invalidate_summaries_matching, migrate_cache, run_with_options};
+use std::str::FromStr;
 
 /// Luminis - система мониторинга и публикации новостей законодательства
 #[derive(Parser, Debug)]
@@ -13,6 +17,86 @@ struct Args {
     /// Путь к файлу для записи логов (опционально)
     #[arg(long)]
     log_file: Option<String>,
+
+    /// Формат логов: text или json (вместо run.log_format)
+    #[arg(long, value_enum)]
+    log_format: Option<LogFormat>,
+
+    /// Разовый offset для npalist (минуя вычисленный по manifest)
+    #[arg(long)]
+    offset: Option<u32>,
+
+    /// Разовый limit для npalist (вместо crawler.npalist.limit)
+    #[arg(long)]
+    limit: Option<u32>,
+
+    /// Печатать промпт суммаризатора и исходный текст вместо вызова LLM (без публикации)
+    #[arg(long)]
+    print_prompt: bool,
+
+    /// Показать в логе готовые посты каналов без публикации (суммаризации и посты кэшируются,
+    /// каналы не отмечаются опубликованными)
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Разовый запуск для cron: завершиться с кодом 3, если ничего нового не опубликовано
+    #[arg(long)]
+    once: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Сбросить кэшированные данные проектов в диапазоне id и/или дат публикации (документы сохраняются)
+    Invalidate {
+        /// Удалить суммаризации и посты каналов, чтобы они были сгенерированы заново
+        #[arg(long)]
+        summaries: bool,
+
+        /// Первый project_id диапазона (включительно)
+        #[arg(long)]
+        from: Option<u32>,
+
+        /// Последний project_id диапазона (включительно)
+        #[arg(long)]
+        to: Option<u32>,
+
+        /// Первая дата публикации (PublishDate) диапазона, включительно: YYYY-MM-DD или DD.MM.YYYY
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Последняя дата публикации (PublishDate) диапазона, включительно: YYYY-MM-DD или DD.MM.YYYY
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Сразу опубликовать проекты диапазона заново во все включенные каналы (как backfill),
+        /// в том числе в каналы, где они уже опубликованы
+        #[arg(long)]
+        republish: bool,
+    },
+    /// Опубликовать один проект минуя краулер, даже если он уже опубликован в канале
+    Backfill {
+        /// project_id проекта
+        #[arg(long)]
+        project_id: String,
+
+        /// Заголовок проекта; по умолчанию — сохраненный в кэше при прошлой обработке
+        #[arg(long)]
+        title: Option<String>,
+
+        /// Канал публикации (можно повторять); по умолчанию все включенные каналы
+        #[arg(long = "channel")]
+        channels: Vec<String>,
+    },
+    /// Перенести плоские файлы старого кэша ({id}_metadata.json, {id}_extracted.md, {id}_summary.txt)
+    /// в каталоги проектов; после переноса можно включить run.cache_layout: v2
+    MigrateCache {
+        /// Удалить плоские файлы перенесенных проектов
+        #[arg(long)]
+        delete_legacy: bool,
+    },
 }
 
 #[tokio::main]
@@ -23,6 +107,83 @@ async fn main() -> std::io::Result<()> {
     // Parse command line arguments
     let args = Args::parse();
 
+    if let Some(Command::Backfill { project_id, title, channels }) = &args.command {
+        let channels = channels
+            .iter()
+            .map(|name| {
+                PublisherChannel::from_str(name).map_err(|_| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("unknown channel: {}", name))
+                })
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+        if backfill(&args.config, project_id, title.as_deref(), &channels).await? == 0 {
+            println!("backfill: project {} was not published", project_id);
+        } else {
+            println!("backfill: project {} published", project_id);
+        }
+        return Ok(());
+    }
+
+    if let Some(Command::MigrateCache { delete_legacy }) = &args.command {
+        let report = migrate_cache(&args.config, *delete_legacy).await?;
+        println!(
+            "migrate-cache: migrated {} projects, skipped {}, deleted {} legacy files",
+            report.migrated, report.skipped, report.deleted_files
+        );
+        return Ok(());
+    }
+
+    if let Some(Command::Invalidate { summaries, from, to, since, until, republish }) = args.command {
+        if !summaries {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "nothing to invalidate: pass --summaries",
+            ));
+        }
+        let selection = CacheSelection {
+            from,
+            to,
+            since: since.as_deref().map(parse_cli_date).transpose()?,
+            until: until.as_deref().map(parse_cli_date).transpose()?,
+        };
+        if selection.from.is_none() && selection.to.is_none() && selection.since.is_none() && selection.until.is_none() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "nothing to invalidate: pass --from/--to and/or --since/--until",
+            ));
+        }
+        if republish {
+            let republished = invalidate_and_republish_matching(&args.config, &selection).await?;
+            println!("invalidated and republished {} cached projects matching {:?}", republished, selection);
+            return Ok(());
+        }
+        let cleared = invalidate_summaries_matching(&args.config, &selection).await?;
+        println!("invalidated summaries for {} cached projects matching {:?}", cleared, selection);
+        return Ok(());
+    }
+
     // Load config, init logging and run
-    run_with_config_path(&args.config, args.log_file.as_deref()).await
+    let options = RunOptions {
+        offset: args.offset,
+        limit: args.limit,
+        print_prompt: args.print_prompt,
+        dry_run: args.dry_run,
+        log_format: args.log_format,
+    };
+    let outcome = run_with_options(&args.config, args.log_file.as_deref(), options).await?;
+    if args.once && outcome == RunOutcome::NothingNew {
+        eprintln!("nothing new published");
+        std::process::exit(outcome.exit_code());
+    }
+    Ok(())
+}
+
+/// Разбирает дату границы --since/--until
+fn parse_cli_date(s: &str) -> std::io::Result<chrono::NaiveDate> {
+    parse_date(s).map(|d| d.date_naive()).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("invalid date {}: expected YYYY-MM-DD or DD.MM.YYYY", s),
+        )
+    })
 }
@@ -13,6 +13,12 @@ pub enum PublisherChannel {
     Console,
     /// Файловый вывод
     File,
+    /// Комната Matrix
+    Matrix,
+    /// Slack (incoming webhook)
+    Slack,
+    /// JSON-событие на произвольный endpoint
+    Webhook,
 }
 
 /// Перечисление каналов краулинга
@@ -41,6 +47,9 @@ impl PublisherChannel {
             PublisherChannel::Mastodon,
             PublisherChannel::Console,
             PublisherChannel::File,
+            PublisherChannel::Matrix,
+            PublisherChannel::Slack,
+            PublisherChannel::Webhook,
         ]
     }
 }
@@ -86,6 +95,9 @@ mod tests {
         assert_eq!(PublisherChannel::Mastodon.as_str(), "mastodon");
         assert_eq!(PublisherChannel::Console.as_str(), "console");
         assert_eq!(PublisherChannel::File.as_str(), "file");
+        assert_eq!(PublisherChannel::Matrix.as_str(), "matrix");
+        assert_eq!(PublisherChannel::Slack.as_str(), "slack");
+        assert_eq!(PublisherChannel::Webhook.as_str(), "webhook");
     }
 
     #[test]
@@ -105,11 +117,14 @@ mod tests {
     #[test]
     fn test_publisher_channel_all() {
         let all_channels = PublisherChannel::all();
-        assert_eq!(all_channels.len(), 4);
+        assert_eq!(all_channels.len(), 7);
         assert!(all_channels.contains(&PublisherChannel::Telegram));
         assert!(all_channels.contains(&PublisherChannel::Mastodon));
         assert!(all_channels.contains(&PublisherChannel::Console));
         assert!(all_channels.contains(&PublisherChannel::File));
+        assert!(all_channels.contains(&PublisherChannel::Matrix));
+        assert!(all_channels.contains(&PublisherChannel::Slack));
+        assert!(all_channels.contains(&PublisherChannel::Webhook));
     }
 
     #[test]
@@ -1,13 +1,110 @@
+use bon::Builder;
 use serde::Deserialize;
 
-#[derive(Debug, Deserialize, Clone)]
+use crate::models::types::MetadataItem;
+
+/// Конфигурация приложения. `AppConfig::default()` — минимальная конфигурация с задокументированными
+/// значениями (см. config.yaml.example); для запуска в ней нужно включить хотя бы один канал.
+/// Builder задает только переданные секции, остальные остаются пустыми
+#[derive(Debug, Deserialize, Clone, Builder)]
 pub struct AppConfig {
     pub telegram: Option<TelegramConfig>,
+    #[builder(default)]
     pub llm: LlmConfig,
+    #[builder(default)]
     pub crawler: CrawlerConfig,
     pub mastodon: Option<MastodonConfig>,
+    pub matrix: Option<MatrixConfig>,
+    pub slack: Option<SlackConfig>,
+    pub webhook: Option<WebhookConfig>,
     pub output: Option<OutputConfig>,
     pub run: Option<RunConfig>,
+    pub summarizer: Option<SummarizerConfig>,
+    pub templates: Option<TemplatesConfig>,
+    pub filter: Option<FilterConfig>,
+    pub channels: Option<ChannelsConfig>,
+    pub cache: Option<CacheConfig>,        // параметры хранения артефактов кэша
+    pub health: Option<HealthConfig>,      // HTTP-эндпоинты /healthz и /readyz для проб оркестратора
+    pub http: Option<HttpConfig>,          // общий HTTP-клиент краулеров, скачивания документов и публикаторов
+}
+
+/// Шаблон поста по умолчанию для `AppConfig::default()`
+pub const DEFAULT_POST_TEMPLATE: &str = "{{ url }}\n{{ summary }}";
+
+impl Default for AppConfig {
+    /// Источник regulation.gov.ru, кэш в ./cache, шаблон поста DEFAULT_POST_TEMPLATE;
+    /// каналы публикации не заданы
+    fn default() -> Self {
+        Self {
+            telegram: None,
+            llm: LlmConfig::default(),
+            crawler: CrawlerConfig::default(),
+            mastodon: None,
+            matrix: None,
+            slack: None,
+            webhook: None,
+            output: None,
+            run: Some(RunConfig {
+                cache_dir: Some("./cache".to_string()),
+                post_template: Some(DEFAULT_POST_TEMPLATE.to_string()),
+                ..Default::default()
+            }),
+            summarizer: None,
+            templates: None,
+            filter: None,
+            channels: None,
+            cache: None,
+            health: None,
+            http: None,
+        }
+    }
+}
+
+impl AppConfig {
+    /// Проверяет согласованность конфигурации до запуска: шаблон поста задан, включен хотя бы
+    /// один канал публикации, хост Mastodon разрешен mastodon.allowed_hosts
+    pub fn validate(&self) -> Result<(), String> {
+        if self.run.as_ref().and_then(|r| r.post_template.as_ref()).is_none() {
+            return Err("run.post_template is required in config (no fallback post formatting)".to_string());
+        }
+        if !self.has_enabled_channel() {
+            return Err("no publishing channel is enabled (telegram, mastodon, matrix, slack, webhook, output.console_enabled or output.file_enabled)".to_string());
+        }
+        if let Some(mastodon) = self.mastodon.as_ref().filter(|m| m.enabled) {
+            mastodon.check_allowed_host()?;
+        }
+        if let Some(limit) = self.crawler.npalist.as_ref().and_then(|n| n.limit) {
+            check_npalist_limit("crawler.npalist.limit", limit)?;
+        }
+        let prompt_templates = [
+            ("summarizer.prompt_template", self.summarizer.as_ref().and_then(|s| s.prompt_template.as_deref())),
+            ("run.prompt_template", self.run.as_ref().and_then(|r| r.prompt_template.as_deref())),
+        ];
+        for (key, template) in prompt_templates {
+            if let Some(template) = template {
+                check_tera_template(key, template)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Включен ли хотя бы один канал (значения по умолчанию как в ChannelManager)
+    fn has_enabled_channel(&self) -> bool {
+        self.telegram.as_ref().is_some_and(|t| t.enabled)
+            || self.mastodon.as_ref().is_some_and(|m| m.enabled)
+            || self.matrix.as_ref().is_some_and(|m| m.enabled)
+            || self.slack.as_ref().is_some_and(|s| s.enabled)
+            || self.webhook.as_ref().is_some_and(|w| w.enabled)
+            || self.output.as_ref().is_some_and(|o| o.console_enabled.unwrap_or(true) || o.file_enabled.unwrap_or(false))
+    }
+}
+
+/// Проверяет синтаксис Tera-шаблона; ошибка содержит ключ конфигурации и причину
+fn check_tera_template(key: &str, template: &str) -> Result<(), String> {
+    tera::Tera::default().add_raw_template(key, template).map(|_| ()).map_err(|e| {
+        let cause = std::error::Error::source(&e).map(|s| s.to_string()).unwrap_or_default();
+        format!("{} is not a valid Tera template: {} {}", key, e, cause).trim_end().to_string()
+    })
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -17,9 +114,46 @@ pub struct TelegramConfig {
     pub target_chat_id: i64,
     pub enabled: bool,
     pub max_chars: Option<usize>,
+    pub send_document: Option<bool>, // send the source document via sendDocument with the post as caption
+    pub message_thread_id: Option<i64>, // topic id in a forum group (message_thread_id of sendMessage/sendDocument)
+    pub extra_chat_ids: Option<Vec<i64>>, // additional chats that receive the same post as target_chat_id
+    pub parse_mode: Option<TelegramParseMode>, // None (plain text, default) | MarkdownV2 | HTML
+    pub split_long_messages: Option<bool>, // send a post longer than max_chars as several messages instead of trimming it
 }
 
-#[derive(Debug, Deserialize, Clone)]
+/// Telegram `parse_mode` of sent posts. With MarkdownV2 and HTML the post template is markup:
+/// values substituted into it (summary, title, url, metadata) are escaped
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TelegramParseMode {
+    /// Plain text, no parse_mode field
+    #[default]
+    None,
+    MarkdownV2,
+    #[serde(rename = "HTML")]
+    Html,
+}
+
+impl TelegramParseMode {
+    /// Value of the `parse_mode` field of the Bot API, `None` for plain text
+    pub fn as_api_str(&self) -> Option<&'static str> {
+        match self {
+            TelegramParseMode::None => None,
+            TelegramParseMode::MarkdownV2 => Some("MarkdownV2"),
+            TelegramParseMode::Html => Some("HTML"),
+        }
+    }
+
+    /// Escapes a plain-text value substituted into the post template
+    pub fn escape(&self, text: &str) -> String {
+        match self {
+            TelegramParseMode::None => text.to_string(),
+            TelegramParseMode::MarkdownV2 => crate::publishers::utils::escape_markdown_v2(text),
+            TelegramParseMode::Html => crate::publishers::utils::escape_html(text),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
 pub struct LlmConfig {
     pub model: Option<String>,
     pub use_local: Option<bool>,     // if true, use local kalosm
@@ -40,7 +174,7 @@ pub struct LlmConfig {
     pub minhash_band_width: Option<usize>,
     pub minhash_jaccard_threshold: Option<f32>,   // 0.0..=1.0
     // ai-lib cloud/provider options
-    pub provider: Option<String>,                 // "OpenAI" | "Groq" | ...
+    pub provider: Option<String>,                 // "OpenAI" | "Groq" | "Ollama" (native /api/generate, no api_key) | ...
     pub base_url: Option<String>,
     pub proxy: Option<String>,
     pub api_key: Option<String>,
@@ -58,24 +192,178 @@ pub struct CrawlerConfig {
     pub request_timeout_secs: Option<u64>,
     pub poll_delay_secs: Option<u64>,
     pub max_retry_attempts: Option<u64>, // 0 = бесконечно, >0 = ограниченное количество попыток
+    pub file_max_retry_attempts: Option<u64>, // повторы скачивания документа (0 = без повторов)
+    pub verify_checksum: Option<bool>, // сверять sha256 документа с контрольной суммой из stages endpoint
+    pub head_before_get: Option<bool>, // HEAD перед повторным скачиванием документа: не качать, если ETag/Last-Modified не изменились
+    pub fetch_concurrency: Option<usize>, // не больше N одновременных скачиваний документов (по умолчанию без лимита)
+    pub scan_concurrency: Option<usize>, // не больше N одновременных запросов stages для поиска fileId (по умолчанию без лимита)
+    pub history_pages_per_run: Option<u32>, // не больше N страниц истории за запуск; прогресс сохраняется в manifest
+    pub poll_interval_secs: Option<u64>, // непрерывный опрос: период циклов обхода, сек; run.max_posts_per_run ограничивает публикации одного цикла (не задан — разовый запуск)
+    pub force_document_type: Option<DocumentType>, // тип скачанного документа: auto (по сигнатуре файла, по умолчанию) | docx | pdf
+    pub resolve_parallel_stage_files: Option<bool>, // имена и URL файлов параллельной стадии через Files endpoint (HEAD)
+    pub project_url_template: Option<String>, // URL страницы проекта с плейсхолдером {id} (или {project_id}) для всех источников
     pub npalist: Option<NpaListConfig>,
+    pub atom: Option<AtomConfig>,
+    pub rss: Option<RssSources>, // один источник RSS или список источников
     pub file_id: Option<FileIdConfig>,
 }
 
+impl Default for CrawlerConfig {
+    /// Список проектов и поиск fileId на regulation.gov.ru, как в config.yaml.example
+    fn default() -> Self {
+        Self {
+            interval_seconds: 10,
+            request_timeout_secs: Some(30),
+            poll_delay_secs: None,
+            max_retry_attempts: None,
+            file_max_retry_attempts: None,
+            verify_checksum: None,
+            head_before_get: None,
+            fetch_concurrency: None,
+            scan_concurrency: None,
+            history_pages_per_run: None,
+            poll_interval_secs: None,
+            force_document_type: None,
+            resolve_parallel_stage_files: None,
+            project_url_template: None,
+            npalist: Some(NpaListConfig {
+                enabled: Some(true),
+                url: "https://regulation.gov.ru/api/npalist/?limit={limit}&offset={offset}&sort=desc".to_string(),
+                limit: Some(50),
+                sort: None,
+                regex: None,
+                project_url_template: None,
+                max_items_per_page: None,
+                label: None,
+                interval_seconds: None,
+            }),
+            atom: None,
+            rss: None,
+            file_id: Some(FileIdConfig {
+                url: "https://regulation.gov.ru/api/public/PublicProjects/GetProjectStages/{project_id}".to_string(),
+                regex: r#""fileId"\s*:\s*"([^"]+)""#.to_string(),
+                timeout_secs: None,
+                max_retry_attempts: None,
+            }),
+        }
+    }
+}
+
+impl CrawlerConfig {
+    /// Шаблон URL страницы проекта для npalist: crawler.npalist.project_url_template,
+    /// иначе общий crawler.project_url_template
+    pub fn npalist_project_url_template(&self) -> Option<&str> {
+        self.npalist
+            .as_ref()
+            .and_then(|n| n.project_url_template.as_deref())
+            .or(self.project_url_template.as_deref())
+    }
+}
+
+/// Тип документа проекта для извлечения текста
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentType {
+    /// По сигнатуре файла (`%PDF` — PDF, `PK` — DOCX), независимо от Content-Type ответа
+    #[default]
+    Auto,
+    /// Всегда DOCX, независимо от Content-Type
+    Docx,
+    /// Всегда PDF, независимо от Content-Type
+    Pdf,
+}
+
+impl DocumentType {
+    /// Расширение файла вложения с документом (Auto — как DOCX)
+    pub fn extension(self) -> &'static str {
+        match self {
+            DocumentType::Auto | DocumentType::Docx => "docx",
+            DocumentType::Pdf => "pdf",
+        }
+    }
+
+    /// MIME-тип вложения с документом (Auto — как DOCX)
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            DocumentType::Auto | DocumentType::Docx => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            DocumentType::Pdf => "application/pdf",
+        }
+    }
+}
+
+/// Допустимый размер страницы npalist (crawler.npalist.limit, --limit)
+pub const NPALIST_LIMIT_RANGE: std::ops::RangeInclusive<u32> = 1..=200;
+
+/// Проверяет, что размер страницы npalist `limit` из настройки `name` входит в NPALIST_LIMIT_RANGE
+pub fn check_npalist_limit(name: &str, limit: u32) -> Result<(), String> {
+    if NPALIST_LIMIT_RANGE.contains(&limit) {
+        return Ok(());
+    }
+    Err(format!(
+        "{} must be between {} and {}, got {}",
+        name,
+        NPALIST_LIMIT_RANGE.start(),
+        NPALIST_LIMIT_RANGE.end(),
+        limit
+    ))
+}
+
 // NPA list sources (API)
 #[derive(Debug, Deserialize, Clone)]
 pub struct NpaListConfig {
     pub enabled: Option<bool>,
     pub url: String,
-    pub limit: Option<u32>,
-    pub regex: Option<String>,
+    pub limit: Option<u32>,                   // размер страницы {limit}, 1..=200 (по умолчанию 50)
+    pub sort: Option<String>,                 // порядок {sort} и параметра sort URL (по умолчанию desc, "" = не передавать sort)
+    pub regex: Option<String>,                // regex с группой project_id, применяется к URL проекта
+    pub project_url_template: Option<String>, // URL страницы проекта с плейсхолдером {project_id}
+    pub max_items_per_page: Option<usize>,    // сколько элементов одной страницы истории отправлять в worker (не больше run.max_posts_per_run)
+    pub label: Option<String>,                // метка источника для постов: {{ source_label }}
     pub interval_seconds: Option<u64>, // интервал для периодического запуска NPA краулера
 }
 
+// Atom 1.0 feed source
+#[derive(Debug, Deserialize, Clone)]
+pub struct AtomConfig {
+    pub enabled: Option<bool>,
+    pub url: String,
+    pub regex: Option<String>, // regex с группой project_id, применяется к ссылке записи, затем к <id>
+    pub label: Option<String>, // метка источника для постов: {{ source_label }}
+}
+
+// RSS 2.0 feed sources: одна запись `{ url, regex }` или список таких записей
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum RssSources {
+    One(RssConfig),
+    Many(Vec<RssConfig>),
+}
+
+impl RssSources {
+    /// Включенные источники в порядке конфигурации
+    pub fn enabled(&self) -> Vec<&RssConfig> {
+        let all: Vec<&RssConfig> = match self {
+            RssSources::One(one) => vec![one],
+            RssSources::Many(many) => many.iter().collect(),
+        };
+        all.into_iter().filter(|r| r.enabled.unwrap_or(true)).collect()
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct RssConfig {
+    pub enabled: Option<bool>,
+    pub url: String,
+    pub regex: Option<String>, // regex с группой project_id, применяется к <guid>, затем к <link>
+    pub label: Option<String>, // метка источника для постов: {{ source_label }}
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct FileIdConfig {
     pub url: String,   // e.g. https://.../GetProjectStages/{project_id}
     pub regex: String,          // regex with capture group for fileId
+    pub timeout_secs: Option<u64>,       // stages request timeout (default crawler.request_timeout_secs)
+    pub max_retry_attempts: Option<u64>, // retries on network errors and 5xx (default 2, 0 = no retries)
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -89,9 +377,84 @@ pub struct MastodonConfig {
     pub spoiler_text: Option<String>, // default "Новости"
     pub sensitive: Option<bool>,
     pub max_chars: Option<usize>,
+    pub allowed_hosts: Option<Vec<String>>, // hosts base_url may point to; mismatch is a startup error
+    pub api_flavor: Option<MastodonApiFlavor>, // mastodon | pleroma: server-specific request quirks
+    pub link_chars: Option<usize>, // weight of any link in max_chars; default depends on api_flavor
+    pub attach_files: Option<bool>, // attach the first project file (images, video, audio only) via /api/v2/media
+    pub thread_long_posts: Option<bool>, // post longer than max_chars goes out as a reply chain split on paragraphs instead of being trimmed
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct MatrixConfig {
+    pub homeserver_url: String, // https://matrix.org
+    pub access_token: String,   // access token of the bot user, must be joined to the room
+    pub room_id: String,        // !roomid:matrix.org
+    pub enabled: bool,
+    pub max_chars: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
+pub struct SlackConfig {
+    pub webhook_url: String, // https://hooks.slack.com/services/...
+    pub enabled: bool,
+    pub max_chars: Option<usize>,
+    pub blocks_template: Option<String>, // Tera template of a JSON array of Block Kit blocks (text, title, url)
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct WebhookConfig {
+    pub url: String,     // endpoint receiving JSON events (POST)
+    pub enabled: bool,
+    pub max_chars: Option<usize>, // summary limit of the channel
+    pub headers: Option<std::collections::BTreeMap<String, String>>, // extra request headers, e.g. Authorization
+    pub secret: Option<String>, // HMAC-SHA256 key; the body signature is sent as sha256=<hex>
+    pub signature_header: Option<String>, // header of the signature (default X-Luminis-Signature)
+}
+
+/// Диалект API fediverse-сервера, совместимого с Mastodon
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MastodonApiFlavor {
+    /// Mastodon API как есть
+    #[default]
+    Mastodon,
+    /// Pleroma/Akkoma: явный content_type=text/plain, иначе текст может быть разобран как Markdown/HTML
+    Pleroma,
+}
+
+impl MastodonConfig {
+    /// Сколько символов лимита занимает ссылка: mastodon.link_chars, иначе 23 для Mastodon;
+    /// Pleroma/Akkoma считают ссылки полной длиной (None)
+    pub fn effective_link_chars(&self) -> Option<usize> {
+        match (self.link_chars, self.api_flavor.unwrap_or_default()) {
+            (Some(n), _) => Some(n),
+            (None, MastodonApiFlavor::Mastodon) => Some(crate::publishers::utils::MASTODON_LINK_CHARS),
+            (None, MastodonApiFlavor::Pleroma) => None,
+        }
+    }
+
+    /// Проверяет, что хост base_url входит в mastodon.allowed_hosts (если список задан)
+    pub fn check_allowed_host(&self) -> Result<(), String> {
+        let Some(allowed) = self.allowed_hosts.as_ref().filter(|h| !h.is_empty()) else {
+            return Ok(());
+        };
+        let host = url::Url::parse(&self.base_url)
+            .map_err(|e| format!("mastodon.base_url is not a valid URL ({}): {}", self.base_url, e))?
+            .host_str()
+            .map(|h| h.to_ascii_lowercase())
+            .ok_or_else(|| format!("mastodon.base_url has no host: {}", self.base_url))?;
+        if allowed.iter().any(|h| h.trim().eq_ignore_ascii_case(&host)) {
+            Ok(())
+        } else {
+            Err(format!(
+                "mastodon.base_url host '{}' is not in mastodon.allowed_hosts {:?}",
+                host, allowed
+            ))
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
 pub struct OutputConfig {
     pub console_enabled: Option<bool>,
     pub file_enabled: Option<bool>,
@@ -99,18 +462,400 @@ pub struct OutputConfig {
     pub console_max_chars: Option<usize>,
     pub file_max_chars: Option<usize>,
     pub file_append: Option<bool>,
+    pub file_targets: Option<Vec<FileTargetConfig>>, // несколько файлов/форматов для канала File (вместо file_path)
+    pub file_bom: Option<bool>,                      // писать UTF-8 BOM в начало нового файла
+    pub file_line_ending: Option<LineEnding>,        // lf (по умолчанию) | crlf
 }
 
+/// Один файл вывода канала File
 #[derive(Debug, Deserialize, Clone)]
+pub struct FileTargetConfig {
+    pub path: String,
+    pub format: Option<FileFormat>, // text (по умолчанию) | jsonl
+    pub append: Option<bool>,       // по умолчанию: output.file_append для text, true для jsonl
+}
+
+/// Формат записи поста в файл
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FileFormat {
+    /// Текст поста как есть
+    #[default]
+    Text,
+    /// Одна JSON-строка на пост: title, url, text, published_at
+    Jsonl,
+}
+
+/// Перевод строки в файлах канала File
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
 pub struct RunConfig {
     pub single_shot: Option<bool>,
-    pub max_posts_per_run: Option<usize>,
+    pub max_posts_per_run: Option<usize>, // at most N published projects per run (a project posted to several channels counts once)
     pub summarization_timeout_secs: Option<u64>,
     pub processing_delay_secs: Option<u64>,
     pub input_sample_percent: Option<f32>, // 0.0..=1.0, how much of docx text to feed LLM
     pub post_max_chars: Option<usize>,      // hard limit for final post (will be trimmed)
+    pub trim_on_word_boundary: Option<bool>, // trim posts at the last whitespace instead of mid-word (default false)
+    pub publish_retry: Option<PublishRetryConfig>, // retries of transient publisher HTTP failures (5xx, 429, connection errors; timeouts only for idempotent requests)
     pub hard_max_chars: Option<usize>,     // deprecated; not used
     pub prompt_template: Option<String>,   // Tera template for summarizer prompt
     pub cache_dir: Option<String>,         // directory for caching artifacts
+    pub cache_backend: Option<CacheBackend>, // filesystem (default) or sqlite (single cache_dir/cache.sqlite3 file)
+    pub cache_layout: Option<CacheLayout>, // v1 (default) also reads legacy flat {id}_* files; v2 only per-project directories (after migrate-cache)
+    pub cache_ttl_days: Option<u64>,       // fully published projects older than this are pruned at startup (not set or 0 = keep forever)
     pub post_template: Option<String>,     // Tera template for final post formatting
+    pub require_project_id: Option<bool>,  // false: items without project_id get a synthetic id from the URL hash
+    pub max_duration_secs: Option<u64>,    // hard cap for the whole run; watchdog requests graceful shutdown
+    pub on_published_webhook: Option<String>, // URL notified with a JSON payload after an item is published
+    pub publish_concurrency_per_item: Option<usize>, // channels of one item published concurrently (default 1)
+    pub combine_identical_channels: Option<bool>, // channels with equal limit, style and post template share one summary/post (default false)
+    pub report_path: Option<String>,       // JSON run report, written on every exit including shutdown
+    pub item_timeout_secs: Option<u64>,    // cap for fetch+summarize+publish of one item; expired item is retried on the next cycle
+    pub item_timeout_max_attempts: Option<u32>, // timed out attempts before the item is recorded as skipped (default 3)
+    pub filters: Option<MetadataFilters>,  // include/exclude predicates over crawler metadata, checked before summarization
+    pub keyword_filter: Option<KeywordFilter>, // keywords the document text must (not) contain to be summarized
+    pub log_format: Option<LogFormat>,     // text (default) or json: one JSON object per event, fields as top-level keys
+}
+
+/// Keywords matched case-insensitively against the extracted document text before summarization
+/// (`run.keyword_filter`). Unset or empty lists do not restrict anything
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct KeywordFilter {
+    pub any_of: Option<Vec<String>>,  // at least one of the keywords must occur
+    pub all_of: Option<Vec<String>>,  // every keyword must occur
+    pub none_of: Option<Vec<String>>, // none of the keywords may occur
+    pub mark_skipped: Option<bool>,   // record a rejected item as skipped in the cache so it is not evaluated again (default true)
+}
+
+impl KeywordFilter {
+    /// Reason the text is rejected, None if it passes all lists
+    pub fn reject_reason(&self, text: &str) -> Option<String> {
+        let text = text.to_lowercase();
+        let occurs = |keyword: &String| text.contains(&keyword.trim().to_lowercase());
+
+        if let Some(any_of) = self.any_of.as_deref().filter(|l| !l.is_empty()) {
+            if !any_of.iter().any(occurs) {
+                return Some(format!("none of run.keyword_filter.any_of {:?} found", any_of));
+            }
+        }
+        if let Some(missing) = self.all_of.as_deref().and_then(|l| l.iter().find(|k| !occurs(k))) {
+            return Some(format!("keyword {:?} of run.keyword_filter.all_of not found", missing));
+        }
+        if let Some(found) = self.none_of.as_deref().and_then(|l| l.iter().find(|k| occurs(k))) {
+            return Some(format!("keyword {:?} of run.keyword_filter.none_of found", found));
+        }
+        None
+    }
+}
+
+/// Include/exclude predicates over crawler metadata (`run.filters`). Values are compared
+/// case-insensitively; an unset or empty list allows everything. An item without the field
+/// fails the `_in` predicate of that field and passes its `_not_in` predicate
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct MetadataFilters {
+    pub department_in: Option<Vec<String>>,
+    pub department_not_in: Option<Vec<String>>,
+    pub status_in: Option<Vec<String>>,
+    pub status_not_in: Option<Vec<String>>,
+    pub kind_in: Option<Vec<String>>,
+    pub kind_not_in: Option<Vec<String>>,
+    pub stage_in: Option<Vec<String>>,
+    pub stage_not_in: Option<Vec<String>>,
+    pub procedure_in: Option<Vec<String>>,
+    pub procedure_not_in: Option<Vec<String>>,
+}
+
+impl MetadataFilters {
+    /// Reason the item is rejected by the first failing predicate, None if all of them pass
+    pub fn reject_reason(&self, metadata: &[MetadataItem]) -> Option<String> {
+        let fields: [(&str, &Option<Vec<String>>, &Option<Vec<String>>, fn(&MetadataItem) -> Option<&str>); 5] = [
+            ("department", &self.department_in, &self.department_not_in, |m| match m {
+                MetadataItem::Department(v) => Some(v.as_str()),
+                _ => None,
+            }),
+            ("status", &self.status_in, &self.status_not_in, |m| match m {
+                MetadataItem::Status(v) => Some(v.as_str()),
+                _ => None,
+            }),
+            ("kind", &self.kind_in, &self.kind_not_in, |m| match m {
+                MetadataItem::Kind(v) => Some(v.as_str()),
+                _ => None,
+            }),
+            ("stage", &self.stage_in, &self.stage_not_in, |m| match m {
+                MetadataItem::Stage(v) => Some(v.as_str()),
+                _ => None,
+            }),
+            ("procedure", &self.procedure_in, &self.procedure_not_in, |m| match m {
+                MetadataItem::Procedure(v) => Some(v.as_str()),
+                _ => None,
+            }),
+        ];
+        let contains = |list: &[String], value: &str| list.iter().any(|l| l.trim().to_lowercase() == value.trim().to_lowercase());
+
+        for (field, allow, deny, value_of) in fields {
+            let value = metadata.iter().find_map(value_of);
+            if let Some(allow) = allow.as_deref().filter(|l| !l.is_empty()) {
+                if !value.is_some_and(|v| contains(allow, v)) {
+                    return Some(format!("{} {:?} is not in run.filters.{}_in", field, value.unwrap_or_default(), field));
+                }
+            }
+            if let (Some(deny), Some(v)) = (deny.as_deref(), value) {
+                if contains(deny, v) {
+                    return Some(format!("{} {:?} is in run.filters.{}_not_in", field, v, field));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Where cached artifacts, channel data and the manifest are stored
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheBackend {
+    /// Per-project directories with metadata.json under run.cache_dir
+    #[default]
+    Filesystem,
+    /// One SQLite database run.cache_dir/cache.sqlite3; an existing filesystem cache is imported on first use
+    Sqlite,
+}
+
+/// Layout of the filesystem cache under run.cache_dir
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheLayout {
+    /// Per-project directories; legacy flat files ({id}_metadata.json, {id}_extracted.md, {id}_summary.txt) are still read
+    #[default]
+    V1,
+    /// Per-project directories only, legacy flat files are ignored (migrated with `migrate-cache`)
+    V2,
+}
+
+/// Format of log events, both on the console and in --log-file
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// Compact human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per event; event fields (project_id, channel, error, ...) are top-level keys
+    Json,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SummarizerConfig {
+    pub length_guard: Option<LengthGuardConfig>, // контроль длины суммаризаций относительно лимита канала
+    pub on_unavailable: Option<OnUnavailable>,   // поведение при полной недоступности LLM
+    pub validate_on_start: Option<bool>,         // проверить LLM канареечным промптом до начала краулинга
+    pub strategy: Option<SummaryStrategy>,       // single — один вызов LLM, map_reduce — по частям для длинных документов
+    pub chunk_chars: Option<usize>,              // размер части документа для map_reduce в символах (по умолчанию 12000)
+    pub test_fixed_summary: Option<String>,      // тестовый режим: фиксированная суммаризация без вызова LLM
+    pub input_source: Option<SummaryInputSource>, // что суммаризировать: document | body | both
+    pub fallback: Option<SummaryFallback>,       // ошибка LLM после всех повторов: fail | truncate_source
+    pub requests_per_minute: Option<u32>,        // общий лимит вызовов LLM в минуту (все каналы и элементы, включая повторы)
+    pub prompt_template: Option<String>,         // Tera-шаблон промпта (важнее run.prompt_template), проверяется при запуске
+    pub language: Option<String>,                // язык суммаризации, переменная {{ language }} шаблона (по умолчанию ru)
+    pub stream: Option<bool>,                    // потоковый ответ LLM: run.summarization_timeout_secs — таймаут простоя, а не общий
+    pub max_input_tokens: Option<usize>,         // приблизительный бюджет токенов исходного текста в промпте
+    pub max_input_tokens_by_provider: Option<std::collections::BTreeMap<String, usize>>, // бюджет по llm.provider (важнее max_input_tokens)
+    pub input_tail_share: Option<f32>,           // доля бюджета для конца документа при обрезке, 0.0..=0.5 (по умолчанию 0)
+}
+
+/// Что возвращает Summarizer, если LLM не ответил после всех повторов
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryFallback {
+    /// Вернуть ошибку (дальше действует summarizer.on_unavailable)
+    #[default]
+    Fail,
+    /// Вернуть начало исходного текста, обрезанное по лимиту канала
+    TruncateSource,
+}
+
+/// Какой текст передается в LLM для суммаризации
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryInputSource {
+    /// Текст скачанного документа
+    #[default]
+    Document,
+    /// Структурированные поля элемента краулера (CrawlItem.body)
+    Body,
+    /// Поля элемента, затем текст документа
+    Both,
+}
+
+/// Как суммаризировать документ
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryStrategy {
+    /// Один вызов LLM по (выборке) документа
+    #[default]
+    Single,
+    /// Документ длиннее summarizer.chunk_chars делится на части; каждая часть кратко излагается
+    /// отдельно, итоговая суммаризация строится по изложениям частей
+    MapReduce,
+}
+
+/// Что делать с элементом, если LLM недоступен после всех повторов
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OnUnavailable {
+    /// Не публиковать (ошибка суммаризации прерывает обработку)
+    #[default]
+    Skip,
+    /// Публиковать пост по шаблону templates.no_summary_post без суммаризации
+    FallbackTemplate,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct LengthGuardConfig {
+    pub enabled: Option<bool>,
+    pub min_ratio: Option<f64>,     // нижняя граница среднего отношения длина/лимит
+    pub max_ratio: Option<f64>,     // верхняя граница среднего отношения длина/лимит
+    pub min_samples: Option<usize>, // сколько суммаризаций накопить перед проверкой
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct TemplatesConfig {
+    pub update_post: Option<String>, // Tera template for lightweight "stage update" reposts
+    pub no_summary_post: Option<String>, // Tera template for posts published when the LLM is unavailable
+    pub metadata_allow: Option<Vec<String>>, // only these metadata fields reach post templates (snake_case names)
+    pub metadata_deny: Option<Vec<String>>,  // metadata fields never exposed to post templates
+    pub redact_emails: Option<bool>,         // mask e-mail addresses in metadata values (default true)
+    pub project_id_format: Option<String>,   // pattern for {{ project_id_formatted }}: {project_id}, {project_id_grouped}
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct FilterConfig {
+    pub max_age_days: Option<u64>, // пропускать элементы, опубликованные (PublishDate) раньше N дней назад
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct CacheConfig {
+    pub compress: Option<bool>, // хранить extracted.md сжатым (extracted.md.gz)
+    pub in_progress_ttl_secs: Option<u64>, // время жизни маркера обработки проекта (0 = без маркеров)
+    pub on_unwritable: Option<OnUnwritableCache>, // поведение, если cache_dir недоступен для записи при запуске
+    pub share_documents: Option<bool>, // общий кэш документов по fileId (cache_dir/documents): общий документ скачивается один раз
+    pub externalize_large_fields: Option<bool>, // хранить длинные суммаризации и посты каналов в отдельных файлах
+    pub externalize_threshold_bytes: Option<usize>, // порог длины для externalize_large_fields (по умолчанию 4096)
+}
+
+/// HTTP-сервер проб живости и готовности (liveness/readiness)
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct HealthConfig {
+    pub enabled: Option<bool>, // по умолчанию true, если секция задана
+    pub bind: Option<String>,  // адрес сервера (по умолчанию 0.0.0.0:8080)
+    pub stall_timeout_secs: Option<u64>, // цикл обхода или элемент дольше — /healthz отвечает 503 (по умолчанию 3600)
+}
+
+/// Общий HTTP-клиент исходящих запросов (кроме LLM: у него свои llm.proxy и llm.request_timeout_secs)
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct HttpConfig {
+    pub proxy: Option<String>,      // прокси для всех запросов, например http://proxy.corp:3128
+    pub user_agent: Option<String>, // заголовок User-Agent (по умолчанию не отправляется)
+    pub timeout_secs: Option<u64>,  // общий таймаут запроса; таймауты crawler.* действуют поверх него
+}
+
+/// Что делать при запуске, если каталог кэша недоступен для записи
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OnUnwritableCache {
+    /// Завершить запуск с ошибкой: без кэша элементы публиковались бы повторно
+    #[default]
+    Fail,
+    /// Только предупредить в логе и продолжить
+    Warn,
+}
+
+/// Переопределения по каналам публикации
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ChannelsConfig {
+    pub telegram: Option<ChannelSettings>,
+    pub mastodon: Option<ChannelSettings>,
+    pub console: Option<ChannelSettings>,
+    pub file: Option<ChannelSettings>,
+    pub matrix: Option<ChannelSettings>,
+    pub slack: Option<ChannelSettings>,
+    pub webhook: Option<ChannelSettings>,
+    pub default_limit: Option<usize>, // лимит символов для канала без своего лимита (по умолчанию 300)
+}
+
+impl ChannelsConfig {
+    pub fn get(&self, channel: crate::models::channel::PublisherChannel) -> Option<&ChannelSettings> {
+        use crate::models::channel::PublisherChannel;
+        match channel {
+            PublisherChannel::Telegram => self.telegram.as_ref(),
+            PublisherChannel::Mastodon => self.mastodon.as_ref(),
+            PublisherChannel::Console => self.console.as_ref(),
+            PublisherChannel::File => self.file.as_ref(),
+            PublisherChannel::Matrix => self.matrix.as_ref(),
+            PublisherChannel::Slack => self.slack.as_ref(),
+            PublisherChannel::Webhook => self.webhook.as_ref(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ChannelSettings {
+    pub style: Option<String>, // стиль изложения суммаризации для канала (добавляется в промпт)
+    pub language: Option<String>, // язык суммаризации канала (ISO 639-1, например en), {{ language }} промпта
+    pub retry: Option<ChannelRetry>, // повторы публикации в канал при ошибке
+    pub on_partial: Option<OnPartial>, // fail | succeed_if_any: итог канала, если ошибка только у части адресатов
+    pub post_template: Option<String>, // Tera-шаблон поста канала вместо run.post_template
+}
+
+/// Итог публикации в канал с несколькими адресатами, если часть адресатов ответила ошибкой
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OnPartial {
+    /// Канал опубликован, только если успешны все адресаты
+    #[default]
+    Fail,
+    /// Канал опубликован, если успешен хотя бы один адресат
+    SucceedIfAny,
+}
+
+/// Повторы неудачной публикации в канал (channels.<name>.retry)
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ChannelRetry {
+    pub max_attempts: Option<u32>, // всего попыток, включая первую (по умолчанию 1 — без повторов)
+    pub backoff_secs: Option<u64>, // задержка перед первым повтором, удваивается с каждой попыткой (по умолчанию 1)
+}
+
+/// Повторы HTTP-запросов публикации (Telegram, Mastodon) при временных ошибках: 5xx, 429
+/// и ошибки соединения; таймауты — только у идемпотентных запросов. Прочие 4xx не повторяются
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct PublishRetryConfig {
+    pub max_attempts: Option<u32>,  // всего попыток, включая первую (по умолчанию 3)
+    pub base_delay_ms: Option<u64>, // задержка перед первым повтором (по умолчанию 500)
+    pub multiplier: Option<f64>,    // множитель задержки для каждого следующего повтора (по умолчанию 2.0)
+}
+
+/// Переопределения параметров запуска из командной строки (не читаются из YAML)
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    pub offset: Option<u32>, // фиксированный offset npalist вместо вычисленного по manifest
+    pub limit: Option<u32>,  // limit npalist вместо crawler.npalist.limit
+    pub print_prompt: bool,  // печатать промпт суммаризатора вместо вызова LLM, без публикации
+    pub dry_run: bool,       // логировать готовые посты вместо публикации, каналы не отмечаются опубликованными
+    pub log_format: Option<LogFormat>, // формат логов вместо run.log_format
+}
+
+impl RunOptions {
+    /// Проверяет значения из командной строки теми же правилами, что и конфиг
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(limit) = self.limit {
+            check_npalist_limit("--limit", limit)?;
+        }
+        Ok(())
+    }
 }
@@ -211,18 +211,38 @@ impl CreatedAt {
     pub fn into_inner(self) -> String {
         self.0
     }
+
+    /// Разбирает метку как RFC 3339; `None`, если строка повреждена
+    pub fn parse(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::parse_from_rfc3339(&self.0)
+            .ok()
+            .map(|t| t.with_timezone(&chrono::Utc))
+    }
 }
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct Manifest {
+    /// Общий min_published_project_id прежних версий: используется источником, у которого еще нет своего
     #[serde(default)]
     pub min_published_project_id: Option<u32>,
+    /// min_published_project_id по источникам (`Crawler::source_id`)
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub min_published_project_ids: std::collections::BTreeMap<String, u32>,
+    /// Offset следующей страницы истории по источникам (`Crawler::source_id`), на котором остановился
+    /// запуск при crawler.history_pages_per_run; следующий запуск продолжает с него
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub history_offsets: std::collections::BTreeMap<String, u32>,
 }
 
 impl Manifest {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// min_published_project_id источника, для источника без своего значения — общее значение прежних версий
+    pub fn min_published_for(&self, source_id: &str) -> Option<u32> {
+        self.min_published_project_ids.get(source_id).copied().or(self.min_published_project_id)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -232,6 +252,11 @@ pub struct CrawlItem {
     pub body: String,
     pub project_id: Option<String>,
     pub metadata: Vec<MetadataItem>,
+    /// Метка источника (crawler.npalist.label), в шаблонах — {{ source_label }}
+    pub source_label: Option<String>,
+    /// Источник элемента (`Crawler::source_id`): ключ состояния источника в manifest.json.
+    /// Для элементов не от краулера (встраивание, backfill) manifest не обновляется
+    pub source_id: Option<String>,
 }
 
 #[derive(Clone, Debug, StrumDisplay, Serialize, Deserialize)]
@@ -271,9 +296,13 @@ pub enum MetadataItem {
     CompliteNumberDepAct(String),
     CompliteNumberRegAct(String),
     ParallelStageFiles(Vec<String>),
+    /// Имена файлов параллельной стадии (по порядку ParallelStageFiles), crawler.resolve_parallel_stage_files
+    ParallelStageFileNames(Vec<String>),
+    /// URL скачивания файлов параллельной стадии (по порядку ParallelStageFiles)
+    ParallelStageFileUrls(Vec<String>),
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct CacheMetadata {
     pub project_id: ProjectId,
     pub docx_path: DocxPath,
@@ -285,12 +314,363 @@ pub struct CacheMetadata {
     pub channel_posts: std::collections::HashMap<crate::models::channel::PublisherChannel, PostText>,     // channel -> post_text
     // Метаданные из NpaListCrawler
     pub crawl_metadata: Vec<MetadataItem>,
+    // Хэш исходного документа (sha256) для обнаружения изменений
+    #[serde(default)]
+    pub document_hash: Option<String>,
+    // Причина, по которой элемент пропущен без публикации (например, фильтр по возрасту)
+    #[serde(default)]
+    pub skip_reason: Option<String>,
+    // Число попыток обработки, прерванных run.item_timeout_secs; после run.item_timeout_max_attempts
+    // элемент записывается как пропущенный
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub timeout_attempts: u32,
+    // HTTP-валидаторы скачанного документа (для crawler.head_before_get)
+    #[serde(default)]
+    pub document_validators: Option<DocumentValidators>,
+    // Модель LLM (llm.model), которой сделаны суммаризации каналов; часть ключа кэша суммаризаций
+    #[serde(default)]
+    pub summary_model: Option<String>,
+    // Суммаризации и посты, вынесенные в файлы каталога проекта (cache.externalize_large_fields):
+    // канал -> имя файла. При загрузке тексты подставляются обратно в channel_summaries/channel_posts
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub external_summaries: std::collections::HashMap<crate::models::channel::PublisherChannel, String>,
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub external_posts: std::collections::HashMap<crate::models::channel::PublisherChannel, String>,
+    // Результаты последней публикации по адресатам каналов с несколькими адресатами
+    // (чаты Telegram, файлы канала File): канал -> адресат -> успех
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub target_results: std::collections::HashMap<crate::models::channel::PublisherChannel, std::collections::BTreeMap<String, bool>>,
+    // Хэш (sha256) опубликованного поста по каналам: канал отмечен опубликованным и пост не изменился —
+    // повторная публикация не нужна; изменившийся пост (например, после правки шаблона) публикуется снова
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub content_hash: std::collections::HashMap<crate::models::channel::PublisherChannel, String>,
+    // Оценки блока «Рейтинг» из суммаризаций каналов (пересчитываются из channel_summaries при записи)
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub summary_ratings: std::collections::HashMap<crate::models::channel::PublisherChannel, SummaryRatings>,
+    // Статусы поста, опубликованного цепочкой ответов (mastodon.thread_long_posts); записываются
+    // по мере публикации частей, чтобы повтор продолжил цепочку, а не публиковал ее заново
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub threads: std::collections::HashMap<crate::models::channel::PublisherChannel, ThreadProgress>,
+    // Заголовок элемента из источника; по нему backfill публикует проект без обращения к краулеру
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+/// Опубликованные части цепочки статусов одного поста
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreadProgress {
+    /// Хэш поста (content_hash): цепочка другого текста не продолжается
+    pub content_hash: String,
+    /// id опубликованных статусов по порядку; первый — корень цепочки
+    pub status_ids: Vec<String>,
+}
+
+/// Оценки блока «Рейтинг» суммаризации (N/10); не найденная оценка — `None`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SummaryRatings {
+    pub usefulness: Option<u8>,
+    pub repressiveness: Option<u8>,
+    pub corruption: Option<u8>,
+}
+
+/// Суммаризация, разобранная на текст и оценки блока «Рейтинг»
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SummaryParsed {
+    // Текст суммаризации без заголовка «Рейтинг» и строк оценок
+    pub summary: String,
+    pub ratings: SummaryRatings,
+}
+
+/// Оценка в строке рейтинга: «5/10» или «: 5»
+static SCORE_RE: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
+    regex::Regex::new(r"(\d{1,2})\s*/\s*\d{1,2}|:\W*(\d{1,2})").unwrap()
+});
+
+impl SummaryParsed {
+    /// Разбирает ответ LLM: строки «Полезность», «Репрессивность» и «Коррупц. емкость»
+    /// (также «Коррупционная емкость») дают оценки, они и заголовок «Рейтинг» убираются из текста.
+    /// Не найденная оценка остается `None`
+    pub fn parse(text: &str) -> Self {
+        let mut parsed = SummaryParsed::default();
+        let mut kept = Vec::new();
+        for line in text.lines() {
+            let key = line
+                .trim()
+                .trim_start_matches(|c: char| c == '-' || c == '*' || c == '•' || c == '#' || c.is_whitespace())
+                .to_lowercase();
+            if key.trim_end_matches(|c: char| c == ':' || c == '*' || c.is_whitespace()) == "рейтинг" {
+                continue;
+            }
+            let slot = if key.starts_with("полезн") {
+                &mut parsed.ratings.usefulness
+            } else if key.starts_with("репрессивн") {
+                &mut parsed.ratings.repressiveness
+            } else if key.starts_with("коррупц") {
+                &mut parsed.ratings.corruption
+            } else {
+                kept.push(line);
+                continue;
+            };
+            let score = SCORE_RE
+                .captures(line)
+                .and_then(|c| c.get(1).or_else(|| c.get(2)))
+                .and_then(|m| m.as_str().parse().ok());
+            match score {
+                Some(score) => *slot = Some(score),
+                None => kept.push(line),
+            }
+        }
+        parsed.summary = kept.join("\n").trim().to_string();
+        parsed
+    }
+}
+
+/// HTTP-валидаторы документа: ETag, Last-Modified и Content-Length ответа на скачивание
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocumentValidators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub content_length: Option<u64>,
+}
+
+impl DocumentValidators {
+    /// Валидаторы из заголовков HTTP-ответа
+    pub fn from_headers(headers: &reqwest::header::HeaderMap) -> Self {
+        let text = |name: reqwest::header::HeaderName| {
+            headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string)
+        };
+        Self {
+            etag: text(reqwest::header::ETAG),
+            last_modified: text(reqwest::header::LAST_MODIFIED),
+            content_length: text(reqwest::header::CONTENT_LENGTH).and_then(|v| v.parse().ok()),
+        }
+    }
+
+    /// Документ не изменился: совпадает ETag, а без ETag — Last-Modified и Content-Length.
+    /// Если сравнивать нечего, документ считается измененным
+    pub fn unchanged_since(&self, cached: &DocumentValidators) -> bool {
+        if let (Some(a), Some(b)) = (&self.etag, &cached.etag) {
+            return a == b;
+        }
+        match (&self.last_modified, &cached.last_modified) {
+            (Some(a), Some(b)) => a == b && self.content_length == cached.content_length,
+            _ => false,
+        }
+    }
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+impl CacheMetadata {
+    /// Пустые метаданные проекта (используются, когда metadata.json отсутствует или поврежден)
+    pub fn empty(project_id: &str) -> Self {
+        Self {
+            project_id: project_id.to_string().into(),
+            docx_path: String::new().into(),
+            markdown_path: String::new().into(),
+            published_channels: vec![],
+            created_at: chrono::Utc::now().to_rfc3339().into(),
+            channel_summaries: std::collections::HashMap::new(),
+            channel_posts: std::collections::HashMap::new(),
+            crawl_metadata: vec![],
+            document_hash: None,
+            skip_reason: None,
+            timeout_attempts: 0,
+            document_validators: None,
+            summary_model: None,
+            external_summaries: std::collections::HashMap::new(),
+            external_posts: std::collections::HashMap::new(),
+            target_results: std::collections::HashMap::new(),
+            content_hash: std::collections::HashMap::new(),
+            summary_ratings: std::collections::HashMap::new(),
+            threads: std::collections::HashMap::new(),
+            title: None,
+        }
+    }
+
+    /// Пересчитывает summary_ratings из суммаризаций каналов
+    pub fn refresh_summary_ratings(&mut self) {
+        self.summary_ratings = self
+            .channel_summaries
+            .iter()
+            .map(|(channel, summary)| (*channel, SummaryParsed::parse(summary.as_str()).ratings))
+            .collect();
+    }
+
+    /// Заменяет неразбираемый created_at на `fallback`; возвращает true, если метка исправлена
+    pub fn repair_created_at(&mut self, fallback: chrono::DateTime<chrono::Utc>) -> bool {
+        if self.created_at.parse().is_some() {
+            return false;
+        }
+        self.created_at = fallback.to_rfc3339().into();
+        true
+    }
+
+    /// Возраст записи относительно `now` (метки из будущего дают нулевой возраст);
+    /// `None`, если created_at не разбирается
+    pub fn age(&self, now: chrono::DateTime<chrono::Utc>) -> Option<chrono::Duration> {
+        self.created_at
+            .parse()
+            .map(|created| (now - created).max(chrono::Duration::zero()))
+    }
+
+    /// Значение стадии проекта из сохраненных метаданных краулера
+    pub fn stage(&self) -> Option<&str> {
+        self.crawl_metadata.iter().find_map(|m| match m {
+            MetadataItem::Stage(v) => Some(v.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Дата публикации проекта (PublishDate) из сохраненных метаданных краулера, приведенная к UTC
+    pub fn publish_date(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.crawl_metadata.iter().find_map(|m| match m {
+            MetadataItem::PublishDate(v) => parse_date(v),
+            _ => None,
+        })
+    }
+}
+
+/// Выборка проектов кэша: диапазон project_id и даты публикации (PublishDate), границы включительно.
+/// Незаданная граница не ограничивает выборку
+#[derive(Debug, Clone, Default)]
+pub struct CacheSelection {
+    pub from: Option<u32>,
+    pub to: Option<u32>,
+    pub since: Option<chrono::NaiveDate>,
+    pub until: Option<chrono::NaiveDate>,
+}
+
+impl CacheSelection {
+    /// Проверяет, что проект попадает в выборку. При границах по id проекты с нечисловым id
+    /// не подходят, при границах по дате — проекты без PublishDate
+    pub fn matches(&self, meta: &CacheMetadata) -> bool {
+        if self.from.is_some() || self.to.is_some() {
+            let (lo, hi) = (self.from.unwrap_or(0), self.to.unwrap_or(u32::MAX));
+            match meta.project_id.as_str().parse::<u32>() {
+                Ok(id) if (lo.min(hi)..=lo.max(hi)).contains(&id) => {}
+                _ => return false,
+            }
+        }
+        if self.since.is_some() || self.until.is_some() {
+            let Some(date) = meta.publish_date().map(|d| d.date_naive()) else {
+                return false;
+            };
+            if self.since.is_some_and(|since| date < since) || self.until.is_some_and(|until| date > until) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl CrawlItem {
+    /// Значение стадии проекта из метаданных элемента
+    pub fn stage(&self) -> Option<&str> {
+        self.metadata.iter().find_map(|m| match m {
+            MetadataItem::Stage(v) => Some(v.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Дата публикации проекта (PublishDate), приведенная к UTC
+    pub fn publish_date(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.metadata.iter().find_map(|m| match m {
+            MetadataItem::PublishDate(v) => parse_date(v),
+            _ => None,
+        })
+    }
+}
+
+/// Нормализует дату из источника: RFC 3339 (`2025-09-20T17:07:27.95Z`), RFC 2822 (pubDate RSS),
+/// дата-время без зоны (считается UTC), `YYYY-MM-DD` или `DD.MM.YYYY`
+pub fn parse_date(s: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+    let s = s.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc2822(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    if let Ok(dt) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f") {
+        return Some(dt.and_utc());
+    }
+    ["%Y-%m-%d", "%d.%m.%Y"]
+        .iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(s, fmt).ok())
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc())
+}
+
+/// Код выхода `--once`, если за запуск ничего не опубликовано
+pub const NOTHING_NEW_EXIT_CODE: i32 = 3;
+
+/// Итог запуска: сколько проектов опубликовано
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunOutcome {
+    /// Опубликован хотя бы один проект (число проектов, опубликованных хотя бы в один канал)
+    Published(usize),
+    /// Новых публикаций нет
+    NothingNew,
+}
+
+impl RunOutcome {
+    pub fn from_published(projects: usize) -> Self {
+        if projects > 0 { RunOutcome::Published(projects) } else { RunOutcome::NothingNew }
+    }
+
+    /// Код выхода процесса для `--once`: 0 — опубликовано, NOTHING_NEW_EXIT_CODE — ничего нового
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            RunOutcome::Published(_) => 0,
+            RunOutcome::NothingNew => NOTHING_NEW_EXIT_CODE,
+        }
+    }
+}
+
+/// Префикс синтетических идентификаторов проектов, построенных по URL
+pub const SYNTHETIC_PROJECT_ID_PREFIX: &str = "url-";
+
+/// Стабильный синтетический project_id для элементов без идентификатора (по хэшу URL)
+pub fn synthetic_project_id(url: &str) -> String {
+    format!("{}{}", SYNTHETIC_PROJECT_ID_PREFIX, &content_hash(url.trim().as_bytes())[..16])
+}
+
+/// Проверяет, что project_id синтетический (построен по URL, а не получен от источника)
+pub fn is_synthetic_project_id(project_id: &str) -> bool {
+    project_id.starts_with(SYNTHETIC_PROJECT_ID_PREFIX)
+}
+
+/// Стабильный хэш содержимого (sha256 в hex)
+pub fn content_hash(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn document_validators_compare_etag_then_last_modified() {
+        let cached = DocumentValidators {
+            etag: Some("\"v1\"".to_string()),
+            last_modified: Some("Sat, 20 Sep 2025 10:00:00 GMT".to_string()),
+            content_length: Some(100),
+        };
+        assert!(cached.clone().unchanged_since(&cached));
+        let new_etag = DocumentValidators { etag: Some("\"v2\"".to_string()), ..cached.clone() };
+        assert!(!new_etag.unchanged_since(&cached));
+        let no_etag = DocumentValidators { etag: None, ..cached.clone() };
+        assert!(no_etag.unchanged_since(&cached));
+        let resized = DocumentValidators { etag: None, content_length: Some(101), ..cached.clone() };
+        assert!(!resized.unchanged_since(&cached));
+        assert!(!DocumentValidators::default().unchanged_since(&DocumentValidators::default()));
+    }
+
     #[test]
     fn test_project_id() {
         let id = ProjectId::from("test-project");
@@ -329,4 +709,23 @@ mod tests {
         let summary_from_str: SummaryText = "Test summary".parse().unwrap();
         assert_eq!(summary_from_str, summary);
     }
+
+    #[test]
+    fn test_synthetic_project_id_is_stable() {
+        let id = synthetic_project_id("https://example.org/news/1");
+        assert_eq!(id, synthetic_project_id(" https://example.org/news/1 "));
+        assert_ne!(id, synthetic_project_id("https://example.org/news/2"));
+        assert!(is_synthetic_project_id(&id));
+        assert!(!is_synthetic_project_id("160532"));
+        assert_eq!(id.len(), SYNTHETIC_PROJECT_ID_PREFIX.len() + 16);
+    }
+
+    #[test]
+    fn test_parse_date_formats() {
+        let expected = chrono::NaiveDate::from_ymd_opt(2025, 9, 20).unwrap();
+        for s in ["2025-09-20T17:07:27.95Z", "2025-09-20T17:07:27", "2025-09-20", "20.09.2025", " 2025-09-20T20:07:27+03:00 "] {
+            assert_eq!(parse_date(s).map(|d| d.date_naive()), Some(expected), "{}", s);
+        }
+        assert_eq!(parse_date("вчера"), None);
+    }
 }
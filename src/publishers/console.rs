@@ -10,7 +10,7 @@ pub struct ConsolePublisher {
 
 #[async_trait]
 impl Publisher for ConsolePublisher {
-    fn name(&self) -> &str { "console" }
+    fn name(&self) -> &'static str { "console" }
     async fn publish(&self, title: &str, url: &str, text: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
         let final_text = if let Some(maxc) = self.max_chars { trim_with_ellipsis(text, maxc) } else { text.to_string() };
         #[cfg(test)]
@@ -2,28 +2,53 @@ use async_trait::async_trait;
 use std::error::Error;
 
 use super::utils::trim_with_ellipsis;
+use crate::models::config::{FileFormat, LineEnding};
 use crate::traits::publisher::Publisher;
 
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
 pub struct FilePublisher {
     pub path: String,
     pub max_chars: Option<usize>,
     pub append: bool,
+    pub format: FileFormat,
+    /// UTF-8 BOM в начале файла (пишется, только если файл новый или пустой)
+    pub bom: bool,
+    pub line_ending: LineEnding,
 }
 
 #[async_trait]
 impl Publisher for FilePublisher {
-    fn name(&self) -> &str { "file" }
-    async fn publish(&self, _title: &str,_urll: &str, text: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
-        let final_text = if let Some(maxc) = self.max_chars { trim_with_ellipsis(text, maxc) } else { text.to_string() };
+    fn name(&self) -> &'static str { "file" }
+    async fn publish(&self, title: &str, url: &str, text: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let trimmed = if let Some(maxc) = self.max_chars { trim_with_ellipsis(text, maxc) } else { text.to_string() };
+        let final_text = match self.format {
+            FileFormat::Text => trimmed,
+            FileFormat::Jsonl => serde_json::json!({
+                "title": title,
+                "url": url,
+                "text": trimmed,
+                "published_at": chrono::Utc::now().to_rfc3339(),
+            })
+            .to_string(),
+        };
+        let record = format!("{}\n", final_text);
+        let record = match self.line_ending {
+            LineEnding::Lf => record,
+            LineEnding::Crlf => record.replace("\r\n", "\n").replace('\n', "\r\n"),
+        };
         let p = std::path::Path::new(&self.path);
         if let Some(parent) = p.parent() { let _ = std::fs::create_dir_all(parent); }
-        if self.append {
-            use std::io::Write;
-            let mut f = std::fs::OpenOptions::new().create(true).append(true).open(p)?;
-            writeln!(f, "{}", final_text)?;
+        use std::io::Write;
+        let mut f = if self.append {
+            std::fs::OpenOptions::new().create(true).append(true).open(p)?
         } else {
-            std::fs::write(p, format!("{}\n", final_text))?;
+            std::fs::File::create(p)?
+        };
+        if self.bom && f.metadata()?.len() == 0 {
+            f.write_all(UTF8_BOM)?;
         }
+        f.write_all(record.as_bytes())?;
         Ok(())
     }
 }
@@ -12,6 +12,7 @@ use tracing::{error, info};
 use bon::Builder;
 use async_trait::async_trait;
 use crate::traits::publisher::Publisher;
+use crate::models::config::MastodonApiFlavor;
 
 #[derive(Builder)]
 pub struct MastodonPublisher {
@@ -24,9 +25,79 @@ pub struct MastodonPublisher {
     #[builder(default = false)]
     pub sensitive: bool,
     pub max_chars: Option<usize>,
+    #[builder(default)]
+    pub api_flavor: MastodonApiFlavor,
+    /// Вес ссылки в лимите max_chars (None — ссылки считаются полной длиной)
+    pub link_chars: Option<usize>,
+    #[builder(default)]
+    pub trim_on_word_boundary: bool, // run.trim_on_word_boundary
+    #[builder(default)]
+    pub thread_long_posts: bool, // mastodon.thread_long_posts
+    #[builder(default)]
+    pub retry: super::utils::HttpRetryPolicy, // run.publish_retry
+    /// Интервал опроса /api/v1/media/:id, пока сервер обрабатывает вложение
+    #[builder(default = MEDIA_POLL_INTERVAL)]
+    pub media_poll_interval: std::time::Duration,
+}
+
+/// Лимит длины статуса, если max_chars не задан (лимит Mastodon по умолчанию)
+pub const MASTODON_STATUS_MAX_CHARS: usize = 500;
+
+/// Интервал опроса обработки вложения по умолчанию
+pub const MEDIA_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Сколько раз опрашивать обработку вложения, прежде чем отказаться от него
+const MEDIA_POLL_ATTEMPTS: u32 = 30;
+
+/// Наибольший размер вложения (лимит изображений Mastodon по умолчанию); файл больше не скачивается
+pub const MEDIA_MAX_BYTES: usize = 16 * 1024 * 1024;
+
+/// MIME-тип вложения, если его примет /api/v2/media: Mastodon принимает только изображения,
+/// видео и аудио, документы (DOCX, PDF) сервер отклоняет. Тип берется из Content-Type,
+/// а для неизвестного типа — по сигнатуре изображения
+pub fn media_type(content_type: Option<&str>, bytes: &[u8]) -> Option<String> {
+    let declared = content_type
+        .and_then(|ct| ct.split(';').next())
+        .map(|ct| ct.trim().to_ascii_lowercase())
+        .filter(|ct| ["image/", "video/", "audio/"].iter().any(|p| ct.starts_with(p)));
+    if declared.is_some() {
+        return declared;
+    }
+    let sniffed = if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        "image/png"
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        "image/jpeg"
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        "image/gif"
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        "image/webp"
+    } else {
+        return None;
+    };
+    Some(sniffed.to_string())
+}
+
+/// Ответ /api/v1/statuses (нужные поля)
+#[derive(Debug, serde::Deserialize)]
+struct StatusResponse {
+    id: String,
+}
+
+/// Ответ /api/v2/media и /api/v1/media/:id (нужные поля)
+#[derive(Debug, serde::Deserialize)]
+struct MediaAttachment {
+    id: String,
+    url: Option<String>,
 }
 
 impl MastodonPublisher {
+    /// Параметры, которые требует диалект сервера (mastodon.api_flavor)
+    fn push_flavor_params(&self, body: &mut Vec<(&str, String)>) {
+        match self.api_flavor {
+            MastodonApiFlavor::Mastodon => {}
+            MastodonApiFlavor::Pleroma => body.push(("content_type", "text/plain".to_string())),
+        }
+    }
 
     pub async fn post_status(
         &self,
@@ -39,13 +110,17 @@ impl MastodonPublisher {
         if let Some(v) = visibility {
             body.push(("visibility", v.to_string()));
         }
-        let res = self
-            .client
-            .post(&url)
-            .bearer_auth(&self.access_token)
-            .form(&body)
-            .send()
-            .await?;
+        self.push_flavor_params(&mut body);
+        // Один и тот же статус сервер создает не больше одного раза: повтор после таймаута не дублирует пост
+        let idempotency_key = crate::models::types::content_hash(status.as_bytes());
+        let res = super::utils::send_with_retry(&self.retry, || {
+            self.client
+                .post(&url)
+                .bearer_auth(&self.access_token)
+                .header(super::utils::IDEMPOTENCY_KEY_HEADER, &idempotency_key)
+                .form(&body)
+        })
+        .await?;
         let code = res.status();
         let text = res.text().await.unwrap_or_default();
         if code.is_success() {
@@ -57,6 +132,67 @@ impl MastodonPublisher {
         }
     }
 
+    /// Загружает вложение (`mime_type` — см. `media_type`) через POST /api/v2/media и возвращает его id.
+    /// Если сервер ответил 202 (обработка идет асинхронно), опрашивает /api/v1/media/:id, пока у вложения не появится url
+    pub async fn upload_media(
+        &self,
+        file_name: &str,
+        mime_type: &str,
+        bytes: Vec<u8>,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let base = self.base_url.trim_end_matches('/');
+        let url = format!("{}/api/v2/media", base);
+        info!(url = %url, file_name = %file_name, mime_type = %mime_type, size = bytes.len(), "mastodon: upload_media");
+        // Проверяем MIME-тип заранее: форма собирается заново для каждой попытки
+        reqwest::multipart::Part::bytes(Vec::new()).mime_str(mime_type)?;
+        // multipart-форма не клонируется: для каждой попытки собирается заново
+        let form = || {
+            let part = reqwest::multipart::Part::bytes(bytes.clone())
+                .file_name(file_name.to_string())
+                .mime_str(mime_type)
+                .expect("mime type validated above");
+            reqwest::multipart::Form::new().part("file", part)
+        };
+        let res = super::utils::send_with_retry(&self.retry, || {
+            self.client.post(&url).bearer_auth(&self.access_token).multipart(form())
+        })
+        .await?;
+        let code = res.status();
+        if !code.is_success() {
+            let text = res.text().await.unwrap_or_default();
+            error!(status = %code, body = %text, "mastodon: upload_media error");
+            return Err(format!("Mastodon media upload error: {}", code).into());
+        }
+        let media: MediaAttachment = res.json().await?;
+        if code != reqwest::StatusCode::ACCEPTED {
+            return Ok(media.id);
+        }
+
+        let media_url = format!("{}/api/v1/media/{}", base, media.id);
+        for attempt in 1..=MEDIA_POLL_ATTEMPTS {
+            tokio::time::sleep(self.media_poll_interval).await;
+            let res = self.client.get(&media_url).bearer_auth(&self.access_token).send().await?;
+            let code = res.status();
+            // 206 Partial Content — вложение еще обрабатывается
+            if code == reqwest::StatusCode::PARTIAL_CONTENT {
+                info!(media_id = %media.id, attempt = attempt, "mastodon: media is still processing");
+                continue;
+            }
+            if !code.is_success() {
+                return Err(format!("Mastodon media status error: {}", code).into());
+            }
+            let polled: MediaAttachment = res.json().await?;
+            if polled.url.is_some() {
+                info!(media_id = %polled.id, attempt = attempt, "mastodon: media processed");
+                return Ok(polled.id);
+            }
+        }
+        Err(format!("Mastodon media {} was not processed after {} checks", media.id, MEDIA_POLL_ATTEMPTS).into())
+    }
+
+    /// Публикует статус (ответом на `in_reply_to_id`, если он задан) и возвращает id созданного статуса,
+    /// если сервер его вернул
+    #[allow(clippy::too_many_arguments)]
     pub async fn post_status_advanced(
         &self,
         status: &str,
@@ -64,9 +200,17 @@ impl MastodonPublisher {
         language: Option<Language>,
         spoiler_text: Option<&str>,
         sensitive: bool,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        media_ids: &[String],
+        in_reply_to_id: Option<&str>,
+    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
         let url = format!("{}/api/v1/statuses", self.base_url.trim_end_matches('/'));
         let mut body: Vec<(&str, String)> = vec![("status", status.to_string())];
+        for id in media_ids {
+            body.push(("media_ids[]", id.clone()));
+        }
+        if let Some(id) = in_reply_to_id {
+            body.push(("in_reply_to_id", id.to_string()));
+        }
         if let Some(v) = visibility {
             body.push(("visibility", v.to_string()));
         }
@@ -83,19 +227,24 @@ impl MastodonPublisher {
         if sensitive {
             body.push(("sensitive", "true".to_string()));
         }
-        info!(url = %url, text_len = status.len(), visibility = ?visibility, language = ?language, spoiler = ?spoiler_text, sensitive = sensitive, "mastodon: post_status_advanced");
-        let res = self
-            .client
-            .post(&url)
-            .bearer_auth(&self.access_token)
-            .form(&body)
-            .send()
-            .await?;
+        self.push_flavor_params(&mut body);
+        info!(url = %url, text_len = status.len(), visibility = ?visibility, language = ?language, spoiler = ?spoiler_text, sensitive = sensitive, flavor = ?self.api_flavor, in_reply_to_id = ?in_reply_to_id, "mastodon: post_status_advanced");
+        // Один и тот же статус (текст и адресат ответа) сервер создает не больше одного раза:
+        // повтор после таймаута не дублирует пост
+        let idempotency_key = crate::models::types::content_hash(format!("{}\n{}", in_reply_to_id.unwrap_or(""), status).as_bytes());
+        let res = super::utils::send_with_retry(&self.retry, || {
+            self.client
+                .post(&url)
+                .bearer_auth(&self.access_token)
+                .header(super::utils::IDEMPOTENCY_KEY_HEADER, &idempotency_key)
+                .form(&body)
+        })
+        .await?;
         let code = res.status();
         let text = res.text().await.unwrap_or_default();
         if code.is_success() {
             info!(status = %code, body = %text, "mastodon: post_status_advanced ok");
-            Ok(())
+            Ok(serde_json::from_str::<StatusResponse>(&text).ok().map(|s| s.id))
         } else {
             error!(status = %code, body = %text, "mastodon: post_status_advanced error");
             Err(format!("Mastodon error: {}", code).into())
@@ -105,25 +254,84 @@ impl MastodonPublisher {
 
 #[async_trait]
 impl Publisher for MastodonPublisher {
-    fn name(&self) -> &str { "mastodon" }
+    fn name(&self) -> &'static str { "mastodon" }
     async fn publish(&self, _title: &str, _url: &str, text: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let cut = if let Some(maxc) = self.max_chars { 
-            super::utils::trim_with_ellipsis(text, maxc) 
-        } else { 
-            text.to_string() 
-        };
+        self.publish_with_media(text, &[]).await.map(|_| ())
+    }
+}
+
+impl MastodonPublisher {
+    /// Статусы одного поста: с `thread_long_posts` длинный пост делится по абзацам на части
+    /// не длиннее max_chars (по умолчанию MASTODON_STATUS_MAX_CHARS), иначе обрезается до max_chars
+    pub fn status_chunks(&self, text: &str) -> Vec<String> {
+        let limit = self.max_chars.unwrap_or(MASTODON_STATUS_MAX_CHARS);
+        if self.thread_long_posts && super::utils::counted_chars(text, self.link_chars) > limit {
+            super::utils::split_into_chunks(text, limit)
+        } else {
+            // Mastodon засчитывает любую ссылку как link_chars символов (по умолчанию MASTODON_LINK_CHARS)
+            vec![super::utils::fit_to_limit(text, self.max_chars, self.link_chars, self.trim_on_word_boundary)]
+        }
+    }
+
+    /// Публикует пост с вложениями, загруженными через `upload_media`. Пост из нескольких частей
+    /// (`thread_long_posts`) публикуется цепочкой: каждая следующая часть — ответ на предыдущую,
+    /// вложения прикрепляются к первой. Возвращает id первого статуса (корня цепочки), если сервер его вернул
+    pub async fn publish_with_media(&self, text: &str, media_ids: &[String]) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let ids = self.publish_thread(text, media_ids, &[], |_| async {}).await?;
+        Ok(ids.into_iter().next())
+    }
+
+    /// Как `publish_with_media`, но продолжает цепочку: первые `posted.len()` частей уже опубликованы
+    /// статусами `posted`, следующая публикуется ответом на последний из них. После каждой части
+    /// `on_posted` получает id всех опубликованных статусов цепочки. Возвращает эти id
+    pub async fn publish_thread<F, Fut>(
+        &self,
+        text: &str,
+        media_ids: &[String],
+        posted: &[String],
+        mut on_posted: F,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        F: FnMut(Vec<String>) -> Fut,
+        Fut: std::future::Future<Output = ()>,
+    {
+        let chunks = self.status_chunks(text);
         let lang = self.language.as_deref().unwrap_or("ru");
         let lang = Language::from_639_1(lang);
         let vis = self.visibility.as_deref();
         let spoiler = self.spoiler_text.as_deref().filter(|s| !s.is_empty());
         info!(
-            text_len = cut.len(), visibility = ?vis, language = ?self.language, spoiler = ?spoiler,
-            sensitive = self.sensitive, "mastodon: publish start"
+            text_len = text.len(), statuses = chunks.len(), visibility = ?vis, language = ?self.language, spoiler = ?spoiler,
+            sensitive = self.sensitive, media = media_ids.len(), "mastodon: publish start"
         );
-        match self.post_status_advanced(&cut, vis, lang, spoiler, self.sensitive).await {
-            Ok(()) => { info!("mastodon: publish success"); Ok(()) }
-            Err(e) => { error!(error = %e, "mastodon: publish failed"); Err(e) }
+        let mut status_ids = posted.to_vec();
+        let mut reply_to = posted.last().cloned();
+        if !posted.is_empty() {
+            info!(published = posted.len(), total = chunks.len(), reply_to = ?reply_to, "mastodon: resuming thread");
+        }
+        for (i, chunk) in chunks.iter().enumerate().skip(posted.len()) {
+            if i > 0 && reply_to.is_none() {
+                let e = "Mastodon response has no status id, cannot continue the thread";
+                error!(published = i, total = chunks.len(), "mastodon: {}", e);
+                return Err(e.into());
+            }
+            let media = if i == 0 { media_ids } else { &[] };
+            match self.post_status_advanced(chunk, vis, lang, spoiler, self.sensitive, media, reply_to.as_deref()).await {
+                Ok(id) => {
+                    if let Some(id) = &id {
+                        status_ids.push(id.clone());
+                        on_posted(status_ids.clone()).await;
+                    }
+                    reply_to = id;
+                }
+                Err(e) => {
+                    error!(error = %e, published = i, total = chunks.len(), "mastodon: publish failed");
+                    return Err(e);
+                }
+            }
         }
+        info!(root_id = ?status_ids.first(), statuses = chunks.len(), "mastodon: publish success");
+        Ok(status_ids)
     }
 }
 
@@ -0,0 +1,128 @@
+use async_trait::async_trait;
+use bon::Builder;
+use reqwest::Client;
+use tracing::{error, info};
+
+use super::utils::{escape_html, fit_to_limit};
+use crate::models::types::content_hash;
+use crate::traits::publisher::Publisher;
+
+/// Публикация в комнату Matrix через Client-Server API (m.room.message)
+#[derive(Builder)]
+pub struct MatrixPublisher {
+    pub client: Client,
+    pub homeserver_url: String,
+    pub access_token: String,
+    pub room_id: String,
+    pub max_chars: Option<usize>,
+    #[builder(default)]
+    pub trim_on_word_boundary: bool, // run.trim_on_word_boundary
+    #[builder(default)]
+    pub retry: super::utils::HttpRetryPolicy, // run.publish_retry
+}
+
+/// Тело события m.room.message с HTML-вариантом текста
+#[derive(Debug, serde::Serialize)]
+struct RoomMessage<'a> {
+    msgtype: &'a str,
+    body: &'a str,
+    format: &'a str,
+    formatted_body: String,
+}
+
+impl MatrixPublisher {
+    /// Идентификатор транзакции из комнаты, project_id и хэша текста: повторы запроса и повторная
+    /// публикация того же поста (channels.<name>.retry, следующий цикл) идут с тем же txnId,
+    /// и сервер не создаст дубликат сообщения; измененный пост или другая комната получают новый txnId
+    pub fn txn_id(room_id: &str, project_id: &str, text: &str) -> String {
+        let hash = content_hash(format!("{}\n{}\n{}", room_id, project_id, text).as_bytes());
+        format!("luminis-{}", &hash[..32])
+    }
+
+    /// Публикует пост проекта `project_id`, обрезанный до max_chars, с txnId по project_id и тексту
+    pub async fn publish_project(&self, project_id: &str, text: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let text = fit_to_limit(text, self.max_chars, None, self.trim_on_word_boundary);
+        self.send_message(&text, &Self::txn_id(&self.room_id, project_id, &text)).await
+    }
+
+    /// Отправляет текст в комнату с транзакцией `txn_id`
+    pub async fn send_message(&self, text: &str, txn_id: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let url = format!(
+            "{}/_matrix/client/v3/rooms/{}/send/m.room.message/{}",
+            self.homeserver_url.trim_end_matches('/'),
+            urlencoding::encode(&self.room_id),
+            urlencoding::encode(txn_id)
+        );
+        let message = RoomMessage {
+            msgtype: "m.text",
+            body: text,
+            format: "org.matrix.custom.html",
+            formatted_body: to_html(text),
+        };
+        info!(room_id = %self.room_id, txn_id = %txn_id, text_len = text.len(), "matrix: send m.room.message");
+        let res = super::utils::send_with_retry(&self.retry, || {
+            self.client.put(&url).bearer_auth(&self.access_token).json(&message)
+        })
+        .await?;
+        let code = res.status();
+        let body = res.text().await.unwrap_or_default();
+        if code.is_success() {
+            info!(status = %code, body = %body, "matrix: message sent");
+            Ok(())
+        } else {
+            error!(status = %code, body = %body, "matrix: send error");
+            Err(format!("Matrix error: {}", code).into())
+        }
+    }
+}
+
+/// HTML-вариант текста: спецсимволы экранируются, ссылки становятся `<a>`, переводы строк — `<br>`
+fn to_html(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            line.split(' ')
+                .map(|word| {
+                    if word.starts_with("http://") || word.starts_with("https://") {
+                        let href = escape_html(word);
+                        format!("<a href=\"{}\">{}</a>", href, href)
+                    } else {
+                        escape_html(word)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("<br>")
+}
+
+#[async_trait]
+impl Publisher for MatrixPublisher {
+    fn name(&self) -> &'static str { "matrix" }
+    /// Без project_id проект определяется по URL элемента
+    async fn publish(&self, _title: &str, url: &str, text: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.publish_project(url, text).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn html_variant_escapes_text_and_links_urls() {
+        assert_eq!(
+            to_html("https://regulation.gov.ru/projects/1\nСрок <30> дней & более"),
+            "<a href=\"https://regulation.gov.ru/projects/1\">https://regulation.gov.ru/projects/1</a><br>Срок &lt;30&gt; дней &amp; более"
+        );
+    }
+
+    #[test]
+    fn txn_id_depends_on_room_project_and_text() {
+        let txn_id = MatrixPublisher::txn_id("!room:example.org", "160532", "пост");
+        assert_eq!(txn_id, MatrixPublisher::txn_id("!room:example.org", "160532", "пост"));
+        assert_ne!(txn_id, MatrixPublisher::txn_id("!other:example.org", "160532", "пост"));
+        assert_ne!(txn_id, MatrixPublisher::txn_id("!room:example.org", "160533", "пост"));
+        assert_ne!(txn_id, MatrixPublisher::txn_id("!room:example.org", "160532", "другой пост"));
+    }
+}
@@ -1,11 +1,17 @@
 pub mod console;
 pub mod file;
 pub mod mastodon;
+pub mod matrix;
+pub mod slack;
 pub mod telegram;
 pub mod utils;
+pub mod webhook;
 
 pub use console::ConsolePublisher;
 pub use file::FilePublisher;
 pub use mastodon::MastodonPublisher;
+pub use matrix::MatrixPublisher;
+pub use slack::SlackPublisher;
+pub use webhook::WebhookPublisher;
 pub use telegram::RealTelegramApi;
 pub use crate::traits::publisher::Publisher;
@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+use bon::Builder;
+use reqwest::Client;
+use tera::{Context, Tera};
+use tracing::{error, info};
+
+use super::utils::fit_to_limit;
+use crate::traits::publisher::Publisher;
+
+/// Лимит поля text сообщения Slack: длиннее Slack обрезает сообщение сам
+pub const SLACK_TEXT_MAX_CHARS: usize = 40000;
+
+/// Лимит текста блока section в Block Kit
+pub const SLACK_SECTION_MAX_CHARS: usize = 3000;
+
+/// Публикация в Slack через incoming webhook: `{ "text": ... }`, а с `blocks_template` —
+/// еще и блоки Block Kit (text остается для уведомлений)
+#[derive(Builder)]
+pub struct SlackPublisher {
+    pub client: Client,
+    pub webhook_url: String,
+    pub max_chars: Option<usize>,
+    /// Tera-шаблон JSON-массива блоков; переменные text, title, url
+    pub blocks_template: Option<String>,
+    #[builder(default)]
+    pub trim_on_word_boundary: bool, // run.trim_on_word_boundary
+    #[builder(default)]
+    pub retry: super::utils::HttpRetryPolicy, // run.publish_retry; при 429 выдерживается Retry-After
+}
+
+/// Экранирует управляющие символы mrkdwn (`&`, `<`, `>`): текст поста не превращается
+/// в ссылки, упоминания и команды Slack
+pub fn escape_mrkdwn(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+impl SlackPublisher {
+    /// Тело запроса к webhook: text и, если задан шаблон, blocks
+    pub fn payload(&self, title: &str, url: &str, text: &str) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(template) = self.blocks_template.as_deref() else {
+            let text = self.fit_escaped(text, self.max_chars.map_or(SLACK_TEXT_MAX_CHARS, |l| l.min(SLACK_TEXT_MAX_CHARS)));
+            return Ok(serde_json::json!({ "text": text }));
+        };
+        // Текст поста попадает в section, у которого свой лимит
+        let text = self.fit_escaped(text, self.max_chars.map_or(SLACK_SECTION_MAX_CHARS, |l| l.min(SLACK_SECTION_MAX_CHARS)));
+        let mut ctx = Context::new();
+        ctx.insert("text", &text);
+        ctx.insert("title", title);
+        ctx.insert("url", url);
+        let rendered = Tera::one_off(template, &ctx, false)
+            .map_err(|e| format!("slack.blocks_template render error: {}", e))?;
+        let blocks: serde_json::Value = serde_json::from_str(&rendered)
+            .map_err(|e| format!("slack.blocks_template is not a JSON array of blocks: {}", e))?;
+        Ok(serde_json::json!({ "text": text, "blocks": blocks }))
+    }
+
+    /// Текст, обрезанный так, чтобы после экранирования mrkdwn он укладывался в `limit` символов
+    fn fit_escaped(&self, text: &str, limit: usize) -> String {
+        let mut budget = limit;
+        loop {
+            let escaped = escape_mrkdwn(&fit_to_limit(text, Some(budget), None, self.trim_on_word_boundary));
+            let overflow = escaped.chars().count().saturating_sub(limit);
+            if overflow == 0 || budget <= overflow {
+                return escaped;
+            }
+            budget -= overflow;
+        }
+    }
+}
+
+#[async_trait]
+impl Publisher for SlackPublisher {
+    fn name(&self) -> &'static str { "slack" }
+    async fn publish(&self, title: &str, url: &str, text: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let payload = self.payload(title, url, text)?;
+        info!(blocks = self.blocks_template.is_some(), text_len = text.len(), "slack: post to incoming webhook");
+        let res = super::utils::send_with_retry(&self.retry, || {
+            self.client.post(&self.webhook_url).json(&payload)
+        })
+        .await?;
+        let code = res.status();
+        let body = res.text().await.unwrap_or_default();
+        if code.is_success() {
+            info!(status = %code, body = %body, "slack: message sent");
+            Ok(())
+        } else {
+            error!(status = %code, body = %body, "slack: webhook error");
+            Err(format!("Slack error: {} {}", code, body).into())
+        }
+    }
+}
@@ -2,10 +2,18 @@ use async_trait::async_trait;
 use reqwest::Client;
 use std::env;
 
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use crate::traits::telegram_api::TelegramApi;
 use crate::traits::publisher::Publisher;
 use bon::Builder;
+use super::utils::{send_with_retry, HttpRetryPolicy};
+use crate::models::config::TelegramParseMode;
+
+/// Telegram limit for media captions (the message text limit is separate)
+pub const TELEGRAM_CAPTION_MAX_CHARS: usize = 1024;
+
+/// Telegram limit for the text of one message
+pub const TELEGRAM_MESSAGE_MAX_CHARS: usize = 4096;
 
 /// A real implementation of the `TelegramApi` trait that sends HTTP requests to the Telegram Bot API.
 #[derive(Builder)]
@@ -15,6 +23,15 @@ pub struct RealTelegramApi {
     pub token: String,
     pub chat_id: i64,
     pub max_chars: Option<usize>,
+    pub message_thread_id: Option<i64>, // тема (forum topic) в группе-форуме
+    #[builder(default)]
+    pub trim_on_word_boundary: bool, // run.trim_on_word_boundary
+    #[builder(default)]
+    pub retry: HttpRetryPolicy, // run.publish_retry
+    #[builder(default)]
+    pub parse_mode: TelegramParseMode, // telegram.parse_mode
+    #[builder(default)]
+    pub split_long_messages: bool, // telegram.split_long_messages
 }
 
 impl RealTelegramApi {
@@ -48,8 +65,25 @@ impl RealTelegramApi {
             token,
             chat_id: 0, // Will be set later
             max_chars: None,
+            message_thread_id: None,
+            trim_on_word_boundary: false,
+            retry: HttpRetryPolicy::default(),
+            parse_mode: TelegramParseMode::None,
+            split_long_messages: false,
         })
     }
+
+    /// Messages to send for one post: with `split_long_messages` the post is split into chunks
+    /// of at most `max_chars` (and the message limit), otherwise it is trimmed to `max_chars`.
+    /// With `parse_mode` the limit counts visible characters and the markup stays valid
+    pub fn message_chunks(&self, text: &str) -> Vec<String> {
+        if self.split_long_messages {
+            let limit = self.max_chars.map_or(TELEGRAM_MESSAGE_MAX_CHARS, |l| l.min(TELEGRAM_MESSAGE_MAX_CHARS));
+            super::utils::split_markup_into_chunks(text, limit, self.parse_mode)
+        } else {
+            vec![super::utils::fit_markup_to_limit(text, self.max_chars, self.parse_mode, self.trim_on_word_boundary)]
+        }
+    }
 }
 
 #[async_trait]
@@ -66,13 +100,14 @@ impl TelegramApi for RealTelegramApi {
     /// `Ok(())` on success, or `Err(String)` with an error message on failure.
     async fn send_telegram_message(&self, chat_id: i64, text: String) -> Result<(), String> {
         let url = format!("{}/bot{}/sendMessage", self.base_url, self.token);
-        let message = SendMessageRequest { chat_id, text };
+        let message = SendMessageRequest {
+            chat_id,
+            text,
+            message_thread_id: self.message_thread_id,
+            parse_mode: self.parse_mode.as_api_str(),
+        };
 
-        let response = self
-            .client
-            .post(&url)
-            .json(&message)
-            .send()
+        let response = send_with_retry(&self.retry, || self.client.post(&url).json(&message))
             .await
             .map_err(|e| {
                 tracing::error!(error = %e, "HTTP error sending Telegram message");
@@ -87,6 +122,57 @@ impl TelegramApi for RealTelegramApi {
             Err(format!("Telegram API error {}: {}", status, body))
         }
     }
+
+    /// Sends a document with a caption using `sendDocument` (multipart upload).
+    ///
+    /// The caption is trimmed to `TELEGRAM_CAPTION_MAX_CHARS`.
+    async fn send_telegram_document(
+        &self,
+        chat_id: i64,
+        file_name: String,
+        mime_type: String,
+        bytes: Vec<u8>,
+        caption: String,
+    ) -> Result<(), String> {
+        let url = format!("{}/bot{}/sendDocument", self.base_url, self.token);
+        // Проверяем MIME-тип заранее: форма собирается заново для каждой попытки
+        reqwest::multipart::Part::bytes(Vec::new())
+            .mime_str(&mime_type)
+            .map_err(|e| format!("invalid document mime type {}: {}", mime_type, e))?;
+        let caption = super::utils::fit_markup_to_limit(&caption, Some(TELEGRAM_CAPTION_MAX_CHARS), self.parse_mode, self.trim_on_word_boundary);
+        // multipart-форма не клонируется: для каждой попытки собирается заново
+        let form = || {
+            let mut form = reqwest::multipart::Form::new()
+                .text("chat_id", chat_id.to_string())
+                .text("caption", caption.clone());
+            if let Some(thread_id) = self.message_thread_id {
+                form = form.text("message_thread_id", thread_id.to_string());
+            }
+            if let Some(parse_mode) = self.parse_mode.as_api_str() {
+                form = form.text("parse_mode", parse_mode);
+            }
+            let part = reqwest::multipart::Part::bytes(bytes.clone())
+                .file_name(file_name.clone())
+                .mime_str(&mime_type)
+                .expect("mime type validated above");
+            form.part("document", part)
+        };
+
+        let response = send_with_retry(&self.retry, || self.client.post(&url).multipart(form()))
+            .await
+            .map_err(|e| {
+                tracing::error!(error = %e, "HTTP error sending Telegram document");
+                format!("HTTP error: {}", e)
+            })?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("Telegram API error {}: {}", status, body))
+        }
+    }
     
     fn client(&self) -> &reqwest::Client {
         &self.client
@@ -103,20 +189,28 @@ impl TelegramApi for RealTelegramApi {
 
 #[async_trait]
 impl Publisher for RealTelegramApi {
-    fn name(&self) -> &str { "telegram" }
+    fn name(&self) -> &'static str { "telegram" }
     async fn publish(&self, _title: &str, _url: &str, text: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let cut = if let Some(maxc) = self.max_chars { 
-            super::utils::trim_with_ellipsis(text, maxc) 
-        } else { 
-            text.to_string() 
-        };
-        let _ = self.send_telegram_message(self.chat_id, cut).await;
+        // Telegram считает ссылки полной длиной. Части длинного поста отправляются по порядку;
+        // после ошибки остальные части не отправляются, чтобы не нарушить порядок текста
+        let chunks = self.message_chunks(text);
+        let total = chunks.len();
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            if let Err(e) = self.send_telegram_message(self.chat_id, chunk).await {
+                tracing::error!(part = i + 1, parts = total, error = %e, "telegram: failed to send message part");
+                return Err(format!("telegram: failed to send part {} of {}: {}", i + 1, total, e).into());
+            }
+        }
         Ok(())
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize)]
 struct SendMessageRequest {
     chat_id: i64,
     text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message_thread_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parse_mode: Option<&'static str>,
 }
@@ -1,5 +1,7 @@
-/// Trim text to at most `max_chars` characters, appending an ellipsis if trimmed.
-/// Uses char-aware slicing to avoid breaking UTF-8 sequences.
+use crate::models::config::TelegramParseMode;
+
+/// Обрезает текст до `max_chars` символов, добавляя многоточие, если текст обрезан.
+/// Режет по символам, а не байтам, чтобы не разрывать последовательности UTF-8
 pub fn trim_with_ellipsis(text: &str, max_chars: usize) -> String {
     if max_chars == 0 { return String::new(); }
     let count = text.chars().count();
@@ -11,6 +13,568 @@ pub fn trim_with_ellipsis(text: &str, max_chars: usize) -> String {
     s
 }
 
+/// Обрезает текст до `max_chars` символов как `trim_with_ellipsis`, но по последнему пробелу
+/// до лимита, а не посреди слова (run.trim_on_word_boundary). Одно слово длиннее лимита
+/// обрезается жестко
+pub fn trim_on_word_boundary(text: &str, max_chars: usize) -> String {
+    if max_chars == 0 { return String::new(); }
+    if text.chars().count() <= max_chars { return text.to_string(); }
+    if max_chars == 1 { return "…".to_string(); }
+    let mut s = cut_chars(text, max_chars - 1, true).to_string();
+    s.push('…');
+    s
+}
+
+/// Первые `n` символов `text`; с `word_boundary` разрез внутри слова переносится назад к последнему
+/// пробелу (пробелы в конце отбрасываются). Если пробела до разреза нет, разрез остается жестким
+fn cut_chars(text: &str, n: usize, word_boundary: bool) -> &str {
+    let end = text.char_indices().nth(n).map_or(text.len(), |(i, _)| i);
+    let head = &text[..end];
+    if !word_boundary || end == text.len() {
+        return head;
+    }
+    let inside_word = text[end..].chars().next().is_some_and(|c| !c.is_whitespace());
+    let cut = match head.rfind(char::is_whitespace) {
+        Some(i) if inside_word => &head[..i],
+        _ => head,
+    };
+    match cut.trim_end() {
+        "" => head,
+        trimmed => trimmed,
+    }
+}
+
+/// Длина, которой Mastodon засчитывает любую ссылку в лимит поста, независимо от ее реальной длины
+pub const MASTODON_LINK_CHARS: usize = 23;
+
+static URL_RE: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
+    regex::Regex::new(r"https?://\S+").unwrap()
+});
+
+/// Нормализует пробелы перед публикацией: убирает пробелы в конце строк, схлопывает
+/// несколько пустых строк подряд в одну и обрезает пробелы по краям текста
+pub fn normalize_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut empty_run = 0usize;
+    for line in text.trim().lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            empty_run += 1;
+            if empty_run > 1 {
+                continue;
+            }
+        } else {
+            empty_run = 0;
+        }
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(line);
+    }
+    out
+}
+
+/// Длина `text` так, как ее считает канал: с `link_chars` каждая http(s)-ссылка засчитывается
+/// как `link_chars` символов (Mastodon: `MASTODON_LINK_CHARS`), иначе считаются все символы
+pub fn counted_chars(text: &str, link_chars: Option<usize>) -> usize {
+    let Some(link_chars) = link_chars else { return text.chars().count() };
+    let links = URL_RE.find_iter(text);
+    let (count, link_len) = links.fold((0usize, 0usize), |(n, len), m| (n + 1, len + m.as_str().chars().count()));
+    text.chars().count() - link_len + count * link_chars
+}
+
+/// Готовит текст для канала: нормализует пробелы и обрезает до `max_chars` по подсчету
+/// `counted_chars`, добавляя многоточие. Ссылки не разрезаются: ссылка, не помещающаяся
+/// в оставшийся бюджет, отбрасывается вместе с остальным текстом.
+/// С `word_boundary` обычный текст режется между словами (см. `trim_on_word_boundary`)
+pub fn fit_to_limit(text: &str, max_chars: Option<usize>, link_chars: Option<usize>, word_boundary: bool) -> String {
+    let text = normalize_whitespace(text);
+    let Some(max_chars) = max_chars else { return text };
+    if counted_chars(&text, link_chars) <= max_chars {
+        return text;
+    }
+    if link_chars.is_none() {
+        return if word_boundary { trim_on_word_boundary(&text, max_chars) } else { trim_with_ellipsis(&text, max_chars) };
+    }
+    if max_chars == 0 { return String::new(); }
+    // Один символ бюджета оставляем под многоточие
+    let mut budget = max_chars - 1;
+    let link_cost = link_chars.unwrap_or_default();
+    let mut out = String::new();
+    let mut pos = 0usize;
+    for m in URL_RE.find_iter(&text).map(Some).chain(std::iter::once(None)) {
+        let plain = &text[pos..m.map_or(text.len(), |m| m.start())];
+        let plain_len = plain.chars().count();
+        if plain_len > budget {
+            out.push_str(cut_chars(plain, budget, word_boundary));
+            break;
+        }
+        out.push_str(plain);
+        budget -= plain_len;
+        match m {
+            Some(m) if link_cost <= budget => {
+                out.push_str(m.as_str());
+                budget -= link_cost;
+                pos = m.end();
+            }
+            _ => break,
+        }
+    }
+    out.push('…');
+    out
+}
+
+/// Делит текст на последовательные части не длиннее `max_chars` символов для отправки подряд
+/// (telegram.split_long_messages). Абзацы (разделенные пустой строкой) по возможности не делятся;
+/// более длинный абзац делится между словами, более длинное слово — жестко. Части не перекрываются,
+/// первая начинается с начала текста
+pub fn split_into_chunks(text: &str, max_chars: usize) -> Vec<String> {
+    let text = normalize_whitespace(text);
+    if max_chars == 0 {
+        return Vec::new();
+    }
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for paragraph in text.split("\n\n") {
+        let mut rest = paragraph;
+        while rest.chars().count() > max_chars {
+            if !current.is_empty() {
+                chunks.push(std::mem::take(&mut current));
+            }
+            let head = cut_chars(rest, max_chars, true);
+            chunks.push(head.to_string());
+            rest = rest[head.len()..].trim_start();
+        }
+        if rest.is_empty() {
+            continue;
+        }
+        if !current.is_empty() && current.chars().count() + 2 + rest.chars().count() > max_chars {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push_str("\n\n");
+        }
+        current.push_str(rest);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Элемент разметки Telegram: видимый символ (HTML-сущность и экранированный символ MarkdownV2
+/// неделимы и считаются одним символом) либо открывающий/закрывающий тег, не занимающий места в лимите
+#[derive(Debug)]
+enum MarkupPiece<'a> {
+    Char(&'a str),
+    Open { raw: &'a str, close: String },
+    Close(&'a str),
+}
+
+impl MarkupPiece<'_> {
+    fn raw(&self) -> &str {
+        match self {
+            MarkupPiece::Char(raw) | MarkupPiece::Close(raw) | MarkupPiece::Open { raw, .. } => raw,
+        }
+    }
+
+    fn is_space(&self) -> bool {
+        matches!(self, MarkupPiece::Char(raw) if raw.chars().all(char::is_whitespace))
+    }
+}
+
+/// Маркеры форматирования MarkdownV2, более длинные раньше
+const MARKDOWN_V2_TOGGLES: [&str; 7] = ["```", "||", "__", "*", "_", "~", "`"];
+
+/// Разбирает текст в разметке `parse_mode` на неделимые элементы
+fn markup_pieces(text: &str, parse_mode: TelegramParseMode) -> Vec<MarkupPiece<'_>> {
+    let mut pieces = Vec::new();
+    // Открытые маркеры MarkdownV2 (для ссылки — ее закрывающая часть `](url)`)
+    let mut open: Vec<&str> = Vec::new();
+    let mut pos = 0;
+    while pos < text.len() {
+        let rest = &text[pos..];
+        let piece = match parse_mode {
+            TelegramParseMode::Html => html_piece(rest),
+            TelegramParseMode::MarkdownV2 => markdown_v2_piece(rest, &mut open),
+            TelegramParseMode::None => first_char(rest),
+        };
+        pos += piece.raw().len();
+        pieces.push(piece);
+    }
+    pieces
+}
+
+fn first_char(rest: &str) -> MarkupPiece<'_> {
+    let len = rest.chars().next().map_or(0, char::len_utf8);
+    MarkupPiece::Char(&rest[..len])
+}
+
+fn html_piece(rest: &str) -> MarkupPiece<'_> {
+    if rest.starts_with('<') {
+        if let Some(end) = rest.find('>') {
+            let tag = &rest[..=end];
+            if tag.starts_with("</") {
+                return MarkupPiece::Close(tag);
+            }
+            let name: String = tag[1..].chars().take_while(|c| c.is_ascii_alphanumeric() || *c == '-').collect();
+            return MarkupPiece::Open { raw: tag, close: format!("</{}>", name) };
+        }
+    } else if rest.starts_with('&') {
+        let end = rest.char_indices().take(12).find(|(_, c)| *c == ';').map(|(i, _)| i);
+        if let Some(end) = end.filter(|&end| end > 1 && rest[1..end].chars().all(|c| c.is_ascii_alphanumeric() || c == '#')) {
+            return MarkupPiece::Char(&rest[..=end]);
+        }
+    }
+    first_char(rest)
+}
+
+fn markdown_v2_piece<'a>(rest: &'a str, open: &mut Vec<&'a str>) -> MarkupPiece<'a> {
+    if let Some(escaped) = rest.strip_prefix('\\') {
+        let len = 1 + escaped.chars().next().map_or(0, char::len_utf8);
+        return MarkupPiece::Char(&rest[..len]);
+    }
+    if let Some(last) = open.last().copied().filter(|last| rest.starts_with(*last)) {
+        open.pop();
+        return MarkupPiece::Close(&rest[..last.len()]);
+    }
+    // Внутри кода остальные маркеры — обычный текст
+    if open.last().is_some_and(|last| last.starts_with('`')) {
+        return first_char(rest);
+    }
+    if rest.starts_with('[')
+        && let Some(tail) = markdown_v2_link_tail(rest)
+    {
+        open.push(tail);
+        return MarkupPiece::Open { raw: &rest[..1], close: tail.to_string() };
+    }
+    if let Some(toggle) = MARKDOWN_V2_TOGGLES.iter().find(|t| rest.starts_with(*t)) {
+        let raw = &rest[..toggle.len()];
+        open.push(raw);
+        return MarkupPiece::Open { raw, close: raw.to_string() };
+    }
+    first_char(rest)
+}
+
+/// Закрывающая часть `](url)` ссылки MarkdownV2, начинающейся с `[`
+fn markdown_v2_link_tail(rest: &str) -> Option<&str> {
+    let mut chars = rest.char_indices().skip(1);
+    let mut text_end = None;
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => { chars.next(); }
+            ']' => { text_end = Some(i); break; }
+            _ => {}
+        }
+    }
+    let text_end = text_end?;
+    if !rest[text_end..].starts_with("](") {
+        return None;
+    }
+    let mut chars = rest[text_end + 2..].char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => { chars.next(); }
+            ')' => return Some(&rest[text_end..=text_end + 2 + i]),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn visible_chars(pieces: &[MarkupPiece]) -> usize {
+    pieces.iter().filter(|p| matches!(p, MarkupPiece::Char(_))).count()
+}
+
+/// Конец (не включая) самого длинного участка от `start`, в котором не больше `budget` видимых символов.
+/// Открывающие теги перед местом обрезки в участок не входят
+fn markup_cut(pieces: &[MarkupPiece], start: usize, budget: usize) -> usize {
+    let mut visible = 0;
+    let mut end = start;
+    while end < pieces.len() {
+        if matches!(pieces[end], MarkupPiece::Char(_)) {
+            if visible == budget {
+                break;
+            }
+            visible += 1;
+        }
+        end += 1;
+    }
+    if end < pieces.len() {
+        while end > start && matches!(pieces[end - 1], MarkupPiece::Open { .. }) {
+            end -= 1;
+        }
+    }
+    end
+}
+
+/// Переносит место обрезки `end` на последний пробел, если оно внутри слова (как `cut_chars`)
+fn markup_word_cut(pieces: &[MarkupPiece], start: usize, end: usize) -> usize {
+    let inside_word = pieces[end..]
+        .iter()
+        .find(|p| matches!(p, MarkupPiece::Char(_)))
+        .is_some_and(|p| !p.is_space());
+    if !inside_word {
+        return end;
+    }
+    let Some(space) = (start..end).rev().find(|&i| pieces[i].is_space()) else { return end };
+    if !pieces[start..space].iter().any(|p| matches!(p, MarkupPiece::Char(_)) && !p.is_space()) {
+        return end;
+    }
+    let mut cut = space;
+    while cut > start && matches!(pieces[cut - 1], MarkupPiece::Open { .. }) {
+        cut -= 1;
+    }
+    cut
+}
+
+/// Стек открытых тегов после `pieces`, если до них были открыты `open`
+fn open_markup<'p, 'a>(mut open: Vec<&'p MarkupPiece<'a>>, pieces: &'p [MarkupPiece<'a>]) -> Vec<&'p MarkupPiece<'a>> {
+    for piece in pieces {
+        match piece {
+            MarkupPiece::Open { .. } => open.push(piece),
+            MarkupPiece::Close(raw) => {
+                let matching = open.iter().rposition(|p| matches!(p, MarkupPiece::Open { close, .. } if close == raw));
+                if let Some(i) = matching {
+                    open.truncate(i);
+                }
+            }
+            MarkupPiece::Char(_) => {}
+        }
+    }
+    open
+}
+
+fn closing_markup(open: &[&MarkupPiece]) -> String {
+    open.iter()
+        .rev()
+        .filter_map(|p| match p {
+            MarkupPiece::Open { close, .. } => Some(close.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn raw_markup(pieces: &[MarkupPiece]) -> String {
+    pieces.iter().map(MarkupPiece::raw).collect()
+}
+
+/// Обрезает текст в разметке Telegram (`parse_mode`) до `max_chars` видимых символов с многоточием,
+/// как `fit_to_limit`, но не разрезает HTML-сущности, теги и экранирование MarkdownV2,
+/// а теги, открытые на месте обрезки, закрывает. Без разметки — то же, что `fit_to_limit`
+pub fn fit_markup_to_limit(text: &str, max_chars: Option<usize>, parse_mode: TelegramParseMode, word_boundary: bool) -> String {
+    if parse_mode == TelegramParseMode::None {
+        return fit_to_limit(text, max_chars, None, word_boundary);
+    }
+    let text = normalize_whitespace(text);
+    let Some(max_chars) = max_chars else { return text };
+    let pieces = markup_pieces(&text, parse_mode);
+    if visible_chars(&pieces) <= max_chars {
+        return text;
+    }
+    if max_chars == 0 { return String::new(); }
+    let mut end = markup_cut(&pieces, 0, max_chars - 1);
+    if word_boundary {
+        end = markup_word_cut(&pieces, 0, end);
+    }
+    let open = open_markup(Vec::new(), &pieces[..end]);
+    let mut out = raw_markup(&pieces[..end]).trim_end().to_string();
+    out.push('…');
+    out.push_str(&closing_markup(&open));
+    out
+}
+
+/// Делит текст в разметке Telegram (`parse_mode`) на части не длиннее `max_chars` видимых символов,
+/// как `split_into_chunks`: по абзацам, затем по словам. Теги, открытые на границе частей, закрываются
+/// в конце части и открываются заново в начале следующей. Без разметки — то же, что `split_into_chunks`
+pub fn split_markup_into_chunks(text: &str, max_chars: usize, parse_mode: TelegramParseMode) -> Vec<String> {
+    if parse_mode == TelegramParseMode::None {
+        return split_into_chunks(text, max_chars);
+    }
+    let text = normalize_whitespace(text);
+    if max_chars == 0 {
+        return Vec::new();
+    }
+    let pieces = markup_pieces(&text, parse_mode);
+    let is_newline = |i: usize| matches!(pieces.get(i), Some(MarkupPiece::Char("\n")));
+    let mut chunks = Vec::new();
+    let mut reopen = Vec::new();
+    let mut start = 0;
+    while start < pieces.len() {
+        let mut end = markup_cut(&pieces, start, max_chars);
+        if end < pieces.len() {
+            // Граница абзаца в пределах лимита, иначе граница слова
+            end = match (start + 1..=end).rev().find(|&i| is_newline(i) && is_newline(i + 1) && visible_chars(&pieces[start..i]) > 0) {
+                Some(paragraph) => paragraph,
+                None => markup_word_cut(&pieces, start, end),
+            };
+        }
+        if visible_chars(&pieces[start..end]) == 0 {
+            break;
+        }
+        let open = open_markup(reopen.clone(), &pieces[start..end]);
+        let prefix: String = reopen.iter().map(|p| p.raw()).collect();
+        chunks.push(format!("{}{}{}", prefix, raw_markup(&pieces[start..end]).trim_end(), closing_markup(&open)));
+        reopen = open;
+        start = end;
+        while start < pieces.len() && pieces[start].is_space() {
+            start += 1;
+        }
+    }
+    chunks
+}
+
+/// Политика повторов HTTP-запросов публикации (run.publish_retry)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HttpRetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: std::time::Duration,
+    pub multiplier: f64,
+}
+
+impl Default for HttpRetryPolicy {
+    /// Без run.publish_retry запрос выполняется один раз
+    fn default() -> Self {
+        Self { max_attempts: 1, base_delay: std::time::Duration::from_millis(500), multiplier: 2.0 }
+    }
+}
+
+impl HttpRetryPolicy {
+    pub fn from_config(config: Option<&crate::models::config::PublishRetryConfig>) -> Self {
+        let Some(c) = config else { return Self::default() };
+        Self {
+            max_attempts: c.max_attempts.unwrap_or(3).max(1),
+            base_delay: std::time::Duration::from_millis(c.base_delay_ms.unwrap_or(500)),
+            multiplier: c.multiplier.unwrap_or(2.0).max(1.0),
+        }
+    }
+
+    /// Задержка перед повтором после неудачной попытки `attempt` (с 1), не больше MAX_RETRY_DELAY
+    fn delay(&self, attempt: u32) -> std::time::Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let secs = self.base_delay.as_secs_f64() * self.multiplier.powi(exponent);
+        std::time::Duration::try_from_secs_f64(secs)
+            .unwrap_or(MAX_RETRY_DELAY)
+            .min(MAX_RETRY_DELAY)
+    }
+}
+
+/// Верхняя граница задержки между повторами
+const MAX_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// Заголовок ключа идемпотентности: запрос с ним сервер не выполнит повторно
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Верхняя граница ожидания по заголовку Retry-After
+const MAX_RETRY_AFTER: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Задержка из заголовка Retry-After ответа 429 (в секундах), не больше MAX_RETRY_AFTER
+fn retry_after(res: &reqwest::Response) -> Option<std::time::Duration> {
+    if res.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    let secs: u64 = res.headers().get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim().parse().ok()?;
+    Some(std::time::Duration::from_secs(secs).min(MAX_RETRY_AFTER))
+}
+
+/// Отправляет запрос, собранный `request`, повторяя его по `policy` с экспоненциальной задержкой.
+/// Всегда повторяются 429 (с ожиданием не меньше Retry-After), 5xx и ошибки соединения: сервер
+/// ответил ошибкой или запрос не получил. 429 с Retry-After повторяется хотя бы один раз и без
+/// run.publish_retry. Таймауты (ответа нет, а запрос мог быть выполнен) повторяются только для
+/// идемпотентных запросов (PUT, GET и т.п. или с заголовком Idempotency-Key), иначе повтор мог бы
+/// опубликовать пост дважды. Ответ с другим статусом (в том числе 4xx) возвращается сразу
+pub async fn send_with_retry<F>(policy: &HttpRetryPolicy, request: F) -> Result<reqwest::Response, reqwest::Error>
+where
+    F: Fn() -> reqwest::RequestBuilder,
+{
+    let mut attempt = 1;
+    loop {
+        let (client, built) = request().build_split();
+        let built = built?;
+        let idempotent = built.method().is_idempotent() || built.headers().contains_key(IDEMPOTENCY_KEY_HEADER);
+        let result = client.execute(built).await;
+        let retry = match &result {
+            Ok(res) => {
+                res.status() == reqwest::StatusCode::TOO_MANY_REQUESTS || res.status().is_server_error()
+            }
+            Err(e) => e.is_connect() || (idempotent && (e.is_timeout() || e.is_request())),
+        };
+        let server_delay = result.as_ref().ok().and_then(retry_after);
+        let max_attempts = if server_delay.is_some() { policy.max_attempts.max(2) } else { policy.max_attempts };
+        if !retry || attempt >= max_attempts {
+            return result;
+        }
+        let delay = match server_delay {
+            Some(server_delay) => server_delay.max(policy.delay(attempt)),
+            None => policy.delay(attempt),
+        };
+        tracing::warn!(
+            attempt,
+            max_attempts,
+            delay_ms = delay.as_millis() as u64,
+            status = ?result.as_ref().ok().map(|r| r.status()),
+            error = ?result.as_ref().err().map(|e| e.to_string()),
+            "publisher request failed transiently, retrying"
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+static EMAIL_RE: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
+    regex::Regex::new(r"(?i)\b([a-z0-9])[a-z0-9._%+-]*@([a-z0-9.-]+\.[a-z]{2,})\b").unwrap()
+});
+
+/// Маскирует адреса e-mail, оставляя первый символ имени и домен:
+/// `khandzhyanaa@minobrnauki.gov.ru` -> `k***@minobrnauki.gov.ru`
+pub fn redact_emails(text: &str) -> String {
+    EMAIL_RE.replace_all(text, "${1}***@${2}").into_owned()
+}
+
+/// Форматирует project_id по `pattern` с плейсхолдерами `{project_id}` (как есть) и
+/// `{project_id_grouped}` (разряды через пробел: `160532` -> `160 532`).
+/// Нечисловой id подставляется без изменений в оба плейсхолдера
+pub fn format_project_id(pattern: &str, project_id: &str) -> String {
+    let grouped = if !project_id.is_empty() && project_id.chars().all(|c| c.is_ascii_digit()) {
+        let digits: Vec<char> = project_id.chars().collect();
+        let mut out = String::new();
+        for (i, c) in digits.iter().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                out.push(' ');
+            }
+            out.push(*c);
+        }
+        out
+    } else {
+        project_id.to_string()
+    };
+    pattern
+        .replace("{project_id_grouped}", &grouped)
+        .replace("{project_id}", project_id)
+}
+
+/// Символы, зарезервированные в Telegram MarkdownV2: в обычном тексте их нужно экранировать
+const MARKDOWN_V2_RESERVED: &str = "_*[]()~`>#+-=|{}.!\\";
+
+/// Экранирует обычный текст для Telegram `parse_mode: MarkdownV2`: перед каждым зарезервированным
+/// символом (включая `\`) ставится обратная косая черта
+pub fn escape_markdown_v2(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if MARKDOWN_V2_RESERVED.contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Экранирует обычный текст для Telegram `parse_mode: HTML` (`&`, `<`, `>`, `"`), поэтому значение
+/// безопасно и внутри атрибута тега
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
 #[cfg(test)]
 use std::sync::Mutex;
 #[cfg(test)]
@@ -30,4 +594,138 @@ mod tests {
         assert_eq!(trim_with_ellipsis(s, 5), "абвгд");
         assert_eq!(trim_with_ellipsis(s, 10), "абвгд");
     }
+
+    #[test]
+    fn trims_on_word_boundary_cyrillic() {
+        let s = "Губернаторы смогут передавать полномочия";
+        // Лимит 16 приходится на середину слова «смогут»: обрезка по пробелу перед ним
+        assert_eq!(trim_with_ellipsis(s, 16), "Губернаторы смо…");
+        assert_eq!(trim_on_word_boundary(s, 16), "Губернаторы…");
+        assert_eq!(trim_on_word_boundary(s, 16).chars().count() <= 16, true);
+        // Граница слова совпала с лимитом: слово целиком
+        assert_eq!(trim_on_word_boundary(s, 19), "Губернаторы смогут…");
+        // Первое слово длиннее лимита: жесткая обрезка
+        assert_eq!(trim_on_word_boundary(s, 6), "Губер…");
+        assert_eq!(trim_on_word_boundary(s, 100), s);
+        assert_eq!(trim_on_word_boundary(s, 1), "…");
+        assert_eq!(trim_on_word_boundary(s, 0), "");
+        assert_eq!(fit_to_limit(s, Some(16), None, true), "Губернаторы…");
+    }
+
+    #[test]
+    fn retry_delay_grows_by_multiplier() {
+        let policy = HttpRetryPolicy::from_config(Some(&crate::models::config::PublishRetryConfig {
+            max_attempts: Some(4),
+            base_delay_ms: Some(100),
+            multiplier: Some(3.0),
+        }));
+        assert_eq!(policy.delay(1), std::time::Duration::from_millis(100));
+        assert_eq!(policy.delay(2), std::time::Duration::from_millis(300));
+        assert_eq!(policy.delay(3), std::time::Duration::from_millis(900));
+        assert_eq!(HttpRetryPolicy::from_config(None).max_attempts, 1);
+    }
+
+    #[test]
+    fn retry_delay_is_clamped() {
+        let policy = HttpRetryPolicy {
+            max_attempts: u32::MAX,
+            base_delay: std::time::Duration::from_secs(1),
+            multiplier: 10.0,
+        };
+        assert_eq!(policy.delay(400), MAX_RETRY_DELAY);
+        assert_eq!(policy.delay(u32::MAX), MAX_RETRY_DELAY);
+    }
+
+    #[test]
+    fn escapes_markdown_v2_reserved_characters() {
+        assert_eq!(escape_markdown_v2("Срок - 30 дней. Итог!"), "Срок \\- 30 дней\\. Итог\\!");
+        assert_eq!(escape_markdown_v2("a_b*(c)\\"), "a\\_b\\*\\(c\\)\\\\");
+        assert_eq!(escape_markdown_v2("без спецсимволов"), "без спецсимволов");
+        assert_eq!(escape_html("a < b & c"), "a &lt; b &amp; c");
+        assert_eq!(escape_html(r#"<a href="x">"#), "&lt;a href=&quot;x&quot;&gt;");
+    }
+
+    #[test]
+    fn splits_on_paragraphs_then_words() {
+        let text = "https://example.org/p/1\nЗаголовок\n\nПервый абзац текста\n\nВторой абзац";
+        assert_eq!(
+            split_into_chunks(text, 40),
+            vec!["https://example.org/p/1\nЗаголовок", "Первый абзац текста\n\nВторой абзац"]
+        );
+        // Абзац длиннее лимита делится по словам, слово длиннее лимита — жестко
+        assert_eq!(split_into_chunks("один два три", 8), vec!["один два", "три"]);
+        assert_eq!(split_into_chunks("абвгдеж", 3), vec!["абв", "где", "ж"]);
+        assert_eq!(split_into_chunks("коротко", 100), vec!["коротко"]);
+    }
+
+    #[test]
+    fn fits_markup_without_cutting_entities_and_tags() {
+        let html = "<b>Итог</b>: a &amp; b &lt; c";
+        // Видимых символов 15: сущность считается одним символом и не разрезается
+        assert_eq!(fit_markup_to_limit(html, Some(15), TelegramParseMode::Html, false), html);
+        assert_eq!(fit_markup_to_limit(html, Some(10), TelegramParseMode::Html, false), "<b>Итог</b>: a &amp;…");
+        // Обрезка внутри тега закрывает его
+        assert_eq!(fit_markup_to_limit("<b>Длинный заголовок</b>", Some(8), TelegramParseMode::Html, true), "<b>Длинный…</b>");
+        let md = "*Срок \\- 30 дней\\.*";
+        assert_eq!(fit_markup_to_limit(md, Some(7), TelegramParseMode::MarkdownV2, false), "*Срок \\-…*");
+        assert_eq!(fit_markup_to_limit("[ссылка на проект](https://x.io/p)", Some(7), TelegramParseMode::MarkdownV2, false), "[ссылка…](https://x.io/p)");
+    }
+
+    #[test]
+    fn splits_markup_reopening_tags() {
+        assert_eq!(
+            split_markup_into_chunks("<i>один два три</i>", 8, TelegramParseMode::Html),
+            vec!["<i>один два</i>", "<i>три</i>"]
+        );
+        assert_eq!(
+            split_markup_into_chunks("a &amp;&amp; b\n\n<b>c</b>", 6, TelegramParseMode::Html),
+            vec!["a &amp;&amp; b", "<b>c</b>"]
+        );
+        assert_eq!(split_markup_into_chunks("один два три", 8, TelegramParseMode::None), vec!["один два", "три"]);
+    }
+
+    #[test]
+    fn normalizes_whitespace() {
+        assert_eq!(normalize_whitespace("  строка  \n\n\n\nвторая \t\n"), "строка\n\nвторая");
+        assert_eq!(normalize_whitespace("a\nb"), "a\nb");
+    }
+
+    #[test]
+    fn counts_links_with_fixed_budget() {
+        let text = format!("Проект: https://regulation.gov.ru/projects/{}", "1".repeat(40));
+        assert_eq!(counted_chars(&text, None), text.chars().count());
+        assert_eq!(counted_chars(&text, Some(MASTODON_LINK_CHARS)), "Проект: ".chars().count() + 23);
+    }
+
+    #[test]
+    fn fits_long_link_into_mastodon_limit() {
+        // Реальная длина больше лимита, но ссылка засчитывается как 23 символа
+        let url = format!("https://regulation.gov.ru/projects/{}", "1".repeat(40));
+        let text = format!("Текст {}", url);
+        assert_eq!(fit_to_limit(&text, Some(30), Some(MASTODON_LINK_CHARS), false), text);
+        // Без бюджета ссылок тот же текст обрезается
+        assert_eq!(fit_to_limit(&text, Some(30), None, false).chars().count(), 30);
+    }
+
+    #[test]
+    fn never_cuts_link_in_the_middle() {
+        let text = "Начало https://example.org/very/long/path конец";
+        // 7 ("Начало ") + 23 > 29: ссылка не помещается и отбрасывается целиком
+        assert_eq!(fit_to_limit(text, Some(29), Some(MASTODON_LINK_CHARS), false), "Начало …");
+        // 7 + 23 + 1 + многоточие = 32: ссылка помещается, остальной текст обрезается
+        assert_eq!(
+            fit_to_limit(text, Some(32), Some(MASTODON_LINK_CHARS), false),
+            "Начало https://example.org/very/long/path …"
+        );
+        assert_eq!(fit_to_limit(text, None, Some(MASTODON_LINK_CHARS), false), text);
+    }
+
+    #[test]
+    fn redacts_emails_keeping_domain() {
+        assert_eq!(
+            redact_emails("Хаджян А.А., khandzhyanaa@minobrnauki.gov.ru; a.b@x.io"),
+            "Хаджян А.А., k***@minobrnauki.gov.ru; a***@x.io"
+        );
+        assert_eq!(redact_emails("без адресов"), "без адресов");
+    }
 }
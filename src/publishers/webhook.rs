@@ -0,0 +1,140 @@
+use async_trait::async_trait;
+use bon::Builder;
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde::Serialize;
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use tracing::{error, info};
+
+use crate::models::types::CrawlItem;
+use crate::traits::publisher::Publisher;
+
+/// Заголовок подписи тела по умолчанию (webhook.signature_header)
+pub const DEFAULT_SIGNATURE_HEADER: &str = "X-Luminis-Signature";
+
+/// Событие публикации для webhook: данные элемента без отрендеренного поста
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookEvent {
+    pub project_id: Option<String>,
+    pub url: String,
+    pub title: String,
+    pub summary: Option<String>,
+    /// Метаданные краулера: snake_case имя поля -> значение (строка или массив)
+    pub metadata: BTreeMap<String, serde_json::Value>,
+    pub channel: String,
+    /// Время отправки, RFC 3339
+    pub published_at: String,
+}
+
+impl WebhookEvent {
+    pub fn from_item(item: &CrawlItem, summary: Option<&str>) -> Self {
+        // MetadataItem сериализуется как {"Variant": value}: берем значение, имя поля — snake_case
+        let metadata = item
+            .metadata
+            .iter()
+            .filter_map(|m| match serde_json::to_value(m) {
+                Ok(serde_json::Value::Object(obj)) => obj.into_iter().next().map(|(_, v)| (m.to_string(), v)),
+                _ => None,
+            })
+            .collect();
+        Self {
+            project_id: item.project_id.clone(),
+            url: item.url.clone(),
+            title: item.title.clone(),
+            summary: summary.map(str::to_string),
+            metadata,
+            channel: "webhook".to_string(),
+            published_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Публикация структурированного события POST-запросом с JSON на произвольный endpoint
+#[derive(Builder)]
+pub struct WebhookPublisher {
+    pub client: Client,
+    pub url: String,
+    /// Дополнительные заголовки запроса (например, Authorization)
+    #[builder(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Секрет HMAC-SHA256: подпись тела передается в `signature_header` как `sha256=<hex>`
+    pub secret: Option<String>,
+    #[builder(default = DEFAULT_SIGNATURE_HEADER.to_string())]
+    pub signature_header: String,
+    #[builder(default)]
+    pub retry: super::utils::HttpRetryPolicy, // run.publish_retry
+}
+
+/// Подпись тела HMAC-SHA256 в виде `sha256=<hex>`
+pub fn sign_body(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    let digest = mac.finalize().into_bytes();
+    format!("sha256={}", digest.iter().map(|b| format!("{:02x}", b)).collect::<String>())
+}
+
+impl WebhookPublisher {
+    /// Отправляет событие; тело подписывается ровно в том виде, в каком уходит в запросе
+    pub async fn send_event(&self, event: &WebhookEvent) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let body = serde_json::to_vec(event)?;
+        let signature = self.secret.as_deref().map(|s| sign_body(s, &body));
+        info!(url = %self.url, project_id = ?event.project_id, body_len = body.len(), signed = signature.is_some(), "webhook: send event");
+        let res = super::utils::send_with_retry(&self.retry, || {
+            let mut req = self
+                .client
+                .post(&self.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.clone());
+            for (name, value) in &self.headers {
+                req = req.header(name.as_str(), value.as_str());
+            }
+            if let Some(sig) = &signature {
+                req = req.header(self.signature_header.as_str(), sig.as_str());
+            }
+            req
+        })
+        .await?;
+        let code = res.status();
+        let text = res.text().await.unwrap_or_default();
+        if code.is_success() {
+            info!(status = %code, "webhook: event delivered");
+            Ok(())
+        } else {
+            error!(status = %code, body = %text, "webhook: endpoint error");
+            Err(format!("Webhook error: {}", code).into())
+        }
+    }
+}
+
+#[async_trait]
+impl Publisher for WebhookPublisher {
+    fn name(&self) -> &'static str { "webhook" }
+    /// Без элемента краулера: событие с текстом в summary и пустыми метаданными
+    async fn publish(&self, title: &str, url: &str, text: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let item = CrawlItem {
+            title: title.to_string(),
+            url: url.to_string(),
+            body: String::new(),
+            project_id: None,
+            metadata: Vec::new(),
+            source_label: None,
+            source_id: None,
+        };
+        self.send_event(&WebhookEvent::from_item(&item, Some(text))).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_body_with_hmac_sha256() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            sign_body("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
@@ -1,18 +1,72 @@
 use async_trait::async_trait;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::fs;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
 use serde_json;
 use bon::Builder;
 
-use crate::traits::cache_manager::CacheManager;
+use crate::traits::cache_manager::{CacheManager, ManifestUpdate};
+use crate::models::config::CacheLayout;
 use crate::models::types::CacheMetadata;
 use crate::models::channel::PublisherChannel;
-use crate::models::types::{CreatedAt, SummaryText, PostText};
+use crate::models::types::{CreatedAt, DocumentValidators, SummaryText, PostText, ThreadProgress, content_hash};
+
+/// Суффиксы плоских файлов старого формата кэша `{project_id}{суффикс}` в корне cache_dir
+const LEGACY_METADATA: &str = "_metadata.json";
+const LEGACY_EXTRACTED: &str = "_extracted.md";
+const LEGACY_SUMMARY: &str = "_summary.txt";
+const LEGACY_SUFFIXES: [&str; 3] = [LEGACY_METADATA, LEGACY_EXTRACTED, LEGACY_SUMMARY];
+
+/// Итог переноса старого формата кэша (migrate-cache)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheMigrationReport {
+    /// Проекты, перенесенные в каталоги с metadata.json
+    pub migrated: usize,
+    /// Проекты, у которых каталог с metadata.json уже был (их плоские файлы не тронуты)
+    pub skipped: usize,
+    /// Удаленные плоские файлы (при delete_legacy)
+    pub deleted_files: usize,
+}
+
+/// Содержимое файла in_progress в каталоге проекта
+#[derive(Debug, Serialize, Deserialize)]
+struct InProgressMarker {
+    owner: String,
+    claimed_at: String, // RFC 3339
+}
+
+/// Удерживаемая блокировка manifest.lock; снимается в drop, в том числе на путях с ошибкой
+struct ManifestLock(fs::File);
+
+impl Drop for ManifestLock {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.0);
+    }
+}
 
 /// Реализация CacheManager для файловой системы
 #[derive(Builder)]
 pub struct FileSystemCacheManager {
     cache_dir: String,
+    /// Сохранять extracted.md сжатым в extracted.md.gz
+    #[builder(default)]
+    compress: bool,
+    /// Модель LLM (llm.model): суммаризации и посты, сделанные другой моделью, считаются отсутствующими
+    summary_model: Option<String>,
+    /// Порог в байтах, выше которого суммаризации и посты каналов хранятся в отдельных файлах
+    /// (cache.externalize_large_fields); None — всё хранится в metadata.json
+    externalize_threshold: Option<usize>,
+    /// Формат каталога кэша (run.cache_layout): при V2 плоские файлы старого формата не читаются
+    #[builder(default)]
+    layout: CacheLayout,
+    /// Идентификатор экземпляра, записываемый в маркеры in_progress
+    #[builder(skip = format!("{}-{}", std::process::id(), chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()))]
+    instance_id: String,
 }
 
 impl FileSystemCacheManager {
@@ -25,6 +79,188 @@ impl FileSystemCacheManager {
     fn meta_path_for(&self, project_id: &str) -> PathBuf {
         self.project_dir(project_id).join("metadata.json")
     }
+
+    fn in_progress_path_for(&self, project_id: &str) -> PathBuf {
+        self.project_dir(project_id).join("in_progress")
+    }
+
+    /// Существующий плоский файл старого формата `{project_id}{suffix}`; при cache_layout v2 не ищется
+    fn legacy_path(&self, project_id: &str, suffix: &str) -> Option<PathBuf> {
+        if self.layout == CacheLayout::V2 {
+            return None;
+        }
+        Some(Path::new(&self.cache_dir).join(format!("{}{}", project_id, suffix))).filter(|p| p.exists())
+    }
+
+    /// Создает маркер in_progress, только если его еще нет (create_new атомарен между процессами)
+    fn create_marker(&self, path: &Path) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let mut file = match fs::OpenOptions::new().write(true).create_new(true).open(path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+        let marker = InProgressMarker { owner: self.instance_id.clone(), claimed_at: chrono::Utc::now().to_rfc3339() };
+        file.write_all(serde_json::to_string(&marker)?.as_bytes())?;
+        Ok(true)
+    }
+
+    /// Путь к извлеченному тексту: extracted.md.gz, если он есть, иначе extracted.md
+    fn markdown_path_for(&self, project_id: &str) -> PathBuf {
+        let gz = self.project_dir(project_id).join("extracted.md.gz");
+        if gz.exists() {
+            gz
+        } else {
+            self.project_dir(project_id).join("extracted.md")
+        }
+    }
+
+    /// Записывает извлеченный текст (сжатым при включенном compress) и удаляет копию в другом формате
+    fn write_markdown(&self, project_id: &str, markdown_text: &str) -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+        let base = self.project_dir(project_id);
+        let plain = base.join("extracted.md");
+        let gz = base.join("extracted.md.gz");
+        let (target, stale) = if self.compress { (gz, plain) } else { (plain, gz) };
+        if self.compress {
+            let mut encoder = GzEncoder::new(fs::File::create(&target)?, Compression::default());
+            encoder.write_all(markdown_text.as_bytes())?;
+            encoder.finish()?;
+        } else {
+            fs::write(&target, markdown_text)?;
+        }
+        if stale.exists() {
+            fs::remove_file(&stale)?;
+        }
+        Ok(target)
+    }
+
+    /// Читает извлеченный текст, распаковывая .gz по расширению
+    fn read_markdown(path: &Path) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        if path.extension().is_some_and(|e| e == "gz") {
+            let mut s = String::new();
+            GzDecoder::new(fs::File::open(path)?).read_to_string(&mut s)?;
+            Ok(s)
+        } else {
+            Ok(fs::read_to_string(path)?)
+        }
+    }
+
+    fn summaries_current(&self, meta: &CacheMetadata) -> bool {
+        summaries_current(self.summary_model.as_deref(), meta)
+    }
+
+    fn stamp_summary_model(&self, meta: &mut CacheMetadata) {
+        stamp_summary_model(self.summary_model.as_deref(), meta)
+    }
+
+    /// Разбирает metadata.json проекта и подставляет тексты, вынесенные в отдельные файлы
+    fn parse_metadata(&self, project_id: &str, data: &str) -> serde_json::Result<CacheMetadata> {
+        let mut meta = serde_json::from_str::<CacheMetadata>(data)?;
+        let dir = self.project_dir(project_id);
+        inline_external(&dir, &mut meta.external_summaries, &mut meta.channel_summaries);
+        inline_external(&dir, &mut meta.external_posts, &mut meta.channel_posts);
+        Ok(meta)
+    }
+
+    /// Сериализует метаданные; при cache.externalize_large_fields длинные суммаризации и посты
+    /// записываются в файлы каталога проекта, а в JSON остаются ссылки на них
+    fn serialize_metadata(&self, project_id: &str, meta: &CacheMetadata) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let mut meta = meta.clone();
+        meta.refresh_summary_ratings();
+        let dir = self.project_dir(project_id);
+        meta.external_summaries = externalize(&dir, "summary", self.externalize_threshold, &mut meta.channel_summaries, SummaryText::as_str)?;
+        meta.external_posts = externalize(&dir, "post", self.externalize_threshold, &mut meta.channel_posts, PostText::as_str)?;
+        Ok(serde_json::to_string_pretty(&meta)?)
+    }
+
+    fn manifest_path(&self) -> PathBuf {
+        Path::new(&self.cache_dir).join("manifest.json")
+    }
+
+    /// Записывает metadata.json через временный файл и rename, чтобы читатели не видели частичную запись
+    fn write_metadata_atomic(&self, project_id: &str, meta: &CacheMetadata) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        fs::create_dir_all(self.project_dir(project_id))?;
+        write_atomic(&self.meta_path_for(project_id), self.serialize_metadata(project_id, meta)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Плоские файлы старого формата в каталоге кэша, сгруппированные по проектам
+    fn legacy_files(&self) -> Result<std::collections::BTreeMap<String, Vec<PathBuf>>, Box<dyn std::error::Error + Send + Sync>> {
+        let dir = Path::new(&self.cache_dir);
+        let mut legacy: std::collections::BTreeMap<String, Vec<PathBuf>> = std::collections::BTreeMap::new();
+        if !dir.exists() {
+            return Ok(legacy);
+        }
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if !path.is_file() {
+                continue;
+            }
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            if let Some(project_id) = LEGACY_SUFFIXES.iter().find_map(|s| name.strip_suffix(s)).filter(|id| !id.is_empty()) {
+                legacy.entry(project_id.to_string()).or_default().push(path);
+            }
+        }
+        Ok(legacy)
+    }
+
+    /// Проекты, у которых есть плоские файлы старого формата (без учета cache_layout)
+    pub(crate) fn legacy_project_ids(&self) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.legacy_files()?.into_keys().collect())
+    }
+
+    /// Метаданные проекта из плоских файлов старого формата. Старая суммаризация summary.txt
+    /// становится суммаризацией опубликованных каналов, если своих у них нет; без опубликованных
+    /// каналов (или без читаемых метаданных) — суммаризацией всех каналов, чтобы она не потерялась
+    pub(crate) fn legacy_project_metadata(&self, project_id: &str) -> CacheMetadata {
+        let flat = |suffix: &str| Path::new(&self.cache_dir).join(format!("{}{}", project_id, suffix));
+        let mut meta = fs::read_to_string(flat(LEGACY_METADATA))
+            .ok()
+            .and_then(|data| legacy_metadata(project_id, &data))
+            .unwrap_or_else(|| CacheMetadata::empty(project_id));
+        meta.project_id = project_id.to_string().into();
+        if let Ok(summary) = fs::read_to_string(flat(LEGACY_SUMMARY)) {
+            let channels = if meta.published_channels.is_empty() {
+                PublisherChannel::all()
+            } else {
+                meta.published_channels.clone()
+            };
+            for channel in channels {
+                meta.channel_summaries.entry(channel).or_insert_with(|| summary.clone().into());
+            }
+        }
+        meta
+    }
+
+    /// Переносит плоские файлы старого формата ({id}_metadata.json, {id}_extracted.md, {id}_summary.txt)
+    /// в каталоги проектов с metadata.json. Проект, у которого каталог с metadata.json уже есть, пропускается.
+    /// Старая суммаризация summary.txt сохраняется как в `legacy_project_metadata`.
+    /// С `delete_legacy` плоские файлы перенесенных проектов удаляются
+    pub fn migrate_legacy_layout(&self, delete_legacy: bool) -> Result<CacheMigrationReport, Box<dyn std::error::Error + Send + Sync>> {
+        let dir = Path::new(&self.cache_dir);
+        let mut report = CacheMigrationReport::default();
+        for (project_id, files) in self.legacy_files()? {
+            if self.meta_path_for(&project_id).exists() {
+                tracing::info!(project_id = %project_id, "migrate-cache: project already has metadata.json, skipped");
+                report.skipped += 1;
+                continue;
+            }
+            let mut meta = self.legacy_project_metadata(&project_id);
+            fs::create_dir_all(self.project_dir(&project_id))?;
+            if let Ok(markdown) = fs::read_to_string(dir.join(format!("{}{}", project_id, LEGACY_EXTRACTED))) {
+                meta.markdown_path = self.write_markdown(&project_id, &markdown)?.to_string_lossy().to_string().into();
+            }
+            self.write_metadata_atomic(&project_id, &meta)?;
+            if delete_legacy {
+                for file in &files {
+                    fs::remove_file(file)?;
+                    report.deleted_files += 1;
+                }
+            }
+            tracing::info!(project_id = %project_id, files = files.len(), "migrate-cache: project migrated");
+            report.migrated += 1;
+        }
+        Ok(report)
+    }
 }
 
 #[async_trait]
@@ -45,25 +281,18 @@ impl CacheManager for FileSystemCacheManager {
 
         // per-project subdir layout
         let docx_path = base.join("source.docx");
-        let md_path = base.join("extracted.md");
         let meta_path = base.join("metadata.json");
 
         if let Some(bytes) = docx_bytes {
             fs::write(&docx_path, bytes)?;
         }
-        fs::write(&md_path, markdown_text)?;
+        let md_path = self.write_markdown(project_id, markdown_text)?;
 
         // Загружаем существующие метаданные, если они есть, чтобы сохранить published_channels
-        let (existing_published_channels, existing_channel_summaries, existing_channel_posts, existing_crawl_metadata) = if meta_path.exists() {
-            let data = fs::read_to_string(&meta_path).ok();
-            if let Some(meta) = data.and_then(|d| serde_json::from_str::<CacheMetadata>(&d).ok()) {
-                (meta.published_channels, meta.channel_summaries, meta.channel_posts, meta.crawl_metadata)
-            } else {
-                (vec![], std::collections::HashMap::new(), std::collections::HashMap::new(), vec![])
-            }
-        } else {
-            (vec![], std::collections::HashMap::new(), std::collections::HashMap::new(), vec![])
-        };
+        let existing = fs::read_to_string(&meta_path)
+            .ok()
+            .and_then(|d| self.parse_metadata(project_id, &d).ok())
+            .unwrap_or_else(|| CacheMetadata::empty(project_id));
 
         let meta = CacheMetadata {
             project_id: project_id.to_string().into(),
@@ -71,22 +300,28 @@ impl CacheManager for FileSystemCacheManager {
             markdown_path: md_path.to_string_lossy().to_string().into(),
             // Сохраняем существующие published_channels, если передан пустой список
             published_channels: if published_channels.is_empty() {
-                existing_published_channels
+                existing.published_channels.clone()
             } else {
                 published_channels.to_vec()
             },
             created_at: ts.into(),
-            channel_summaries: existing_channel_summaries,
-            channel_posts: existing_channel_posts,
             // Сохраняем метаданные из crawler, если переданы, иначе сохраняем существующие
             crawl_metadata: if crawl_metadata.is_empty() {
-                existing_crawl_metadata
+                existing.crawl_metadata.clone()
             } else {
                 crawl_metadata.to_vec()
             },
+            // Хэш документа обновляется только при сохранении новых байт документа
+            document_hash: docx_bytes.map(content_hash).or(existing.document_hash.clone()),
+            // Новые байты документа делают прежние HTTP-валидаторы недействительными
+            document_validators: if docx_bytes.is_some() { None } else { existing.document_validators.clone() },
+            external_summaries: std::collections::HashMap::new(),
+            external_posts: std::collections::HashMap::new(),
+            summary_ratings: std::collections::HashMap::new(),
+            ..existing
         };
-        let json = serde_json::to_string_pretty(&meta).unwrap_or_else(|_| "{}".to_string());
-        fs::write(&meta_path, json)?;
+        let json = self.serialize_metadata(project_id, &meta)?;
+        write_atomic(&meta_path, json.as_bytes())?;
         Ok(())
     }
 
@@ -96,20 +331,42 @@ impl CacheManager for FileSystemCacheManager {
     ) -> Result<Option<CacheMetadata>, Box<dyn std::error::Error + Send + Sync>> {
         // new layout first
         let p = self.meta_path_for(project_id);
-        let data = if p.exists() {
-            fs::read_to_string(p)?
-        } else {
+        let is_legacy = !p.exists();
+        let path = if is_legacy {
             // legacy fallback
-            let legacy = Path::new(&self.cache_dir).join(format!("{}_metadata.json", project_id));
-            if !legacy.exists() {
+            let Some(legacy) = self.legacy_path(project_id, LEGACY_METADATA) else {
                 return Ok(None);
-            }
-            fs::read_to_string(legacy)?
+            };
+            legacy
+        } else {
+            p
         };
-        match serde_json::from_str::<CacheMetadata>(&data) {
-            Ok(m) => Ok(Some(m)),
-            Err(_) => Ok(None),
+        let data = fs::read_to_string(&path)?;
+        let mut meta = match self.parse_metadata(project_id, &data) {
+            Ok(m) => m,
+            Err(_) => return Ok(None),
+        };
+        // created_at нужен функциям, зависящим от времени: поврежденную метку заменяем
+        // временем изменения файла метаданных (или текущим временем)
+        let broken = meta.created_at.as_str().to_string();
+        let fallback = fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .map(chrono::DateTime::<chrono::Utc>::from)
+            .unwrap_or_else(|_| chrono::Utc::now());
+        if meta.repair_created_at(fallback) {
+            tracing::warn!(
+                project_id = project_id,
+                created_at = %broken,
+                repaired_to = %meta.created_at,
+                "cache_manager: unparseable created_at in metadata, repaired"
+            );
+            if !is_legacy {
+                if let Err(e) = self.write_metadata_atomic(project_id, &meta) {
+                    tracing::warn!(project_id = project_id, error = %e, "cache_manager: failed to persist repaired created_at");
+                }
+            }
         }
+        Ok(Some(meta))
     }
 
     async fn load_summary(
@@ -126,8 +383,7 @@ impl CacheManager for FileSystemCacheManager {
         }
         
         // Legacy fallback - проверяем старый файл summary.txt
-        let legacy = Path::new(&self.cache_dir).join(format!("{}_summary.txt", project_id));
-        if legacy.exists() {
+        if let Some(legacy) = self.legacy_path(project_id, LEGACY_SUMMARY) {
             return Ok(Some(fs::read_to_string(legacy)?));
         }
         
@@ -138,16 +394,15 @@ impl CacheManager for FileSystemCacheManager {
         &self,
         project_id: &str,
     ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
-        // new layout first
-        let p = self.project_dir(project_id).join("extracted.md");
+        // new layout first (extracted.md.gz или extracted.md)
+        let p = self.markdown_path_for(project_id);
         let s = if p.exists() {
-            fs::read_to_string(p)?
+            Self::read_markdown(&p)?
         } else {
             // legacy fallback
-            let legacy = Path::new(&self.cache_dir).join(format!("{}_extracted.md", project_id));
-            if !legacy.exists() {
+            let Some(legacy) = self.legacy_path(project_id, LEGACY_EXTRACTED) else {
                 return Ok(None);
-            }
+            };
             fs::read_to_string(legacy)?
         };
         Ok(Some(s))
@@ -161,35 +416,17 @@ impl CacheManager for FileSystemCacheManager {
         let p = self.meta_path_for(project_id);
         let mut meta = if p.exists() {
             let data = fs::read_to_string(&p)?;
-            serde_json::from_str::<CacheMetadata>(&data).unwrap_or(CacheMetadata {
-                project_id: project_id.to_string().into(),
-                docx_path: String::new().into(),
-                markdown_path: String::new().into(),
-                published_channels: vec![],
-                created_at: chrono::Utc::now().to_rfc3339().into(),
-                channel_summaries: std::collections::HashMap::new(),
-                channel_posts: std::collections::HashMap::new(),
-                crawl_metadata: vec![],
-            })
+            self.parse_metadata(project_id, &data).unwrap_or(CacheMetadata::empty(project_id))
         } else {
-            CacheMetadata {
-                project_id: project_id.to_string().into(),
-                docx_path: String::new().into(),
-                markdown_path: String::new().into(),
-                published_channels: vec![],
-                created_at: chrono::Utc::now().to_rfc3339().into(),
-                channel_summaries: std::collections::HashMap::new(),
-                channel_posts: std::collections::HashMap::new(),
-                crawl_metadata: vec![],
-            }
+            CacheMetadata::empty(project_id)
         };
         for ch in new_channels {
             if !meta.published_channels.iter().any(|c| c == ch) {
                 meta.published_channels.push(*ch);
             }
         }
-        let out = serde_json::to_string_pretty(&meta).unwrap_or_else(|_| "{}".to_string());
-        fs::write(p, out)?;
+        let out = self.serialize_metadata(project_id, &meta)?;
+        write_atomic(&p, out.as_bytes())?;
         Ok(())
     }
 
@@ -202,38 +439,253 @@ impl CacheManager for FileSystemCacheManager {
         let mut meta = if p.exists() {
             let data = fs::read_to_string(&p)?;
             // Читаем существующие данные или создаем новые только если файл пуст/поврежден
-            serde_json::from_str::<CacheMetadata>(&data).unwrap_or_else(|_| {
+            self.parse_metadata(project_id, &data).unwrap_or_else(|_| {
                 // При ошибке парсинга НЕ перезаписываем весь файл - только добавляем канал
-                CacheMetadata {
-                    project_id: project_id.to_string().into(),
-                    docx_path: String::new().into(),
-                    markdown_path: String::new().into(),
-                    published_channels: vec![],
-                    created_at: chrono::Utc::now().to_rfc3339().into(),
-                    channel_summaries: std::collections::HashMap::new(),
-                    channel_posts: std::collections::HashMap::new(),
-                    crawl_metadata: vec![],
-                }
+                CacheMetadata::empty(project_id)
             })
         } else {
-            CacheMetadata {
-                project_id: project_id.to_string().into(),
-                docx_path: String::new().into(),
-                markdown_path: String::new().into(),
-                published_channels: vec![],
-                created_at: chrono::Utc::now().to_rfc3339().into(),
-                channel_summaries: std::collections::HashMap::new(),
-                channel_posts: std::collections::HashMap::new(),
-                crawl_metadata: vec![],
-            }
+            CacheMetadata::empty(project_id)
         };
         
         if !meta.published_channels.iter().any(|c| c == &channel) {
             meta.published_channels.push(channel);
         }
         
-        let out = serde_json::to_string_pretty(&meta).unwrap_or_else(|_| "{}".to_string());
-        fs::write(p, out)?;
+        let out = self.serialize_metadata(project_id, &meta)?;
+        write_atomic(&p, out.as_bytes())?;
+        Ok(())
+    }
+
+    async fn mark_published(
+        &self,
+        project_id: &str,
+        channel: PublisherChannel,
+        summary_text: Option<&str>,
+        post_text: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut meta = self
+            .load_metadata(project_id)
+            .await?
+            .unwrap_or_else(|| CacheMetadata::empty(project_id));
+
+        self.stamp_summary_model(&mut meta);
+        if let Some(summary) = summary_text {
+            meta.channel_summaries.insert(channel, summary.to_string().into());
+        }
+        meta.channel_posts.insert(channel, post_text.to_string().into());
+        meta.content_hash.insert(channel, content_hash(post_text.as_bytes()));
+        if !meta.published_channels.contains(&channel) {
+            meta.published_channels.push(channel);
+        }
+
+        self.write_metadata_atomic(project_id, &meta)
+    }
+
+    async fn update_document_state(
+        &self,
+        project_id: &str,
+        validators: Option<&DocumentValidators>,
+        crawl_metadata: &[crate::models::types::MetadataItem],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut meta = self
+            .load_metadata(project_id)
+            .await?
+            .unwrap_or_else(|| CacheMetadata::empty(project_id));
+        if let Some(v) = validators {
+            meta.document_validators = Some(v.clone());
+        }
+        if !crawl_metadata.is_empty() {
+            meta.crawl_metadata = crawl_metadata.to_vec();
+        }
+        self.write_metadata_atomic(project_id, &meta)
+    }
+
+    async fn mark_skipped(
+        &self,
+        project_id: &str,
+        reason: &str,
+        crawl_metadata: &[crate::models::types::MetadataItem],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut meta = self
+            .load_metadata(project_id)
+            .await?
+            .unwrap_or_else(|| CacheMetadata::empty(project_id));
+        meta.skip_reason = Some(reason.to_string());
+        if !crawl_metadata.is_empty() {
+            meta.crawl_metadata = crawl_metadata.to_vec();
+        }
+        self.write_metadata_atomic(project_id, &meta)
+    }
+
+    async fn record_timeout(
+        &self,
+        project_id: &str,
+        crawl_metadata: &[crate::models::types::MetadataItem],
+    ) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
+        let mut meta = self
+            .load_metadata(project_id)
+            .await?
+            .unwrap_or_else(|| CacheMetadata::empty(project_id));
+        meta.timeout_attempts += 1;
+        if !crawl_metadata.is_empty() {
+            meta.crawl_metadata = crawl_metadata.to_vec();
+        }
+        self.write_metadata_atomic(project_id, &meta)?;
+        Ok(meta.timeout_attempts)
+    }
+
+    async fn record_target_results(
+        &self,
+        project_id: &str,
+        channel: PublisherChannel,
+        results: &[(String, bool)],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut meta = self
+            .load_metadata(project_id)
+            .await?
+            .unwrap_or_else(|| CacheMetadata::empty(project_id));
+        meta.target_results.insert(channel, results.iter().cloned().collect());
+        self.write_metadata_atomic(project_id, &meta)
+    }
+
+    async fn record_thread_progress(
+        &self,
+        project_id: &str,
+        channel: PublisherChannel,
+        progress: &ThreadProgress,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut meta = self
+            .load_metadata(project_id)
+            .await?
+            .unwrap_or_else(|| CacheMetadata::empty(project_id));
+        meta.threads.insert(channel, progress.clone());
+        self.write_metadata_atomic(project_id, &meta)
+    }
+
+    async fn record_title(
+        &self,
+        project_id: &str,
+        title: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut meta = self
+            .load_metadata(project_id)
+            .await?
+            .unwrap_or_else(|| CacheMetadata::empty(project_id));
+        if meta.title.as_deref() == Some(title) {
+            return Ok(());
+        }
+        meta.title = Some(title.to_string());
+        self.write_metadata_atomic(project_id, &meta)
+    }
+
+    async fn clear_summaries(&self, project_id: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(mut meta) = self.load_metadata(project_id).await? else {
+            return Ok(false);
+        };
+        meta.channel_summaries.clear();
+        meta.channel_posts.clear();
+        self.write_metadata_atomic(project_id, &meta)?;
+
+        // Legacy summary.txt тоже считается суммаризацией (см. has_summary)
+        if let Some(legacy) = self.legacy_path(project_id, LEGACY_SUMMARY) {
+            fs::remove_file(legacy)?;
+        }
+        Ok(true)
+    }
+
+    async fn list_project_ids(&self) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let dir = Path::new(&self.cache_dir);
+        if !dir.exists() {
+            return Ok(vec![]);
+        }
+        let mut ids = std::collections::BTreeSet::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.path().join("metadata.json").is_file() {
+                ids.insert(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+        // Пока кэш не переведен на v2, проекты в плоских файлах старого формата тоже читаются
+        if self.layout != CacheLayout::V2 {
+            ids.extend(self.legacy_project_ids()?);
+        }
+        Ok(ids.into_iter().collect())
+    }
+
+    async fn prune_expired(
+        &self,
+        ttl: std::time::Duration,
+        enabled_channels: &[PublisherChannel],
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let ttl = chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::MAX);
+        let now = chrono::Utc::now();
+        let mut removed = 0;
+        for project_id in self.list_project_ids().await? {
+            let Some(meta) = self.load_metadata(&project_id).await? else { continue };
+            if !meta.age(now).is_some_and(|age| age > ttl) {
+                continue;
+            }
+            if !self.is_fully_published(&project_id, enabled_channels).await? {
+                tracing::info!(project_id = %project_id, "cache_manager: expired project is not fully published, keeping it");
+                continue;
+            }
+            let dir = self.project_dir(&project_id);
+            if dir.exists() {
+                fs::remove_dir_all(dir)?;
+            }
+            for suffix in LEGACY_SUFFIXES {
+                if let Some(legacy) = self.legacy_path(&project_id, suffix) {
+                    fs::remove_file(legacy)?;
+                }
+            }
+            removed += 1;
+        }
+        Ok(removed)
+    }
+
+    async fn try_claim(
+        &self,
+        project_id: &str,
+        ttl: std::time::Duration,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        fs::create_dir_all(self.project_dir(project_id))?;
+        let path = self.in_progress_path_for(project_id);
+        if self.create_marker(&path)? {
+            return Ok(true);
+        }
+
+        // Маркер есть: нечитаемый или старше ttl считается брошенным (упавший экземпляр)
+        let existing = fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<InProgressMarker>(&s).ok());
+        let ttl = chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::MAX);
+        let is_fresh = |m: &InProgressMarker| {
+            chrono::DateTime::parse_from_rfc3339(&m.claimed_at)
+                .is_ok_and(|t| chrono::Utc::now().signed_duration_since(t) < ttl)
+        };
+        match existing {
+            Some(m) if m.owner == self.instance_id => Ok(true),
+            Some(m) if is_fresh(&m) => {
+                tracing::info!(project_id = %project_id, owner = %m.owner, claimed_at = %m.claimed_at, "in_progress marker is active");
+                Ok(false)
+            }
+            stale => {
+                tracing::warn!(project_id = %project_id, marker = ?stale, "taking over stale in_progress marker");
+                let _ = fs::remove_file(&path);
+                self.create_marker(&path)
+            }
+        }
+    }
+
+    async fn release_claim(&self, project_id: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let path = self.in_progress_path_for(project_id);
+        let owned = fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<InProgressMarker>(&s).ok())
+            .is_some_and(|m| m.owner == self.instance_id);
+        if owned {
+            fs::remove_file(&path)?;
+        }
         Ok(())
     }
 
@@ -249,35 +701,21 @@ impl CacheManager for FileSystemCacheManager {
         let p = self.meta_path_for(project_id);
         let mut meta = if p.exists() {
             let data = fs::read_to_string(&p)?;
-            match serde_json::from_str::<CacheMetadata>(&data) {
+            match self.parse_metadata(project_id, &data) {
                 Ok(parsed_meta) => parsed_meta,
                 Err(e) => {
                     tracing::warn!(project_id = %project_id, error = %e, "failed to parse existing metadata.json, creating new one");
-                    CacheMetadata {
-                        project_id: project_id.to_string().into(),
-                        docx_path: String::new().into(),
-                        markdown_path: String::new().into(),
-                        published_channels: vec![],
-                        created_at: chrono::Utc::now().to_rfc3339().into(),
-                        channel_summaries: std::collections::HashMap::new(),
-                        channel_posts: std::collections::HashMap::new(),
-                        crawl_metadata: vec![],
-                    }
+                    CacheMetadata::empty(project_id)
                 }
             }
         } else {
-            CacheMetadata {
-                project_id: project_id.to_string().into(),
-                docx_path: String::new().into(),
-                markdown_path: String::new().into(),
-                published_channels: vec![],
-                created_at: chrono::Utc::now().to_rfc3339().into(),
-                channel_summaries: std::collections::HashMap::new(),
-                channel_posts: std::collections::HashMap::new(),
-                crawl_metadata: vec![],
-            }
+            CacheMetadata::empty(project_id)
         };
         
+        if summary_text.is_some() || post_text.is_some() {
+            self.stamp_summary_model(&mut meta);
+        }
+
         // Обновляем суммаризацию, если передана
         if let Some(summary) = summary_text {
             meta.channel_summaries.insert(channel, summary.to_string().into());
@@ -288,25 +726,28 @@ impl CacheManager for FileSystemCacheManager {
             meta.channel_posts.insert(channel, post.to_string().into());
         }
         
-        // Обновляем статус публикации
-        if is_published && !meta.published_channels.iter().any(|c| c == &channel) {
-            meta.published_channels.push(channel);
+        // Обновляем статус публикации и хэш опубликованного поста
+        if is_published {
+            if let Some(post) = post_text {
+                meta.content_hash.insert(channel, content_hash(post.as_bytes()));
+            }
+            if !meta.published_channels.iter().any(|c| c == &channel) {
+                meta.published_channels.push(channel);
+            }
         }
         
-        let json = serde_json::to_string_pretty(&meta).unwrap_or_else(|_| "{}".to_string());
-        fs::write(&p, json)?;
+        let json = self.serialize_metadata(project_id, &meta)?;
+        write_atomic(&p, json.as_bytes())?;
         Ok(())
     }
 
     async fn has_data(&self, project_id: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
         // new layout first
-        let p = self.project_dir(project_id).join("extracted.md");
-        if p.exists() {
+        if self.markdown_path_for(project_id).exists() {
             return Ok(true);
         }
         // legacy fallback
-        let legacy = Path::new(&self.cache_dir).join(format!("{}_extracted.md", project_id));
-        Ok(legacy.exists())
+        Ok(self.legacy_path(project_id, LEGACY_EXTRACTED).is_some())
     }
 
     async fn has_summary(&self, project_id: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
@@ -319,8 +760,7 @@ impl CacheManager for FileSystemCacheManager {
         }
         
         // Legacy fallback - проверяем старый файл summary.txt
-        let legacy = Path::new(&self.cache_dir).join(format!("{}_summary.txt", project_id));
-        Ok(legacy.exists())
+        Ok(self.legacy_path(project_id, LEGACY_SUMMARY).is_some())
     }
 
     async fn is_published_in_channel(
@@ -346,7 +786,7 @@ impl CacheManager for FileSystemCacheManager {
         channel: PublisherChannel,
     ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
         let meta = self.load_metadata(project_id).await?;
-        Ok(meta.map(|m| m.channel_summaries.contains_key(&channel)).unwrap_or(false))
+        Ok(meta.is_some_and(|m| self.summaries_current(&m) && m.channel_summaries.contains_key(&channel)))
     }
 
     async fn load_channel_summary(
@@ -355,7 +795,7 @@ impl CacheManager for FileSystemCacheManager {
         channel: PublisherChannel,
     ) -> Result<Option<SummaryText>, Box<dyn std::error::Error + Send + Sync>> {
         let meta = self.load_metadata(project_id).await?;
-        Ok(meta.and_then(|m| m.channel_summaries.get(&channel).cloned()))
+        Ok(meta.filter(|m| self.summaries_current(m)).and_then(|m| m.channel_summaries.get(&channel).cloned()))
     }
 
     async fn update_channel_summary(
@@ -367,33 +807,16 @@ impl CacheManager for FileSystemCacheManager {
         let p = self.meta_path_for(project_id);
         let mut meta = if p.exists() {
             let data = fs::read_to_string(&p)?;
-            serde_json::from_str::<CacheMetadata>(&data).unwrap_or(CacheMetadata {
-                project_id: project_id.to_string().into(),
-                docx_path: String::new().into(),
-                markdown_path: String::new().into(),
-                published_channels: vec![],
-                created_at: chrono::Utc::now().to_rfc3339().into(),
-                channel_summaries: std::collections::HashMap::new(),
-                channel_posts: std::collections::HashMap::new(),
-                crawl_metadata: vec![],
-            })
+            self.parse_metadata(project_id, &data).unwrap_or(CacheMetadata::empty(project_id))
         } else {
-            CacheMetadata {
-                project_id: project_id.to_string().into(),
-                docx_path: String::new().into(),
-                markdown_path: String::new().into(),
-                published_channels: vec![],
-                created_at: chrono::Utc::now().to_rfc3339().into(),
-                channel_summaries: std::collections::HashMap::new(),
-                channel_posts: std::collections::HashMap::new(),
-                crawl_metadata: vec![],
-            }
+            CacheMetadata::empty(project_id)
         };
         
+        self.stamp_summary_model(&mut meta);
         meta.channel_summaries.insert(channel, summary_text.to_string().into());
         
-        let json = serde_json::to_string_pretty(&meta).unwrap_or_else(|_| "{}".to_string());
-        fs::write(&p, json)?;
+        let json = self.serialize_metadata(project_id, &meta)?;
+        write_atomic(&p, json.as_bytes())?;
         Ok(())
     }
 
@@ -403,7 +826,7 @@ impl CacheManager for FileSystemCacheManager {
         channel: PublisherChannel,
     ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
         let meta = self.load_metadata(project_id).await?;
-        Ok(meta.map(|m| m.channel_posts.contains_key(&channel)).unwrap_or(false))
+        Ok(meta.is_some_and(|m| self.summaries_current(&m) && m.channel_posts.contains_key(&channel)))
     }
 
     async fn load_channel_post(
@@ -412,7 +835,7 @@ impl CacheManager for FileSystemCacheManager {
         channel: PublisherChannel,
     ) -> Result<Option<PostText>, Box<dyn std::error::Error + Send + Sync>> {
         let meta = self.load_metadata(project_id).await?;
-        Ok(meta.and_then(|m| m.channel_posts.get(&channel).cloned()))
+        Ok(meta.filter(|m| self.summaries_current(m)).and_then(|m| m.channel_posts.get(&channel).cloned()))
     }
 
     async fn update_channel_post(
@@ -424,38 +847,21 @@ impl CacheManager for FileSystemCacheManager {
         let p = self.meta_path_for(project_id);
         let mut meta = if p.exists() {
             let data = fs::read_to_string(&p)?;
-            serde_json::from_str::<CacheMetadata>(&data).unwrap_or(CacheMetadata {
-                project_id: project_id.to_string().into(),
-                docx_path: String::new().into(),
-                markdown_path: String::new().into(),
-                published_channels: vec![],
-                created_at: chrono::Utc::now().to_rfc3339().into(),
-                channel_summaries: std::collections::HashMap::new(),
-                channel_posts: std::collections::HashMap::new(),
-                crawl_metadata: vec![],
-            })
+            self.parse_metadata(project_id, &data).unwrap_or(CacheMetadata::empty(project_id))
         } else {
-            CacheMetadata {
-                project_id: project_id.to_string().into(),
-                docx_path: String::new().into(),
-                markdown_path: String::new().into(),
-                published_channels: vec![],
-                created_at: chrono::Utc::now().to_rfc3339().into(),
-                channel_summaries: std::collections::HashMap::new(),
-                channel_posts: std::collections::HashMap::new(),
-                crawl_metadata: vec![],
-            }
+            CacheMetadata::empty(project_id)
         };
         
+        self.stamp_summary_model(&mut meta);
         meta.channel_posts.insert(channel, post_text.to_string().into());
         
-        let json = serde_json::to_string_pretty(&meta).unwrap_or_else(|_| "{}".to_string());
-        fs::write(&p, json)?;
+        let json = self.serialize_metadata(project_id, &meta)?;
+        write_atomic(&p, json.as_bytes())?;
         Ok(())
     }
 
     async fn load_manifest(&self) -> Result<crate::models::types::Manifest, Box<dyn std::error::Error + Send + Sync>> {
-        let manifest_path = Path::new(&self.cache_dir).join("manifest.json");
+        let manifest_path = self.manifest_path();
         if manifest_path.exists() {
             if let Ok(s) = fs::read_to_string(&manifest_path) {
                 if let Ok(m) = serde_json::from_str::<crate::models::types::Manifest>(&s) {
@@ -467,22 +873,37 @@ impl CacheManager for FileSystemCacheManager {
     }
 
     async fn save_manifest(&self, manifest: &crate::models::types::Manifest) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Ensure cache dir exists
-        let manifest_path = Path::new(&self.cache_dir).join("manifest.json");
-        if let Some(dir) = manifest_path.parent() {
-            fs::create_dir_all(dir)?;
-        }
-        let json = serde_json::to_string_pretty(manifest).unwrap_or_else(|_| "{}".to_string());
-        tracing::info!(manifest_path = %manifest_path.display(), manifest_content = %json, "npalist: saving manifest");
-        fs::write(&manifest_path, json)?;
-        Ok(())
+        let cache_dir = PathBuf::from(&self.cache_dir);
+        let manifest = manifest.clone();
+        tokio::task::spawn_blocking(move || {
+            let _lock = lock_manifest(&cache_dir)?;
+            write_manifest(&cache_dir, &manifest)
+        })
+        .await?
+    }
+
+    async fn update_manifest(&self, update: ManifestUpdate) -> Result<crate::models::types::Manifest, Box<dyn std::error::Error + Send + Sync>> {
+        // Блокировка fs2 ждет другой процесс синхронно: держим ее вне потоков runtime
+        let cache_dir = PathBuf::from(&self.cache_dir);
+        tokio::task::spawn_blocking(move || {
+            let _lock = lock_manifest(&cache_dir)?;
+            let mut manifest = fs::read_to_string(cache_dir.join("manifest.json"))
+                .ok()
+                .and_then(|s| serde_json::from_str::<crate::models::types::Manifest>(&s).ok())
+                .unwrap_or_default();
+            update(&mut manifest);
+            write_manifest(&cache_dir, &manifest)?;
+            Ok(manifest)
+        })
+        .await?
     }
 
-    async fn update_min_published_project_id(&self, min_id: u32) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let mut manifest = self.load_manifest().await?;
-        manifest.min_published_project_id = Some(min_id);
-        tracing::info!(new_min_id = min_id, "cache_manager: updating min_published_project_id");
-        self.save_manifest(&manifest).await?;
+    async fn update_min_published_project_id(&self, source_id: &str, min_id: u32) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        tracing::info!(source = %source_id, new_min_id = min_id, "cache_manager: updating min_published_project_id");
+        let source_id = source_id.to_string();
+        self.update_manifest(Box::new(move |manifest| {
+            manifest.min_published_project_ids.insert(source_id, min_id);
+        })).await?;
         Ok(())
     }
 
@@ -494,29 +915,13 @@ impl CacheManager for FileSystemCacheManager {
         let p = self.meta_path_for(project_id);
         let mut meta = if p.exists() {
             let data = fs::read_to_string(&p)?;
-            serde_json::from_str::<CacheMetadata>(&data).unwrap_or(CacheMetadata {
-                project_id: project_id.to_string().into(),
-                docx_path: String::new().into(),
-                markdown_path: String::new().into(),
-                published_channels: vec![],
-                created_at: chrono::Utc::now().to_rfc3339().into(),
-                channel_summaries: std::collections::HashMap::new(),
-                channel_posts: std::collections::HashMap::new(),
-                crawl_metadata: vec![],
-            })
+            self.parse_metadata(project_id, &data).unwrap_or(CacheMetadata::empty(project_id))
         } else {
-            CacheMetadata {
-                project_id: project_id.to_string().into(),
-                docx_path: String::new().into(),
-                markdown_path: String::new().into(),
-                published_channels: vec![],
-                created_at: chrono::Utc::now().to_rfc3339().into(),
-                channel_summaries: std::collections::HashMap::new(),
-                channel_posts: std::collections::HashMap::new(),
-                crawl_metadata: vec![],
-            }
+            CacheMetadata::empty(project_id)
         };
         
+        self.stamp_summary_model(&mut meta);
+
         // Обновляем данные для всех каналов
         for (channel, summary, post) in channel_data {
             meta.channel_summaries.insert(*channel, summary.to_string().into());
@@ -528,8 +933,8 @@ impl CacheManager for FileSystemCacheManager {
             }
         }
         
-        let json = serde_json::to_string_pretty(&meta).unwrap_or_else(|_| "{}".to_string());
-        fs::write(&p, json)?;
+        let json = self.serialize_metadata(project_id, &meta)?;
+        write_atomic(&p, json.as_bytes())?;
         Ok(())
     }
 
@@ -540,6 +945,12 @@ impl CacheManager for FileSystemCacheManager {
             None => return Ok(false), // Нет метаданных - не опубликован
         };
 
+        // Пропущенный элемент считается обработанным, чтобы краулер двигался дальше
+        if let Some(reason) = metadata.skip_reason.as_deref() {
+            tracing::info!(project_id = project_id, skip_reason = reason, "Element was skipped earlier, treating as processed");
+            return Ok(true);
+        }
+
         // Проверяем, что элемент опубликован во все включенные каналы
         for channel in enabled_channels {
             if !metadata.published_channels.contains(channel) {
@@ -561,3 +972,440 @@ impl CacheManager for FileSystemCacheManager {
         Ok(true)
     }
 }
+
+/// Проверяет, что суммаризации в метаданных сделаны текущей моделью `summary_model`.
+/// Метаданные без записанной модели (старый кэш) считаются актуальными
+pub(crate) fn summaries_current(summary_model: Option<&str>, meta: &CacheMetadata) -> bool {
+    match (summary_model, meta.summary_model.as_deref()) {
+        (Some(current), Some(cached)) if current != cached => {
+            tracing::info!(project_id = %meta.project_id, cached_model = %cached, model = %current, "cached summaries were made by another model, ignoring them");
+            false
+        }
+        _ => true,
+    }
+}
+
+/// Отмечает суммаризации моделью `summary_model`; устаревшие суммаризации и посты неопубликованных
+/// каналов при смене модели удаляются, чтобы не выдать их за сделанные новой моделью
+pub(crate) fn stamp_summary_model(summary_model: Option<&str>, meta: &mut CacheMetadata) {
+    if meta.summary_model.is_some() && meta.summary_model.as_deref() != summary_model {
+        let published = meta.published_channels.clone();
+        meta.channel_summaries.retain(|c, _| published.contains(c));
+        meta.channel_posts.retain(|c, _| published.contains(c));
+    }
+    meta.summary_model = summary_model.map(str::to_string);
+}
+
+/// Метаданные старого формата: CacheMetadata целиком, а если он не разбирается — только published_channels
+fn legacy_metadata(project_id: &str, data: &str) -> Option<CacheMetadata> {
+    if let Ok(meta) = serde_json::from_str::<CacheMetadata>(data) {
+        return Some(meta);
+    }
+    let value: serde_json::Value = serde_json::from_str(data).ok()?;
+    let mut meta = CacheMetadata::empty(project_id);
+    meta.published_channels = serde_json::from_value(value.get("published_channels")?.clone()).ok()?;
+    Some(meta)
+}
+
+/// Счетчик имен временных файлов: одновременные записи одного файла не делят временный файл
+static TMP_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+/// Эксклюзивная advisory-блокировка manifest.lock в каталоге кэша: сериализует изменения
+/// manifest.json между процессами. Снимается при освобождении возвращенного файла
+fn lock_manifest(cache_dir: &Path) -> Result<ManifestLock, Box<dyn std::error::Error + Send + Sync>> {
+    fs::create_dir_all(cache_dir)?;
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(cache_dir.join("manifest.lock"))?;
+    file.lock_exclusive()?;
+    Ok(ManifestLock(file))
+}
+
+/// Записывает manifest.json; вызывающий держит блокировку manifest
+fn write_manifest(cache_dir: &Path, manifest: &crate::models::types::Manifest) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let manifest_path = cache_dir.join("manifest.json");
+    let json = serde_json::to_string_pretty(manifest).unwrap_or_else(|_| "{}".to_string());
+    tracing::info!(manifest_path = %manifest_path.display(), manifest_content = %json, "npalist: saving manifest");
+    write_atomic(&manifest_path, json.as_bytes())?;
+    Ok(())
+}
+
+/// Записывает файл атомарно: во временный файл того же каталога, fsync и rename поверх `path`.
+/// При падении процесса на диске остается либо прежнее, либо новое содержимое целиком;
+/// недописанный временный файл игнорируется
+fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("cache");
+    let n = TMP_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let tmp = path.with_file_name(format!(".{}.{}.{}.tmp", name, std::process::id(), n));
+    let result = fs::File::create(&tmp)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// Подставляет тексты из файлов `refs` (канал -> имя файла в `dir`) в `texts`
+fn inline_external<T: From<String>>(
+    dir: &Path,
+    refs: &mut std::collections::HashMap<PublisherChannel, String>,
+    texts: &mut std::collections::HashMap<PublisherChannel, T>,
+) {
+    for (channel, file) in refs.drain() {
+        // В метаданных хранится только имя файла; каталоги в ссылке не допускаются
+        let Some(name) = Path::new(&file).file_name() else { continue };
+        match fs::read_to_string(dir.join(name)) {
+            Ok(text) => {
+                texts.insert(channel, text.into());
+            }
+            Err(e) => tracing::warn!(channel = %channel.as_str(), file = %file, error = %e, "cache_manager: externalized text is missing, ignoring it"),
+        }
+    }
+}
+
+/// Выносит тексты длиннее `threshold` байт в файлы `{prefix}_{channel}.txt` и удаляет их из `texts`;
+/// файлы, которые больше не нужны, удаляются. Возвращает ссылки канал -> имя файла
+fn externalize<T>(
+    dir: &Path,
+    prefix: &str,
+    threshold: Option<usize>,
+    texts: &mut std::collections::HashMap<PublisherChannel, T>,
+    text_of: fn(&T) -> &str,
+) -> Result<std::collections::HashMap<PublisherChannel, String>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut refs = std::collections::HashMap::new();
+    for channel in PublisherChannel::all() {
+        let name = format!("{}_{}.txt", prefix, channel.as_str());
+        let path = dir.join(&name);
+        let large = threshold.zip(texts.get(&channel)).is_some_and(|(limit, t)| text_of(t).len() > limit);
+        if large {
+            if let Some(text) = texts.remove(&channel) {
+                fs::create_dir_all(dir)?;
+                write_atomic(&path, text_of(&text).as_bytes())?;
+                refs.insert(channel, name);
+            }
+        } else if path.exists() {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(refs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager(dir: &tempfile::TempDir) -> FileSystemCacheManager {
+        FileSystemCacheManager::builder()
+            .cache_dir(dir.path().to_string_lossy().to_string())
+            .build()
+    }
+
+    #[tokio::test]
+    async fn mark_published_writes_channel_data_in_one_step() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cm = manager(&dir);
+        cm.save_artifacts("160532", None, "md", "", "", &[], &[]).await.unwrap();
+
+        cm.mark_published("160532", PublisherChannel::File, Some("summary"), "post").await.unwrap();
+
+        let meta = cm.load_metadata("160532").await.unwrap().unwrap();
+        assert_eq!(meta.published_channels, vec![PublisherChannel::File]);
+        assert_eq!(meta.channel_summaries[&PublisherChannel::File].as_str(), "summary");
+        assert_eq!(meta.channel_posts[&PublisherChannel::File].as_str(), "post");
+        // Временный файл не остается после записи
+        assert!(!dir.path().join("160532").join("metadata.json.tmp").exists());
+    }
+
+    #[tokio::test]
+    async fn published_post_hash_is_stored_only_for_published_channels() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cm = manager(&dir);
+
+        cm.mark_published("5", PublisherChannel::File, None, "post").await.unwrap();
+        cm.update_channel_data("5", PublisherChannel::Telegram, Some("s"), Some("draft"), false).await.unwrap();
+        cm.update_channel_data("5", PublisherChannel::Console, None, Some("console post"), true).await.unwrap();
+
+        let meta = cm.load_metadata("5").await.unwrap().unwrap();
+        assert_eq!(meta.content_hash.get(&PublisherChannel::File), Some(&content_hash(b"post")));
+        assert_eq!(meta.content_hash.get(&PublisherChannel::Console), Some(&content_hash(b"console post")));
+        assert!(!meta.content_hash.contains_key(&PublisherChannel::Telegram));
+    }
+
+    #[tokio::test]
+    async fn mark_published_keeps_other_channels_and_missing_summary() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cm = manager(&dir);
+
+        cm.mark_published("1", PublisherChannel::Mastodon, Some("m"), "mp").await.unwrap();
+        cm.mark_published("1", PublisherChannel::File, None, "fp").await.unwrap();
+        cm.mark_published("1", PublisherChannel::File, None, "fp").await.unwrap();
+
+        let meta = cm.load_metadata("1").await.unwrap().unwrap();
+        assert_eq!(meta.published_channels, vec![PublisherChannel::Mastodon, PublisherChannel::File]);
+        assert!(!meta.channel_summaries.contains_key(&PublisherChannel::File));
+        assert_eq!(meta.channel_posts[&PublisherChannel::Mastodon].as_str(), "mp");
+        assert!(cm.is_fully_published("1", &[PublisherChannel::Mastodon, PublisherChannel::File]).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn large_summary_round_trips_via_external_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cm = FileSystemCacheManager::builder()
+            .cache_dir(dir.path().to_string_lossy().to_string())
+            .externalize_threshold(16)
+            .build();
+        let long_summary = "длинная суммаризация ".repeat(20);
+
+        cm.mark_published("3", PublisherChannel::Telegram, Some(&long_summary), "short").await.unwrap();
+
+        let project = dir.path().join("3");
+        assert_eq!(fs::read_to_string(project.join("summary_telegram.txt")).unwrap(), long_summary);
+        assert!(!project.join("post_telegram.txt").exists());
+        let raw = fs::read_to_string(project.join("metadata.json")).unwrap();
+        assert!(!raw.contains("длинная суммаризация"));
+        assert!(raw.contains("summary_telegram.txt"));
+
+        let meta = cm.load_metadata("3").await.unwrap().unwrap();
+        assert_eq!(meta.channel_summaries[&PublisherChannel::Telegram].as_str(), long_summary);
+        assert_eq!(meta.channel_posts[&PublisherChannel::Telegram].as_str(), "short");
+        assert!(meta.external_summaries.is_empty());
+
+        // Короткий текст возвращается в metadata.json, файл удаляется
+        cm.update_channel_summary("3", PublisherChannel::Telegram, "short").await.unwrap();
+        assert!(!project.join("summary_telegram.txt").exists());
+        assert_eq!(cm.load_channel_summary("3", PublisherChannel::Telegram).await.unwrap().unwrap().as_str(), "short");
+    }
+
+    #[tokio::test]
+    async fn skipped_item_counts_as_processed() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cm = manager(&dir);
+
+        cm.mark_skipped("2", "older than 30 days", &[]).await.unwrap();
+
+        assert!(cm.is_fully_published("2", &[PublisherChannel::File]).await.unwrap());
+        assert_eq!(cm.load_metadata("2").await.unwrap().unwrap().skip_reason.as_deref(), Some("older than 30 days"));
+    }
+
+    #[tokio::test]
+    async fn prune_expired_removes_only_old_published_projects() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cm = manager(&dir);
+        let ttl = std::time::Duration::from_secs(30 * 24 * 3600);
+        let old = (chrono::Utc::now() - chrono::Duration::days(60)).to_rfc3339();
+        for id in ["1", "2", "3"] {
+            cm.save_artifacts(id, None, "md", "", "", &[], &[]).await.unwrap();
+        }
+        cm.mark_published("1", PublisherChannel::File, None, "p").await.unwrap();
+        cm.mark_published("3", PublisherChannel::File, None, "p").await.unwrap();
+        // "1" и "2" созданы давно, "3" — только что; "2" еще не опубликован
+        for id in ["1", "2"] {
+            let mut meta = cm.load_metadata(id).await.unwrap().unwrap();
+            meta.created_at = old.clone().into();
+            cm.write_metadata_atomic(id, &meta).unwrap();
+        }
+
+        assert_eq!(cm.prune_expired(ttl, &[PublisherChannel::File]).await.unwrap(), 1);
+        assert!(!dir.path().join("1").exists());
+        assert_eq!(cm.list_project_ids().await.unwrap(), vec!["2".to_string(), "3".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn legacy_projects_are_listed_and_pruned_until_layout_v2() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cm = manager(&dir);
+        let mut meta = CacheMetadata::empty("7");
+        meta.created_at = (chrono::Utc::now() - chrono::Duration::days(60)).to_rfc3339().into();
+        meta.published_channels = vec![PublisherChannel::File];
+        fs::write(dir.path().join("7_metadata.json"), serde_json::to_string(&meta).unwrap()).unwrap();
+        fs::write(dir.path().join("7_extracted.md"), "md").unwrap();
+        cm.save_artifacts("8", None, "md", "", "", &[], &[]).await.unwrap();
+
+        let v2 = FileSystemCacheManager::builder()
+            .cache_dir(dir.path().to_string_lossy().to_string())
+            .layout(CacheLayout::V2)
+            .build();
+        assert_eq!(v2.list_project_ids().await.unwrap(), vec!["8".to_string()]);
+        assert_eq!(cm.list_project_ids().await.unwrap(), vec!["7".to_string(), "8".to_string()]);
+
+        let ttl = std::time::Duration::from_secs(30 * 24 * 3600);
+        assert_eq!(cm.prune_expired(ttl, &[PublisherChannel::File]).await.unwrap(), 1);
+        assert!(!dir.path().join("7_metadata.json").exists());
+        assert!(!dir.path().join("7_extracted.md").exists());
+        assert_eq!(cm.list_project_ids().await.unwrap(), vec!["8".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn fresh_marker_of_other_instance_blocks_claim() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let first = manager(&dir);
+        let second = manager(&dir);
+        let ttl = std::time::Duration::from_secs(600);
+
+        assert!(first.try_claim("5", ttl).await.unwrap());
+        assert!(!second.try_claim("5", ttl).await.unwrap());
+
+        // Чужой маркер не снимается, свой — снимается, после чего проект свободен
+        second.release_claim("5").await.unwrap();
+        assert!(dir.path().join("5").join("in_progress").exists());
+        first.release_claim("5").await.unwrap();
+        assert!(!dir.path().join("5").join("in_progress").exists());
+        assert!(second.try_claim("5", ttl).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn stale_marker_is_taken_over() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cm = manager(&dir);
+        fs::create_dir_all(dir.path().join("6")).unwrap();
+        fs::write(
+            dir.path().join("6").join("in_progress"),
+            r#"{"owner":"crashed-instance","claimed_at":"2001-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+
+        assert!(cm.try_claim("6", std::time::Duration::from_secs(600)).await.unwrap());
+        let marker = fs::read_to_string(dir.path().join("6").join("in_progress")).unwrap();
+        assert!(!marker.contains("crashed-instance"));
+    }
+
+    #[tokio::test]
+    async fn compressed_markdown_round_trips() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cm = FileSystemCacheManager::builder()
+            .cache_dir(dir.path().to_string_lossy().to_string())
+            .compress(true)
+            .build();
+        let text = "# Проект\n\nСобрание законодательства Российской Федерации".repeat(100);
+
+        cm.save_artifacts("3", None, &text, "", "", &[], &[]).await.unwrap();
+
+        let project_dir = dir.path().join("3");
+        assert!(project_dir.join("extracted.md.gz").exists());
+        assert!(!project_dir.join("extracted.md").exists());
+        assert!(fs::metadata(project_dir.join("extracted.md.gz")).unwrap().len() < text.len() as u64);
+        assert!(cm.has_data("3").await.unwrap());
+        assert_eq!(cm.load_cached_data("3").await.unwrap().as_deref(), Some(text.as_str()));
+        assert!(cm.load_metadata("3").await.unwrap().unwrap().markdown_path.as_path().ends_with("extracted.md.gz"));
+    }
+
+    #[tokio::test]
+    async fn uncompressed_markdown_still_readable_with_compress_enabled() {
+        let dir = tempfile::TempDir::new().unwrap();
+        manager(&dir).save_artifacts("4", None, "plain", "", "", &[], &[]).await.unwrap();
+
+        let cm = FileSystemCacheManager::builder()
+            .cache_dir(dir.path().to_string_lossy().to_string())
+            .compress(true)
+            .build();
+        assert_eq!(cm.load_cached_data("4").await.unwrap().as_deref(), Some("plain"));
+    }
+
+    #[tokio::test]
+    async fn malformed_created_at_is_repaired_on_load() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cm = manager(&dir);
+        cm.mark_published("160532", PublisherChannel::File, None, "post").await.unwrap();
+        let meta_path = dir.path().join("160532").join("metadata.json");
+        let mut raw: serde_json::Value = serde_json::from_str(&fs::read_to_string(&meta_path).unwrap()).unwrap();
+        raw["created_at"] = serde_json::json!("20.09.2025 garbage");
+        fs::write(&meta_path, serde_json::to_string_pretty(&raw).unwrap()).unwrap();
+
+        let meta = cm.load_metadata("160532").await.unwrap().unwrap();
+        assert!(meta.created_at.parse().is_some());
+        // Возраст считается без паники и не отрицателен
+        let age = meta.age(chrono::Utc::now()).unwrap();
+        assert!(age >= chrono::Duration::zero());
+        // Исправленная метка сохранена на диск, опубликованные каналы не потеряны
+        let persisted: CacheMetadata = serde_json::from_str(&fs::read_to_string(&meta_path).unwrap()).unwrap();
+        assert_eq!(persisted.created_at, meta.created_at);
+        assert_eq!(persisted.published_channels, vec![PublisherChannel::File]);
+    }
+
+    #[tokio::test]
+    async fn interrupted_write_keeps_previous_metadata_readable() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cm = manager(&dir);
+        cm.mark_published("7", PublisherChannel::File, Some("s"), "post").await.unwrap();
+        // Процесс упал посреди записи: на диске остался недописанный временный файл
+        let project = dir.path().join("7");
+        fs::write(project.join(".metadata.json.999.0.tmp"), "{\"project_id\": \"7\", \"published_").unwrap();
+
+        let meta = cm.load_metadata("7").await.unwrap().unwrap();
+        assert_eq!(meta.published_channels, vec![PublisherChannel::File]);
+        cm.mark_published("7", PublisherChannel::Console, None, "post").await.unwrap();
+        let meta = cm.load_metadata("7").await.unwrap().unwrap();
+        assert_eq!(meta.published_channels, vec![PublisherChannel::File, PublisherChannel::Console]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_writes_never_expose_partial_metadata() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cm = std::sync::Arc::new(manager(&dir));
+        cm.save_artifacts("8", None, "md", "", "", &[], &[]).await.unwrap();
+        let meta_path = dir.path().join("8").join("metadata.json");
+
+        let writers: Vec<_> = (0..4)
+            .map(|i| {
+                let cm = std::sync::Arc::clone(&cm);
+                tokio::spawn(async move {
+                    for n in 0..50 {
+                        let summary = format!("суммаризация {} {} ", i, n).repeat(200);
+                        cm.update_channel_summary("8", PublisherChannel::File, &summary).await.unwrap();
+                    }
+                })
+            })
+            .collect();
+        let reader = tokio::task::spawn_blocking(move || {
+            for _ in 0..500 {
+                let raw = fs::read_to_string(&meta_path).unwrap();
+                assert!(serde_json::from_str::<CacheMetadata>(&raw).is_ok(), "partial metadata.json: {} bytes", raw.len());
+            }
+        });
+        for writer in writers {
+            writer.await.unwrap();
+        }
+        reader.await.unwrap();
+
+        let leftovers: Vec<_> = fs::read_dir(dir.path().join("8"))
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().ends_with(".tmp"))
+            .collect();
+        assert!(leftovers.is_empty(), "temporary files left: {:?}", leftovers);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn concurrent_manifest_updates_are_not_lost() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let first = std::sync::Arc::new(manager(&dir));
+        let second = std::sync::Arc::new(manager(&dir));
+
+        // Один экземпляр сдвигает min_published_project_id, другой — offset истории;
+        // без блокировки чтение-изменение-запись одного затирает изменения другого
+        let bump_min = tokio::spawn(async move {
+            for id in 1..=100 {
+                first.update_min_published_project_id("npalist", id).await.unwrap();
+            }
+        });
+        let bump_offset = tokio::spawn(async move {
+            for offset in 1..=100 {
+                second.update_manifest(Box::new(move |m| {
+                    m.history_offsets.insert("npalist".to_string(), offset);
+                })).await.unwrap();
+            }
+        });
+        bump_min.await.unwrap();
+        bump_offset.await.unwrap();
+
+        let manifest = manager(&dir).load_manifest().await.unwrap();
+        assert_eq!(manifest.min_published_for("npalist"), Some(100));
+        assert_eq!(manifest.history_offsets.get("npalist"), Some(&100));
+    }
+}
@@ -0,0 +1,831 @@
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use bon::bon;
+use rusqlite::{Connection, OptionalExtension, params};
+
+use crate::traits::cache_manager::{CacheManager, ManifestUpdate};
+use crate::models::types::{CacheMetadata, CreatedAt, DocumentValidators, Manifest, MetadataItem, PostText, SummaryText, ThreadProgress, content_hash};
+use crate::models::channel::PublisherChannel;
+use crate::services::cache_manager_impl::{FileSystemCacheManager, stamp_summary_model, summaries_current};
+
+/// Имя файла базы в run.cache_dir при run.cache_backend: sqlite
+pub const SQLITE_CACHE_FILE: &str = "cache.sqlite3";
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS projects (
+        project_id TEXT PRIMARY KEY,
+        metadata TEXT NOT NULL,
+        markdown TEXT,
+        docx BLOB
+    );
+    CREATE TABLE IF NOT EXISTS claims (
+        project_id TEXT PRIMARY KEY,
+        owner TEXT NOT NULL,
+        claimed_at TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS manifest (
+        id INTEGER PRIMARY KEY CHECK (id = 1),
+        data TEXT NOT NULL
+    );
+";
+
+/// Реализация CacheManager в одном файле SQLite: метаданные проекта (с суммаризациями, постами
+/// и опубликованными каналами) хранятся JSON-ом CacheMetadata, документ и извлеченный текст —
+/// в той же строке, манифест — в отдельной таблице
+pub struct SqliteCacheManager {
+    conn: Arc<Mutex<Connection>>,
+    /// Модель LLM (llm.model): суммаризации и посты, сделанные другой моделью, считаются отсутствующими
+    summary_model: Option<String>,
+    /// Идентификатор экземпляра, записываемый в claims
+    instance_id: String,
+}
+
+#[bon]
+impl SqliteCacheManager {
+    /// Открывает (и при необходимости создает) базу `db_path`
+    #[builder]
+    pub fn new(
+        db_path: PathBuf,
+        summary_model: Option<String>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(dir) = db_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let conn = Connection::open(&db_path)?;
+        // Несколько экземпляров могут работать с одной базой: ждем блокировку, а не падаем сразу
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            summary_model,
+            instance_id: format!("{}-{}", std::process::id(), chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()),
+        })
+    }
+
+    /// Выполняет работу с базой в пуле блокирующих потоков: запросы rusqlite и ожидание блокировки
+    /// базы другим экземпляром (busy_timeout) не занимают потоки async-рантайма
+    async fn with_conn<T, F>(&self, work: F) -> Result<T, Box<dyn std::error::Error + Send + Sync>>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T, Box<dyn std::error::Error + Send + Sync>> + Send + 'static,
+    {
+        let conn = Arc::clone(&self.conn);
+        tokio::task::spawn_blocking(move || -> Result<T, Box<dyn std::error::Error + Send + Sync>> {
+            let mut conn = conn.lock().map_err(|_| "sqlite cache connection lock is poisoned")?;
+            work(&mut conn)
+        })
+        .await?
+    }
+
+    /// Читает метаданные проекта; поврежденный JSON считается отсутствующими метаданными
+    fn read_metadata(conn: &Connection, project_id: &str) -> Result<Option<CacheMetadata>, Box<dyn std::error::Error + Send + Sync>> {
+        let data: Option<String> = conn
+            .query_row("SELECT metadata FROM projects WHERE project_id = ?1", params![project_id], |r| r.get(0))
+            .optional()?;
+        Ok(data.and_then(|d| serde_json::from_str::<CacheMetadata>(&d).ok()))
+    }
+
+    /// Записывает метаданные проекта; оценки summary_ratings пересчитываются из суммаризаций
+    fn write_metadata(conn: &Connection, project_id: &str, meta: &CacheMetadata) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut meta = meta.clone();
+        meta.refresh_summary_ratings();
+        conn.execute(
+            "INSERT INTO projects (project_id, metadata) VALUES (?1, ?2)
+             ON CONFLICT(project_id) DO UPDATE SET metadata = excluded.metadata",
+            params![project_id, serde_json::to_string(&meta)?],
+        )?;
+        Ok(())
+    }
+
+    /// Читает манифест; поврежденный JSON считается пустым манифестом
+    fn read_manifest(conn: &Connection) -> Result<Manifest, Box<dyn std::error::Error + Send + Sync>> {
+        let data: Option<String> = conn
+            .query_row("SELECT data FROM manifest WHERE id = 1", [], |r| r.get(0))
+            .optional()?;
+        Ok(data.and_then(|d| serde_json::from_str::<Manifest>(&d).ok()).unwrap_or_default())
+    }
+
+    fn write_manifest(conn: &Connection, manifest: &Manifest) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string(manifest)?;
+        tracing::info!(manifest_content = %json, "npalist: saving manifest");
+        conn.execute("INSERT OR REPLACE INTO manifest (id, data) VALUES (1, ?1)", params![json])?;
+        Ok(())
+    }
+
+    /// Изменяет метаданные проекта в одной транзакции (отсутствующие создаются пустыми)
+    async fn modify_metadata<T: Send + 'static>(
+        &self,
+        project_id: &str,
+        change: impl FnOnce(&mut CacheMetadata) -> T + Send + 'static,
+    ) -> Result<T, Box<dyn std::error::Error + Send + Sync>> {
+        let project_id = project_id.to_string();
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            let mut meta = Self::read_metadata(&tx, &project_id)?.unwrap_or_else(|| CacheMetadata::empty(&project_id));
+            let result = change(&mut meta);
+            Self::write_metadata(&tx, &project_id, &meta)?;
+            tx.commit()?;
+            Ok(result)
+        })
+        .await
+    }
+
+    /// Импортирует файловый кэш `cache_dir` (metadata.json, документы, извлеченный текст и манифест),
+    /// включая проекты в плоских файлах старого формата. Проекты, уже записанные в базу, не перезаписываются.
+    /// Возвращает число импортированных проектов
+    pub async fn import_filesystem_cache(&self, cache_dir: &str) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let source = FileSystemCacheManager::builder().cache_dir(cache_dir.to_string()).build();
+        let mut project_ids: std::collections::BTreeSet<String> = source.list_project_ids().await?.into_iter().collect();
+        project_ids.extend(source.legacy_project_ids()?);
+        let mut imported = 0;
+        for project_id in project_ids {
+            let has_dir = Path::new(cache_dir).join(&project_id).join("metadata.json").is_file();
+            let mut meta = if has_dir {
+                let Some(meta) = source.load_metadata(&project_id).await? else {
+                    tracing::warn!(project_id = %project_id, "sqlite cache: unreadable metadata.json, not imported");
+                    continue;
+                };
+                meta
+            } else {
+                source.legacy_project_metadata(&project_id)
+            };
+            meta.refresh_summary_ratings();
+            let markdown = source.load_cached_data(&project_id).await?;
+            let docx = std::fs::read(Path::new(cache_dir).join(&project_id).join("source.docx")).ok();
+            let metadata = serde_json::to_string(&meta)?;
+            imported += self
+                .with_conn(move |conn| {
+                    Ok(conn.execute(
+                        "INSERT OR IGNORE INTO projects (project_id, metadata, markdown, docx) VALUES (?1, ?2, ?3, ?4)",
+                        params![project_id, metadata, markdown, docx],
+                    )?)
+                })
+                .await?;
+        }
+
+        let has_manifest = self
+            .with_conn(|conn| Ok(conn.query_row("SELECT COUNT(*) FROM manifest", [], |r| r.get::<_, i64>(0))? > 0))
+            .await?;
+        if !has_manifest && Path::new(cache_dir).join("manifest.json").exists() {
+            self.save_manifest(&source.load_manifest().await?).await?;
+        }
+        tracing::info!(cache_dir = %cache_dir, imported = imported, "sqlite cache: imported filesystem cache");
+        Ok(imported)
+    }
+}
+
+#[async_trait]
+impl CacheManager for SqliteCacheManager {
+    async fn save_artifacts(
+        &self,
+        project_id: &str,
+        docx_bytes: Option<&[u8]>,
+        markdown_text: &str,
+        _summary_text: &str,
+        _post_text: &str,
+        published_channels: &[PublisherChannel],
+        crawl_metadata: &[MetadataItem],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let project_id = project_id.to_string();
+        let docx_bytes = docx_bytes.map(<[u8]>::to_vec);
+        let markdown_text = markdown_text.to_string();
+        let published_channels = published_channels.to_vec();
+        let crawl_metadata = crawl_metadata.to_vec();
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            let existing = Self::read_metadata(&tx, &project_id)?.unwrap_or_else(|| CacheMetadata::empty(&project_id));
+            let ts: CreatedAt = chrono::Utc::now().to_rfc3339().into();
+
+            // Как и в файловом кэше: прежние каналы, суммаризации и метаданные краулера сохраняются,
+            // если не переданы новые; пути файлов не заполняются — документ и текст лежат в базе
+            let meta = CacheMetadata {
+                created_at: ts,
+                published_channels: if published_channels.is_empty() {
+                    existing.published_channels.clone()
+                } else {
+                    published_channels
+                },
+                crawl_metadata: if crawl_metadata.is_empty() {
+                    existing.crawl_metadata.clone()
+                } else {
+                    crawl_metadata
+                },
+                // Хэш документа обновляется только при сохранении новых байт документа
+                document_hash: docx_bytes.as_deref().map(content_hash).or(existing.document_hash.clone()),
+                // Новые байты документа делают прежние HTTP-валидаторы недействительными
+                document_validators: if docx_bytes.is_some() { None } else { existing.document_validators.clone() },
+                ..existing
+            };
+
+            Self::write_metadata(&tx, &project_id, &meta)?;
+            tx.execute("UPDATE projects SET markdown = ?2 WHERE project_id = ?1", params![project_id, markdown_text])?;
+            if let Some(bytes) = docx_bytes {
+                tx.execute("UPDATE projects SET docx = ?2 WHERE project_id = ?1", params![project_id, bytes])?;
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn load_metadata(
+        &self,
+        project_id: &str,
+    ) -> Result<Option<CacheMetadata>, Box<dyn std::error::Error + Send + Sync>> {
+        let project_id = project_id.to_string();
+        self.with_conn(move |conn| {
+            let Some(mut meta) = Self::read_metadata(conn, &project_id)? else {
+                return Ok(None);
+            };
+            // created_at нужен функциям, зависящим от времени: поврежденную метку заменяем текущим временем
+            let broken = meta.created_at.as_str().to_string();
+            if meta.repair_created_at(chrono::Utc::now()) {
+                tracing::warn!(
+                    project_id = %project_id,
+                    created_at = %broken,
+                    repaired_to = %meta.created_at,
+                    "cache_manager: unparseable created_at in metadata, repaired"
+                );
+                if let Err(e) = Self::write_metadata(conn, &project_id, &meta) {
+                    tracing::warn!(project_id = %project_id, error = %e, "cache_manager: failed to persist repaired created_at");
+                }
+            }
+            Ok(Some(meta))
+        })
+        .await
+    }
+
+    async fn load_summary(
+        &self,
+        project_id: &str,
+    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let meta = self.load_metadata(project_id).await?;
+        Ok(meta.and_then(|m| m.channel_summaries.values().next().map(|s| s.as_str().to_string())))
+    }
+
+    async fn load_cached_data(
+        &self,
+        project_id: &str,
+    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let project_id = project_id.to_string();
+        self.with_conn(move |conn| {
+            let markdown: Option<Option<String>> = conn
+                .query_row("SELECT markdown FROM projects WHERE project_id = ?1", params![project_id], |r| r.get(0))
+                .optional()?;
+            Ok(markdown.flatten())
+        })
+        .await
+    }
+
+    async fn add_published_channels(
+        &self,
+        project_id: &str,
+        new_channels: &[PublisherChannel],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let new_channels = new_channels.to_vec();
+        self.modify_metadata(project_id, move |meta| {
+            for ch in new_channels {
+                if !meta.published_channels.contains(&ch) {
+                    meta.published_channels.push(ch);
+                }
+            }
+        })
+        .await
+    }
+
+    async fn add_published_channel(
+        &self,
+        project_id: &str,
+        channel: PublisherChannel,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.add_published_channels(project_id, &[channel]).await
+    }
+
+    async fn mark_published(
+        &self,
+        project_id: &str,
+        channel: PublisherChannel,
+        summary_text: Option<&str>,
+        post_text: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let summary_model = self.summary_model.clone();
+        let summary_text = summary_text.map(str::to_string);
+        let post_text = post_text.to_string();
+        self.modify_metadata(project_id, move |meta| {
+            stamp_summary_model(summary_model.as_deref(), meta);
+            if let Some(summary) = summary_text {
+                meta.channel_summaries.insert(channel, summary.into());
+            }
+            meta.content_hash.insert(channel, content_hash(post_text.as_bytes()));
+            meta.channel_posts.insert(channel, post_text.into());
+            if !meta.published_channels.contains(&channel) {
+                meta.published_channels.push(channel);
+            }
+        })
+        .await
+    }
+
+    async fn update_document_state(
+        &self,
+        project_id: &str,
+        validators: Option<&DocumentValidators>,
+        crawl_metadata: &[MetadataItem],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let validators = validators.cloned();
+        let crawl_metadata = crawl_metadata.to_vec();
+        self.modify_metadata(project_id, move |meta| {
+            if let Some(v) = validators {
+                meta.document_validators = Some(v);
+            }
+            if !crawl_metadata.is_empty() {
+                meta.crawl_metadata = crawl_metadata;
+            }
+        })
+        .await
+    }
+
+    async fn mark_skipped(
+        &self,
+        project_id: &str,
+        reason: &str,
+        crawl_metadata: &[MetadataItem],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let reason = reason.to_string();
+        let crawl_metadata = crawl_metadata.to_vec();
+        self.modify_metadata(project_id, move |meta| {
+            meta.skip_reason = Some(reason);
+            if !crawl_metadata.is_empty() {
+                meta.crawl_metadata = crawl_metadata;
+            }
+        })
+        .await
+    }
+
+    async fn record_timeout(
+        &self,
+        project_id: &str,
+        crawl_metadata: &[MetadataItem],
+    ) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
+        let crawl_metadata = crawl_metadata.to_vec();
+        self.modify_metadata(project_id, move |meta| {
+            meta.timeout_attempts += 1;
+            if !crawl_metadata.is_empty() {
+                meta.crawl_metadata = crawl_metadata;
+            }
+            meta.timeout_attempts
+        })
+        .await
+    }
+
+    async fn record_target_results(
+        &self,
+        project_id: &str,
+        channel: PublisherChannel,
+        results: &[(String, bool)],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let results = results.to_vec();
+        self.modify_metadata(project_id, move |meta| {
+            meta.target_results.insert(channel, results.into_iter().collect());
+        })
+        .await
+    }
+
+    async fn record_thread_progress(
+        &self,
+        project_id: &str,
+        channel: PublisherChannel,
+        progress: &ThreadProgress,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let progress = progress.clone();
+        self.modify_metadata(project_id, move |meta| {
+            meta.threads.insert(channel, progress);
+        })
+        .await
+    }
+
+    async fn record_title(
+        &self,
+        project_id: &str,
+        title: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let title = title.to_string();
+        self.modify_metadata(project_id, move |meta| {
+            meta.title = Some(title);
+        })
+        .await
+    }
+
+    async fn clear_summaries(&self, project_id: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let project_id = project_id.to_string();
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            let Some(mut meta) = Self::read_metadata(&tx, &project_id)? else {
+                return Ok(false);
+            };
+            meta.channel_summaries.clear();
+            meta.channel_posts.clear();
+            Self::write_metadata(&tx, &project_id, &meta)?;
+            tx.commit()?;
+            Ok(true)
+        })
+        .await
+    }
+
+    async fn list_project_ids(&self) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare("SELECT project_id FROM projects ORDER BY project_id")?;
+            let ids = stmt.query_map([], |r| r.get::<_, String>(0))?.collect::<Result<Vec<_>, _>>()?;
+            Ok(ids)
+        })
+        .await
+    }
+
+    async fn prune_expired(
+        &self,
+        ttl: std::time::Duration,
+        enabled_channels: &[PublisherChannel],
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let ttl = chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::MAX);
+        let now = chrono::Utc::now();
+        let mut removed = 0;
+        for project_id in self.list_project_ids().await? {
+            let Some(meta) = self.load_metadata(&project_id).await? else { continue };
+            if !meta.age(now).is_some_and(|age| age > ttl) {
+                continue;
+            }
+            if !self.is_fully_published(&project_id, enabled_channels).await? {
+                tracing::info!(project_id = %project_id, "cache_manager: expired project is not fully published, keeping it");
+                continue;
+            }
+            self.with_conn(move |conn| {
+                conn.execute("DELETE FROM projects WHERE project_id = ?1", params![project_id])?;
+                Ok(())
+            })
+            .await?;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+
+    async fn try_claim(
+        &self,
+        project_id: &str,
+        ttl: std::time::Duration,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let project_id = project_id.to_string();
+        let instance_id = self.instance_id.clone();
+        self.with_conn(move |conn| {
+            // IMMEDIATE: проверка и запись claim не пересекаются с другими экземплярами
+            let tx = conn.transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
+            let existing: Option<(String, String)> = tx
+                .query_row("SELECT owner, claimed_at FROM claims WHERE project_id = ?1", params![project_id], |r| {
+                    Ok((r.get(0)?, r.get(1)?))
+                })
+                .optional()?;
+            let ttl = chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::MAX);
+            let is_fresh = |claimed_at: &str| {
+                chrono::DateTime::parse_from_rfc3339(claimed_at)
+                    .is_ok_and(|t| chrono::Utc::now().signed_duration_since(t) < ttl)
+            };
+            match existing {
+                Some((owner, _)) if owner == instance_id => return Ok(true),
+                Some((owner, claimed_at)) if is_fresh(&claimed_at) => {
+                    tracing::info!(project_id = %project_id, owner = %owner, claimed_at = %claimed_at, "in_progress marker is active");
+                    return Ok(false);
+                }
+                Some(stale) => {
+                    tracing::warn!(project_id = %project_id, marker = ?stale, "taking over stale in_progress marker");
+                }
+                None => {}
+            }
+            tx.execute(
+                "INSERT OR REPLACE INTO claims (project_id, owner, claimed_at) VALUES (?1, ?2, ?3)",
+                params![project_id, instance_id, chrono::Utc::now().to_rfc3339()],
+            )?;
+            tx.commit()?;
+            Ok(true)
+        })
+        .await
+    }
+
+    async fn release_claim(&self, project_id: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let project_id = project_id.to_string();
+        let instance_id = self.instance_id.clone();
+        self.with_conn(move |conn| {
+            conn.execute(
+                "DELETE FROM claims WHERE project_id = ?1 AND owner = ?2",
+                params![project_id, instance_id],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn update_channel_data(
+        &self,
+        project_id: &str,
+        channel: PublisherChannel,
+        summary_text: Option<&str>,
+        post_text: Option<&str>,
+        is_published: bool,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let summary_model = self.summary_model.clone();
+        let summary_text = summary_text.map(str::to_string);
+        let post_text = post_text.map(str::to_string);
+        self.modify_metadata(project_id, move |meta| {
+            if summary_text.is_some() || post_text.is_some() {
+                stamp_summary_model(summary_model.as_deref(), meta);
+            }
+            if let Some(summary) = summary_text {
+                meta.channel_summaries.insert(channel, summary.into());
+            }
+            if is_published {
+                if let Some(post) = &post_text {
+                    meta.content_hash.insert(channel, content_hash(post.as_bytes()));
+                }
+                if !meta.published_channels.contains(&channel) {
+                    meta.published_channels.push(channel);
+                }
+            }
+            if let Some(post) = post_text {
+                meta.channel_posts.insert(channel, post.into());
+            }
+        })
+        .await
+    }
+
+    async fn has_data(&self, project_id: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.load_cached_data(project_id).await?.is_some())
+    }
+
+    async fn has_summary(&self, project_id: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let meta = self.load_metadata(project_id).await?;
+        Ok(meta.is_some_and(|m| !m.channel_summaries.is_empty()))
+    }
+
+    async fn is_published_in_channel(
+        &self,
+        project_id: &str,
+        channel: PublisherChannel,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let meta = self.load_metadata(project_id).await?;
+        Ok(meta.is_some_and(|m| m.published_channels.contains(&channel)))
+    }
+
+    async fn get_published_channels(
+        &self,
+        project_id: &str,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let meta = self.load_metadata(project_id).await?;
+        Ok(meta.map(|m| m.published_channels.iter().map(|c| c.as_str().to_string()).collect()).unwrap_or_default())
+    }
+
+    async fn has_channel_summary(
+        &self,
+        project_id: &str,
+        channel: PublisherChannel,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let meta = self.load_metadata(project_id).await?;
+        Ok(meta.is_some_and(|m| summaries_current(self.summary_model.as_deref(), &m) && m.channel_summaries.contains_key(&channel)))
+    }
+
+    async fn load_channel_summary(
+        &self,
+        project_id: &str,
+        channel: PublisherChannel,
+    ) -> Result<Option<SummaryText>, Box<dyn std::error::Error + Send + Sync>> {
+        let meta = self.load_metadata(project_id).await?;
+        Ok(meta
+            .filter(|m| summaries_current(self.summary_model.as_deref(), m))
+            .and_then(|m| m.channel_summaries.get(&channel).cloned()))
+    }
+
+    async fn update_channel_summary(
+        &self,
+        project_id: &str,
+        channel: PublisherChannel,
+        summary_text: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let summary_model = self.summary_model.clone();
+        let summary_text = summary_text.to_string();
+        self.modify_metadata(project_id, move |meta| {
+            stamp_summary_model(summary_model.as_deref(), meta);
+            meta.channel_summaries.insert(channel, summary_text.into());
+        })
+        .await
+    }
+
+    async fn has_channel_post(
+        &self,
+        project_id: &str,
+        channel: PublisherChannel,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let meta = self.load_metadata(project_id).await?;
+        Ok(meta.is_some_and(|m| summaries_current(self.summary_model.as_deref(), &m) && m.channel_posts.contains_key(&channel)))
+    }
+
+    async fn load_channel_post(
+        &self,
+        project_id: &str,
+        channel: PublisherChannel,
+    ) -> Result<Option<PostText>, Box<dyn std::error::Error + Send + Sync>> {
+        let meta = self.load_metadata(project_id).await?;
+        Ok(meta
+            .filter(|m| summaries_current(self.summary_model.as_deref(), m))
+            .and_then(|m| m.channel_posts.get(&channel).cloned()))
+    }
+
+    async fn update_channel_post(
+        &self,
+        project_id: &str,
+        channel: PublisherChannel,
+        post_text: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let summary_model = self.summary_model.clone();
+        let post_text = post_text.to_string();
+        self.modify_metadata(project_id, move |meta| {
+            stamp_summary_model(summary_model.as_deref(), meta);
+            meta.channel_posts.insert(channel, post_text.into());
+        })
+        .await
+    }
+
+    async fn load_manifest(&self) -> Result<Manifest, Box<dyn std::error::Error + Send + Sync>> {
+        self.with_conn(|conn| Self::read_manifest(conn)).await
+    }
+
+    async fn save_manifest(&self, manifest: &Manifest) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let manifest = manifest.clone();
+        self.with_conn(move |conn| Self::write_manifest(conn, &manifest)).await
+    }
+
+    async fn update_manifest(&self, update: ManifestUpdate) -> Result<Manifest, Box<dyn std::error::Error + Send + Sync>> {
+        self.with_conn(move |conn| {
+            // IMMEDIATE: чтение и запись манифеста не пересекаются с изменениями других экземпляров
+            let tx = conn.transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
+            let mut manifest = Self::read_manifest(&tx)?;
+            update(&mut manifest);
+            Self::write_manifest(&tx, &manifest)?;
+            tx.commit()?;
+            Ok(manifest)
+        })
+        .await
+    }
+
+    async fn update_min_published_project_id(&self, source_id: &str, min_id: u32) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        tracing::info!(source = %source_id, new_min_id = min_id, "cache_manager: updating min_published_project_id");
+        let source_id = source_id.to_string();
+        self.update_manifest(Box::new(move |manifest| {
+            manifest.min_published_project_ids.insert(source_id, min_id);
+        })).await?;
+        Ok(())
+    }
+
+    async fn update_all_channels_data(
+        &self,
+        project_id: &str,
+        channel_data: &[(PublisherChannel, &str, &str)],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let summary_model = self.summary_model.clone();
+        let channel_data: Vec<(PublisherChannel, String, String)> =
+            channel_data.iter().map(|(channel, summary, post)| (*channel, summary.to_string(), post.to_string())).collect();
+        self.modify_metadata(project_id, move |meta| {
+            stamp_summary_model(summary_model.as_deref(), meta);
+            for (channel, summary, post) in channel_data {
+                meta.channel_summaries.insert(channel, summary.into());
+                meta.channel_posts.insert(channel, post.into());
+                if !meta.published_channels.contains(&channel) {
+                    meta.published_channels.push(channel);
+                }
+            }
+        })
+        .await
+    }
+
+    async fn is_fully_published(&self, project_id: &str, enabled_channels: &[PublisherChannel]) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(metadata) = self.load_metadata(project_id).await? else {
+            return Ok(false);
+        };
+
+        // Пропущенный элемент считается обработанным, чтобы краулер двигался дальше
+        if let Some(reason) = metadata.skip_reason.as_deref() {
+            tracing::info!(project_id = project_id, skip_reason = reason, "Element was skipped earlier, treating as processed");
+            return Ok(true);
+        }
+
+        if let Some(missing) = enabled_channels.iter().find(|c| !metadata.published_channels.contains(*c)) {
+            tracing::info!(project_id = project_id, missing_channel = %missing, "Element not fully published - missing channel");
+            return Ok(false);
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager(dir: &tempfile::TempDir) -> SqliteCacheManager {
+        SqliteCacheManager::builder()
+            .db_path(dir.path().join(SQLITE_CACHE_FILE))
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn mark_published_round_trips_channel_data() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cm = manager(&dir);
+        cm.save_artifacts("160532", Some(b"docx"), "md", "", "", &[], &[]).await.unwrap();
+
+        cm.mark_published("160532", PublisherChannel::Mastodon, Some("summary"), "post").await.unwrap();
+        cm.mark_published("160532", PublisherChannel::File, None, "fp").await.unwrap();
+
+        let meta = cm.load_metadata("160532").await.unwrap().unwrap();
+        assert_eq!(meta.published_channels, vec![PublisherChannel::Mastodon, PublisherChannel::File]);
+        assert_eq!(meta.channel_summaries[&PublisherChannel::Mastodon].as_str(), "summary");
+        assert!(!meta.channel_summaries.contains_key(&PublisherChannel::File));
+        assert_eq!(meta.document_hash, Some(content_hash(b"docx")));
+        assert_eq!(cm.load_cached_data("160532").await.unwrap().as_deref(), Some("md"));
+        assert!(cm.is_fully_published("160532", &[PublisherChannel::Mastodon, PublisherChannel::File]).await.unwrap());
+        assert!(!cm.is_fully_published("160532", &[PublisherChannel::Telegram]).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn summaries_of_another_model_are_ignored() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db_path = dir.path().join(SQLITE_CACHE_FILE);
+        let old = SqliteCacheManager::builder().db_path(db_path.clone()).summary_model("old".to_string()).build().unwrap();
+        old.update_channel_summary("7", PublisherChannel::Telegram, "s").await.unwrap();
+        drop(old);
+
+        let cm = SqliteCacheManager::builder().db_path(db_path.clone()).summary_model("new".to_string()).build().unwrap();
+        assert!(!cm.has_channel_summary("7", PublisherChannel::Telegram).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn manifest_updates_of_two_instances_are_merged() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let first = manager(&dir);
+        let second = manager(&dir);
+
+        first.update_min_published_project_id("npalist", 42).await.unwrap();
+        second.update_manifest(Box::new(|m| {
+            m.history_offsets.insert("npalist".to_string(), 200);
+        })).await.unwrap();
+
+        let manifest = first.load_manifest().await.unwrap();
+        assert_eq!(manifest.min_published_for("npalist"), Some(42));
+        assert_eq!(manifest.history_offsets.get("npalist"), Some(&200));
+    }
+
+    #[tokio::test]
+    async fn claim_of_other_instance_blocks_until_released() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let first = manager(&dir);
+        let second = manager(&dir);
+        let ttl = std::time::Duration::from_secs(600);
+
+        assert!(first.try_claim("5", ttl).await.unwrap());
+        assert!(!second.try_claim("5", ttl).await.unwrap());
+        second.release_claim("5").await.unwrap();
+        assert!(!second.try_claim("5", ttl).await.unwrap());
+        first.release_claim("5").await.unwrap();
+        assert!(second.try_claim("5", ttl).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn imports_filesystem_cache() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let fs_dir = dir.path().join("cache").to_string_lossy().to_string();
+        let fs_cache = FileSystemCacheManager::builder().cache_dir(fs_dir.clone()).compress(true).build();
+        fs_cache.save_artifacts("1", Some(b"docx"), "текст", "", "", &[], &[]).await.unwrap();
+        fs_cache.mark_published("1", PublisherChannel::File, Some("s"), "p").await.unwrap();
+        fs_cache.mark_skipped("2", "older than 30 days", &[]).await.unwrap();
+        fs_cache.update_min_published_project_id("npalist", 42).await.unwrap();
+
+        let cm = manager(&dir);
+        assert_eq!(cm.import_filesystem_cache(&fs_dir).await.unwrap(), 2);
+
+        assert_eq!(cm.list_project_ids().await.unwrap(), vec!["1".to_string(), "2".to_string()]);
+        assert_eq!(cm.load_cached_data("1").await.unwrap().as_deref(), Some("текст"));
+        assert_eq!(cm.load_channel_post("1", PublisherChannel::File).await.unwrap().unwrap().as_str(), "p");
+        assert!(cm.is_fully_published("2", &[PublisherChannel::File]).await.unwrap());
+        assert_eq!(cm.load_manifest().await.unwrap().min_published_for("npalist"), Some(42));
+
+        // Проект в плоских файлах старого формата тоже импортируется
+        std::fs::write(Path::new(&fs_dir).join("3_metadata.json"), r#"{"published_channels":["File"]}"#).unwrap();
+        std::fs::write(Path::new(&fs_dir).join("3_extracted.md"), "старый текст").unwrap();
+        std::fs::write(Path::new(&fs_dir).join("3_summary.txt"), "старая суммаризация").unwrap();
+        assert_eq!(cm.import_filesystem_cache(&fs_dir).await.unwrap(), 1);
+        assert_eq!(cm.load_cached_data("3").await.unwrap().as_deref(), Some("старый текст"));
+        assert_eq!(cm.load_summary("3").await.unwrap().as_deref(), Some("старая суммаризация"));
+        assert!(cm.is_published_in_channel("3", PublisherChannel::File).await.unwrap());
+
+        // Повторный импорт не перезаписывает проекты базы
+        cm.mark_published("1", PublisherChannel::Telegram, None, "tp").await.unwrap();
+        assert_eq!(cm.import_filesystem_cache(&fs_dir).await.unwrap(), 0);
+        assert!(cm.is_published_in_channel("1", PublisherChannel::Telegram).await.unwrap());
+    }
+}
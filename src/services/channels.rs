@@ -1,7 +1,12 @@
 use crate::models::config::AppConfig;
 use crate::models::channel::PublisherChannel;
+use crate::services::summarizer::ChannelPrompt;
 use std::collections::HashMap;
 use bon::bon;
+use tracing::warn;
+
+/// Лимит символов для канала без своего лимита, если channels.default_limit не задан
+const DEFAULT_CHANNEL_LIMIT: usize = 300;
 
 /// Определение канала публикации с его лимитами
 #[derive(Debug, Clone)]
@@ -9,11 +14,15 @@ pub struct ChannelConfig {
     pub channel: PublisherChannel,
     pub max_chars: usize,
     pub enabled: bool,
+    pub style: Option<String>,
+    pub language: Option<String>,
+    pub post_template: Option<String>,
 }
 
 /// Менеджер каналов публикации
 pub struct ChannelManager {
     channels: HashMap<PublisherChannel, ChannelConfig>,
+    default_limit: usize,
 }
 
 #[bon]
@@ -28,6 +37,9 @@ impl ChannelManager {
                 channel: PublisherChannel::Telegram,
                 max_chars: telegram.max_chars.unwrap_or(4096),
                 enabled: telegram.enabled,
+                style: channel_style(config, PublisherChannel::Telegram),
+                language: channel_language(config, PublisherChannel::Telegram),
+                post_template: channel_post_template(config, PublisherChannel::Telegram),
             });
         }
 
@@ -37,6 +49,9 @@ impl ChannelManager {
                 channel: PublisherChannel::Mastodon,
                 max_chars: mastodon.max_chars.unwrap_or(495),
                 enabled: mastodon.enabled,
+                style: channel_style(config, PublisherChannel::Mastodon),
+                language: channel_language(config, PublisherChannel::Mastodon),
+                post_template: channel_post_template(config, PublisherChannel::Mastodon),
             });
         }
 
@@ -46,6 +61,9 @@ impl ChannelManager {
                 channel: PublisherChannel::Console,
                 max_chars: output.console_max_chars.unwrap_or(10000),
                 enabled: output.console_enabled.unwrap_or(true),
+                style: channel_style(config, PublisherChannel::Console),
+                language: channel_language(config, PublisherChannel::Console),
+                post_template: channel_post_template(config, PublisherChannel::Console),
             });
         }
 
@@ -55,10 +73,53 @@ impl ChannelManager {
                 channel: PublisherChannel::File,
                 max_chars: output.file_max_chars.unwrap_or(20000),
                 enabled: output.file_enabled.unwrap_or(false),
+                style: channel_style(config, PublisherChannel::File),
+                language: channel_language(config, PublisherChannel::File),
+                post_template: channel_post_template(config, PublisherChannel::File),
+            });
+        }
+
+        // Matrix канал
+        if let Some(matrix) = &config.matrix {
+            channels.insert(PublisherChannel::Matrix, ChannelConfig {
+                channel: PublisherChannel::Matrix,
+                max_chars: matrix.max_chars.unwrap_or(4000),
+                enabled: matrix.enabled,
+                style: channel_style(config, PublisherChannel::Matrix),
+                language: channel_language(config, PublisherChannel::Matrix),
+                post_template: channel_post_template(config, PublisherChannel::Matrix),
+            });
+        }
+
+        // Slack канал: по умолчанию лимит блока section
+        if let Some(slack) = &config.slack {
+            channels.insert(PublisherChannel::Slack, ChannelConfig {
+                channel: PublisherChannel::Slack,
+                max_chars: slack.max_chars.unwrap_or(crate::publishers::slack::SLACK_SECTION_MAX_CHARS),
+                enabled: slack.enabled,
+                style: channel_style(config, PublisherChannel::Slack),
+                language: channel_language(config, PublisherChannel::Slack),
+                post_template: channel_post_template(config, PublisherChannel::Slack),
+            });
+        }
+
+        // Webhook канал: лимит относится к суммаризации в событии
+        if let Some(webhook) = &config.webhook {
+            channels.insert(PublisherChannel::Webhook, ChannelConfig {
+                channel: PublisherChannel::Webhook,
+                max_chars: webhook.max_chars.unwrap_or(4096),
+                enabled: webhook.enabled,
+                style: channel_style(config, PublisherChannel::Webhook),
+                language: channel_language(config, PublisherChannel::Webhook),
+                post_template: channel_post_template(config, PublisherChannel::Webhook),
             });
         }
 
-        Self { channels }
+        let default_limit = config.channels.as_ref()
+            .and_then(|c| c.default_limit)
+            .unwrap_or(DEFAULT_CHANNEL_LIMIT);
+
+        Self { channels, default_limit }
     }
 
     /// Получает список всех включенных каналов
@@ -85,4 +146,60 @@ impl ChannelManager {
     pub fn get_channel_limit(&self, channel: PublisherChannel) -> Option<usize> {
         self.channels.get(&channel).map(|c| c.max_chars)
     }
+
+    /// Получает лимит символов для канала; для канала без своего лимита возвращает
+    /// channels.default_limit и пишет предупреждение — это признак ошибки в конфигурации
+    pub fn channel_limit_or_default(&self, channel: PublisherChannel) -> usize {
+        match self.get_channel_limit(channel) {
+            Some(limit) => limit,
+            None => {
+                warn!(channel = %channel, limit = self.default_limit, "channel has no limit configured, using channels.default_limit");
+                self.default_limit
+            }
+        }
+    }
+
+    /// Проверяет, что каналы дают одинаковую суммаризацию и пост (совпадают лимит, стиль,
+    /// язык и шаблон поста канала)
+    pub fn same_output(&self, a: PublisherChannel, b: PublisherChannel) -> bool {
+        match (self.channels.get(&a), self.channels.get(&b)) {
+            (Some(a), Some(b)) => a.max_chars == b.max_chars && a.style == b.style && a.language == b.language && a.post_template == b.post_template,
+            _ => false,
+        }
+    }
+
+    /// Получает стиль изложения суммаризации для канала (channels.<name>.style)
+    pub fn get_channel_style(&self, channel: PublisherChannel) -> Option<&str> {
+        self.channels.get(&channel).and_then(|c| c.style.as_deref())
+    }
+
+    /// Получает язык суммаризации канала (channels.<name>.language)
+    pub fn get_channel_language(&self, channel: PublisherChannel) -> Option<&str> {
+        self.channels.get(&channel).and_then(|c| c.language.as_deref())
+    }
+
+    /// Параметры промпта суммаризатора для канала: стиль и язык
+    pub fn channel_prompt(&self, channel: PublisherChannel) -> ChannelPrompt<'_> {
+        ChannelPrompt {
+            style: self.get_channel_style(channel),
+            language: self.get_channel_language(channel),
+        }
+    }
+
+    /// Получает шаблон поста канала (channels.<name>.post_template), если он задан
+    pub fn get_channel_post_template(&self, channel: PublisherChannel) -> Option<&str> {
+        self.channels.get(&channel).and_then(|c| c.post_template.as_deref())
+    }
+}
+
+fn channel_post_template(config: &AppConfig, channel: PublisherChannel) -> Option<String> {
+    config.channels.as_ref().and_then(|c| c.get(channel)).and_then(|c| c.post_template.clone())
+}
+
+fn channel_style(config: &AppConfig, channel: PublisherChannel) -> Option<String> {
+    config.channels.as_ref().and_then(|c| c.get(channel)).and_then(|c| c.style.clone())
+}
+
+fn channel_language(config: &AppConfig, channel: PublisherChannel) -> Option<String> {
+    config.channels.as_ref().and_then(|c| c.get(channel)).and_then(|c| c.language.clone())
 }
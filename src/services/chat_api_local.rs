@@ -1,4 +1,4 @@
-use crate::models::config::LlmConfig;
+use crate::models::config::{HttpConfig, LlmConfig};
 use crate::traits::chat_api::ChatApi;
 use async_trait::async_trait;
 // tracing is available if needed
@@ -7,6 +7,7 @@ use ai_lib::ConnectionOptions;
 use ai_lib::prelude::*;
 use bon::Builder;
 use std::str::FromStr;
+use std::time::Duration;
 use strum_macros::EnumString;
 use tokio::sync::Mutex;
 use tracing::info;
@@ -67,9 +68,63 @@ fn map_provider(p: ProviderName) -> Provider {
     }
 }
 
-/// LocalChatApi uses a cloud provider via ai-lib.
-struct Engine {
-    cloud: AiClient,
+/// Адрес Ollama по умолчанию, если llm.base_url не задан
+const DEFAULT_OLLAMA_BASE_URL: &str = "http://localhost:11434";
+
+/// Адрес Gemini API по умолчанию для потокового ответа, если llm.base_url не задан
+const DEFAULT_GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+
+/// Адрес OpenAI API по умолчанию для потокового ответа, если llm.base_url не задан
+const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// Формат потокового ответа LLM (summarizer.stream)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StreamFormat {
+    /// OpenAI-совместимый POST {base_url}/chat/completions со `stream: true`, события SSE
+    OpenAi,
+    /// Gemini POST {base_url}/models/{model}:streamGenerateContent?alt=sse
+    Gemini,
+}
+
+/// Прямой HTTP-доступ к провайдеру для потокового ответа (ai-lib отдает только полный ответ)
+#[derive(Clone)]
+struct StreamEndpoint {
+    client: reqwest::Client,
+    format: StreamFormat,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+/// Формат и адрес потокового API провайдера: Gemini и OpenAI — с адресом по умолчанию,
+/// прочие провайдеры — как OpenAI-совместимые, если задан llm.base_url
+fn stream_target(name: Option<&ProviderName>, base_url: Option<String>) -> Option<(StreamFormat, String)> {
+    let base_url = base_url.filter(|u| !u.trim().is_empty());
+    match name {
+        Some(ProviderName::Gemini) => Some((StreamFormat::Gemini, base_url.unwrap_or_else(|| DEFAULT_GEMINI_BASE_URL.to_string()))),
+        Some(ProviderName::OpenAI) => Some((StreamFormat::OpenAi, base_url.unwrap_or_else(|| DEFAULT_OPENAI_BASE_URL.to_string()))),
+        Some(ProviderName::Ollama) | Some(ProviderName::Anthropic) => None,
+        _ => base_url.map(|u| (StreamFormat::OpenAi, u)),
+    }
+}
+
+/// LocalChatApi uses a cloud provider via ai-lib, or a local Ollama via its native API.
+enum Engine {
+    /// `stream` — потоковый API провайдера, если он поддерживается
+    Cloud { client: AiClient, stream: Option<StreamEndpoint> },
+    /// Ollama: POST {base_url}/api/generate без ключа API
+    Ollama { client: reqwest::Client, base_url: String },
+}
+
+#[derive(serde::Serialize)]
+struct OllamaGenerateRequest<'a> {
+    model: &'a str,
+    prompt: &'a str,
+    stream: bool,
+}
+
+#[derive(serde::Deserialize)]
+struct OllamaGenerateResponse {
+    response: String,
 }
 
 #[derive(Builder)]
@@ -77,6 +132,10 @@ pub struct LocalChatApi {
     pub model: String,
     pub model_path: Option<String>,
     pub tokenizer_path: Option<String>,
+    /// User-Agent общего HTTP-клиента (http.user_agent)
+    pub user_agent: Option<String>,
+    /// Прокси общего HTTP-клиента (http.proxy), если llm.proxy не задан
+    pub http_proxy: Option<String>,
     engine: Mutex<Option<Engine>>,
 }
 
@@ -87,10 +146,33 @@ impl LocalChatApi {
             model: llm.model.clone().unwrap_or_else(|| "".to_string()),
             model_path: llm.model_path.clone(),
             tokenizer_path: llm.tokenizer_path.clone(),
+            user_agent: None,
+            http_proxy: None,
             engine: Mutex::new(None),
         }
     }
 
+    /// Прямые HTTP-запросы к LLM (Ollama, потоковый ответ) идут с User-Agent и прокси секции http
+    pub fn with_http_config(mut self, http: Option<&HttpConfig>) -> Self {
+        if let Some(http) = http {
+            self.user_agent = http.user_agent.clone();
+            self.http_proxy = http.proxy.clone();
+        }
+        self
+    }
+
+    /// Построитель клиента прямых HTTP-запросов к LLM: llm.proxy (или http.proxy) и http.user_agent
+    fn http_client_builder(&self) -> Result<reqwest::ClientBuilder, Box<dyn std::error::Error + Send + Sync>> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(proxy) = llm_defaults::proxy().or_else(|| self.http_proxy.clone()).filter(|p| !p.trim().is_empty()) {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+        if let Some(user_agent) = self.user_agent.as_deref() {
+            builder = builder.user_agent(user_agent);
+        }
+        Ok(builder)
+    }
+
     async fn ensure_engine(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let mut guard = self.engine.lock().await;
         if guard.is_some() {
@@ -99,9 +181,7 @@ impl LocalChatApi {
 
         // Configure ai-lib client from config/env
         let provider = llm_defaults::provider().unwrap_or_else(|| "Groq".to_string());
-        let prov = ProviderName::from_str(&provider)
-            .map(map_provider)
-            .unwrap_or(Provider::Groq);
+        let name = ProviderName::from_str(&provider).ok();
 
         info!(
             provider = %provider,
@@ -110,23 +190,85 @@ impl LocalChatApi {
             timeout = %llm_defaults::timeout().map_or("None".to_string(), |t| t.to_string()),
         );
 
+        if matches!(name, Some(ProviderName::Ollama)) {
+            let mut builder = self.http_client_builder()?;
+            if let Some(t) = llm_defaults::timeout() {
+                builder = builder.timeout(std::time::Duration::from_secs(t));
+            }
+            let base_url = llm_defaults::base_url()
+                .filter(|u| !u.trim().is_empty())
+                .unwrap_or_else(|| DEFAULT_OLLAMA_BASE_URL.to_string());
+            *guard = Some(Engine::Ollama { client: builder.build()?, base_url });
+            return Ok(());
+        }
+
+        let api_key = std::env::var(format!("{}_API_KEY", provider.to_uppercase()))
+            .ok()
+            .or_else(|| llm_defaults::api_key());
+        // Клиент потока без общего таймаута: ожидание частей ограничивает таймаут простоя
+        let stream = match stream_target(name.as_ref(), llm_defaults::base_url()) {
+            Some((format, base_url)) => {
+                let client = self.http_client_builder()?.build()?;
+                Some(StreamEndpoint { client, format, base_url, api_key: api_key.clone() })
+            }
+            None => None,
+        };
+        let prov = name.map(map_provider).unwrap_or(Provider::Groq);
         let client = AiClient::with_options(
             prov,
             ConnectionOptions {
                 base_url: llm_defaults::base_url(),
                 proxy: llm_defaults::proxy(),
-                api_key: std::env::var(format!("{}_API_KEY", provider.to_uppercase()))
-                    .ok()
-                    .or_else(|| llm_defaults::api_key()),
+                api_key,
                 timeout: llm_defaults::timeout().map(std::time::Duration::from_secs),
                 disable_proxy: false,
             },
         )?;
-        *guard = Some(Engine { cloud: client });
+        *guard = Some(Engine::Cloud { client, stream });
         Ok(())
     }
 }
 
+impl LocalChatApi {
+    /// Запрос к Ollama: POST /api/generate с `stream: false`, ответ — поле `response`
+    async fn call_ollama(
+        &self,
+        client: &reqwest::Client,
+        base_url: &str,
+        prompt: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let url = format!("{}/api/generate", base_url.trim_end_matches('/'));
+        let preview_len: usize = llm_defaults::log_prompt_preview_chars().unwrap_or(200);
+        let prompt_preview: String = prompt.chars().take(preview_len).collect();
+        info!(
+            model = %self.model,
+            url = %url,
+            prompt_len = prompt.len(),
+            prompt_preview = %prompt_preview,
+            "ollama: generate request"
+        );
+        let res = client
+            .post(&url)
+            .json(&OllamaGenerateRequest { model: &self.model, prompt, stream: false })
+            .send()
+            .await?;
+        let status = res.status();
+        if !status.is_success() {
+            let body = res.text().await.unwrap_or_default();
+            return Err(format!("Ollama error {}: {}", status, body).into());
+        }
+        let text = res.json::<OllamaGenerateResponse>().await?.response;
+        let response_preview: String = text.chars().take(preview_len).collect();
+        info!(
+            model = %self.model,
+            response_len = text.len(),
+            response_preview = %response_preview,
+            "ollama: generate response"
+        );
+        Ok(text)
+    }
+}
+
 #[async_trait]
 impl ChatApi for LocalChatApi {
     async fn call_chat_api(
@@ -136,7 +278,10 @@ impl ChatApi for LocalChatApi {
         self.ensure_engine().await?;
         let mut guard = self.engine.lock().await;
         let engine = guard.as_mut().expect("engine initialized");
-        let client = &engine.cloud;
+        let client = match engine {
+            Engine::Cloud { client, .. } => client,
+            Engine::Ollama { client, base_url } => return self.call_ollama(client, base_url, prompt).await,
+        };
         // Log request details (without leaking entire prompt)
         let model_name = if self.model.trim().is_empty() {
             client.default_chat_model().to_string()
@@ -176,6 +321,156 @@ impl ChatApi for LocalChatApi {
         );
         Ok(text)
     }
+
+    fn supports_streaming(&self) -> bool {
+        let provider = llm_defaults::provider().unwrap_or_else(|| "Groq".to_string());
+        stream_target(ProviderName::from_str(&provider).ok().as_ref(), llm_defaults::base_url()).is_some()
+    }
+
+    async fn call_chat_api_streaming(
+        &self,
+        prompt: &str,
+        idle_timeout: Duration,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        self.ensure_engine().await?;
+        // Блокировка снимается до запроса: потоковые вызовы идут параллельно
+        let target = match self.engine.lock().await.as_ref().expect("engine initialized") {
+            Engine::Cloud { client, stream: Some(endpoint) } => {
+                let model_name = if self.model.trim().is_empty() {
+                    client.default_chat_model().to_string()
+                } else {
+                    self.model.clone()
+                };
+                Some((endpoint.clone(), model_name))
+            }
+            _ => None,
+        };
+        let Some((endpoint, model_name)) = target else {
+            info!(model = %self.model, "llm: provider has no streaming API, using regular request");
+            return self.call_chat_api(prompt).await;
+        };
+        self.call_stream(&endpoint, &model_name, prompt, idle_timeout).await
+    }
+}
+
+impl LocalChatApi {
+    /// Потоковый запрос: части ответа собираются по мере поступления, запрос прерывается,
+    /// только если новая часть не пришла за `idle_timeout`
+    async fn call_stream(
+        &self,
+        endpoint: &StreamEndpoint,
+        model_name: &str,
+        prompt: &str,
+        idle_timeout: Duration,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let base_url = endpoint.base_url.trim_end_matches('/');
+        let request = match endpoint.format {
+            StreamFormat::OpenAi => {
+                let request = endpoint.client.post(format!("{}/chat/completions", base_url)).json(&serde_json::json!({
+                    "model": model_name,
+                    "messages": [{ "role": "user", "content": prompt }],
+                    "stream": true,
+                }));
+                match endpoint.api_key.as_deref() {
+                    Some(key) => request.bearer_auth(key),
+                    None => request,
+                }
+            }
+            StreamFormat::Gemini => {
+                let request = endpoint
+                    .client
+                    .post(format!("{}/models/{}:streamGenerateContent?alt=sse", base_url, model_name))
+                    .json(&serde_json::json!({
+                        "contents": [{ "role": "user", "parts": [{ "text": prompt }] }],
+                    }));
+                match endpoint.api_key.as_deref() {
+                    Some(key) => request.header("x-goog-api-key", key),
+                    None => request,
+                }
+            }
+        };
+        let preview_len: usize = llm_defaults::log_prompt_preview_chars().unwrap_or(200);
+        let prompt_preview: String = prompt.chars().take(preview_len).collect();
+        info!(
+            model = %model_name,
+            format = ?endpoint.format,
+            prompt_len = prompt.len(),
+            prompt_preview = %prompt_preview,
+            idle_timeout_secs = idle_timeout.as_secs(),
+            "llm: streaming chat request"
+        );
+
+        let idle_error = || format!("LLM stream idle for more than {} secs", idle_timeout.as_secs());
+        let mut res = tokio::time::timeout(idle_timeout, request.send()).await.map_err(|_| idle_error())??;
+        let status = res.status();
+        if !status.is_success() {
+            let body = res.text().await.unwrap_or_default();
+            return Err(format!("LLM stream error {}: {}", status, body).into());
+        }
+
+        let mut pending: Vec<u8> = Vec::new();
+        let mut text = String::new();
+        let mut chunks = 0usize;
+        'read: loop {
+            let chunk = tokio::time::timeout(idle_timeout, res.chunk()).await.map_err(|_| idle_error())??;
+            let finished = chunk.is_none();
+            if let Some(chunk) = chunk {
+                pending.extend_from_slice(&chunk);
+            } else {
+                // Последнее событие может прийти без завершающего перевода строки
+                pending.push(b'\n');
+            }
+            for data in take_sse_data(&mut pending) {
+                if data == "[DONE]" {
+                    break 'read;
+                }
+                text.push_str(&stream_event_text(endpoint.format, &data)?);
+                chunks += 1;
+            }
+            if finished {
+                break;
+            }
+        }
+
+        let response_preview: String = text.chars().take(preview_len).collect();
+        info!(
+            model = %model_name,
+            chunks,
+            response_len = text.len(),
+            response_preview = %response_preview,
+            "llm: streaming chat response"
+        );
+        Ok(text)
+    }
+}
+
+/// Забирает из буфера завершенные строки потока SSE и возвращает данные событий (`data: ...`).
+/// Незавершенная строка остается в буфере до следующей части
+fn take_sse_data(pending: &mut Vec<u8>) -> Vec<String> {
+    let mut events = Vec::new();
+    while let Some(pos) = pending.iter().position(|b| *b == b'\n') {
+        let line: Vec<u8> = pending.drain(..=pos).collect();
+        let line = String::from_utf8_lossy(&line);
+        if let Some(data) = line.trim().strip_prefix("data:") {
+            events.push(data.trim().to_string());
+        }
+    }
+    events
+}
+
+/// Текст одного события потокового ответа
+fn stream_event_text(format: StreamFormat, data: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let event: serde_json::Value = serde_json::from_str(data)?;
+    if let Some(error) = event.get("error") {
+        return Err(format!("LLM stream error: {}", error).into());
+    }
+    Ok(match format {
+        StreamFormat::OpenAi => event["choices"][0]["delta"]["content"].as_str().unwrap_or_default().to_string(),
+        StreamFormat::Gemini => event["candidates"][0]["content"]["parts"]
+            .as_array()
+            .map(|parts| parts.iter().filter_map(|p| p["text"].as_str()).collect())
+            .unwrap_or_default(),
+    })
 }
 
 mod llm_defaults {
@@ -206,3 +501,32 @@ mod llm_defaults {
         CFG.get().and_then(|c| c.log_prompt_preview_chars)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sse_lines_split_across_chunks_are_joined() {
+        let mut pending = Vec::new();
+        let event = "data: {\"choices\":[{\"delta\":{\"content\":\"Привет\"}}]}\n\n".as_bytes();
+        // Граница части проходит внутри многобайтового символа
+        let split = event.iter().position(|b| *b >= 0x80).unwrap() + 1;
+        pending.extend_from_slice(&event[..split]);
+        assert!(take_sse_data(&mut pending).is_empty());
+        pending.extend_from_slice(&event[split..]);
+        let events = take_sse_data(&mut pending);
+        assert_eq!(events.len(), 1);
+        assert_eq!(stream_event_text(StreamFormat::OpenAi, &events[0]).unwrap(), "Привет");
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn stream_event_text_reads_gemini_parts_and_errors() {
+        let data = r#"{"candidates":[{"content":{"parts":[{"text":"a"},{"text":"b"}],"role":"model"}}]}"#;
+        assert_eq!(stream_event_text(StreamFormat::Gemini, data).unwrap(), "ab");
+        // Финальное событие OpenAI без content
+        assert_eq!(stream_event_text(StreamFormat::OpenAi, r#"{"choices":[{"delta":{},"finish_reason":"stop"}]}"#).unwrap(), "");
+        assert!(stream_event_text(StreamFormat::OpenAi, r#"{"error":{"message":"overloaded"}}"#).is_err());
+    }
+}
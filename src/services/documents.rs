@@ -1,85 +1,233 @@
 //
 
-use crate::crawlers::FileIdScanner;
+use crate::crawlers::{FileIdScanner, FileInfo};
+use crate::models::config::DocumentType;
+use crate::models::types::{DocumentValidators, content_hash};
 use crate::traits::markdown_fetcher::MarkdownFetcher;
 use markdownify::docx;
 use reqwest::Client;
 use std::io::Write;
-use tracing::{debug, info};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tracing::{debug, error, info, warn};
 use bon::bon;
 
-/// Реализация MarkdownFetcher, получающая DOCX и извлекающая из него markdown
+/// Результат получения документа
+pub enum DocumentFetch {
+    /// У проекта нет файла (нет fileId или файл пустой)
+    Missing,
+    /// HEAD показал, что документ не изменился с прошлого скачивания (crawler.head_before_get)
+    Unchanged,
+    /// Документ скачан: байты, markdown и HTTP-валидаторы ответа
+    Fetched {
+        bytes: Vec<u8>,
+        markdown: String,
+        validators: DocumentValidators,
+    },
+}
+
+/// Реализация MarkdownFetcher, получающая документ (DOCX или PDF) и извлекающая из него markdown
 pub struct DocxMarkdownFetcher {
     client: Client,
     file_id_url_template: Option<String>,
     files_base_url: Option<String>,
+    verify_checksum: bool,
+    file_id_timeout: Option<std::time::Duration>,
+    file_id_max_retry_attempts: u64,
+    head_before_get: bool,
+    fetch_permits: Option<Arc<Semaphore>>,
+    scan_permits: Option<Arc<Semaphore>>,
+    document_cache_dir: Option<PathBuf>,
+    document_type: DocumentType,
 }
 
 #[bon]
 impl DocxMarkdownFetcher {
     #[builder]
-    pub fn new(file_id_url_template: Option<String>) -> Self {
+    pub fn new(
+        /// Общий HTTP-клиент (секция http); без него создается клиент по умолчанию
+        client: Option<Client>,
+        file_id_url_template: Option<String>,
+        #[builder(default)]
+        verify_checksum: bool,
+        /// Таймаут запроса stages (fileId); без значения — только таймаут клиента
+        file_id_timeout: Option<std::time::Duration>,
+        #[builder(default)]
+        file_id_max_retry_attempts: u64,
+        /// Перед скачиванием сравнивать HEAD с сохраненными валидаторами документа
+        #[builder(default)]
+        head_before_get: bool,
+        /// Общий лимит одновременных скачиваний документов (crawler.fetch_concurrency)
+        fetch_permits: Option<Arc<Semaphore>>,
+        /// Общий лимит одновременных запросов stages для поиска fileId (crawler.scan_concurrency)
+        scan_permits: Option<Arc<Semaphore>>,
+        /// Каталог общего кэша документов по fileId (cache.share_documents)
+        document_cache_dir: Option<PathBuf>,
+        /// Тип документа вместо определения по Content-Type (crawler.force_document_type)
+        #[builder(default)]
+        document_type: DocumentType,
+    ) -> Self {
         // Derive files base URL from file_id template host if provided
-        let files_base_url = file_id_url_template.as_ref().and_then(|tpl| {
-            let to_parse = tpl.replace("{project_id}", "0");
-            url::Url::parse(&to_parse)
-                .ok()
-                .map(|u| {
-                    let host = u.host_str().unwrap_or("localhost");
-                    match u.port() {
-                        Some(port) => format!("{}://{}:{}", u.scheme(), host, port),
-                        None => format!("{}://{}", u.scheme(), host),
-                    }
-                })
-        });
+        let files_base_url = file_id_url_template.as_deref().and_then(files_base_url);
         Self {
-            client: Client::new(),
+            client: client.unwrap_or_default(),
             file_id_url_template,
             files_base_url,
+            verify_checksum,
+            file_id_timeout,
+            file_id_max_retry_attempts,
+            head_before_get,
+            fetch_permits,
+            scan_permits,
+            document_cache_dir,
+            document_type,
         }
     }
 
-    /// Внутренняя реализация получения DOCX и извлечения markdown
-    async fn fetch_docx_internal(
+    /// Получает DOCX и извлекает markdown. При crawler.head_before_get и известных валидаторах
+    /// прошлого скачивания сначала выполняет HEAD и пропускает скачивание неизмененного документа
+    pub async fn fetch_document(
         &self,
         project_id: &str,
-    ) -> Result<Option<(Vec<u8>, String)>, Box<dyn std::error::Error + Send + Sync>> {
+        cached: Option<&DocumentValidators>,
+    ) -> Result<DocumentFetch, Box<dyn std::error::Error + Send + Sync>> {
         info!(%project_id, "docx: get fileId");
         // Resolve fileId using configured template
         let tpl = self.file_id_url_template.as_ref().ok_or_else(||
             Box::<dyn std::error::Error + Send + Sync>::from("crawler.file_id.url is required in config (no fallback stages endpoint)")
         )?;
         let url = tpl.replace("{project_id}", project_id);
-        let scanner = FileIdScanner::builder().client(Client::new()).build();
-        let file_id = scanner.fetch_file_id(&url).await?;
-        let file_id = match file_id {
+        let scanner = FileIdScanner::builder()
+            .client(self.client.clone())
+            .maybe_timeout(self.file_id_timeout)
+            .max_retry_attempts(self.file_id_max_retry_attempts)
+            .maybe_permits(self.scan_permits.clone())
+            .build();
+        let file_info = scanner.fetch_file_info(&url).await?;
+        let FileInfo { file_id, checksum } = match file_info {
             Some(v) => v,
             None => {
                 info!(%project_id, "docx: skip project without fileId");
-                return Ok(None);
+                return Ok(DocumentFetch::Missing);
             }
         };
-        info!(%file_id, "docx: downloading file");
         let base = self
             .files_base_url
             .as_deref()
             .unwrap_or("https://regulation.gov.ru");
         let file_url = format!("{}/api/public/Files/GetFile?fileId={}", base, file_id);
-        info!(url = %file_url, "docx: GET file url");
-        let response = self.client.get(&file_url).send().await?;
-        info!(status = %response.status(), "docx: response status");
-        let bytes = response.bytes().await?;
-        info!(size = bytes.len(), "docx: downloaded");
+
+        // Разрешение держится до конца скачивания (HEAD и GET), извлечение markdown идет без него
+        let permit = match &self.fetch_permits {
+            Some(permits) => Some(permits.clone().acquire_owned().await?),
+            None => None,
+        };
+
+        if let (true, Some(cached)) = (self.head_before_get, cached) {
+            info!(url = %file_url, "docx: HEAD file url");
+            match self.client.head(&file_url).send().await {
+                Ok(head) if head.status().is_success() => {
+                    let current = DocumentValidators::from_headers(head.headers());
+                    if current.unchanged_since(cached) {
+                        info!(%project_id, validators = ?current, "docx: HEAD shows document unchanged, download skipped");
+                        return Ok(DocumentFetch::Unchanged);
+                    }
+                    info!(%project_id, cached = ?cached, current = ?current, "docx: HEAD shows document changed");
+                }
+                Ok(head) => warn!(%project_id, status = %head.status(), "docx: HEAD not supported, downloading"),
+                Err(e) => warn!(%project_id, error = %e, "docx: HEAD failed, downloading"),
+            }
+        }
+
+        let shared_path = self.document_cache_dir.as_deref().map(|dir| shared_document_path(dir, &file_id));
+        let shared = shared_path.as_deref().and_then(|p| std::fs::read(p).ok()).filter(|b| !b.is_empty());
+        let (bytes, validators, content_type) = match shared {
+            Some(bytes) => {
+                info!(%project_id, %file_id, size = bytes.len(), "docx: using shared cached document, download skipped");
+                (bytes, DocumentValidators::default(), None)
+            }
+            None => {
+                info!(%file_id, "docx: downloading file");
+                info!(url = %file_url, "docx: GET file url");
+                let response = self.client.get(&file_url).send().await?;
+                info!(status = %response.status(), "docx: response status");
+                if !response.status().is_success() {
+                    return Err(format!("docx: http error on file download: {}", response.status()).into());
+                }
+                let validators = DocumentValidators::from_headers(response.headers());
+                let content_type = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .map(|v| v.to_string());
+                let bytes = response.bytes().await?.to_vec();
+                info!(size = bytes.len(), "docx: downloaded");
+                if let Some(path) = shared_path.as_deref().filter(|_| !bytes.is_empty()) {
+                    if let Err(e) = write_shared_document(path, &bytes) {
+                        warn!(%file_id, path = %path.display(), error = %e, "docx: failed to save shared cached document");
+                    }
+                }
+                (bytes, validators, content_type)
+            }
+        };
+        drop(permit);
 
         // Проверяем на пустой файл
         if bytes.is_empty() {
             info!(%project_id, "docx: file is empty, skipping");
-            return Ok(None);
+            return Ok(DocumentFetch::Missing);
+        }
+
+        if self.verify_checksum {
+            match checksum {
+                Some(expected) => {
+                    let actual = content_hash(bytes.as_ref());
+                    if actual != expected {
+                        error!(%project_id, %expected, %actual, "docx: checksum mismatch");
+                        return Err(format!("docx: checksum mismatch for project {}: expected {}, got {}", project_id, expected, actual).into());
+                    }
+                    info!(%project_id, "docx: checksum verified");
+                }
+                None => warn!(%project_id, "docx: no checksum in stages response, skipping verification"),
+            }
         }
 
-        let text = Self::extract_markdown_from_docx(bytes.as_ref())?;
+        // Старый .doc (OLE) не извлекается ни одним из парсеров, в том числе при force_document_type
+        if bytes.starts_with(&OLE_SIGNATURE) {
+            return Err(format!("docx: legacy .doc (OLE) document for project {} is not supported", project_id).into());
+        }
+        let document_type = match self.document_type {
+            DocumentType::Auto => detect_document_type(&bytes).ok_or_else(|| {
+                format!(
+                    "docx: unsupported document type for project {} (content-type: {}); set crawler.force_document_type",
+                    project_id,
+                    content_type.as_deref().unwrap_or("none")
+                )
+            })?,
+            forced => {
+                debug!(%project_id, document_type = ?forced, content_type = ?content_type, "docx: document type forced by config");
+                forced
+            }
+        };
+        let text = match document_type {
+            DocumentType::Docx | DocumentType::Auto => Self::extract_markdown_from_docx(bytes.as_ref())?,
+            DocumentType::Pdf => {
+                // pdf-extract разбирает документ синхронно и паникует на части поврежденных файлов
+                let pdf = bytes.clone();
+                tokio::task::spawn_blocking(move || {
+                    std::panic::catch_unwind(|| Self::extract_markdown_from_pdf(&pdf))
+                        .unwrap_or_else(|panic| Err(format!("pdf-extract panicked: {}", panic_message(&panic)).into()))
+                })
+                .await??
+            }
+        };
         debug!(len = text.len(), "docx: extracted markdown");
-        Ok(Some((bytes.to_vec(), text)))
+        Ok(DocumentFetch::Fetched {
+            bytes,
+            markdown: text,
+            validators,
+        })
     }
 
     // kept functions below
@@ -88,6 +236,136 @@ impl DocxMarkdownFetcher {
 
 
 
+/// Сигнатура составного файла OLE (старый Word .doc)
+const OLE_SIGNATURE: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+/// Текст паники для сообщения об ошибке
+fn panic_message(panic: &Box<dyn std::any::Any + Send>) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Определяет тип документа по сигнатуре, независимо от Content-Type (серверы отдают документы
+/// с неверным или общим типом): PDF начинается с `%PDF`, DOCX является ZIP-архивом (`PK\x03\x04`)
+pub(crate) fn detect_document_type(bytes: &[u8]) -> Option<DocumentType> {
+    if bytes.starts_with(b"%PDF") {
+        Some(DocumentType::Pdf)
+    } else if bytes.starts_with(b"PK\x03\x04") {
+        Some(DocumentType::Docx)
+    } else {
+        None
+    }
+}
+
+/// Базовый URL файлового API (схема, хост и порт) по шаблону crawler.file_id.url
+pub(crate) fn files_base_url(file_id_url_template: &str) -> Option<String> {
+    let to_parse = file_id_url_template.replace("{project_id}", "0");
+    url::Url::parse(&to_parse).ok().map(|u| {
+        let host = u.host_str().unwrap_or("localhost");
+        match u.port() {
+            Some(port) => format!("{}://{}:{}", u.scheme(), host, port),
+            None => format!("{}://{}", u.scheme(), host),
+        }
+    })
+}
+
+/// URL скачивания файла проекта: значение из метаданных может быть полным URL или fileId
+pub(crate) fn file_download_url(files_base_url: Option<&str>, file: &str) -> String {
+    if file.starts_with("http://") || file.starts_with("https://") {
+        return file.to_string();
+    }
+    format!(
+        "{}/api/public/Files/GetFile?fileId={}",
+        files_base_url.unwrap_or("https://regulation.gov.ru"),
+        urlencoding::encode(file)
+    )
+}
+
+/// Скачивает файл не больше `max_bytes`: возвращает Content-Type и содержимое или None, если файл больше.
+/// Тело читается частями, поэтому файл без Content-Length тоже не загружается в память целиком
+pub(crate) async fn download_limited(
+    client: &Client,
+    url: &str,
+    max_bytes: usize,
+) -> Result<Option<(Option<String>, Vec<u8>)>, reqwest::Error> {
+    let mut resp = client.get(url).send().await?.error_for_status()?;
+    if resp.content_length().is_some_and(|len| len > max_bytes as u64) {
+        return Ok(None);
+    }
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let mut bytes = Vec::new();
+    while let Some(chunk) = resp.chunk().await? {
+        if bytes.len() + chunk.len() > max_bytes {
+            return Ok(None);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(Some((content_type, bytes)))
+}
+
+/// Имя файла из заголовка Content-Disposition ответа Files endpoint (HEAD, без скачивания тела)
+pub(crate) async fn fetch_file_name(
+    client: &Client,
+    url: &str,
+    timeout: std::time::Duration,
+) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let response = client.head(url).timeout(timeout).send().await?.error_for_status()?;
+    Ok(response
+        .headers()
+        .get(reqwest::header::CONTENT_DISPOSITION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(content_disposition_filename))
+}
+
+/// Разбирает `filename*=UTF-8''...` (приоритетно) или `filename="..."` из Content-Disposition
+fn content_disposition_filename(header: &str) -> Option<String> {
+    let params: Vec<(&str, &str)> = header
+        .split(';')
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.trim(), v.trim()))
+        .collect();
+    let extended = params
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("filename*"))
+        .and_then(|(_, v)| v.split_once("''"))
+        .and_then(|(_, encoded)| urlencoding::decode(encoded).ok())
+        .map(|s| s.into_owned());
+    extended
+        .or_else(|| {
+            params
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case("filename"))
+                .map(|(_, v)| v.trim_matches('"').to_string())
+        })
+        .filter(|name| !name.is_empty())
+}
+
+/// Путь документа в общем кэше: fileId с заменой небезопасных для имени файла символов
+fn shared_document_path(dir: &Path, file_id: &str) -> PathBuf {
+    let name: String = file_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    dir.join(format!("{}.docx", name))
+}
+
+/// Записывает документ в общий кэш через временный файл и rename
+fn write_shared_document(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("docx.tmp");
+    std::fs::write(&tmp, bytes)?;
+    std::fs::rename(&tmp, path)
+}
+
 // New helper that converts DOCX bytes to Markdown via markdownify
 impl DocxMarkdownFetcher {
     fn extract_markdown_from_docx(
@@ -101,6 +379,26 @@ impl DocxMarkdownFetcher {
         info!(len = md.len(), "docx: extracted markdown");
         Ok(md)
     }
+
+    /// Извлекает текст PDF; строки очищаются от крайних пробелов, серии пустых строк
+    /// сводятся к одной (граница абзаца)
+    fn extract_markdown_from_pdf(
+        pdf_bytes: &[u8],
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        info!(bytes_len = pdf_bytes.len(), "pdf: received bytes for text extraction");
+        let text = pdf_extract::extract_text_from_mem(pdf_bytes).map_err(|e| format!("pdf-extract failed: {}", e))?;
+        let mut md = String::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() && (md.is_empty() || md.ends_with("\n\n")) {
+                continue;
+            }
+            md.push_str(line);
+            md.push('\n');
+        }
+        let md = md.trim_end().to_string();
+        info!(len = md.len(), "pdf: extracted markdown");
+        Ok(md)
+    }
 }
 
 #[async_trait::async_trait]
@@ -109,6 +407,9 @@ impl MarkdownFetcher for DocxMarkdownFetcher {
         &self,
         project_id: &str,
     ) -> Result<Option<(Vec<u8>, String)>, Box<dyn std::error::Error + Send + Sync>> {
-        self.fetch_docx_internal(project_id).await
+        match self.fetch_document(project_id, None).await? {
+            DocumentFetch::Fetched { bytes, markdown, .. } => Ok(Some((bytes, markdown))),
+            DocumentFetch::Missing | DocumentFetch::Unchanged => Ok(None),
+        }
     }
 }
@@ -5,4 +5,7 @@ pub mod settings;
 pub mod chat_api_local;
 pub mod worker;
 pub mod cache_manager_impl;
+pub mod cache_manager_sqlite;
 pub mod channels;
+pub mod summary_guard;
+pub mod rate_limiter;
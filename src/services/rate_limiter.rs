@@ -0,0 +1,47 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+use tracing::debug;
+
+/// Token bucket на один токен: токен пополняется раз в `60 / requests_per_minute` секунд,
+/// поэтому вызовы идут не чаще этого интервала. Клоны делят одно ведро, так что
+/// одновременные суммаризации разных каналов и элементов выстраиваются в общую очередь
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    interval: Duration,
+    /// Момент выдачи последнего токена; блокировка — очередь ожидающих
+    last_token: Arc<Mutex<Option<Instant>>>,
+}
+
+impl RateLimiter {
+    /// Ограничение `requests_per_minute` вызовов в минуту; 0 трактуется как 1
+    pub fn per_minute(requests_per_minute: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(60) / requests_per_minute.max(1),
+            last_token: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Интервал между вызовами
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Ждет токен. Ожидающие получают токены в порядке обращения (блокировка tokio справедливая);
+    /// токен считается выданным только после ожидания, поэтому отмененный ожидающий
+    /// освобождает очередь при drop и не занимает интервал следующих
+    pub async fn acquire(&self) {
+        let mut last_token = self.last_token.lock().await;
+        if let Some(last) = *last_token {
+            let slot = last + self.interval;
+            let wait = slot.saturating_duration_since(Instant::now());
+            if !wait.is_zero() {
+                debug!(wait_ms = wait.as_millis() as u64, "rate limiter: waiting for token");
+            }
+            tokio::time::sleep_until(slot).await;
+        }
+        *last_token = Some(Instant::now());
+    }
+}
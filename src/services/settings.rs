@@ -1,11 +1,89 @@
 use std::fs;
 use std::path::Path;
+use serde_yaml::Value;
 use crate::models::config::AppConfig;
 
+/// `${VAR}` — ссылка на переменную окружения; `$${VAR}` оставляет текст `${VAR}` как есть
+static ENV_VAR_RE: once_cell::sync::Lazy<regex::Regex> = once_cell::sync::Lazy::new(|| {
+    regex::Regex::new(r"\$?\$\{([A-Za-z_][A-Za-z0-9_]*)\}").unwrap()
+});
+
 pub fn load_config<P: AsRef<Path>>(path: P) -> Result<AppConfig, Box<dyn std::error::Error + Send + Sync>> {
     let content = fs::read_to_string(path)?;
-    let cfg: AppConfig = serde_yaml::from_str(&content)?;
+    let mut value: Value = serde_yaml::from_str(&content)?;
+    interpolate_env(&mut value)?;
+    let cfg: AppConfig = serde_yaml::from_value(value)?;
     Ok(cfg)
 }
 
+/// Подставляет значения переменных окружения вместо `${VAR}` в строковые значения уже разобранного YAML,
+/// поэтому спецсимволы YAML в значении переменной не меняют структуру конфига. Значение, целиком
+/// состоящее из одной ссылки, становится целым числом или true/false, если переменная так записана.
+/// Незаданная переменная — ошибка с путем ключа
+pub fn interpolate_env(value: &mut Value) -> Result<(), String> {
+    interpolate_at(value, "")
+}
+
+fn interpolate_at(value: &mut Value, path: &str) -> Result<(), String> {
+    match value {
+        Value::String(text) => {
+            if let Some(resolved) = interpolate_str(text, path)? {
+                *value = resolved;
+            }
+        }
+        Value::Sequence(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                interpolate_at(item, &format!("{}[{}]", path, i))?;
+            }
+        }
+        Value::Mapping(map) => {
+            for (key, item) in map.iter_mut() {
+                let key = match key {
+                    Value::String(k) => k.clone(),
+                    other => serde_yaml::to_string(other).unwrap_or_default().trim().to_string(),
+                };
+                let item_path = if path.is_empty() { key } else { format!("{}.{}", path, key) };
+                interpolate_at(item, &item_path)?;
+            }
+        }
+        Value::Tagged(tagged) => interpolate_at(&mut tagged.value, path)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Новое значение строки с подставленными переменными или None, если ссылок в строке нет
+fn interpolate_str(text: &str, path: &str) -> Result<Option<Value>, String> {
+    if !text.contains("${") {
+        return Ok(None);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    let mut whole_reference = false;
+    for caps in ENV_VAR_RE.captures_iter(text) {
+        let m = caps.get(0).unwrap();
+        out.push_str(&text[last..m.start()]);
+        last = m.end();
+        if m.as_str().starts_with("$$") {
+            out.push_str(&m.as_str()[1..]);
+            continue;
+        }
+        let name = &caps[1];
+        let value = std::env::var(name)
+            .map_err(|_| format!("environment variable {} referenced in config at {} is not set", name, path))?;
+        whole_reference = m.start() == 0 && m.end() == text.len();
+        out.push_str(&value);
+    }
+    out.push_str(&text[last..]);
 
+    // target_chat_id: ${CHAT_ID} — числовые и логические поля тоже можно брать из окружения
+    if whole_reference {
+        if let Ok(n) = out.parse::<i64>() {
+            return Ok(Some(Value::Number(n.into())));
+        }
+        if let Ok(b) = out.parse::<bool>() {
+            return Ok(Some(Value::Bool(b)));
+        }
+    }
+    Ok(Some(Value::String(out)))
+}
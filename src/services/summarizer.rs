@@ -2,12 +2,69 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use crate::models::types::CrawlItem;
-use crate::models::config::AppConfig;
+use crate::models::config::{AppConfig, SummaryFallback, SummaryStrategy};
+use crate::publishers::utils::fit_to_limit;
+use crate::services::rate_limiter::RateLimiter;
 use crate::traits::chat_api::ChatApi;
 use backon::{ExponentialBuilder, Retryable};
 use bon::Builder;
+use tera::ast::{Expr, ExprVal, Node};
 use tera::{Context, Tera};
-use tracing::{debug, info, warn};
+use tracing::{debug, error, info, warn};
+
+/// Размер части документа для map_reduce по умолчанию (символов)
+const DEFAULT_CHUNK_CHARS: usize = 12000;
+
+/// Промпт этапа map: краткое изложение одной части документа
+const MAP_PROMPT_TEMPLATE: &str = "Кратко изложи суть части {{ part }} из {{ parts }} документа «{{ title }}». \
+Сохрани факты, цифры, сроки и названия, без оценок и вступлений.\nТекст части:\n{{ body }}";
+
+/// Промпт суммаризации, если не задан ни summarizer.prompt_template, ни run.prompt_template
+pub const DEFAULT_PROMPT_TEMPLATE: &str = "Создай краткий пост суммаризации для Telegram/Mastodon.
+Требования:
+- Язык ответа: {{ language }}.
+- Уложить в {{ max_chars }} символов ответа, это очень важно.
+- Без воды, факты и суть.
+- Оцени полезность проекта от 1 до 10
+- Оцени репрессивность от 1 до 10
+- Оцени коррупционную емкость проекта от 1 до 10
+- Все оценки должны быть кратко пояснены в нескольких словах и представлены в виде оценка/максимум оценка
+- Оценки должны быть написаны под общим заголовком \"Рейтинг\"
+Данные с сайта:
+Заголовок: {{ title }}
+Текст: {{ text }}
+Ссылка: {{ url }}";
+
+/// Язык суммаризации по умолчанию ({{ language }} шаблона промпта)
+const DEFAULT_LANGUAGE: &str = "ru";
+
+/// Вставка на месте вырезанной середины документа (summarizer.input_tail_share)
+const TRUNCATION_MARKER: &str = "\n\n[…]\n\n";
+
+/// Грубая оценка числа токенов без токенизатора: большее из «слова × 4/3» и «символы / 4»
+pub fn estimate_tokens(text: &str) -> usize {
+    let words = text.split_whitespace().count();
+    let chars = text.chars().count();
+    (words * 4).div_ceil(3).max(chars.div_ceil(4))
+}
+
+/// Обрезает текст до `budget` токенов по оценке `estimate_tokens`, сохраняя начало и
+/// (при `tail_share` > 0) конец документа. None — текст укладывается в бюджет
+fn truncate_to_token_budget(text: &str, budget: usize, tail_share: f32) -> Option<String> {
+    let tokens = estimate_tokens(text);
+    if tokens <= budget {
+        return None;
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let keep = (chars.len() * budget / tokens).saturating_sub(TRUNCATION_MARKER.chars().count());
+    let tail = (keep as f32 * tail_share.clamp(0.0, 0.5)) as usize;
+    let mut truncated: String = chars[..keep - tail].iter().collect();
+    if tail > 0 {
+        truncated.push_str(TRUNCATION_MARKER);
+        truncated.extend(&chars[chars.len() - tail..]);
+    }
+    Some(truncated)
+}
 
 /// Service that wraps `ChatApi` and generates concise Telegram-ready posts
 /// from raw website content.
@@ -20,6 +77,64 @@ pub struct Summarizer {
     preview_chars: Option<usize>,
     max_retry_attempts: u64,
     retry_delay_secs: u64,
+    /// Режим отладки промпта: печатать промпт вместо вызова модели
+    #[builder(default)]
+    print_prompt: bool,
+    /// Стратегия суммаризации (summarizer.strategy)
+    #[builder(default)]
+    strategy: SummaryStrategy,
+    /// Размер части документа для map_reduce (summarizer.chunk_chars)
+    #[builder(default = DEFAULT_CHUNK_CHARS)]
+    chunk_chars: usize,
+    /// Фиксированная суммаризация вместо вызова LLM (summarizer.test_fixed_summary)
+    fixed_summary: Option<String>,
+    /// Поведение при ошибке LLM (summarizer.fallback)
+    #[builder(default)]
+    fallback: SummaryFallback,
+    /// Общий лимит вызовов LLM (summarizer.requests_per_minute)
+    rate_limiter: Option<RateLimiter>,
+    /// Язык суммаризации (summarizer.language)
+    language: Option<String>,
+    /// summarizer.stream: потоковый ответ LLM с таймаутом простоя между частями
+    /// (run.summarization_timeout_secs) вместо общего таймаута
+    stream_idle_timeout: Option<Duration>,
+    /// Таймаут одного вызова LLM после получения токена summarizer.requests_per_minute
+    /// (run.summarization_timeout_secs): ожидание лимита в таймаут не входит
+    call_timeout: Option<Duration>,
+    /// Бюджет токенов исходного текста (summarizer.max_input_tokens или по llm.provider)
+    max_input_tokens: Option<usize>,
+    /// Доля бюджета для конца документа (summarizer.input_tail_share)
+    #[builder(default)]
+    input_tail_share: f32,
+}
+
+/// Параметры промпта канала: стиль (channels.<name>.style) и язык (channels.<name>.language)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChannelPrompt<'a> {
+    pub style: Option<&'a str>,
+    pub language: Option<&'a str>,
+}
+
+/// Результат суммаризации с учетом summarizer.fallback
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SummaryOutcome {
+    /// Ответ LLM
+    Generated(String),
+    /// LLM недоступен: начало исходного текста, обрезанное по лимиту
+    SourceExcerpt(String),
+}
+
+impl SummaryOutcome {
+    pub fn into_text(self) -> String {
+        match self {
+            SummaryOutcome::Generated(s) | SummaryOutcome::SourceExcerpt(s) => s,
+        }
+    }
+
+    /// Вместо суммаризации — отрывок исходника: такой текст не кэшируется как суммаризация
+    pub fn is_source_excerpt(&self) -> bool {
+        matches!(self, SummaryOutcome::SourceExcerpt(_))
+    }
 }
 
 impl Summarizer {
@@ -40,9 +155,64 @@ impl Summarizer {
         // Настройка параметров retry
         self.max_retry_attempts = cfg.llm.max_retry_attempts.unwrap_or(3);
         self.retry_delay_secs = cfg.llm.retry_delay_secs.unwrap_or(2);
+        if let Some(summarizer) = cfg.summarizer.as_ref() {
+            self.strategy = summarizer.strategy.unwrap_or_default();
+            if let Some(chunk_chars) = summarizer.chunk_chars {
+                self.chunk_chars = chunk_chars.max(1);
+            }
+            self.fixed_summary = summarizer.test_fixed_summary.clone();
+            self.fallback = summarizer.fallback.unwrap_or_default();
+            self.rate_limiter = summarizer.requests_per_minute.map(RateLimiter::per_minute);
+            if let Some(tpl) = summarizer.prompt_template.clone() {
+                self.template = Some(tpl);
+            }
+            self.language = summarizer.language.clone();
+            // Без потокового API у провайдера ответ читается целиком, и общий таймаут сохраняется
+            let stream = summarizer.stream.unwrap_or(false);
+            if stream && !self.chat_api.supports_streaming() {
+                warn!(provider = ?cfg.llm.provider, "summarizer.stream: provider has no streaming API, keeping the overall summarization timeout");
+            }
+            let summarization_timeout =
+                Duration::from_secs(cfg.run.as_ref().and_then(|r| r.summarization_timeout_secs).unwrap_or(120));
+            self.stream_idle_timeout = (stream && self.chat_api.supports_streaming()).then_some(summarization_timeout);
+            self.call_timeout = (self.rate_limiter.is_some() && self.stream_idle_timeout.is_none())
+                .then_some(summarization_timeout);
+            let provider_budget = cfg.llm.provider.as_deref().and_then(|provider| {
+                summarizer
+                    .max_input_tokens_by_provider
+                    .as_ref()?
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(provider))
+                    .map(|(_, budget)| *budget)
+            });
+            self.max_input_tokens = provider_budget.or(summarizer.max_input_tokens).filter(|b| *b > 0);
+            self.input_tail_share = summarizer.input_tail_share.unwrap_or(0.0);
+        }
+        self
+    }
+
+    /// Таймаут действует внутри вызовов LLM (summarizer.stream или summarizer.requests_per_minute):
+    /// общий таймаут суммаризации не применяется
+    pub fn times_out_calls(&self) -> bool {
+        self.stream_idle_timeout.is_some() || self.call_timeout.is_some()
+    }
+
+    /// Enables "dry summarize": the rendered prompt is printed and the chat API is never called.
+    pub fn with_print_prompt(mut self, print_prompt: bool) -> Self {
+        self.print_prompt = print_prompt;
         self
     }
 
+    pub fn prints_prompt(&self) -> bool {
+        self.print_prompt
+    }
+
+    /// Печатает промпт и исходный текст в stdout (режим --print-prompt)
+    fn emit_prompt(&self, title: &str, prompt: &str, body_text: &str) {
+        info!(%title, prompt_len = prompt.len(), body_len = body_text.len(), "summarize: print-prompt mode, chat api call skipped");
+        println!("===== PROMPT: {} =====\n{}\n===== INPUT TEXT ({} chars) =====\n{}\n===== END =====", title, prompt, body_text.chars().count(), body_text);
+    }
+
     /// Builds a prompt by rendering a Tera template from config.
     fn build_prompt(
         &self,
@@ -51,28 +221,82 @@ impl Summarizer {
         source_url: &str,
         meta: Option<&CrawlItem>,
         model_limit: Option<usize>,
+        channel: ChannelPrompt<'_>,
     ) -> String {
-        // limit: prefer per-call model_limit, else fallback to hard_max_chars as a coarse hint
-        let limit = model_limit.unwrap_or(self.hard_max_chars);
-        // take leading slice of the text by sample_percent
-        // символобезопасное усечение (по char), чтобы не резать UTF-8 на байтах
+        self.render_prompt(title, self.sample(body_text), source_url, meta, model_limit, channel)
+    }
+
+    /// Обрезает исходный текст до summarizer.max_input_tokens, чтобы промпт не превышал окно контекста
+    fn fit_input_budget(&self, text: String) -> String {
+        let Some(budget) = self.max_input_tokens else {
+            return text;
+        };
+        match truncate_to_token_budget(&text, budget, self.input_tail_share) {
+            Some(truncated) => {
+                let original_chars = text.chars().count();
+                let kept_chars = truncated.chars().count();
+                info!(
+                    estimated_tokens = estimate_tokens(&text),
+                    budget,
+                    original_chars,
+                    kept_chars,
+                    dropped_chars = original_chars.saturating_sub(kept_chars),
+                    tail_share = self.input_tail_share,
+                    "summarize: source truncated to max_input_tokens"
+                );
+                truncated
+            }
+            None => text,
+        }
+    }
+
+    /// Takes the leading slice of the text by sample_percent.
+    /// Символобезопасное усечение (по char), чтобы не резать UTF-8 на байтах
+    fn sample(&self, body_text: &str) -> String {
         let total_chars = body_text.chars().count();
         let take_chars = (((total_chars as f32) * self.sample_percent).max(1.0)) as usize;
         let take_chars = take_chars.min(total_chars);
-        let sampled: String = body_text.chars().take(take_chars).collect();
+        body_text.chars().take(take_chars).collect()
+    }
 
-        if let Some(tpl) = &self.template {
+    /// Renders the prompt template for already sampled text.
+    fn render_prompt(
+        &self,
+        title: &str,
+        sampled: String,
+        source_url: &str,
+        meta: Option<&CrawlItem>,
+        model_limit: Option<usize>,
+        channel: ChannelPrompt<'_>,
+    ) -> String {
+        let sampled = self.fit_input_budget(sampled);
+        // limit: prefer per-call model_limit, else fallback to hard_max_chars as a coarse hint
+        let limit = model_limit.unwrap_or(self.hard_max_chars);
+        // Язык канала важнее summarizer.language
+        let explicit_language = channel.language.or(self.language.as_deref()).filter(|l| !l.trim().is_empty());
+
+        let (prompt, uses_language) = {
+            let tpl = self.template.as_deref().unwrap_or(DEFAULT_PROMPT_TEMPLATE);
             let mut tera = Tera::default();
             // Register ad-hoc template name
             let template_name = "summarizer_prompt";
             if let Err(e) = tera.add_raw_template(template_name, tpl) {
                 warn!("tera add_raw_template failed: {}", e);
             }
+            // Переменная ищется в разобранном шаблоне: слово language в тексте промпта не считается
+            let uses_language = tera
+                .get_template(template_name)
+                .is_ok_and(|t| nodes_use_variable(&t.ast, "language"));
             let mut ctx = Context::new();
+            // text/max_chars и body/limit — одни и те же значения под двумя именами
             ctx.insert("limit", &limit);
+            ctx.insert("max_chars", &limit);
             ctx.insert("title", &title);
             ctx.insert("body", &sampled);
+            ctx.insert("text", &sampled);
             ctx.insert("url", &source_url);
+            ctx.insert("style", &channel.style.unwrap_or(""));
+            ctx.insert("language", explicit_language.unwrap_or(DEFAULT_LANGUAGE));
             if let Some(m) = meta {
                 // Insert project_id and all metadata items into template context
                 ctx.insert("project_id", &m.project_id);
@@ -113,11 +337,13 @@ impl Summarizer {
                         crate::models::types::MetadataItem::CompliteNumberDepAct(v) => v,
                         crate::models::types::MetadataItem::CompliteNumberRegAct(v) => v,
                         crate::models::types::MetadataItem::ParallelStageFiles(v) => &v.join(", "),
+                        crate::models::types::MetadataItem::ParallelStageFileNames(v) => &v.join(", "),
+                        crate::models::types::MetadataItem::ParallelStageFileUrls(v) => &v.join(", "),
                     };
                     ctx.insert(&key, value);
                 }
             }
-            match tera.render(template_name, &ctx) {
+            let prompt = match tera.render(template_name, &ctx) {
                 Ok(s) => {
                     let preview_len = self.preview_chars.unwrap_or(200);
                     let preview: String = s.chars().take(preview_len).collect();
@@ -128,16 +354,37 @@ impl Summarizer {
                     warn!("tera render failed: {}", e);
                     sampled
                 }
-            }
-        } else {
-            sampled
+            };
+            (prompt, uses_language)
+        };
+
+        // Стиль канала: если шаблон не использует {{ style }}, добавляем отдельный фрагмент
+        let prompt = match channel.style.filter(|s| !s.trim().is_empty()) {
+            Some(style) if !prompt.contains(style) => format!("{}\nСтиль изложения: {}", prompt, style),
+            _ => prompt,
+        };
+        // Заданный язык: если шаблон не использует {{ language }}, добавляем отдельное указание
+        match explicit_language {
+            Some(language) if !uses_language => format!("{}\nЯзык ответа: {}", prompt, language),
+            _ => prompt,
         }
     }
 
     /// Выполняет вызов AI API с retry логикой для обработки ошибок перегрузки
     async fn call_chat_api_with_retry(&self, prompt: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        // Каждая попытка, в том числе повтор, ждет токен общего лимита; таймаут идет после токена
         let fetch_data = || async {
-            self.chat_api.call_chat_api(prompt).await
+            if let Some(limiter) = self.rate_limiter.as_ref() {
+                limiter.acquire().await;
+            }
+            match (self.stream_idle_timeout, self.call_timeout) {
+                (Some(idle_timeout), _) => self.chat_api.call_chat_api_streaming(prompt, idle_timeout).await,
+                (None, Some(call_timeout)) => match tokio::time::timeout(call_timeout, self.chat_api.call_chat_api(prompt)).await {
+                    Ok(result) => result,
+                    Err(_) => Err("summarizer timeout".into()),
+                },
+                (None, None) => self.chat_api.call_chat_api(prompt).await,
+            }
         };
 
         // Настраиваем retry стратегию
@@ -169,6 +416,22 @@ impl Summarizer {
             .await
     }
 
+    /// Sends a tiny canary prompt to check LLM credentials and connectivity.
+    /// Transient overload errors are retried as for regular calls.
+    pub async fn validate_connection(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if self.fixed_summary.is_some() {
+            info!("summarizer: test_fixed_summary set, LLM validation skipped");
+            return Ok(());
+        }
+        info!("summarizer: validating LLM connection with canary prompt");
+        let response = self.call_chat_api_with_retry("Ответь одним словом: ok").await?;
+        if response.trim().is_empty() {
+            return Err("LLM returned an empty response to the canary prompt".into());
+        }
+        info!("summarizer: LLM connection validated");
+        Ok(())
+    }
+
     pub async fn summarize(
         &self,
         title: &str,
@@ -181,9 +444,17 @@ impl Summarizer {
             body_len = body_text.len(),
             "summarize: start"
         );
+        if let Some(fixed) = self.fixed_summary.as_ref() {
+            info!("summarize: test_fixed_summary set, chat api not called");
+            return Ok(fixed.clone());
+        }
         // fallback to none: caller may prefer dedicated API using run.model_max_chars
-        let prompt = self.build_prompt(title, body_text, source_url, meta.as_ref(), None);
+        let prompt = self.build_prompt(title, body_text, source_url, meta.as_ref(), None, ChannelPrompt::default());
         debug!(prompt_len = prompt.len(), "summarize: prompt built");
+        if self.print_prompt {
+            self.emit_prompt(title, &prompt, body_text);
+            return Ok(String::new());
+        }
         info!("summarize: calling chat api");
         let text = self.call_chat_api_with_retry(&prompt).await?;
         info!(generated_len = text.len(), "summarize: chat api returned");
@@ -198,14 +469,185 @@ impl Summarizer {
         source_url: &str,
         meta: Option<CrawlItem>,
         model_limit: Option<usize>,
+        channel: ChannelPrompt<'_>,
     ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        info!(title_len = title.len(), body_len = body_text.len(), limit = ?model_limit, "summarize: start with limit");
-        let prompt = self.build_prompt(title, body_text, source_url, meta.as_ref(), model_limit);
+        info!(title_len = title.len(), body_len = body_text.len(), limit = ?model_limit, style = ?channel.style, language = ?channel.language, "summarize: start with limit");
+        if let Some(fixed) = self.fixed_summary.as_ref() {
+            info!("summarize: test_fixed_summary set, chat api not called");
+            return Ok(fixed.clone());
+        }
+        let sampled = self.sample(body_text);
+        if self.strategy == SummaryStrategy::MapReduce && !self.print_prompt && sampled.chars().count() > self.chunk_chars {
+            return self.map_reduce(title, &sampled, source_url, meta.as_ref(), model_limit, channel).await;
+        }
+        let prompt = self.render_prompt(title, sampled, source_url, meta.as_ref(), model_limit, channel);
         debug!(prompt_len = prompt.len(), "summarize: prompt built");
+        if self.print_prompt {
+            self.emit_prompt(title, &prompt, body_text);
+            return Ok(String::new());
+        }
         info!("summarize: calling chat api");
         let text = self.call_chat_api_with_retry(&prompt).await?;
         info!(generated_len = text.len(), "summarize: chat api returned");
         info!(final_len = text.len(), "summarize: done");
         Ok(text)
     }
+
+    /// Как `summarize_with_limit`, но при summarizer.fallback: truncate_source ошибка LLM
+    /// заменяется началом исходного текста, обрезанным по лимиту (по границе слова)
+    pub async fn summarize_with_fallback(
+        &self,
+        title: &str,
+        body_text: &str,
+        source_url: &str,
+        meta: Option<CrawlItem>,
+        model_limit: Option<usize>,
+        channel: ChannelPrompt<'_>,
+    ) -> Result<SummaryOutcome, Box<dyn std::error::Error + Send + Sync>> {
+        match self.summarize_with_limit(title, body_text, source_url, meta, model_limit, channel).await {
+            Ok(text) => Ok(SummaryOutcome::Generated(text)),
+            Err(e) if self.fallback == SummaryFallback::TruncateSource => {
+                let limit = model_limit.unwrap_or(self.hard_max_chars);
+                let excerpt = fit_to_limit(body_text, Some(limit), None, true);
+                error!(error = %e, limit, excerpt_len = excerpt.chars().count(), "summarize: LLM failed, falling back to truncated source");
+                Ok(SummaryOutcome::SourceExcerpt(excerpt))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Map-reduce: каждая часть документа кратко излагается отдельным вызовом LLM,
+    /// затем итоговая суммаризация строится обычным промптом по изложениям частей
+    async fn map_reduce(
+        &self,
+        title: &str,
+        sampled: &str,
+        source_url: &str,
+        meta: Option<&CrawlItem>,
+        model_limit: Option<usize>,
+        channel: ChannelPrompt<'_>,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let chunks = split_into_chunks(sampled, self.chunk_chars);
+        info!(parts = chunks.len(), chunk_chars = self.chunk_chars, "summarize: map_reduce start");
+
+        let mut tera = Tera::default();
+        tera.add_raw_template("map_prompt", MAP_PROMPT_TEMPLATE)?;
+        let mut partials = Vec::with_capacity(chunks.len());
+        for (i, chunk) in chunks.iter().enumerate() {
+            let mut ctx = Context::new();
+            ctx.insert("part", &(i + 1));
+            ctx.insert("parts", &chunks.len());
+            ctx.insert("title", &title);
+            ctx.insert("body", chunk);
+            let prompt = tera.render("map_prompt", &ctx)?;
+            let partial = self.call_chat_api_with_retry(&prompt).await?;
+            info!(part = i + 1, parts = chunks.len(), chunk_len = chunk.chars().count(), partial_len = partial.chars().count(), "summarize: map_reduce part summarized");
+            partials.push(partial.trim().to_string());
+        }
+
+        let combined = partials.join("\n\n");
+        let prompt = self.render_prompt(title, combined, source_url, meta, model_limit, channel);
+        let text = self.call_chat_api_with_retry(&prompt).await?;
+        info!(final_len = text.len(), "summarize: map_reduce done");
+        Ok(text)
+    }
+}
+
+/// Делит текст на части не длиннее `max_chars` символов, по возможности по границам абзацев
+fn split_into_chunks(text: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+    for paragraph in text.split("\n\n") {
+        let paragraph_len = paragraph.chars().count();
+        if current_len > 0 && current_len + 2 + paragraph_len > max_chars {
+            chunks.push(std::mem::take(&mut current));
+            current_len = 0;
+        }
+        if paragraph_len > max_chars {
+            // Абзац длиннее части режется по символам
+            let chars: Vec<char> = paragraph.chars().collect();
+            for piece in chars.chunks(max_chars) {
+                chunks.push(piece.iter().collect());
+            }
+            continue;
+        }
+        if current_len > 0 {
+            current.push_str("\n\n");
+            current_len += 2;
+        }
+        current.push_str(paragraph);
+        current_len += paragraph_len;
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Используется ли переменная `name` (в том числе `name.field`) где-либо в узлах шаблона Tera
+fn nodes_use_variable(nodes: &[Node], name: &str) -> bool {
+    nodes.iter().any(|node| match node {
+        Node::VariableBlock(_, expr) => expr_uses_variable(expr, name),
+        Node::Set(_, set) => expr_uses_variable(&set.value, name),
+        Node::FilterSection(_, section, _) => {
+            section.filter.args.values().any(|arg| expr_uses_variable(arg, name))
+                || nodes_use_variable(&section.body, name)
+        }
+        Node::Block(_, block, _) => nodes_use_variable(&block.body, name),
+        Node::Forloop(_, forloop, _) => {
+            expr_uses_variable(&forloop.container, name)
+                || nodes_use_variable(&forloop.body, name)
+                || forloop.empty_body.as_deref().is_some_and(|body| nodes_use_variable(body, name))
+        }
+        Node::If(cond, _) => {
+            cond.conditions
+                .iter()
+                .any(|(_, expr, body)| expr_uses_variable(expr, name) || nodes_use_variable(body, name))
+                || cond.otherwise.as_ref().is_some_and(|(_, body)| nodes_use_variable(body, name))
+        }
+        _ => false,
+    })
+}
+
+fn expr_uses_variable(expr: &Expr, name: &str) -> bool {
+    expr_val_uses_variable(&expr.val, name)
+        || expr.filters.iter().any(|f| f.args.values().any(|arg| expr_uses_variable(arg, name)))
+}
+
+fn expr_val_uses_variable(val: &ExprVal, name: &str) -> bool {
+    match val {
+        ExprVal::Ident(ident) => ident.split(['.', '[']).next() == Some(name),
+        ExprVal::Math(e) => expr_uses_variable(&e.lhs, name) || expr_uses_variable(&e.rhs, name),
+        ExprVal::Logic(e) => expr_uses_variable(&e.lhs, name) || expr_uses_variable(&e.rhs, name),
+        ExprVal::In(e) => expr_uses_variable(&e.lhs, name) || expr_uses_variable(&e.rhs, name),
+        ExprVal::Test(t) => {
+            t.ident.split(['.', '[']).next() == Some(name) || t.args.iter().any(|arg| expr_uses_variable(arg, name))
+        }
+        ExprVal::FunctionCall(f) => f.args.values().any(|arg| expr_uses_variable(arg, name)),
+        ExprVal::MacroCall(m) => m.args.values().any(|arg| expr_uses_variable(arg, name)),
+        ExprVal::Array(items) => items.iter().any(|item| expr_uses_variable(item, name)),
+        ExprVal::StringConcat(concat) => concat.values.iter().any(|v| expr_val_uses_variable(v, name)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uses_language(template: &str) -> bool {
+        let mut tera = Tera::default();
+        tera.add_raw_template("t", template).unwrap();
+        nodes_use_variable(&tera.get_template("t").unwrap().ast, "language")
+    }
+
+    #[test]
+    fn detects_language_variable_in_parsed_template() {
+        assert!(uses_language(DEFAULT_PROMPT_TEMPLATE));
+        assert!(uses_language("{% if language == \"en\" %}English{% endif %}"));
+        assert!(uses_language("{{ title | truncate(length=10) }} {{ language | upper }}"));
+        assert!(!uses_language("Ответь на языке language: {{ title }}"));
+        assert!(!uses_language("{# language #}{{ text }}"));
+    }
 }
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use tracing::{info, warn};
+
+use crate::models::channel::PublisherChannel;
+use crate::models::config::LengthGuardConfig;
+
+/// Накопленная статистика длины суммаризаций по каналу
+#[derive(Debug, Default, Clone, Copy)]
+struct ChannelLengthStats {
+    samples: usize,
+    ratio_sum: f64,
+}
+
+/// Следит за отношением длины суммаризации к лимиту канала.
+/// Систематически слишком короткие или длинные ответы модели обычно означают сломанный промпт.
+pub struct SummaryLengthGuard {
+    min_ratio: f64,
+    max_ratio: f64,
+    min_samples: usize,
+    stats: Mutex<HashMap<PublisherChannel, ChannelLengthStats>>,
+}
+
+impl SummaryLengthGuard {
+    pub fn from_config(cfg: &LengthGuardConfig) -> Self {
+        Self {
+            min_ratio: cfg.min_ratio.unwrap_or(0.3),
+            max_ratio: cfg.max_ratio.unwrap_or(1.1),
+            min_samples: cfg.min_samples.unwrap_or(3).max(1),
+            stats: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Учитывает очередную суммаризацию канала.
+    /// Возвращает среднее отношение длина/лимит, если оно вышло за `[min_ratio, max_ratio]`.
+    pub fn record(&self, channel: PublisherChannel, summary_chars: usize, limit: usize) -> Option<f64> {
+        if limit == 0 {
+            return None;
+        }
+        let ratio = summary_chars as f64 / limit as f64;
+        let mut stats = self.stats.lock().unwrap();
+        let entry = stats.entry(channel).or_default();
+        entry.samples += 1;
+        entry.ratio_sum += ratio;
+        let avg_ratio = entry.ratio_sum / entry.samples as f64;
+        info!(
+            channel = %channel,
+            summary_chars,
+            limit,
+            ratio,
+            avg_ratio,
+            samples = entry.samples,
+            "summary length stats"
+        );
+
+        if entry.samples < self.min_samples {
+            return None;
+        }
+        if avg_ratio < self.min_ratio || avg_ratio > self.max_ratio {
+            warn!(
+                channel = %channel,
+                avg_ratio,
+                min_ratio = self.min_ratio,
+                max_ratio = self.max_ratio,
+                samples = entry.samples,
+                "summary length out of expected range, check prompt_template"
+            );
+            Some(avg_ratio)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guard() -> SummaryLengthGuard {
+        SummaryLengthGuard::from_config(&LengthGuardConfig {
+            enabled: Some(true),
+            min_ratio: Some(0.5),
+            max_ratio: Some(1.0),
+            min_samples: Some(3),
+        })
+    }
+
+    #[test]
+    fn warns_on_systematically_short_summaries() {
+        let g = guard();
+        assert_eq!(g.record(PublisherChannel::Telegram, 10, 100), None);
+        assert_eq!(g.record(PublisherChannel::Telegram, 12, 100), None);
+        let avg = g.record(PublisherChannel::Telegram, 8, 100);
+        assert!(avg.is_some());
+        assert!((avg.unwrap() - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn no_warning_within_range_and_per_channel() {
+        let g = guard();
+        for _ in 0..3 {
+            assert_eq!(g.record(PublisherChannel::Mastodon, 80, 100), None);
+        }
+        // Короткие суммаризации другого канала не влияют на статистику Mastodon
+        g.record(PublisherChannel::File, 1, 100);
+        assert_eq!(g.record(PublisherChannel::Mastodon, 90, 100), None);
+    }
+}
@@ -1,44 +1,140 @@
-use std::sync::Arc;
-use tracing::{error, info};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use backon::{ExponentialBuilder, Retryable};
+use tracing::{error, info, warn};
 use tera::{Tera, Context};
 use bon::bon;
+use futures_util::StreamExt;
 use reqwest::Client;
+use tokio::sync::Semaphore;
 
-use crate::models::types::CrawlItem;
-use crate::services::documents::DocxMarkdownFetcher;
-use crate::traits::markdown_fetcher::MarkdownFetcher;
-use crate::publishers::{ConsolePublisher, FilePublisher, MastodonPublisher, RealTelegramApi};
-use crate::publishers::mastodon::{ensure_mastodon_token, load_token_from_secrets};
+use crate::models::types::{CrawlItem, DocumentValidators, MetadataItem, SummaryParsed, ThreadProgress, content_hash, is_synthetic_project_id, synthetic_project_id};
+use crate::services::documents::{detect_document_type, download_limited, fetch_file_name, file_download_url, files_base_url, DocumentFetch, DocxMarkdownFetcher};
+use crate::publishers::{ConsolePublisher, FilePublisher, MastodonPublisher, MatrixPublisher, RealTelegramApi, SlackPublisher, WebhookPublisher};
+use crate::publishers::webhook::{WebhookEvent, DEFAULT_SIGNATURE_HEADER};
+use crate::publishers::mastodon::{ensure_mastodon_token, load_token_from_secrets, media_type, MEDIA_MAX_BYTES};
+use crate::publishers::telegram::TELEGRAM_CAPTION_MAX_CHARS;
+use crate::publishers::utils::{fit_markup_to_limit, format_project_id, redact_emails, trim_on_word_boundary, trim_with_ellipsis, HttpRetryPolicy};
 use crate::traits::publisher::Publisher;
 use crate::traits::telegram_api::TelegramApi;
 use crate::traits::cache_manager::CacheManager;
-use crate::services::summarizer::Summarizer;
-use crate::models::config::AppConfig;
+use crate::services::summarizer::{ChannelPrompt, Summarizer, SummaryOutcome};
+use crate::models::config::{AppConfig, FileFormat, OnPartial, OnUnavailable, SummaryInputSource, TelegramParseMode};
 use crate::services::channels::ChannelManager;
 use crate::models::channel::PublisherChannel;
+use crate::services::summary_guard::SummaryLengthGuard;
 
-/// Trim text to at most `max_chars` characters, appending an ellipsis if trimmed.
-/// Uses char-aware slicing to avoid breaking UTF-8 sequences.
-fn trim_with_ellipsis(text: &str, max_chars: usize) -> String {
-    if max_chars == 0 { return String::new(); }
-    let count = text.chars().count();
-    if count <= max_chars { return text.to_string(); }
-    if max_chars == 1 { return "…".to_string(); }
-    let take_chars = max_chars.saturating_sub(1);
-    let mut s: String = text.chars().take(take_chars).collect();
-    s.push('…');
-    s
+/// Одновременные запросы имен файлов параллельной стадии (crawler.resolve_parallel_stage_files)
+const FILE_NAME_LOOKUPS_IN_FLIGHT: usize = 4;
+
+/// Итог публикации элемента в канал
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PublishOutcome {
+    /// Пост опубликован
+    Published,
+    /// --dry-run: пост отрендерен и записан в лог, в канал ничего не отправлено
+    DryRun,
+    /// Канал выключен или не настроен
+    SkippedDisabled,
+    /// Канал уже опубликован, пост не изменился
+    SkippedAlreadyPublished,
+    /// Публикация не удалась
+    Failed(String),
+}
+
+impl PublishOutcome {
+    pub fn is_published(&self) -> bool {
+        matches!(self, PublishOutcome::Published)
+    }
+
+    /// Пост опубликован или (в --dry-run) отрендерен вместо публикации
+    pub fn is_delivered(&self) -> bool {
+        matches!(self, PublishOutcome::Published | PublishOutcome::DryRun)
+    }
 }
 
-/// Обрабатывает элементы краулинга: суммаризация, публикация
+/// Обрабатывает элементы краулинга: суммаризация, публикация.
+///
+/// Worker можно использовать без подсистем и краулеров: собрать через `Worker::builder()`
+/// и передавать элементы в `process_item` / `process_items`. Из `AppConfig` используются:
+/// - `run.post_template` (обязателен), `run.processing_delay_secs` (по умолчанию 120 — для
+///   встраивания обычно 0), `run.cache_dir` и прочие параметры `run`;
+/// - `crawler.file_id` — для элементов с project_id, документ которых нужно скачать;
+///   без него элемент суммаризуется по `body`;
+/// - каналы: `output`, `telegram`, `mastodon`, `matrix`, `slack`, `webhook`, `channels`;
+/// - `summarizer`, `templates`, `filter`, `cache` — по необходимости.
+///
+/// Клиент Telegram и публикаторы можно передать готовыми (`telegram_api`, `mastodon_publisher`,
+/// `matrix_publisher`, `slack_publisher`, `webhook_publisher`, `file_publisher`); канал при этом
+/// должен быть включен в конфигурации.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use luminis::models::config::AppConfig;
+/// use luminis::models::types::CrawlItem;
+/// use luminis::services::cache_manager_impl::FileSystemCacheManager;
+/// use luminis::services::summarizer::Summarizer;
+/// use luminis::services::worker::Worker;
+/// use luminis::traits::chat_api::ChatApi;
+///
+/// # async fn example(config: AppConfig, chat_api: Arc<dyn ChatApi>) -> std::io::Result<()> {
+/// let summarizer = Arc::new(
+///     Summarizer::builder()
+///         .chat_api(chat_api)
+///         .hard_max_chars(600)
+///         .sample_percent(0.05)
+///         .max_retry_attempts(3)
+///         .retry_delay_secs(2)
+///         .build()
+///         .with_config(&config),
+/// );
+/// let worker = Worker::builder()
+///     .config(config)
+///     .summarizer(summarizer)
+///     .cache_manager(Arc::new(FileSystemCacheManager::builder().cache_dir("./cache".to_string()).build()))
+///     .build()
+///     .await?;
+/// let published = worker
+///     .process_item(CrawlItem {
+///         title: "Проект".to_string(),
+///         url: "https://example.org/projects/1".to_string(),
+///         body: "Текст проекта".to_string(),
+///         project_id: None,
+///         metadata: vec![],
+///         source_label: None,
+///         source_id: None,
+///     })
+///     .await?;
+/// # let _ = published;
+/// # Ok(())
+/// # }
+/// ```
 pub struct Worker {
     config: AppConfig,
     summarizer: Arc<Summarizer>,
     telegram_api: Option<Arc<dyn TelegramApi>>,
     target_chat_id: Option<i64>,
     mastodon: Option<Arc<MastodonPublisher>>,
+    /// Готовые публикаторы каналов, переданные в builder вместо создания по конфигурации
+    matrix: Option<Arc<MatrixPublisher>>,
+    slack: Option<Arc<SlackPublisher>>,
+    webhook: Option<Arc<WebhookPublisher>>,
+    file: Option<Arc<FilePublisher>>,
     cache_manager: Arc<dyn CacheManager>,
+    /// Общий HTTP-клиент скачивания документов и публикаторов (секция http)
+    client: Client,
     channel_manager: ChannelManager,
+    length_guard: Option<SummaryLengthGuard>,
+    fetch_permits: Option<Arc<Semaphore>>,
+    scan_permits: Option<Arc<Semaphore>>,
+    document_cache_dir: Option<std::path::PathBuf>,
+    /// --dry-run: посты только логируются, каналы не отмечаются опубликованными
+    dry_run: bool,
+    /// backfill: публиковать только в эти каналы, даже если элемент в них уже опубликован
+    force_channels: Vec<PublisherChannel>,
+    /// id вложений Mastodon по URL файла: повтор публикации не загружает файл заново
+    mastodon_media: Mutex<HashMap<String, String>>,
 }
 
 #[bon]
@@ -50,99 +146,122 @@ impl Worker {
         telegram_api: Option<Arc<dyn TelegramApi>>,
         target_chat_id: Option<i64>,
         cache_manager: Arc<dyn CacheManager>,
+        /// Готовый публикатор Mastodon вместо создания по config.mastodon: используется как есть,
+        /// токен и авторизация не нужны
+        mastodon_publisher: Option<Arc<MastodonPublisher>>,
+        /// Готовый публикатор Matrix вместо создания по config.matrix
+        matrix_publisher: Option<Arc<MatrixPublisher>>,
+        /// Готовый публикатор Slack вместо создания по config.slack
+        slack_publisher: Option<Arc<SlackPublisher>>,
+        /// Готовый публикатор webhook вместо создания по config.webhook
+        webhook_publisher: Option<Arc<WebhookPublisher>>,
+        /// Готовый файловый публикатор вместо output.file_path / output.file_targets
+        file_publisher: Option<Arc<FilePublisher>>,
+        /// Общий HTTP-клиент (см. `luminis::build_http_client`); без него — клиент по умолчанию
+        client: Option<Client>,
+        #[builder(default)]
+        dry_run: bool,
+        #[builder(default)]
+        force_channels: Vec<PublisherChannel>,
     ) -> std::io::Result<Self> {
+        let client = client.unwrap_or_default();
+        let channel_manager = ChannelManager::builder().config(&config).build();
+
         // Инициализация Mastodon
         // КРИТИЧЕСКИ ВАЖНО: Если Mastodon включен как канал публикации (enabled: true),
         // приложение требует успешной авторизации. При неудаче приложение завершается с ошибкой.
-        let mastodon: Option<Arc<MastodonPublisher>> = if let Some(m) = config.mastodon.as_ref().filter(|m| m.enabled) {
+        let mastodon: Option<Arc<MastodonPublisher>> = if let Some(publisher) = mastodon_publisher {
+            Some(publisher)
+        } else if let Some(m) = config.mastodon.as_ref().filter(|m| m.enabled) {
             // 1) Проверяем access_token в конфигурации
-            if !m.access_token.is_empty() {
-                Some(Arc::new(MastodonPublisher::builder()
-                    .client(Client::new())
-                    .base_url(m.base_url.clone())
-                    .access_token(m.access_token.clone())
-                    .build()))
+            let access_token = if !m.access_token.is_empty() {
+                m.access_token.clone()
             } else {
                 // 2) Пытаемся загрузить токен из файла secrets/mastodon.yaml
                 let token_path = std::path::Path::new("./secrets/mastodon.yaml");
                 match load_token_from_secrets(token_path) {
-                    Ok(Some(token)) => {
-                        Some(Arc::new(MastodonPublisher::builder()
-                            .client(Client::new())
-                            .base_url(m.base_url.clone())
-                            .access_token(token)
-                            .build()))
-                    },
-                    Ok(None) => {
-                        // Проверяем, разрешен ли CLI логин
-                        if m.login_cli.unwrap_or(false) {
-                            // CLI логин разрешен, пытаемся авторизоваться
-                            match ensure_mastodon_token(&m.base_url, token_path).await {
-                                Ok(token) => Some(Arc::new(MastodonPublisher {
-                                    client: Client::new(),
-                                    base_url: m.base_url.clone(),
-                                    access_token: token,
-                                    visibility: m.visibility.clone(),
-                                    language: m.language.clone(),
-                                    spoiler_text: m.spoiler_text.clone(),
-                                    sensitive: m.sensitive.unwrap_or(false),
-                                    max_chars: m.max_chars,
-                                })),
-                                Err(e) => { 
-                                    error!(error = %e, "mastodon login_cli failed"); 
-                                    return Err(std::io::Error::new(
-                                        std::io::ErrorKind::PermissionDenied,
-                                        format!("Критическая ошибка: не удалось авторизоваться в Mastodon. Mastodon включен как канал публикации, но авторизация не удалась: {}", e)
-                                    ));
-                                }
-                            }
-                        } else { 
-                            // КРИТИЧЕСКАЯ ОШИБКА: Mastodon включен, но токен недоступен и CLI логин отключен
-                            return Err(std::io::Error::new(
-                                std::io::ErrorKind::InvalidInput,
-                                "Критическая ошибка: Mastodon включен как канал публикации, но токен доступа недоступен. Укажите access_token в конфигурации или установите login_cli: true для интерактивной авторизации."
-                            ));
-                        }
-                    },
-                    Err(_e) => {
-                        // Проверяем, разрешен ли CLI логин
-                        if m.login_cli.unwrap_or(false) {
-                            // CLI логин разрешен, пытаемся авторизоваться
-                            match ensure_mastodon_token(&m.base_url, token_path).await {
-                                Ok(token) => Some(Arc::new(MastodonPublisher {
-                                    client: Client::new(),
-                                    base_url: m.base_url.clone(),
-                                    access_token: token,
-                                    visibility: m.visibility.clone(),
-                                    language: m.language.clone(),
-                                    spoiler_text: m.spoiler_text.clone(),
-                                    sensitive: m.sensitive.unwrap_or(false),
-                                    max_chars: m.max_chars,
-                                })),
-                                Err(e) => { 
-                                    error!(error = %e, "mastodon login_cli failed"); 
-                                    return Err(std::io::Error::new(
-                                        std::io::ErrorKind::PermissionDenied,
-                                        format!("Критическая ошибка: не удалось авторизоваться в Mastodon. Mastodon включен как канал публикации, но авторизация не удалась: {}", e)
-                                    ));
-                                }
+                    Ok(Some(token)) => token,
+                    // Проверяем, разрешен ли CLI логин
+                    Ok(None) | Err(_) if m.login_cli.unwrap_or(false) => {
+                        // CLI логин разрешен, пытаемся авторизоваться
+                        match ensure_mastodon_token(&m.base_url, token_path).await {
+                            Ok(token) => token,
+                            Err(e) => {
+                                error!(error = %e, "mastodon login_cli failed");
+                                return Err(std::io::Error::new(
+                                    std::io::ErrorKind::PermissionDenied,
+                                    format!("Критическая ошибка: не удалось авторизоваться в Mastodon. Mastodon включен как канал публикации, но авторизация не удалась: {}", e)
+                                ));
                             }
-                        } else { 
-                            // КРИТИЧЕСКАЯ ОШИБКА: Mastodon включен, но токен недоступен и CLI логин отключен
-                            return Err(std::io::Error::new(
-                                std::io::ErrorKind::InvalidInput,
-                                "Критическая ошибка: Mastodon включен как канал публикации, но токен доступа недоступен. Укажите access_token в конфигурации или установите login_cli: true для интерактивной авторизации."
-                            ));
                         }
                     }
+                    Ok(None) | Err(_) => {
+                        // КРИТИЧЕСКАЯ ОШИБКА: Mastodon включен, но токен недоступен и CLI логин отключен
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            "Критическая ошибка: Mastodon включен как канал публикации, но токен доступа недоступен. Укажите access_token в конфигурации или установите login_cli: true для интерактивной авторизации."
+                        ));
+                    }
                 }
-            }
+            };
+            Some(Arc::new(
+                MastodonPublisher::builder()
+                    .client(client.clone())
+                    .base_url(m.base_url.clone())
+                    .access_token(access_token)
+                    .maybe_visibility(m.visibility.clone())
+                    // Без mastodon.language язык статуса совпадает с языком суммаризации канала
+                    .maybe_language(
+                        m.language.clone()
+                            .or_else(|| channel_manager.get_channel_language(PublisherChannel::Mastodon).map(str::to_string)),
+                    )
+                    .maybe_spoiler_text(m.spoiler_text.clone())
+                    .sensitive(m.sensitive.unwrap_or(false))
+                    .maybe_max_chars(channel_manager.get_channel_limit(PublisherChannel::Mastodon))
+                    .api_flavor(m.api_flavor.unwrap_or_default())
+                    .maybe_link_chars(m.effective_link_chars())
+                    .trim_on_word_boundary(config.run.as_ref().and_then(|r| r.trim_on_word_boundary).unwrap_or(false))
+                    .thread_long_posts(m.thread_long_posts.unwrap_or(false))
+                    .retry(HttpRetryPolicy::from_config(config.run.as_ref().and_then(|r| r.publish_retry.as_ref())))
+                    .build(),
+            ))
         } else { 
             // Mastodon отключен - это нормально
             None 
         };
 
-        let channel_manager = ChannelManager::builder().config(&config).build();
+        let on_unavailable = config.summarizer.as_ref().and_then(|s| s.on_unavailable).unwrap_or_default();
+        if on_unavailable == OnUnavailable::FallbackTemplate
+            && config.templates.as_ref().and_then(|t| t.no_summary_post.as_ref()).is_none()
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "summarizer.on_unavailable: fallback_template requires templates.no_summary_post",
+            ));
+        }
+
+
+        let length_guard = config.summarizer.as_ref()
+            .and_then(|s| s.length_guard.as_ref())
+            .filter(|g| g.enabled.unwrap_or(true))
+            .map(SummaryLengthGuard::from_config);
+
+        // crawler.fetch_concurrency: лимит одновременных скачиваний документов
+        let fetch_permits = config.crawler.fetch_concurrency
+            .map(|n| Arc::new(Semaphore::new(n.max(1))));
+        // crawler.scan_concurrency: лимит одновременных запросов stages (fileId)
+        let scan_permits = config.crawler.scan_concurrency
+            .map(|n| Arc::new(Semaphore::new(n.max(1))));
+        // cache.share_documents: общий кэш документов по fileId в cache_dir/documents
+        let document_cache_dir = config.cache.as_ref()
+            .and_then(|c| c.share_documents)
+            .filter(|enabled| *enabled)
+            .map(|_| {
+                let cache_dir = config.run.as_ref()
+                    .and_then(|r| r.cache_dir.clone())
+                    .unwrap_or_else(|| "./cache".to_string());
+                std::path::PathBuf::from(cache_dir).join("documents")
+            });
 
         Ok(Self {
             config,
@@ -150,8 +269,20 @@ impl Worker {
             telegram_api,
             target_chat_id,
             mastodon,
+            matrix: matrix_publisher,
+            slack: slack_publisher,
+            webhook: webhook_publisher,
+            file: file_publisher,
             cache_manager,
+            client,
             channel_manager,
+            length_guard,
+            fetch_permits,
+            scan_permits,
+            document_cache_dir,
+            dry_run,
+            force_channels,
+            mastodon_media: Mutex::new(HashMap::new()),
         })
     }
 
@@ -163,8 +294,148 @@ impl Worker {
             .collect()
     }
 
-    /// Обрабатывает один элемент
+    /// Причина, по которой элемент отбрасывается фильтрами (run.filters, filter.*), или None
+    fn filter_reason(&self, item: &CrawlItem) -> Option<String> {
+        if let Some(reason) = self
+            .config
+            .run
+            .as_ref()
+            .and_then(|r| r.filters.as_ref())
+            .and_then(|f| f.reject_reason(&item.metadata))
+        {
+            return Some(reason);
+        }
+        let max_age_days = self.config.filter.as_ref().and_then(|f| f.max_age_days)?;
+        let publish_date = item.publish_date()?;
+        let age = chrono::Utc::now().signed_duration_since(publish_date);
+        if age > chrono::Duration::days(max_age_days as i64) {
+            Some(format!("older than {} days (published {})", max_age_days, publish_date.to_rfc3339()))
+        } else {
+            None
+        }
+    }
+
+    /// Записывает элемент в кэш как пропущенный и сдвигает min_published_project_id его источника,
+    /// чтобы краулер двигался дальше; ошибки только логируются
+    async fn record_skipped(&self, pid: &str, reason: &str, item: &CrawlItem) {
+        if self.dry_run {
+            info!(project_id = %pid, %reason, "dry-run: skipped item not recorded");
+            return;
+        }
+        if let Err(e) = self.cache_manager.mark_skipped(pid, reason, &item.metadata).await {
+            error!(project_id = %pid, error = %e, "failed to record skipped item");
+        }
+        self.update_source_min_id(pid, item).await;
+    }
+
+    /// Сдвигает min_published_project_id источника элемента (`CrawlItem::source_id`) в manifest
+    async fn update_source_min_id(&self, pid: &str, item: &CrawlItem) {
+        let (Some(source_id), Ok(pid_num)) = (item.source_id.as_deref(), pid.parse::<u32>()) else {
+            return;
+        };
+        match self.cache_manager.update_min_published_project_id(source_id, pid_num).await {
+            Ok(()) => info!(project_id = %pid, source = %source_id, min_id = pid_num, "updated min_published_project_id in manifest"),
+            Err(e) => error!(project_id = %pid, source = %source_id, error = %e, "failed to update min_published_project_id in manifest"),
+        }
+    }
+
+    /// Обрабатывает элементы по порядку; возвращает число опубликованных элементов.
+    /// Ошибка элемента прерывает обработку
+    pub async fn process_items(&self, items: impl IntoIterator<Item = CrawlItem>) -> std::io::Result<usize> {
+        let mut published = 0;
+        for item in items {
+            published += self.process_item(item).await?;
+        }
+        Ok(published)
+    }
+
+    /// Обрабатывает один элемент (в том числе при встраивании); возвращает 1, если элемент
+    /// опубликован хотя бы в один канал (в --dry-run — отрендерен), иначе 0
     pub async fn process_item(&self, item: CrawlItem) -> std::io::Result<usize> {
+        // Элементы без project_id при run.require_project_id: false получают синтетический id по URL
+        let require_project_id = self.config.run.as_ref().and_then(|r| r.require_project_id).unwrap_or(true);
+        let item = if item.project_id.is_none() && !require_project_id && !item.url.is_empty() {
+            let synthetic_id = synthetic_project_id(&item.url);
+            info!(url = %item.url, project_id = %synthetic_id, "worker: item without project_id, using synthetic id");
+            CrawlItem { project_id: Some(synthetic_id), ..item }
+        } else {
+            item
+        };
+
+        // Отфильтрованный элемент фиксируем в кэше как пропущенный, чтобы краулер двигался дальше.
+        // Элемент backfill запрошен явно и фильтрами не отбрасывается
+        if let Some(reason) = self.filter_reason(&item).filter(|_| self.force_channels.is_empty()) {
+            if let Some(pid) = item.project_id.as_deref() {
+                info!(project_id = %pid, %reason, "worker: item filtered out, recording as skipped");
+                self.record_skipped(pid, &reason, &item).await;
+            }
+            return Ok(0);
+        }
+
+        // Маркер in_progress в кэше: элемент, который обрабатывает другой экземпляр, пропускаем
+        let claim_ttl = self.in_progress_ttl();
+        let claimed_pid = match (item.project_id.clone(), claim_ttl) {
+            (Some(pid), Some(ttl)) => match self.cache_manager.try_claim(&pid, ttl).await {
+                Ok(true) => Some(pid),
+                Ok(false) => {
+                    info!(project_id = %pid, "worker: item is being processed by another instance, skipping");
+                    return Ok(0);
+                }
+                Err(e) => {
+                    warn!(project_id = %pid, error = %e, "failed to set in_progress marker, processing anyway");
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        let result = match self.config.run.as_ref().and_then(|r| r.item_timeout_secs).filter(|s| *s > 0) {
+            Some(secs) => {
+                let timed_out = item.clone();
+                match tokio::time::timeout(Duration::from_secs(secs), self.process_claimed_item(item)).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        // Зависший элемент не блокирует следующие и повторяется в следующем цикле;
+                        // после run.item_timeout_max_attempts таймаутов он записывается как пропущенный
+                        let max_attempts = self.config.run.as_ref().and_then(|r| r.item_timeout_max_attempts).unwrap_or(3).max(1);
+                        if let Some(pid) = timed_out.project_id.as_deref().filter(|_| !self.dry_run) {
+                            match self.cache_manager.record_timeout(pid, &timed_out.metadata).await {
+                                Ok(attempts) if attempts >= max_attempts => {
+                                    let reason = format!("item timeout after {} secs, {} attempts", secs, attempts);
+                                    warn!(project_id = %pid, url = %timed_out.url, secs, attempts, "worker: item processing timed out too many times, recording as skipped");
+                                    self.record_skipped(pid, &reason, &timed_out).await;
+                                }
+                                Ok(attempts) => {
+                                    warn!(project_id = %pid, url = %timed_out.url, secs, attempts, max_attempts, "worker: item processing timed out, will retry on the next cycle");
+                                }
+                                Err(e) => error!(project_id = %pid, error = %e, "failed to record timed out item"),
+                            }
+                        } else {
+                            warn!(url = %timed_out.url, secs, "worker: item processing timed out");
+                        }
+                        Ok(0)
+                    }
+                }
+            }
+            None => self.process_claimed_item(item).await,
+        };
+
+        if let Some(pid) = claimed_pid {
+            if let Err(e) = self.cache_manager.release_claim(&pid).await {
+                warn!(project_id = %pid, error = %e, "failed to remove in_progress marker");
+            }
+        }
+        result
+    }
+
+    /// TTL маркера in_progress (cache.in_progress_ttl_secs, 0 — маркеры отключены)
+    fn in_progress_ttl(&self) -> Option<Duration> {
+        let secs = self.config.cache.as_ref().and_then(|c| c.in_progress_ttl_secs).unwrap_or(1800);
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+
+    /// Обрабатывает элемент, закрепленный за этим экземпляром
+    async fn process_claimed_item(&self, item: CrawlItem) -> std::io::Result<usize> {
         // Задержка перед обработкой элемента (для контроля скорости обработки)
         let processing_delay_secs = self.config.run.as_ref().and_then(|r| r.processing_delay_secs).unwrap_or(120);
         if processing_delay_secs > 0 {
@@ -174,6 +445,7 @@ impl Worker {
             );
             tokio::time::sleep(std::time::Duration::from_secs(processing_delay_secs)).await;
         }
+        let item = self.enrich_parallel_stage_files(item).await;
         
         let title = if item.title.is_empty() {
             "Обновление".to_string()
@@ -187,6 +459,14 @@ impl Worker {
             // Поэтапная проверка кэша согласно схеме
             let published_names = if let Some(pid) = project_id.as_ref() {
                 info!(%url, %title, project_id = %pid, "worker: processing item");
+
+                // Изменилась только стадия уже опубликованного проекта: короткий пост-обновление
+                if let Some(update_tpl) = self.config.templates.as_ref().and_then(|t| t.update_post.as_deref()) {
+                    if self.force_channels.is_empty() && self.is_stage_update(pid, &item).await {
+                        let published = self.process_stage_update(pid, &item, update_tpl).await?;
+                        return Ok(if published { 1 } else { 0 });
+                    }
+                }
                 
                 // Этап 1: Проверяем наличие данных (docx/markdown)
                 let (markdown_text, docx_bytes) = match self.cache_manager.has_data(pid).await {
@@ -218,15 +498,27 @@ impl Worker {
                 };
 
                 // Если данных нет в кэше, скачиваем их
-                let (final_markdown, final_docx_bytes) = if markdown_text.is_empty() {
+                let (final_markdown, final_docx_bytes) = if markdown_text.is_empty() && is_synthetic_project_id(pid) {
+                    // Для синтетического id документа в источнике нет: используем текст элемента
+                    info!(project_id = %pid, "synthetic project id: using item body as source text");
+                    if let Err(e) = self.cache_manager.save_artifacts(
+                        pid,
+                        None,
+                        &item.body,
+                        "",
+                        "",
+                        &[],
+                        &item.metadata
+                    ).await {
+                        error!(project_id = %pid, error = %e, "failed to save artifacts to cache");
+                    }
+                    (item.body.clone(), None)
+                } else if markdown_text.is_empty() {
                     info!(project_id = %pid, "fetching markdown from source");
-                    let file_id_tpl = self.config.crawler.file_id.as_ref().map(|f| f.url.clone());
-                    let fetcher = DocxMarkdownFetcher::builder().maybe_file_id_url_template(file_id_tpl).build();
-                    
-                    match fetcher.fetch_markdown(pid).await {
-                        Ok(Some((bytes, text))) => {
+                    match self.fetch_document_with_retry(pid, None).await {
+                        Ok(DocumentFetch::Fetched { bytes, markdown: text, validators }) => {
                             // Сохраняем данные в кэш
-                            let _ = self.cache_manager.save_artifacts(
+                            if let Err(e) = self.cache_manager.save_artifacts(
                                 pid,
                                 Some(&bytes),
                                 &text,
@@ -234,10 +526,15 @@ impl Worker {
                                 "",
                                 &[],
                                 &item.metadata
-                            ).await;
+                            ).await {
+                                error!(project_id = %pid, error = %e, "failed to save artifacts to cache");
+                            }
+                            if let Err(e) = self.cache_manager.update_document_state(pid, Some(&validators), &[]).await {
+                                error!(project_id = %pid, error = %e, "failed to save document validators to cache");
+                            }
                             (text, Some(bytes))
                         }
-                        Ok(None) => {
+                        Ok(DocumentFetch::Missing | DocumentFetch::Unchanged) => {
                             info!(project_id = %pid, "no fileId found, skipping");
                             return Ok(0);
                         }
@@ -251,6 +548,37 @@ impl Worker {
                     (markdown_text, docx_bytes.clone())
                 };
 
+                // Заголовок из источника нужен backfill: в stages его нет
+                if !item.title.is_empty() {
+                    if let Err(e) = self.cache_manager.record_title(pid, &item.title).await {
+                        error!(project_id = %pid, error = %e, "failed to save item title to cache");
+                    }
+                }
+
+                // run.keyword_filter: документ без нужных ключевых слов не отправляется в LLM
+                if let Some(keyword_filter) = self.config.run.as_ref().and_then(|r| r.keyword_filter.as_ref()) {
+                    if let Some(reason) = keyword_filter.reject_reason(&final_markdown) {
+                        if keyword_filter.mark_skipped.unwrap_or(true) {
+                            info!(project_id = %pid, %reason, "worker: item rejected by keyword filter, recording as skipped");
+                            self.record_skipped(pid, &reason, &item).await;
+                        } else {
+                            info!(project_id = %pid, %reason, "worker: item rejected by keyword filter");
+                        }
+                        return Ok(0);
+                    }
+                }
+
+                // Режим --print-prompt: печатаем промпты каналов и ничего не публикуем
+                if self.summarizer.prints_prompt() {
+                    for channel in self.get_enabled_publisher_channels() {
+                        let channel_limit = self.channel_manager.channel_limit_or_default(channel);
+                        info!(project_id = %pid, channel = %channel, limit = channel_limit, "print-prompt: rendering channel prompt");
+                        let prompt = self.channel_manager.channel_prompt(channel);
+                        self.summarize_text(&title, &url, &final_markdown, &item, Some(channel_limit), prompt).await?;
+                    }
+                    return Ok(1);
+                }
+
                 // Этап 2: Проверяем наличие суммаризации
                 let summary_text = match self.cache_manager.has_summary(pid).await {
                     Ok(true) => {
@@ -280,10 +608,19 @@ impl Worker {
                 // Если суммаризации нет в кэше, генерируем её
                 let _final_summary = if summary_text.is_empty() {
                     info!(project_id = %pid, "generating summary");
-                    let generated_summary = self.summarize_text(&title, &url, &final_markdown, &item, None).await?;
+                    let generated_summary = match self.summarize_text(&title, &url, &final_markdown, &item, None, ChannelPrompt::default()).await {
+                        // Отрывок исходника не выдается за суммаризацию
+                        Ok(outcome) if outcome.is_source_excerpt() => String::new(),
+                        Ok(outcome) => outcome.into_text(),
+                        Err(e) if self.no_summary_template().is_some() => {
+                            warn!(project_id = %pid, error = %e, "summarizer unavailable, continuing with fallback template");
+                            String::new()
+                        }
+                        Err(e) => return Err(e),
+                    };
                     
                     // Сохраняем суммаризацию в кэш
-                    let _ = self.cache_manager.save_artifacts(
+                    if let Err(e) = self.cache_manager.save_artifacts(
                         pid,
                         final_docx_bytes.as_deref(),
                         &final_markdown,
@@ -291,7 +628,9 @@ impl Worker {
                         "",
                         &[],
                         &item.metadata
-                    ).await;
+                    ).await {
+                        error!(project_id = %pid, error = %e, "failed to save artifacts to cache");
+                    }
                     
                     generated_summary
                 } else {
@@ -299,17 +638,115 @@ impl Worker {
                 };
 
                 // Этап 3: Обрабатываем каждый канал отдельно
-                let published_names = self.process_item_for_channels(pid, &title, &url, &final_markdown, &item, final_docx_bytes.as_deref()).await?;
-                
+                let outcomes = self.process_item_for_channels(pid, &title, &url, &final_markdown, &item, final_docx_bytes.as_deref()).await?;
+                let published_names: Vec<String> = outcomes
+                    .iter()
+                    .filter(|(_, outcome)| outcome.is_delivered())
+                    .map(|(channel, _)| channel.as_str().to_string())
+                    .collect();
+                if !published_names.is_empty() && !self.dry_run {
+                    self.notify_published(pid, &item, &published_names).await;
+                }
+
                 published_names
             } else {
-                error!("project_id not found in url, skipping item");
+                error!(url = %url, "project_id not found in url, skipping item (see run.require_project_id)");
                 return Ok(0);
             };
         
         Ok(if !published_names.is_empty() { 1 } else { 0 })
     }
 
+    /// Отправляет POST с JSON о публикации на run.on_published_webhook.
+    /// Ошибки только логируются: элемент уже опубликован и не должен считаться неудачным
+    async fn notify_published(&self, project_id: &str, item: &CrawlItem, channels: &[String]) {
+        let Some(webhook) = self.config.run.as_ref().and_then(|r| r.on_published_webhook.as_deref()) else {
+            return;
+        };
+        let payload = serde_json::json!({
+            "project_id": project_id,
+            "title": item.title,
+            "url": item.url,
+            "channels": channels,
+        });
+        let result = self.client
+            .post(webhook)
+            .timeout(Duration::from_secs(10))
+            .json(&payload)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status());
+        match result {
+            Ok(_) => info!(project_id = %project_id, webhook = %webhook, "on_published_webhook notified"),
+            Err(e) => warn!(project_id = %project_id, webhook = %webhook, error = %e, "on_published_webhook failed"),
+        }
+    }
+
+    /// Шаблон поста без суммаризации, если включен summarizer.on_unavailable: fallback_template
+    fn no_summary_template(&self) -> Option<&str> {
+        let on_unavailable = self.config.summarizer.as_ref().and_then(|s| s.on_unavailable).unwrap_or_default();
+        if on_unavailable != OnUnavailable::FallbackTemplate {
+            return None;
+        }
+        self.config.templates.as_ref().and_then(|t| t.no_summary_post.as_deref())
+    }
+
+    /// Скачивает документ проекта с повторами при ошибках (crawler.file_max_retry_attempts)
+    async fn fetch_document_with_retry(
+        &self,
+        project_id: &str,
+        cached: Option<&DocumentValidators>,
+    ) -> Result<DocumentFetch, Box<dyn std::error::Error + Send + Sync>> {
+        let file_id_cfg = self.config.crawler.file_id.as_ref();
+        let file_id_timeout_secs = file_id_cfg
+            .and_then(|f| f.timeout_secs)
+            .or(self.config.crawler.request_timeout_secs);
+        let fetcher = DocxMarkdownFetcher::builder()
+            .client(self.client.clone())
+            .maybe_file_id_url_template(file_id_cfg.map(|f| f.url.clone()))
+            .verify_checksum(self.config.crawler.verify_checksum.unwrap_or(false))
+            .maybe_file_id_timeout(file_id_timeout_secs.map(Duration::from_secs))
+            .file_id_max_retry_attempts(file_id_cfg.and_then(|f| f.max_retry_attempts).unwrap_or(2))
+            .head_before_get(self.config.crawler.head_before_get.unwrap_or(false))
+            .maybe_fetch_permits(self.fetch_permits.clone())
+            .maybe_scan_permits(self.scan_permits.clone())
+            .maybe_document_cache_dir(self.document_cache_dir.clone())
+            .maybe_document_type(self.config.crawler.force_document_type)
+            .build();
+        let max_retry_attempts = self.config.crawler.file_max_retry_attempts.unwrap_or(0);
+
+        let builder = ExponentialBuilder::default()
+            .with_max_times(max_retry_attempts as usize)
+            .with_min_delay(Duration::from_millis(500));
+
+        (|| fetcher.fetch_document(project_id, cached))
+            .retry(builder)
+            .sleep(tokio::time::sleep)
+            .notify(|err: &Box<dyn std::error::Error + Send + Sync>, dur: Duration| {
+                info!(
+                    project_id = %project_id,
+                    "Retrying document fetch after {:?} due to error: {}",
+                    dur,
+                    err
+                );
+            })
+            .await
+    }
+
+    /// Текст для LLM по summarizer.input_source: документ, поля элемента или оба
+    fn summary_input(&self, document: &str, item: &CrawlItem) -> String {
+        let source = self.config.summarizer.as_ref()
+            .and_then(|s| s.input_source)
+            .unwrap_or_default();
+        match source {
+            SummaryInputSource::Document => document.to_string(),
+            SummaryInputSource::Body => item.body.clone(),
+            // У синтетических id документом уже служит body: не дублируем
+            SummaryInputSource::Both if document.trim().is_empty() || document == item.body => item.body.clone(),
+            SummaryInputSource::Both => format!("{}\n\n{}", item.body, document),
+        }
+    }
+
     /// Суммаризирует текст
     async fn summarize_text(
         &self,
@@ -318,7 +755,8 @@ impl Worker {
         text: &str,
         item: &CrawlItem,
         channel_limit: Option<usize>,
-    ) -> std::io::Result<String> {
+        channel: ChannelPrompt<'_>,
+    ) -> std::io::Result<SummaryOutcome> {
         // throttle LLM calls using crawler.poll_delay_secs
         let llm_delay = self.config.crawler.poll_delay_secs.unwrap_or(0);
         if llm_delay > 0 { 
@@ -332,31 +770,52 @@ impl Worker {
         // Используем лимит канала, если указан, иначе fallback на post_max_chars
         let model_limit = channel_limit.or_else(|| self.config.run.as_ref().and_then(|r| r.post_max_chars));
         let summarizer_arc = self.summarizer.clone();
+        let input = self.summary_input(text, item);
+        let input = input.as_str();
         
-        match tokio::time::timeout(
-            std::time::Duration::from_secs(
-                self.config.run.as_ref()
-                    .and_then(|r| r.summarization_timeout_secs)
-                    .unwrap_or(120)
-            ),
-            async move { 
-                summarizer_arc.summarize_with_limit(title, text, url, Some(item.clone()), model_limit).await 
-            }
-        ).await {
-            Ok(Ok(s)) => {
-                // Раннее сохранение summary до публикации
-                if let Some(pid) = item.project_id.as_ref() {
-                    let _ = self.cache_manager.save_artifacts(
+        let timeout = std::time::Duration::from_secs(
+            self.config.run.as_ref()
+                .and_then(|r| r.summarization_timeout_secs)
+                .unwrap_or(120)
+        );
+        let times_out_calls = summarizer_arc.times_out_calls();
+        let summarize = async move { 
+            summarizer_arc.summarize_with_fallback(title, input, url, Some(item.clone()), model_limit, channel).await 
+        };
+        // summarizer.stream и summarizer.requests_per_minute: таймаут действует внутри вызова LLM
+        // (для лимита — после получения токена), общий не нужен
+        let result = if times_out_calls {
+            Ok(summarize.await)
+        } else {
+            tokio::time::timeout(timeout, summarize).await
+        };
+        match result {
+            Ok(Ok(outcome)) => {
+                if let SummaryOutcome::SourceExcerpt(excerpt) = &outcome {
+                    warn!(
+                        project_id = ?item.project_id,
+                        limit = ?model_limit,
+                        excerpt_len = excerpt.chars().count(),
+                        degraded = true,
+                        "worker: LLM unavailable, post uses truncated source instead of summary"
+                    );
+                }
+                // Раннее сохранение summary до публикации; отрывок исходника не сохраняется,
+                // чтобы после восстановления LLM элемент суммаризировался заново
+                if let (Some(pid), SummaryOutcome::Generated(s)) = (item.project_id.as_ref(), &outcome) {
+                    if let Err(e) = self.cache_manager.save_artifacts(
                         pid,
                         None,
                         text,
-                        &s,
+                        s,
                         "",
                         &[],
                         &item.metadata
-                    ).await;
+                    ).await {
+                        error!(project_id = %pid, error = %e, "failed to save artifacts to cache");
+                    }
                 }
-                Ok(s)
+                Ok(outcome)
             },
             Ok(Err(e)) => {
                 error!(%e, "summarizer failed");
@@ -370,27 +829,191 @@ impl Worker {
     }
 
 
-    /// Строит пост из шаблона
-    fn build_post(&self, item: &CrawlItem, summary: &str) -> Result<String, std::io::Error> {
+    /// Обрезать посты по границе слова (run.trim_on_word_boundary)
+    fn trims_on_word_boundary(&self) -> bool {
+        self.config.run.as_ref().and_then(|r| r.trim_on_word_boundary).unwrap_or(false)
+    }
+
+    /// Повторы HTTP-запросов публикации (run.publish_retry)
+    fn publish_retry_policy(&self) -> HttpRetryPolicy {
+        HttpRetryPolicy::from_config(self.config.run.as_ref().and_then(|r| r.publish_retry.as_ref()))
+    }
+
+    /// Дополняет fileId параллельной стадии именами файлов и URL скачивания
+    /// (crawler.resolve_parallel_stage_files). Имена, найденные при прошлой обработке проекта,
+    /// берутся из метаданных краулера в кэше; остальные запрашиваются одновременно, не больше
+    /// FILE_NAME_LOOKUPS_IN_FLIGHT запросов. Неудачный запрос оставляет вместо имени fileId
+    async fn enrich_parallel_stage_files(&self, mut item: CrawlItem) -> CrawlItem {
+        if !self.config.crawler.resolve_parallel_stage_files.unwrap_or(false)
+            || item.metadata.iter().any(|m| matches!(m, MetadataItem::ParallelStageFileNames(_)))
+        {
+            return item;
+        }
+        let Some(files) = item.metadata.iter().find_map(|m| match m {
+            MetadataItem::ParallelStageFiles(v) if !v.is_empty() => Some(v.clone()),
+            _ => None,
+        }) else {
+            return item;
+        };
+        let base = self.config.crawler.file_id.as_ref().and_then(|f| files_base_url(&f.url));
+        let timeout = Duration::from_secs(self.config.crawler.request_timeout_secs.unwrap_or(30));
+
+        let cached = match item.project_id.as_deref() {
+            Some(pid) => match self.cache_manager.load_metadata(pid).await {
+                Ok(Some(meta)) => cached_file_names(&meta.crawl_metadata),
+                _ => HashMap::new(),
+            },
+            None => HashMap::new(),
+        };
+
+        let urls: Vec<String> = files.iter().map(|file| file_download_url(base.as_deref(), file)).collect();
+        let names: Vec<String> = futures_util::stream::iter(files.iter().zip(&urls))
+            .map(|(file, url)| {
+                let cached = cached.get(file).cloned();
+                async move {
+                    if let Some(name) = cached {
+                        return name;
+                    }
+                    match fetch_file_name(&self.client, url, timeout).await {
+                        Ok(Some(name)) => name,
+                        Ok(None) => {
+                            warn!(url = %url, "parallel stage file: no file name in response, keeping fileId");
+                            file.clone()
+                        }
+                        Err(e) => {
+                            warn!(url = %url, error = %e, "parallel stage file: lookup failed, keeping fileId");
+                            file.clone()
+                        }
+                    }
+                }
+            })
+            .buffered(FILE_NAME_LOOKUPS_IN_FLIGHT)
+            .collect()
+            .await;
+        item.metadata.push(MetadataItem::ParallelStageFileNames(names));
+        item.metadata.push(MetadataItem::ParallelStageFileUrls(urls));
+        item
+    }
+
+    /// Скачивает первый файл проекта из метаданных и загружает его во вложения Mastodon
+    /// (mastodon.attach_files). Возвращает URL файла и id вложения; id запоминается до успешной
+    /// публикации, поэтому повтор не загружает файл заново. Файл, который Mastodon не примет
+    /// (не изображение, видео или аудио) или больше MEDIA_MAX_BYTES, не прикрепляется.
+    /// Ошибка не мешает публикации: пост уходит без вложения
+    async fn mastodon_attachment(&self, publisher: &MastodonPublisher, item: &CrawlItem) -> Option<(String, String)> {
+        let file = item.metadata.iter().find_map(|m| match m {
+            MetadataItem::ParallelStageFiles(v) => v.first(),
+            _ => None,
+        })?;
+        let base = self.config.crawler.file_id.as_ref().and_then(|f| files_base_url(&f.url));
+        let url = file_download_url(base.as_deref(), file);
+        if let Some(id) = self.mastodon_media.lock().unwrap().get(&url) {
+            info!(url = %url, media_id = %id, "mastodon: reusing uploaded attachment");
+            return Some((url, id.clone()));
+        }
+        let (content_type, bytes) = match download_limited(&publisher.client, &url, MEDIA_MAX_BYTES).await {
+            Ok(Some(downloaded)) => downloaded,
+            Ok(None) => {
+                warn!(url = %url, max_bytes = MEDIA_MAX_BYTES, "mastodon: attachment is too large, posting without it");
+                return None;
+            }
+            Err(e) => {
+                warn!(url = %url, error = %e, "mastodon: failed to download attachment, posting without it");
+                return None;
+            }
+        };
+        let Some(mime_type) = media_type(content_type.as_deref(), &bytes) else {
+            info!(url = %url, content_type = ?content_type, "mastodon: attachment is not an image, video or audio, posting without it");
+            return None;
+        };
+        match publisher.upload_media(file, &mime_type, bytes).await {
+            Ok(id) => {
+                self.mastodon_media.lock().unwrap().insert(url.clone(), id.clone());
+                Some((url, id))
+            }
+            Err(e) => {
+                warn!(file = %file, error = %e, "mastodon: media upload failed, posting without attachment");
+                None
+            }
+        }
+    }
+
+    /// Строит пост канала из шаблона channels.<name>.post_template, иначе из run.post_template
+    fn build_post(&self, channel: PublisherChannel, item: &CrawlItem, summary: &str) -> Result<String, std::io::Error> {
+        let parse_mode = self.post_parse_mode(channel);
+        if let Some(tpl) = self.channel_manager.get_channel_post_template(channel) {
+            return self.render_post(&format!("channels.{}.post_template", channel.as_str()), tpl, item, summary, parse_mode);
+        }
         let tpl = self.config.run.as_ref()
             .and_then(|r| r.post_template.as_ref())
             .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::Other, "run.post_template missing"))?;
-        
+        self.render_post("post_template", tpl, item, summary, parse_mode)
+    }
+
+    /// telegram.parse_mode (по умолчанию без разметки)
+    fn telegram_parse_mode(&self) -> TelegramParseMode {
+        self.config.telegram.as_ref().and_then(|t| t.parse_mode).unwrap_or_default()
+    }
+
+    /// Разметка поста канала: в Telegram с parse_mode шаблон является разметкой,
+    /// и подставляемые в него значения экранируются; остальные каналы — простой текст
+    fn post_parse_mode(&self, channel: PublisherChannel) -> TelegramParseMode {
+        if channel == PublisherChannel::Telegram { self.telegram_parse_mode() } else { TelegramParseMode::None }
+    }
+
+    /// Проверяет, доступно ли поле метаданных в шаблонах постов
+    fn metadata_field_allowed(&self, key: &str) -> bool {
+        let Some(templates) = self.config.templates.as_ref() else {
+            return true;
+        };
+        let allowed = templates
+            .metadata_allow
+            .as_ref()
+            .is_none_or(|allow| allow.iter().any(|k| k == key));
+        let denied = templates
+            .metadata_deny
+            .as_ref()
+            .is_some_and(|deny| deny.iter().any(|k| k == key));
+        allowed && !denied
+    }
+
+    /// Рендерит Tera-шаблон поста с данными элемента и обрезает до run.post_max_chars
+    fn render_post(&self, tpl_name: &str, tpl: &str, item: &CrawlItem, summary: &str, parse_mode: TelegramParseMode) -> Result<String, std::io::Error> {
         let mut tera = Tera::default();
         tera.add_raw_template("post_tpl", tpl)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("invalid post_template: {}", e)))?;
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("invalid {}: {}", tpl_name, e)))?;
         
         let mut ctx = Context::new();
         
         // Базовые поля
-        ctx.insert("title", &item.title);
-        ctx.insert("url", &item.url);
-        ctx.insert("summary", summary);
-        ctx.insert("project_id", &item.project_id);
+        let esc = |text: &str| parse_mode.escape(text);
+        ctx.insert("title", &esc(&item.title));
+        ctx.insert("url", &esc(&item.url));
+        ctx.insert("summary", &esc(summary));
+        ctx.insert("project_id", &item.project_id.as_deref().map(esc));
+        ctx.insert("source_label", &esc(item.source_label.as_deref().unwrap_or("")));
+        // Оценки блока «Рейтинг» и текст суммаризации без него; ненайденная оценка — null
+        let parsed = SummaryParsed::parse(summary);
+        ctx.insert("summary_body", &esc(&parsed.summary));
+        ctx.insert("usefulness", &parsed.ratings.usefulness);
+        ctx.insert("repressiveness", &parsed.ratings.repressiveness);
+        ctx.insert("corruption", &parsed.ratings.corruption);
+        // Числовая форма для фильтров и арифметики Tera и форматированная по templates.project_id_format
+        if let Some(pid) = item.project_id.as_deref() {
+            if let Ok(pid_num) = pid.parse::<u64>() {
+                ctx.insert("project_id_num", &pid_num);
+            }
+            let pattern = self.config.templates.as_ref().and_then(|t| t.project_id_format.as_deref()).unwrap_or("{project_id}");
+            ctx.insert("project_id_formatted", &esc(&format_project_id(pattern, pid)));
+        }
         
-        // Метаданные
+        // Метаданные (с учетом templates.metadata_allow / metadata_deny и templates.redact_emails)
+        let redact = self.config.templates.as_ref().and_then(|t| t.redact_emails).unwrap_or(true);
         for m in &item.metadata {
             let key = m.to_string();
+            if !self.metadata_field_allowed(&key) {
+                continue;
+            }
             let value = match m {
                 crate::models::types::MetadataItem::Date(v) => v,
                 crate::models::types::MetadataItem::PublishDate(v) => v,
@@ -426,16 +1049,26 @@ impl Worker {
                 crate::models::types::MetadataItem::CompliteNumberDepAct(v) => v,
                 crate::models::types::MetadataItem::CompliteNumberRegAct(v) => v,
                 crate::models::types::MetadataItem::ParallelStageFiles(v) => &v.join(", "),
+                crate::models::types::MetadataItem::ParallelStageFileNames(v) => &v.join(", "),
+                crate::models::types::MetadataItem::ParallelStageFileUrls(v) => &v.join(", "),
             };
-            ctx.insert(&key, value);
+            if redact {
+                ctx.insert(&key, &esc(&redact_emails(value)));
+            } else {
+                ctx.insert(&key, &esc(value));
+            }
         }
         
         let rendered = tera.render("post_tpl", &ctx)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("post_template render failed: {}", e)))?;
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, format!("{} render failed: {}", tpl_name, e)))?;
         
         // Применяем жесткий лимит размера поста, если задан
         let final_post = if let Some(max_chars) = self.config.run.as_ref().and_then(|r| r.post_max_chars) {
-            trim_with_ellipsis(&rendered, max_chars)
+            if self.trims_on_word_boundary() {
+                trim_on_word_boundary(&rendered, max_chars)
+            } else {
+                trim_with_ellipsis(&rendered, max_chars)
+            }
         } else {
             rendered
         };
@@ -443,6 +1076,123 @@ impl Worker {
         Ok(final_post)
     }
 
+    /// Проверяет, что проект уже опубликован во всех включенных каналах, но его стадия изменилась
+    async fn is_stage_update(&self, project_id: &str, item: &CrawlItem) -> bool {
+        let enabled_channels = self.get_enabled_publisher_channels();
+        if !self.cache_manager.is_fully_published(project_id, &enabled_channels).await.unwrap_or(false) {
+            return false;
+        }
+        match self.cache_manager.load_metadata(project_id).await {
+            Ok(Some(meta)) => matches!((meta.stage(), item.stage()), (Some(old), Some(new)) if old != new),
+            _ => false,
+        }
+    }
+
+    /// Публикует короткий пост об изменении стадии (templates.update_post), переиспользуя кэшированную суммаризацию.
+    /// Пост публикуется только если исходный документ не изменился; новые метаданные сохраняются в кэш в любом случае.
+    async fn process_stage_update(
+        &self,
+        project_id: &str,
+        item: &CrawlItem,
+        update_tpl: &str,
+    ) -> std::io::Result<bool> {
+        let cached_meta = match self.cache_manager.load_metadata(project_id).await {
+            Ok(Some(meta)) => meta,
+            Ok(None) => return Ok(false),
+            Err(e) => {
+                error!(project_id = %project_id, error = %e, "failed to load cached metadata for stage update");
+                return Ok(false);
+            }
+        };
+
+        // crawler.head_before_get: при известных валидаторах сначала HEAD, без скачивания неизмененного документа
+        // Сохраняем новую стадию, чтобы краулер не присылал проект повторно (в --dry-run кэш не меняется)
+        let document_unchanged = match self.fetch_document_with_retry(project_id, cached_meta.document_validators.as_ref()).await {
+            Ok(DocumentFetch::Fetched { bytes, markdown: text, validators }) => {
+                let new_hash = content_hash(&bytes);
+                let unchanged = cached_meta.document_hash.as_deref() == Some(new_hash.as_str());
+                if self.dry_run {
+                    info!(project_id = %project_id, "dry-run: stage update not saved to cache");
+                } else {
+                    if let Err(e) = self.cache_manager.save_artifacts(
+                        project_id,
+                        Some(&bytes),
+                        &text,
+                        "",
+                        "",
+                        &[],
+                        &item.metadata
+                    ).await {
+                        error!(project_id = %project_id, error = %e, "failed to save metadata for stage update");
+                    }
+                    if let Err(e) = self.cache_manager.update_document_state(project_id, Some(&validators), &[]).await {
+                        error!(project_id = %project_id, error = %e, "failed to save document validators to cache");
+                    }
+                }
+                if !unchanged {
+                    info!(
+                        project_id = %project_id,
+                        old_hash = ?cached_meta.document_hash,
+                        %new_hash,
+                        "document changed, stage update post skipped"
+                    );
+                }
+                unchanged
+            }
+            Ok(DocumentFetch::Unchanged) => {
+                // Кэшированный markdown остается как есть, обновляются только метаданные краулера
+                if self.dry_run {
+                    info!(project_id = %project_id, "dry-run: stage update not saved to cache");
+                } else if let Err(e) = self.cache_manager.update_document_state(project_id, None, &item.metadata).await {
+                    error!(project_id = %project_id, error = %e, "failed to save metadata for stage update");
+                }
+                true
+            }
+            Ok(DocumentFetch::Missing) => {
+                info!(project_id = %project_id, "no fileId found, skipping stage update");
+                return Ok(false);
+            }
+            Err(e) => {
+                error!(project_id = %project_id, error = %e, "failed to fetch document for stage update");
+                return Ok(false);
+            }
+        };
+
+        if !document_unchanged {
+            return Ok(false);
+        }
+
+        info!(
+            project_id = %project_id,
+            old_stage = ?cached_meta.stage(),
+            new_stage = ?item.stage(),
+            "document unchanged, publishing stage update"
+        );
+
+        let mut published_any = false;
+        for channel in self.get_enabled_publisher_channels() {
+            let summary = match cached_meta.channel_summaries.get(&channel) {
+                Some(s) => s.as_str().to_string(),
+                None => self.cache_manager.load_summary(project_id).await.ok().flatten().unwrap_or_default(),
+            };
+            let post = self.render_post("templates.update_post", update_tpl, item, &summary, self.post_parse_mode(channel))?;
+            match self.publish_to_channel_with_retry(channel, &post, Some(&summary), item, None).await {
+                Ok(PublishOutcome::Published | PublishOutcome::DryRun) => {
+                    published_any = true;
+                    info!(project_id = %project_id, channel = %channel, "published stage update to channel");
+                }
+                Ok(outcome) => {
+                    info!(project_id = %project_id, channel = %channel, outcome = ?outcome, "stage update to channel skipped");
+                }
+                Err(e) => {
+                    error!(project_id = %project_id, channel = %channel, error = %e, "failed to publish stage update");
+                }
+            }
+        }
+
+        Ok(published_any)
+    }
+
     /// Обрабатывает суммаризацию для конкретного канала
     async fn process_channel_summary(
         &self,
@@ -452,7 +1202,7 @@ impl Worker {
         url: &str,
         markdown_text: &str,
         item: &CrawlItem,
-    ) -> std::io::Result<String> {
+    ) -> std::io::Result<SummaryOutcome> {
         // Проверяем, есть ли уже суммаризация для этого канала
         match self.cache_manager.has_channel_summary(project_id, channel).await {
             Ok(true) => {
@@ -460,7 +1210,7 @@ impl Worker {
                 match self.cache_manager.load_channel_summary(project_id, channel).await {
                     Ok(Some(summary)) => {
                         info!(project_id = %project_id, channel = %channel, "successfully loaded cached channel summary, len={}", summary.len());
-                        return Ok(summary.into_inner());
+                        return Ok(SummaryOutcome::Generated(summary.into_inner()));
                     },
                     Ok(None) => {
                         error!(project_id = %project_id, channel = %channel, "cache inconsistency: has_channel_summary=true but load_channel_summary=None");
@@ -479,18 +1229,27 @@ impl Worker {
         }
 
         // Получаем лимит символов для канала
-        let channel_limit = self.channel_manager.get_channel_limit(channel)
-            .unwrap_or(300); // fallback лимит
+        let channel_limit = self.channel_manager.channel_limit_or_default(channel);
+
+        let prompt = self.channel_manager.channel_prompt(channel);
 
         info!(
             project_id = %project_id,
             channel = %channel,
             limit = channel_limit,
+            style = ?prompt.style,
+            language = ?prompt.language,
             "generating channel-specific summary"
         );
 
         // Генерируем суммаризацию для конкретного канала
-        let summary = self.summarize_text(title, url, markdown_text, item, Some(channel_limit)).await?;
+        let summary = self.summarize_text(title, url, markdown_text, item, Some(channel_limit), prompt).await?;
+
+        if let Some(guard) = &self.length_guard {
+            if let SummaryOutcome::Generated(text) = &summary {
+                guard.record(channel, text.chars().count(), channel_limit);
+            }
+        }
 
         Ok(summary)
     }
@@ -531,12 +1290,34 @@ impl Worker {
         }
 
         // Генерируем пост для конкретного канала
-        let post = self.build_post(item, summary)?;
+        let post = self.build_post(channel, item, summary)?;
 
         Ok(post)
     }
 
-    /// Обрабатывает элемент для всех включенных каналов с индивидуальными суммаризациями
+    /// Перерендеривает пост уже опубликованного канала из кэшированной суммаризации и сравнивает
+    /// его хэш с content_hash опубликованного. Возвращает новый пост, если он отличается; None —
+    /// пост не изменился или сравнивать не с чем (нет хэша или суммаризации канала)
+    async fn changed_published_post(
+        &self,
+        project_id: &str,
+        channel: PublisherChannel,
+        item: &CrawlItem,
+    ) -> Option<(Option<String>, String)> {
+        let meta = self.cache_manager.load_metadata(project_id).await.ok().flatten()?;
+        let published_hash = meta.content_hash.get(&channel)?;
+        let summary = meta.channel_summaries.get(&channel)?.as_str().to_string();
+        match self.build_post(channel, item, &summary) {
+            Ok(post) => (content_hash(post.as_bytes()) != *published_hash).then(|| (Some(summary), post)),
+            Err(e) => {
+                warn!(project_id = %project_id, channel = %channel, error = %e, "failed to render post for content hash check");
+                None
+            }
+        }
+    }
+
+    /// Обрабатывает элемент для всех включенных каналов с индивидуальными суммаризациями;
+    /// возвращает итог публикации по каждому каналу
     async fn process_item_for_channels(
         &self,
         project_id: &str,
@@ -544,96 +1325,257 @@ impl Worker {
         url: &str,
         markdown_text: &str,
         item: &CrawlItem,
-        _docx_bytes: Option<&[u8]>,
-    ) -> std::io::Result<Vec<String>> {
-        let mut published_channels = Vec::new();
-        
-        // Получаем список всех включенных каналов
-        let enabled_channels = self.channel_manager.get_enabled_channels();
+        docx_bytes: Option<&[u8]>,
+    ) -> std::io::Result<Vec<(PublisherChannel, PublishOutcome)>> {
+        let mut outcomes: Vec<(PublisherChannel, PublishOutcome)> = Vec::new();
         
+        // Получаем список всех включенных каналов (в backfill — только запрошенные)
+        let enabled_channels: Vec<_> = self.channel_manager.get_enabled_channels()
+            .into_iter()
+            .filter(|c| self.force_channels.is_empty() || self.force_channels.contains(&c.channel))
+            .collect();
+
+        // Этап 1: суммаризации и посты каналов готовятся последовательно.
+        // Ошибка суммаризации прерывает подготовку, но уже готовые каналы публикуются
+        let mut prepared: Vec<(PublisherChannel, Option<String>, String)> = Vec::new();
+        let mut pending_error: Option<std::io::Error> = None;
+        // Каналы, где вместо суммаризации отрывок исходника (summarizer.fallback): их данные не кэшируются
+        let mut degraded: HashSet<PublisherChannel> = HashSet::new();
+        let combine = self.config.run.as_ref().and_then(|r| r.combine_identical_channels).unwrap_or(false);
         for channel_config in enabled_channels {
             let channel = channel_config.channel;
             let channel_name = channel.as_str();
             
-            // Проверяем, не опубликован ли уже в этом канале
-            if self.cache_manager.is_published_in_channel(project_id, channel).await.unwrap_or(false) {
-                info!(project_id = %project_id, channel = %channel_name, "skip republish: channel already published");
+            // Проверяем, не опубликован ли уже в этом канале; опубликованный пост, который теперь
+            // рендерится иначе (content_hash не совпадает), публикуется заново. В backfill повтор принудительный
+            if self.force_channels.is_empty() && self.cache_manager.is_published_in_channel(project_id, channel).await.unwrap_or(false) {
+                match self.changed_published_post(project_id, channel, item).await {
+                    Some((channel_summary, channel_post)) => {
+                        info!(project_id = %project_id, channel = %channel_name, "published post changed, republishing");
+                        prepared.push((channel, channel_summary, channel_post));
+                    }
+                    None => {
+                        info!(project_id = %project_id, channel = %channel_name, "skip republish: channel already published");
+                        outcomes.push((channel, PublishOutcome::SkippedAlreadyPublished));
+                    }
+                }
+                continue;
+            }
+
+            // run.combine_identical_channels: переиспользуем результат канала с тем же лимитом, стилем и разметкой
+            let same = prepared
+                .iter()
+                .find(|(c, _, _)| combine && self.channel_manager.same_output(*c, channel) && self.post_parse_mode(*c) == self.post_parse_mode(channel))
+                .map(|(c, summary, post)| (*c, summary.clone(), post.clone()));
+            if let Some((source, channel_summary, channel_post)) = same {
+                info!(project_id = %project_id, channel = %channel_name, source_channel = %source, "reusing summary and post of identically configured channel");
+                if degraded.contains(&source) {
+                    degraded.insert(channel);
+                }
+                prepared.push((channel, channel_summary, channel_post));
                 continue;
             }
             
-            // Генерируем суммаризацию для этого канала
-            let channel_summary = self.process_channel_summary(
+            // Генерируем суммаризацию и пост для этого канала
+            let summary_result = self.process_channel_summary(
                 project_id,
                 channel,
                 title,
                 url,
                 markdown_text,
                 item,
-            ).await?;
-            
-            // Генерируем пост для этого канала
-            let channel_post = self.process_channel_post(
-                project_id,
-                channel,
-                title,
-                url,
-                &channel_summary,
-                item,
-            ).await?;
-            
-            // Публикуем в канале
-            match self.publish_to_channel(channel, &channel_post, &item).await {
-                Ok(success) => {
-                    if success {
-                        published_channels.push(channel_name.to_string());
-                        info!(project_id = %project_id, channel = %channel_name, published_channels_so_far = ?published_channels, "successfully published to channel");
+            ).await;
+            let prepared_channel = match summary_result {
+                Ok(outcome) => {
+                    if outcome.is_source_excerpt() {
+                        degraded.insert(channel);
+                    }
+                    let summary = outcome.into_text();
+                    self.process_channel_post(project_id, channel, title, url, &summary, item)
+                        .await
+                        .map(|post| (Some(summary), post))
+                }
+                Err(e) => match self.no_summary_template() {
+                    Some(tpl) => {
+                        warn!(project_id = %project_id, channel = %channel_name, error = %e, "summarizer unavailable, publishing unsummarized post from templates.no_summary_post");
+                        self.render_post("templates.no_summary_post", tpl, item, "", self.post_parse_mode(channel)).map(|post| (None, post))
+                    }
+                    None => Err(e),
+                },
+            };
+            match prepared_channel {
+                Ok((channel_summary, channel_post)) => prepared.push((channel, channel_summary, channel_post)),
+                Err(e) => {
+                    pending_error = Some(e);
+                    break;
+                }
+            }
+        }
+
+        // Этап 2: публикация; до run.publish_concurrency_per_item каналов одновременно.
+        // Результаты фиксируются в metadata.json по мере завершения, по одному
+        let concurrency = self.config.run.as_ref().and_then(|r| r.publish_concurrency_per_item).unwrap_or(1).max(1);
+        let mut results = futures_util::stream::iter(prepared.iter().map(|(channel, channel_summary, channel_post)| async move {
+            (*channel, self.publish_to_channel_with_retry(*channel, channel_post, channel_summary.as_deref(), item, docx_bytes).await)
+        }))
+        .buffer_unordered(concurrency);
+
+        while let Some((channel, result)) = results.next().await {
+            let channel_name = channel.as_str();
+            let outcome = match result {
+                Ok(outcome) => {
+                    if outcome.is_delivered() {
+                        info!(project_id = %project_id, channel = %channel_name, outcome = ?outcome, "successfully published to channel");
                         
-                        // Немедленно сохраняем данные канала в metadata.json
-                        if let Err(e) = self.cache_manager.update_channel_data(
-                            project_id, 
-                            channel, 
-                            Some(&channel_summary),
-                            Some(&channel_post),
-                            true  // is_published = true
+                        // Немедленно фиксируем публикацию в metadata.json одной записью
+                        let (_, channel_summary, channel_post) = prepared.iter().find(|(c, _, _)| *c == channel).unwrap();
+                        // Отрывок исходника не сохраняется как суммаризация канала: has_summary остается false
+                        let is_degraded = degraded.contains(&channel);
+                        let channel_summary = channel_summary.as_deref().filter(|_| !is_degraded);
+                        if self.dry_run {
+                            // --dry-run: суммаризация и пост кэшируются, но канал не отмечается опубликованным
+                            if let Err(e) = self.cache_manager.update_channel_data(
+                                project_id,
+                                channel,
+                                channel_summary,
+                                Some(channel_post).filter(|_| !is_degraded),
+                                false,
+                            ).await {
+                                error!(project_id = %project_id, channel = %channel_name, error = %e, "dry-run: failed to save channel data");
+                            }
+                        } else if let Err(e) = self.cache_manager.mark_published(
+                            project_id,
+                            channel,
+                            channel_summary,
+                            channel_post,
                         ).await {
                             error!(project_id = %project_id, channel = %channel_name, error = %e, "failed to save channel data");
                         } else {
                             info!(project_id = %project_id, channel = %channel_name, "immediately saved channel data to cache");
                         }
                     } else {
-                        info!(project_id = %project_id, channel = %channel_name, "publication to channel skipped");
+                        info!(project_id = %project_id, channel = %channel_name, outcome = ?outcome, "publication to channel skipped");
                     }
+                    outcome
                 }
                 Err(e) => {
                     error!(project_id = %project_id, channel = %channel_name, error = %e, "failed to publish to channel");
+                    PublishOutcome::Failed(e.to_string())
                 }
-            }
+            };
+            outcomes.push((channel, outcome));
+        }
+
+        if let Some(e) = pending_error {
+            return Err(e);
         }
         
-        info!(project_id = %project_id, final_published_channels = ?published_channels, "worker: finished processing all channels (channels saved immediately)");
+        info!(project_id = %project_id, outcomes = ?outcomes, "worker: finished processing all channels (channels saved immediately)");
         
         // Обновляем min_published_project_id в manifest после успешной публикации
-        if let Ok(pid_num) = project_id.parse::<u32>() {
-            if let Err(e) = self.cache_manager.update_min_published_project_id(pid_num).await {
-                error!(project_id = %project_id, error = %e, "failed to update min_published_project_id in manifest");
-            } else {
-                info!(project_id = %project_id, min_id = pid_num, "updated min_published_project_id in manifest");
-            }
+        if self.dry_run {
+            info!(project_id = %project_id, "dry-run: manifest not updated");
+        } else {
+            self.update_source_min_id(project_id, item).await;
         }
         
-        Ok(published_channels)
+        Ok(outcomes)
+    }
+
+    /// Публикует пост в канале, повторяя неудачную публикацию по channels.<name>.retry
+    async fn publish_to_channel_with_retry(
+        &self,
+        channel: PublisherChannel,
+        post_text: &str,
+        summary: Option<&str>,
+        item: &CrawlItem,
+        docx_bytes: Option<&[u8]>,
+    ) -> std::io::Result<PublishOutcome> {
+        let retry = self
+            .config
+            .channels
+            .as_ref()
+            .and_then(|c| c.get(channel))
+            .and_then(|c| c.retry.clone())
+            .unwrap_or_default();
+        let max_attempts = retry.max_attempts.unwrap_or(1).max(1);
+        let mut delay = Duration::from_secs(retry.backoff_secs.unwrap_or(1));
+
+        let mut attempt = 1;
+        loop {
+            let result = self.publish_to_channel(channel, post_text, summary, item, docx_bytes).await;
+            if !matches!(result, Ok(PublishOutcome::Failed(_))) || attempt >= max_attempts {
+                return result;
+            }
+            warn!(
+                project_id = ?item.project_id,
+                channel = %channel,
+                attempt,
+                max_attempts,
+                delay_secs = delay.as_secs(),
+                "publish to channel failed, retrying"
+            );
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+            attempt += 1;
+        }
     }
 
-    /// Публикует пост в конкретном канале
+    /// Публикует пост в конкретном канале.
+    /// `summary` — суммаризация канала для webhook (остальные каналы публикуют пост),
+    /// `docx_bytes` — исходный документ для telegram.send_document (если он есть)
     async fn publish_to_channel(
         &self,
         channel: PublisherChannel,
         post_text: &str,
+        summary: Option<&str>,
         item: &CrawlItem,
-    ) -> std::io::Result<bool> {
+        docx_bytes: Option<&[u8]>,
+    ) -> std::io::Result<PublishOutcome> {
+        if self.dry_run {
+            info!(
+                project_id = ?item.project_id,
+                channel = %channel,
+                post = %post_text,
+                "dry-run: post not published"
+            );
+            return Ok(PublishOutcome::DryRun);
+        }
         match channel {
             PublisherChannel::Telegram => {
-                if let (Some(api), Some(chat_id)) = (&self.telegram_api, &self.target_chat_id) {
+                let send_document = self.config.telegram.as_ref().and_then(|t| t.send_document).unwrap_or(false);
+                let extra_chat_ids = self.config.telegram.as_ref().and_then(|t| t.extra_chat_ids.clone()).unwrap_or_default();
+                if let (Some(api), Some(chat_id), Some(bytes), true) = (&self.telegram_api, &self.target_chat_id, docx_bytes, send_document) {
+                    // Документ с постом в подписи; лимит подписи (1024) отдельный от лимита сообщения
+                    let caption_limit = self
+                        .channel_manager
+                        .get_channel_limit(PublisherChannel::Telegram)
+                        .map_or(TELEGRAM_CAPTION_MAX_CHARS, |l| l.min(TELEGRAM_CAPTION_MAX_CHARS));
+                    // Имя и тип вложения — по сигнатуре документа, иначе по crawler.force_document_type
+                    let document_type = detect_document_type(bytes)
+                        .or(self.config.crawler.force_document_type)
+                        .unwrap_or_default();
+                    let file_name = format!("{}.{}", item.project_id.as_deref().unwrap_or("document"), document_type.extension());
+                    let caption = fit_markup_to_limit(post_text, Some(caption_limit), self.telegram_parse_mode(), self.trims_on_word_boundary());
+                    let delivered = self.delivered_targets(channel, item).await;
+                    let mut results = Vec::new();
+                    for chat in std::iter::once(*chat_id).chain(extra_chat_ids) {
+                        if delivered.contains(&chat.to_string()) {
+                            info!(chat_id = chat, "telegram: chat already received the post, skipping");
+                            results.push((chat.to_string(), true));
+                            continue;
+                        }
+                        let ok = match api.send_telegram_document(chat, file_name.clone(), document_type.mime_type().to_string(), bytes.to_vec(), caption.clone()).await {
+                            Ok(()) => true,
+                            Err(e) => {
+                                error!(chat_id = chat, error = %e, "telegram sendDocument failed");
+                                false
+                            }
+                        };
+                        results.push((chat.to_string(), ok));
+                    }
+                    Ok(self.settle_targets(channel, item, results).await)
+                } else if let (Some(api), Some(chat_id)) = (&self.telegram_api, &self.target_chat_id) {
                     // Создаем временный publisher с нужными параметрами
                     let publisher = RealTelegramApi {
                         client: api.client().clone(),
@@ -641,71 +1583,341 @@ impl Worker {
                         token: api.token().to_string(),
                         chat_id: *chat_id,
                         max_chars: self.channel_manager.get_channel_limit(PublisherChannel::Telegram),
+                        message_thread_id: self.config.telegram.as_ref().and_then(|t| t.message_thread_id),
+                        trim_on_word_boundary: self.trims_on_word_boundary(),
+                        retry: self.publish_retry_policy(),
+                        parse_mode: self.telegram_parse_mode(),
+                        split_long_messages: self.config.telegram.as_ref().and_then(|t| t.split_long_messages).unwrap_or(false),
                     };
-                    match publisher.publish(&item.title, &item.url, post_text).await {
-                        Ok(_) => Ok(true),
-                        Err(e) => {
-                            error!(error = %e, "telegram publish failed");
-                            Ok(false)
+                    if extra_chat_ids.is_empty() {
+                        return match publisher.publish(&item.title, &item.url, post_text).await {
+                            Ok(_) => Ok(PublishOutcome::Published),
+                            Err(e) => {
+                                error!(publisher = publisher.name(), error = %e, "publish failed");
+                                Ok(PublishOutcome::Failed(e.to_string()))
+                            }
+                        };
+                    }
+                    // Несколько чатов: результат каждого чата учитывается по channels.telegram.on_partial
+                    let chunks = publisher.message_chunks(post_text);
+                    let delivered = self.delivered_targets(channel, item).await;
+                    let mut results = Vec::new();
+                    for chat in std::iter::once(*chat_id).chain(extra_chat_ids) {
+                        if delivered.contains(&chat.to_string()) {
+                            info!(chat_id = chat, "telegram: chat already received the post, skipping");
+                            results.push((chat.to_string(), true));
+                            continue;
+                        }
+                        let mut ok = true;
+                        for chunk in &chunks {
+                            if let Err(e) = publisher.send_telegram_message(chat, chunk.clone()).await {
+                                error!(chat_id = chat, error = %e, "telegram sendMessage failed");
+                                ok = false;
+                                break;
+                            }
                         }
+                        results.push((chat.to_string(), ok));
                     }
+                    Ok(self.settle_targets(channel, item, results).await)
                 } else {
                     info!("telegram: disabled or not configured");
-                    Ok(false)
+                    Ok(PublishOutcome::SkippedDisabled)
                 }
             }
             PublisherChannel::Mastodon => {
-                if let Some(mastodon) = &self.mastodon {
-                    // Создаем временный publisher с нужными параметрами
-                    let publisher = MastodonPublisher::builder()
-                        .client(mastodon.client.clone())
-                        .base_url(mastodon.base_url.clone())
-                        .access_token(mastodon.access_token.clone())
-                        .maybe_visibility(self.config.mastodon.as_ref().and_then(|m| m.visibility.clone()))
-                        .maybe_language(self.config.mastodon.as_ref().and_then(|m| m.language.clone()))
-                        .maybe_spoiler_text(self.config.mastodon.as_ref().and_then(|m| m.spoiler_text.clone()))
-                        .sensitive(self.config.mastodon.as_ref().and_then(|m| m.sensitive).unwrap_or(false))
-                        .maybe_max_chars(self.channel_manager.get_channel_limit(PublisherChannel::Mastodon))
-                        .build();
-                    match publisher.publish(&item.title, &item.url, post_text).await {
-                        Ok(_) => Ok(true),
+                if let Some(publisher) = &self.mastodon {
+                    // mastodon.thread_long_posts: части цепочки записываются в кэш по мере публикации,
+                    // повтор неудачной публикации продолжает цепочку с последнего опубликованного статуса
+                    let thread_project = item.project_id.as_deref().filter(|_| publisher.thread_long_posts);
+                    let post_hash = content_hash(post_text.as_bytes());
+                    let posted = match thread_project {
+                        Some(project_id) => self.thread_resume_point(project_id, channel, &post_hash).await,
+                        None => Vec::new(),
+                    };
+                    // Вложение нужно только первому статусу: при продолжении цепочки он уже опубликован
+                    let attachment = if posted.is_empty() && self.config.mastodon.as_ref().and_then(|m| m.attach_files).unwrap_or(false) {
+                        self.mastodon_attachment(publisher, item).await
+                    } else {
+                        None
+                    };
+                    let media_ids: Vec<String> = attachment.iter().map(|(_, id)| id.clone()).collect();
+                    let result = publisher
+                        .publish_thread(post_text, &media_ids, &posted, |status_ids| {
+                            let post_hash = post_hash.clone();
+                            async move {
+                                let Some(project_id) = thread_project else { return };
+                                let progress = ThreadProgress { content_hash: post_hash, status_ids };
+                                if let Err(e) = self.cache_manager.record_thread_progress(project_id, channel, &progress).await {
+                                    error!(project_id = %project_id, channel = %channel, error = %e, "failed to save thread progress");
+                                }
+                            }
+                        })
+                        .await;
+                    match result {
+                        Ok(_) => {
+                            // Вложение прикреплено к статусу и повторно не используется
+                            if let Some((url, _)) = attachment {
+                                self.mastodon_media.lock().unwrap().remove(&url);
+                            }
+                            Ok(PublishOutcome::Published)
+                        }
                         Err(e) => {
-                            error!(error = %e, "mastodon publish failed");
-                            Ok(false)
+                            error!(publisher = publisher.name(), error = %e, "publish failed");
+                            Ok(PublishOutcome::Failed(e.to_string()))
                         }
                     }
                 } else {
                     info!("mastodon: disabled or not configured");
-                    Ok(false)
+                    Ok(PublishOutcome::SkippedDisabled)
+                }
+            }
+            PublisherChannel::Matrix => {
+                let publisher = match (&self.matrix, self.config.matrix.as_ref().filter(|m| m.enabled)) {
+                    (Some(publisher), _) => Arc::clone(publisher),
+                    (None, Some(matrix)) => Arc::new(
+                        MatrixPublisher::builder()
+                            .client(self.client.clone())
+                            .homeserver_url(matrix.homeserver_url.clone())
+                            .access_token(matrix.access_token.clone())
+                            .room_id(matrix.room_id.clone())
+                            .maybe_max_chars(self.channel_manager.get_channel_limit(PublisherChannel::Matrix))
+                            .trim_on_word_boundary(self.trims_on_word_boundary())
+                            .retry(self.publish_retry_policy())
+                            .build(),
+                    ),
+                    (None, None) => {
+                        info!("matrix: disabled or not configured");
+                        return Ok(PublishOutcome::SkippedDisabled);
+                    }
+                };
+                // txnId по проекту и тексту: повтор канала не создает дубликат сообщения
+                let project_id = item.project_id.as_deref().unwrap_or(&item.url);
+                match publisher.publish_project(project_id, post_text).await {
+                    Ok(_) => Ok(PublishOutcome::Published),
+                    Err(e) => {
+                        error!(publisher = publisher.name(), error = %e, "publish failed");
+                        Ok(PublishOutcome::Failed(e.to_string()))
+                    }
+                }
+            }
+            PublisherChannel::Slack => {
+                let publisher = match (&self.slack, self.config.slack.as_ref().filter(|s| s.enabled)) {
+                    (Some(publisher), _) => Arc::clone(publisher),
+                    (None, Some(slack)) => Arc::new(
+                        SlackPublisher::builder()
+                            .client(self.client.clone())
+                            .webhook_url(slack.webhook_url.clone())
+                            .maybe_max_chars(self.channel_manager.get_channel_limit(PublisherChannel::Slack))
+                            .maybe_blocks_template(slack.blocks_template.clone())
+                            .trim_on_word_boundary(self.trims_on_word_boundary())
+                            .retry(self.publish_retry_policy())
+                            .build(),
+                    ),
+                    (None, None) => {
+                        info!("slack: disabled or not configured");
+                        return Ok(PublishOutcome::SkippedDisabled);
+                    }
+                };
+                match publisher.publish(&item.title, &item.url, post_text).await {
+                    Ok(_) => Ok(PublishOutcome::Published),
+                    Err(e) => {
+                        error!(publisher = publisher.name(), error = %e, "publish failed");
+                        Ok(PublishOutcome::Failed(e.to_string()))
+                    }
+                }
+            }
+            PublisherChannel::Webhook => {
+                let publisher = match (&self.webhook, self.config.webhook.as_ref().filter(|w| w.enabled)) {
+                    (Some(publisher), _) => Arc::clone(publisher),
+                    (None, Some(webhook)) => Arc::new(
+                        WebhookPublisher::builder()
+                            .client(self.client.clone())
+                            .url(webhook.url.clone())
+                            .headers(webhook.headers.clone().unwrap_or_default())
+                            .maybe_secret(webhook.secret.clone())
+                            .signature_header(webhook.signature_header.clone().unwrap_or_else(|| DEFAULT_SIGNATURE_HEADER.to_string()))
+                            .retry(self.publish_retry_policy())
+                            .build(),
+                    ),
+                    (None, None) => {
+                        info!("webhook: disabled or not configured");
+                        return Ok(PublishOutcome::SkippedDisabled);
+                    }
+                };
+                // Событие с суммаризацией и метаданными элемента, пост канала не отправляется
+                match publisher.send_event(&WebhookEvent::from_item(item, summary)).await {
+                    Ok(_) => Ok(PublishOutcome::Published),
+                    Err(e) => {
+                        error!(publisher = publisher.name(), error = %e, "publish failed");
+                        Ok(PublishOutcome::Failed(e.to_string()))
+                    }
                 }
             }
             PublisherChannel::Console => {
                 let publisher = ConsolePublisher { max_chars: self.channel_manager.get_channel_limit(PublisherChannel::Console) };
                 match publisher.publish(&item.title, &item.url, post_text).await {
-                    Ok(_) => Ok(true),
+                    Ok(_) => Ok(PublishOutcome::Published),
                     Err(e) => {
-                        error!(error = %e, "console publish failed");
-                        Ok(false)
+                        error!(publisher = publisher.name(), error = %e, "publish failed");
+                        Ok(PublishOutcome::Failed(e.to_string()))
                     }
                 }
             }
             PublisherChannel::File => {
-                let file_path = self.config.output.as_ref()
-                    .and_then(|o| o.file_path.clone())
-                    .unwrap_or_else(|| "./post.txt".to_string());
-                let publisher = FilePublisher { 
-                    path: file_path,
-                    max_chars: self.channel_manager.get_channel_limit(PublisherChannel::File),
-                    append: self.config.output.as_ref().and_then(|o| o.file_append).unwrap_or(false)
+                if let Some(publisher) = &self.file {
+                    return match publisher.publish(&item.title, &item.url, post_text).await {
+                        Err(e) => {
+                            error!(publisher = publisher.name(), error = %e, path = %publisher.path, "publish failed");
+                            Ok(PublishOutcome::Failed(e.to_string()))
+                        }
+                        Ok(()) => Ok(PublishOutcome::Published),
+                    };
+                }
+                let output = self.config.output.as_ref();
+                let default_append = output.and_then(|o| o.file_append).unwrap_or(false);
+                let max_chars = self.channel_manager.get_channel_limit(PublisherChannel::File);
+                let bom = output.and_then(|o| o.file_bom).unwrap_or(false);
+                let line_ending = output.and_then(|o| o.file_line_ending).unwrap_or_default();
+                let publishers: Vec<FilePublisher> = match output.and_then(|o| o.file_targets.as_ref()) {
+                    Some(targets) if !targets.is_empty() => targets.iter().map(|t| {
+                        let format = t.format.unwrap_or_default();
+                        FilePublisher {
+                            path: t.path.clone(),
+                            max_chars,
+                            append: t.append.unwrap_or(format == FileFormat::Jsonl || default_append),
+                            format,
+                            bom,
+                            line_ending,
+                        }
+                    }).collect(),
+                    _ => vec![FilePublisher {
+                        path: output.and_then(|o| o.file_path.clone()).unwrap_or_else(|| "./post.txt".to_string()),
+                        max_chars,
+                        append: default_append,
+                        format: FileFormat::Text,
+                        bom,
+                        line_ending,
+                    }],
                 };
-                match publisher.publish(&item.title, &item.url, post_text).await {
-                    Ok(_) => Ok(true),
-                    Err(e) => {
-                        error!(error = %e, "file publish failed");
-                        Ok(false)
+                if publishers.len() == 1 {
+                    return match publishers[0].publish(&item.title, &item.url, post_text).await {
+                        Err(e) => {
+                            error!(publisher = publishers[0].name(), error = %e, path = %publishers[0].path, "publish failed");
+                            Ok(PublishOutcome::Failed(e.to_string()))
+                        }
+                        Ok(()) => Ok(PublishOutcome::Published),
+                    };
+                }
+                // Файлы, уже получившие пост при прошлой попытке, пропускаются
+                let delivered = self.delivered_targets(channel, item).await;
+                let (done, pending): (Vec<_>, Vec<_>) = publishers.iter().partition(|p| delivered.contains(&p.path));
+                let mut target_results: Vec<(String, bool)> = done.iter().map(|p| (p.path.clone(), true)).collect();
+                // Остальные файлы канала пишутся параллельно из одного поста
+                let results = futures_util::future::join_all(
+                    pending.iter().map(|p| p.publish(&item.title, &item.url, post_text))
+                ).await;
+                for (publisher, result) in pending.iter().zip(results) {
+                    if let Err(e) = &result {
+                        error!(publisher = publisher.name(), error = %e, path = %publisher.path, "publish failed");
                     }
+                    target_results.push((publisher.path.clone(), result.is_ok()));
                 }
+                Ok(self.settle_targets(channel, item, target_results).await)
             }
         }
     }
+
+    /// Адресаты канала, получившие пост при прошлой неудачной или частичной публикации
+    /// (target_results): повтор им пост не отправляет. Канал уже опубликован (переопубликация
+    /// измененного поста, backfill) — пост получают все адресаты
+    async fn delivered_targets(&self, channel: PublisherChannel, item: &CrawlItem) -> std::collections::BTreeSet<String> {
+        let Some(project_id) = item.project_id.as_deref() else {
+            return Default::default();
+        };
+        match self.cache_manager.load_metadata(project_id).await {
+            Ok(Some(meta)) if !meta.published_channels.contains(&channel) => meta
+                .target_results
+                .get(&channel)
+                .map(|results| results.iter().filter(|(_, ok)| **ok).map(|(target, _)| target.clone()).collect())
+                .unwrap_or_default(),
+            Ok(_) => Default::default(),
+            Err(e) => {
+                warn!(project_id = %project_id, channel = %channel, error = %e, "failed to load target results, posting to all targets");
+                Default::default()
+            }
+        }
+    }
+
+    /// Опубликованные при прошлой неудачной попытке статусы цепочки того же поста (threads):
+    /// повтор продолжает цепочку после них. Канал уже опубликован — цепочка публикуется заново
+    async fn thread_resume_point(&self, project_id: &str, channel: PublisherChannel, post_hash: &str) -> Vec<String> {
+        match self.cache_manager.load_metadata(project_id).await {
+            Ok(Some(meta)) if !meta.published_channels.contains(&channel) => meta
+                .threads
+                .get(&channel)
+                .filter(|t| t.content_hash == post_hash)
+                .map(|t| t.status_ids.clone())
+                .unwrap_or_default(),
+            Ok(_) => Vec::new(),
+            Err(e) => {
+                warn!(project_id = %project_id, channel = %channel, error = %e, "failed to load thread progress, posting the whole thread");
+                Vec::new()
+            }
+        }
+    }
+
+    /// Итог публикации в канал с несколькими адресатами по channels.<name>.on_partial;
+    /// результаты по адресатам сохраняются в кэш
+    async fn settle_targets(&self, channel: PublisherChannel, item: &CrawlItem, results: Vec<(String, bool)>) -> PublishOutcome {
+        let on_partial = self
+            .config
+            .channels
+            .as_ref()
+            .and_then(|c| c.get(channel))
+            .and_then(|c| c.on_partial)
+            .unwrap_or_default();
+        let succeeded = results.iter().filter(|(_, ok)| *ok).count();
+        if succeeded > 0 && succeeded < results.len() {
+            warn!(channel = %channel, succeeded, total = results.len(), on_partial = ?on_partial, "channel published to some targets only");
+        }
+        if let Some(project_id) = item.project_id.as_deref() {
+            if let Err(e) = self.cache_manager.record_target_results(project_id, channel, &results).await {
+                error!(project_id = %project_id, channel = %channel, error = %e, "failed to save target results");
+            }
+        }
+        let published = match on_partial {
+            OnPartial::Fail => succeeded == results.len(),
+            OnPartial::SucceedIfAny => succeeded > 0,
+        };
+        if published {
+            PublishOutcome::Published
+        } else {
+            PublishOutcome::Failed(format!("published to {} of {} targets", succeeded, results.len()))
+        }
+    }
+}
+
+/// Имена файлов параллельной стадии (fileId -> имя), найденные при прошлой обработке проекта.
+/// fileId, для которого имя не нашлось (вместо имени остался fileId), в результат не входит
+fn cached_file_names(crawl_metadata: &[MetadataItem]) -> HashMap<String, String> {
+    let files = crawl_metadata.iter().find_map(|m| match m {
+        MetadataItem::ParallelStageFiles(v) => Some(v),
+        _ => None,
+    });
+    let names = crawl_metadata.iter().find_map(|m| match m {
+        MetadataItem::ParallelStageFileNames(v) => Some(v),
+        _ => None,
+    });
+    let (Some(files), Some(names)) = (files, names) else {
+        return HashMap::new();
+    };
+    if files.len() != names.len() {
+        return HashMap::new();
+    }
+    files
+        .iter()
+        .zip(names)
+        .filter(|(file, name)| file != name)
+        .map(|(file, name)| (file.clone(), name.clone()))
+        .collect()
 }
@@ -0,0 +1,175 @@
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use bon::Builder;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_graceful_shutdown::{FutureExt, SubsystemHandle};
+use tokio_graceful_shutdown::errors::CancelledByShutdown;
+use tracing::{debug, info, warn};
+
+/// Готовность к работе: выставляется сканером после первого успешного обхода источника
+pub type Ready = Arc<AtomicBool>;
+
+/// Время на чтение запроса пробы; медленный клиент не задерживает следующие пробы
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Занятость одной работой дольше этого срока считается зависанием (health.stall_timeout_secs)
+pub const DEFAULT_STALL_TIMEOUT: Duration = Duration::from_secs(3600);
+
+/// Состояние подсистемы для `GET /healthz`: работает ли она и с какого момента занята
+/// текущей работой (цикл обхода сканера, элемент worker). Клоны делят одно состояние
+#[derive(Debug, Clone, Default)]
+pub struct Liveness(Arc<Mutex<LivenessState>>);
+
+#[derive(Debug, Default)]
+struct LivenessState {
+    running: bool,
+    busy_since: Option<Instant>,
+}
+
+/// Снимает отметку `Liveness` при drop, в том числе при ошибке и отмене подсистемы
+#[must_use]
+pub struct LivenessGuard {
+    state: Arc<Mutex<LivenessState>>,
+    reset: fn(&mut LivenessState),
+}
+
+impl Drop for LivenessGuard {
+    fn drop(&mut self) {
+        (self.reset)(&mut self.state.lock().unwrap());
+    }
+}
+
+impl Liveness {
+    /// Подсистема работает, пока жив guard
+    pub fn running(&self) -> LivenessGuard {
+        self.0.lock().unwrap().running = true;
+        LivenessGuard { state: Arc::clone(&self.0), reset: |state| state.running = false }
+    }
+
+    /// Подсистема занята работой, пока жив guard; потом снова простаивает
+    pub fn busy(&self) -> LivenessGuard {
+        self.0.lock().unwrap().busy_since = Some(Instant::now());
+        LivenessGuard { state: Arc::clone(&self.0), reset: |state| state.busy_since = None }
+    }
+
+    /// Почему подсистема нездорова: не работает или занята одной работой дольше `stall_timeout`
+    pub fn problem(&self, stall_timeout: Duration) -> Option<&'static str> {
+        let state = self.0.lock().unwrap();
+        if !state.running {
+            return Some("down");
+        }
+        state.busy_since.filter(|since| since.elapsed() > stall_timeout).map(|_| "stalled")
+    }
+}
+
+/// HTTP-сервер проб (секция health): `GET /healthz` — 200, пока подсистемы `subsystems` работают
+/// и ни одна не занята одной работой дольше `stall_timeout`, иначе 503 с именами подсистем
+/// (при запросе завершения сервер останавливается первым); `GET /readyz` — 200 после
+/// первого успешного обхода источника, до этого 503
+#[derive(Builder)]
+pub struct HealthSubsystem {
+    pub(crate) bind: String,
+    #[builder(default)]
+    pub(crate) ready: Ready,
+    /// Отслеживаемые подсистемы: имя для ответа пробы и состояние
+    #[builder(default)]
+    pub(crate) subsystems: Vec<(&'static str, Liveness)>,
+    #[builder(default = DEFAULT_STALL_TIMEOUT)]
+    pub(crate) stall_timeout: Duration,
+}
+
+impl HealthSubsystem {
+    pub async fn run(self, subsys: SubsystemHandle) -> std::io::Result<()> {
+        let listener = TcpListener::bind(&self.bind).await.map_err(|e| {
+            std::io::Error::new(e.kind(), format!("health: failed to bind {}: {}", self.bind, e))
+        })?;
+        info!(bind = %self.bind, "Starting Health subsystem");
+
+        let serve = async {
+            loop {
+                let (stream, peer) = match listener.accept().await {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        warn!(error = %e, "health: accept failed");
+                        continue;
+                    }
+                };
+                if let Err(e) = self.respond(stream).await {
+                    debug!(peer = %peer, error = %e, "health: failed to answer probe");
+                }
+            }
+        };
+
+        // Сервер работает до запроса завершения
+        if let Err(CancelledByShutdown) = serve.cancel_on_shutdown(&subsys).await {
+            info!("Health subsystem cancelled by shutdown");
+        }
+        Ok(())
+    }
+
+    /// Читает строку запроса и отвечает статусом пробы
+    async fn respond(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        let mut buf = [0u8; 1024];
+        let n = tokio::time::timeout(REQUEST_READ_TIMEOUT, stream.read(&mut buf))
+            .await
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "request read timed out"))??;
+        let request = String::from_utf8_lossy(&buf[..n]);
+        let mut parts = request.lines().next().unwrap_or_default().split_whitespace();
+        let (method, path) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+
+        let (status, body) = match (method, path) {
+            ("GET", "/healthz") => match self.unhealthy() {
+                Some(problem) => ("503 Service Unavailable", problem),
+                None => ("200 OK", "ok".to_string()),
+            },
+            ("GET", "/readyz") if self.ready.load(Ordering::SeqCst) => ("200 OK", "ready".to_string()),
+            ("GET", "/readyz") => ("503 Service Unavailable", "not ready".to_string()),
+            _ => ("404 Not Found", "not found".to_string()),
+        };
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await?;
+        stream.shutdown().await
+    }
+
+    /// Нездоровые подсистемы через запятую, например `scanner stalled, worker stalled`
+    fn unhealthy(&self) -> Option<String> {
+        let problems: Vec<String> = self
+            .subsystems
+            .iter()
+            .filter_map(|(name, liveness)| liveness.problem(self.stall_timeout).map(|problem| format!("{} {}", name, problem)))
+            .collect();
+        (!problems.is_empty()).then(|| problems.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn liveness_reports_down_and_stalled_subsystems() {
+        let liveness = Liveness::default();
+        assert_eq!(liveness.problem(Duration::from_secs(60)), Some("down"));
+
+        let running = liveness.running();
+        assert_eq!(liveness.problem(Duration::from_secs(60)), None);
+
+        let busy = liveness.busy();
+        assert_eq!(liveness.problem(Duration::from_secs(60)), None);
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(liveness.problem(Duration::from_millis(10)), Some("stalled"));
+        drop(busy);
+        assert_eq!(liveness.problem(Duration::from_millis(10)), None);
+
+        drop(running);
+        assert_eq!(liveness.problem(Duration::from_secs(60)), Some("down"));
+    }
+}
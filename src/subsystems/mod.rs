@@ -1,3 +1,5 @@
 pub mod scanner;
 pub mod worker;
+pub mod watchdog;
+pub mod health;
 
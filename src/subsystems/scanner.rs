@@ -3,42 +3,69 @@ use std::time::Duration;
 use anyhow::Result;
 use backon::{ExponentialBuilder, Retryable};
 use bon::Builder;
+use reqwest::Client;
 use tokio::sync::mpsc;
 use tokio_graceful_shutdown::{FutureExt, SubsystemHandle};
 use tokio_graceful_shutdown::errors::CancelledByShutdown;
 use tracing::{error, info};
 
 use crate::models::types::CrawlItem;
-use crate::crawlers::NpaListCrawler;
-use crate::models::config::AppConfig;
+use crate::crawlers::{AtomCrawler, NpaListCrawler, RssCrawler, fetch_merged_with_seen};
+use crate::models::config::{AppConfig, AtomConfig, RssConfig, RunOptions};
 use crate::services::channels::ChannelManager;
+use crate::subsystems::health::{Liveness, Ready};
 use crate::traits::cache_manager::CacheManager;
 use crate::traits::crawler::Crawler;
 use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+/// Сообщение сканера для worker: элемент обхода или маркер начала нового цикла обхода.
+/// Маркер идет через тот же канал, что и элементы, поэтому worker видит его после всех
+/// элементов предыдущего цикла, еще ожидающих в канале
+#[derive(Debug)]
+pub enum ScanMessage {
+    Item(CrawlItem),
+    /// Начался цикл обхода с этим номером: worker сбрасывает счетчик run.max_posts_per_run
+    /// (crawler.poll_interval_secs)
+    CycleStarted(u64),
+}
 
 #[derive(Builder)]
 pub struct ScannerSubsystem {
     pub(crate) config: AppConfig,
     pub(crate) req_timeout: Duration,
-    pub(crate) sender: mpsc::Sender<CrawlItem>,
+    pub(crate) sender: mpsc::Sender<ScanMessage>,
     pub(crate) cache_manager: Arc<dyn CacheManager>,
+    /// Общий HTTP-клиент краулеров (секция http)
+    #[builder(default)]
+    pub(crate) client: Client,
+    #[builder(default)]
+    pub(crate) options: RunOptions,
+    /// Выставляется после первого успешного обхода (GET /readyz)
+    #[builder(default)]
+    pub(crate) ready: Ready,
+    /// Работа и занятость циклом обхода (GET /healthz)
+    #[builder(default)]
+    pub(crate) liveness: Liveness,
 }
 
 impl ScannerSubsystem {
     pub async fn run(self, subsys: SubsystemHandle) -> std::io::Result<()> {
         info!("Starting NPAListCrawler subsystem");
+        let _running = self.liveness.running();
 
         let fut = async {
-            let npa_interval_secs = self
+            // crawler.poll_interval_secs задает период циклов непрерывного опроса; без него
+            // обход повторяется каждые crawler.npalist.interval_seconds, пока worker не завершит запуск
+            let interval_secs = self
                 .config
                 .crawler
-                .npalist
-                .as_ref()
-                .and_then(|n| n.interval_seconds)
-                .unwrap_or(300);
+                .poll_interval_secs
+                .or_else(|| self.config.crawler.npalist.as_ref().and_then(|n| n.interval_seconds))
+                .unwrap_or(300)
+                .max(1);
 
-            let max_retry_attempts = self.config.crawler.max_retry_attempts.unwrap_or(0);
-            let mut interval = tokio::time::interval(Duration::from_secs(npa_interval_secs));
+            let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
             
             // Создаем ChannelManager для получения включенных каналов
             let channel_manager = ChannelManager::builder().config(&self.config).build();
@@ -47,47 +74,28 @@ impl ScannerSubsystem {
                 .map(|config| config.channel)
                 .collect();
 
+            let mut cycle = 0u64;
             loop {
                 interval.tick().await;
+                let _busy = self.liveness.busy();
+                cycle += 1;
+                if self.sender.send(ScanMessage::CycleStarted(cycle)).await.is_err() {
+                    info!("worker channel closed, crawler stops");
+                    break;
+                }
 
-                if let Some(npa) = self
-                    .config
-                    .crawler
-                    .npalist
-                    .as_ref()
-                    .filter(|n| n.enabled.unwrap_or(true))
-                {
-                    let npa_re = npa
-                        .regex
-                        .as_ref()
-                        .and_then(|s| regex::Regex::new(s).ok());
-
-                    let poll_delay = Duration::from_secs(self.config.crawler.poll_delay_secs.unwrap_or(0));
-                    
-                    // Попытка получить данные с retry логикой (потоковая отправка)
-                    let result = Self::try_fetch_data_stream_with_retry(
-                        &self.config,
-                        &self.sender,
-                        self.req_timeout,
-                        Arc::clone(&self.cache_manager),
-                        npa.url.clone(),
-                        npa.limit,
-                        npa_re.clone(),
-                        poll_delay,
-                        max_retry_attempts,
-                        enabled_channels.clone(),
-                    ).await;
-
-                    match result {
-                        Ok(()) => {
-                            info!("crawler: streaming completed successfully");
-                        }
-                        Err(e) => {
-                            error!(error = %e, "All crawlers failed after retries, shutting down");
-                            subsys.request_shutdown();
+                // Краулеры отправляют CrawlItem: пересылаем их в канал worker
+                let (item_tx, mut item_rx) = mpsc::channel(1);
+                let forward = async {
+                    while let Some(item) = item_rx.recv().await {
+                        if self.sender.send(ScanMessage::Item(item)).await.is_err() {
                             break;
                         }
                     }
+                };
+                let (proceed, ()) = tokio::join!(self.crawl_cycle(&subsys, item_tx, cycle, &enabled_channels), forward);
+                if !proceed {
+                    break;
                 }
             }
 
@@ -103,34 +111,167 @@ impl ScannerSubsystem {
         Ok(())
     }
 
+    /// Один цикл обхода всех включенных источников. Ошибка источника пишется в лог, остальные источники
+    /// обходятся дальше; false — не сработал ни один источник и запрошено завершение
+    async fn crawl_cycle(
+        &self,
+        subsys: &SubsystemHandle,
+        sender: mpsc::Sender<CrawlItem>,
+        cycle: u64,
+        enabled_channels: &[crate::models::channel::PublisherChannel],
+    ) -> bool {
+        let max_retry_attempts = self.config.crawler.max_retry_attempts.unwrap_or(0);
+        let mut attempted = 0;
+        let mut failed = 0;
+
+        if let Some(npa) = self
+            .config
+            .crawler
+            .npalist
+            .as_ref()
+            .filter(|n| n.enabled.unwrap_or(true))
+        {
+            let npa_re = npa
+                .regex
+                .as_ref()
+                .and_then(|s| regex::Regex::new(s).ok());
+
+            let poll_delay = Duration::from_secs(self.config.crawler.poll_delay_secs.unwrap_or(0));
+            
+            // Попытка получить данные с retry логикой (потоковая отправка)
+            let result = Self::try_fetch_data_stream_with_retry(
+                &self.config,
+                &sender,
+                &self.client,
+                self.req_timeout,
+                Arc::clone(&self.cache_manager),
+                npa.url.clone(),
+                self.options.limit.or(npa.limit),
+                self.options.offset,
+                npa_re.clone(),
+                poll_delay,
+                max_retry_attempts,
+                enabled_channels.to_vec(),
+                self.options.dry_run,
+            ).await;
+
+            attempted += 1;
+            match result {
+                Ok(()) => {
+                    info!(cycle, "crawler: streaming completed successfully");
+                    self.ready.store(true, Ordering::SeqCst);
+                }
+                Err(e) => {
+                    error!(cycle, error = %e, "NPA crawler failed after retries");
+                    failed += 1;
+                }
+            }
+        }
+
+        if let Some(atom) = self
+            .config
+            .crawler
+            .atom
+            .as_ref()
+            .filter(|a| a.enabled.unwrap_or(true))
+        {
+            let result = Self::try_fetch_atom_with_retry(
+                atom,
+                &sender,
+                &self.client,
+                self.req_timeout,
+                Arc::clone(&self.cache_manager),
+                max_retry_attempts,
+                enabled_channels.to_vec(),
+            ).await;
+
+            attempted += 1;
+            match result {
+                Ok(()) => {
+                    info!(cycle, "atom crawler: streaming completed successfully");
+                    self.ready.store(true, Ordering::SeqCst);
+                }
+                Err(e) => {
+                    error!(cycle, error = %e, "Atom crawler failed after retries");
+                    failed += 1;
+                }
+            }
+        }
+
+        let rss_sources = self.config.crawler.rss.as_ref().map(|r| r.enabled()).unwrap_or_default();
+        if !rss_sources.is_empty() {
+            let result = Self::try_fetch_rss_with_retry(
+                &rss_sources,
+                self.config.crawler.project_url_template.as_deref(),
+                &sender,
+                &self.client,
+                self.req_timeout,
+                Arc::clone(&self.cache_manager),
+                max_retry_attempts,
+                enabled_channels.to_vec(),
+            ).await;
+
+            attempted += 1;
+            match result {
+                Ok(()) => {
+                    info!(cycle, sources = rss_sources.len(), "rss crawlers: streaming completed successfully");
+                    self.ready.store(true, Ordering::SeqCst);
+                }
+                Err(e) => {
+                    error!(cycle, error = %e, "RSS crawlers failed after retries");
+                    failed += 1;
+                }
+            }
+        }
+
+        if attempted > 0 && failed == attempted {
+            error!(cycle, "All crawlers failed after retries, shutting down");
+            subsys.request_shutdown();
+            return false;
+        }
+        true
+    }
+
     async fn try_fetch_data_stream_with_retry(
-        _config: &AppConfig,
+        config: &AppConfig,
         sender: &mpsc::Sender<CrawlItem>,
+        client: &Client,
         req_timeout: Duration,
         cache_manager: Arc<dyn CacheManager>,
         npa_url: String,
         npa_limit: Option<u32>,
+        npa_offset: Option<u32>,
         npa_re: Option<regex::Regex>,
         poll_delay: Duration,
         max_retry_attempts: u64,
         enabled_channels: Vec<crate::models::channel::PublisherChannel>,
+        dry_run: bool,
     ) -> Result<()> {
         let fetch_data = || async {
             // Сначала пытаемся NPA краулер с потоковой отправкой
             let npa_result: Result<()> = match NpaListCrawler::builder()
                 .url_template(npa_url.clone())
                 .maybe_limit_opt(npa_limit)
+                .maybe_sort_param(config.crawler.npalist.as_ref().and_then(|n| n.sort.clone()))
+                .maybe_offset_override(npa_offset)
                 .maybe_project_id_re(npa_re.clone())
+                .maybe_project_url_template(config.crawler.npalist_project_url_template().map(str::to_string))
+                .maybe_max_items_per_page(history_page_cap(config))
+                .maybe_source_label(config.crawler.npalist.as_ref().and_then(|n| n.label.clone()))
                 .timeout(req_timeout)
+                .client(client.clone())
                 .cache_manager(Arc::clone(&cache_manager))
                 .poll_delay(poll_delay)
                 .enabled_channels(enabled_channels.clone())
+                .detect_stage_updates(config.templates.as_ref().and_then(|t| t.update_post.as_ref()).is_some())
+                .maybe_history_pages_per_run(config.crawler.history_pages_per_run)
+                .dry_run(dry_run)
                 .build() {
                 Ok(npa_crawler) => match npa_crawler.fetch_stream(sender.clone()).await {
                     Ok(()) => {
                         return Ok(());
                     }
-                    Err(e) => Err(anyhow::anyhow!("NPA fetch_stream failed: {}", e))
+                    Err(e) => Err(anyhow::anyhow!("NPA fetch_stream failed for source {}: {}", npa_crawler.source_id(), e))
                 },
                 Err(e) => Err(anyhow::anyhow!("NPA crawler creation failed: {}", e))
             };
@@ -162,6 +303,104 @@ impl ScannerSubsystem {
             .await
     }
 
-}
+    /// Обход ленты Atom (crawler.atom) с теми же повторами, что и у NPA краулера
+    async fn try_fetch_atom_with_retry(
+        atom: &AtomConfig,
+        sender: &mpsc::Sender<CrawlItem>,
+        client: &Client,
+        req_timeout: Duration,
+        cache_manager: Arc<dyn CacheManager>,
+        max_retry_attempts: u64,
+        enabled_channels: Vec<crate::models::channel::PublisherChannel>,
+    ) -> Result<()> {
+        let crawler = AtomCrawler::builder()
+            .url(atom.url.clone())
+            .maybe_project_id_re(atom.regex.as_ref().and_then(|s| regex::Regex::new(s).ok()))
+            .maybe_source_label(atom.label.clone())
+            .timeout(req_timeout)
+            .client(client.clone())
+            .cache_manager(cache_manager)
+            .enabled_channels(enabled_channels)
+            .build()
+            .map_err(|e| anyhow::anyhow!("Atom crawler creation failed: {}", e))?;
 
+        let mut builder = ExponentialBuilder::default();
+        if max_retry_attempts > 0 {
+            builder = builder.with_max_times(max_retry_attempts as usize);
+        }
+
+        (|| async {
+            crawler
+                .fetch_stream(sender.clone())
+                .await
+                .map_err(|e| anyhow::anyhow!("Atom fetch_stream failed for source {}: {}", crawler.source_id(), e))
+        })
+            .retry(builder)
+            .sleep(tokio::time::sleep)
+            .notify(|err: &anyhow::Error, dur: Duration| {
+                info!("Retrying atom crawler after {:?} due to error: {}", dur, err);
+            })
+            .await
+    }
 
+    /// Обход всех источников crawler.rss: по краулеру на источник, элементы идут в общий канал
+    /// worker без повторов по project_id
+    async fn try_fetch_rss_with_retry(
+        sources: &[&RssConfig],
+        project_url_template: Option<&str>,
+        sender: &mpsc::Sender<CrawlItem>,
+        client: &Client,
+        req_timeout: Duration,
+        cache_manager: Arc<dyn CacheManager>,
+        max_retry_attempts: u64,
+        enabled_channels: Vec<crate::models::channel::PublisherChannel>,
+    ) -> Result<()> {
+        let crawlers = sources
+            .iter()
+            .map(|rss| {
+                RssCrawler::builder()
+                    .url(rss.url.clone())
+                    .maybe_project_id_re(rss.regex.as_ref().and_then(|s| regex::Regex::new(s).ok()))
+                    .maybe_source_label(rss.label.clone())
+                    .maybe_project_url_template(project_url_template.map(str::to_string))
+                    .timeout(req_timeout)
+                    .client(client.clone())
+                    .cache_manager(Arc::clone(&cache_manager))
+                    .enabled_channels(enabled_channels.clone())
+                    .build()
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| anyhow::anyhow!("RSS crawler creation failed: {}", e))?;
+        let crawlers: Vec<&dyn Crawler> = crawlers.iter().map(|c| c as &dyn Crawler).collect();
+
+        let mut builder = ExponentialBuilder::default();
+        if max_retry_attempts > 0 {
+            builder = builder.with_max_times(max_retry_attempts as usize);
+        }
+
+        // Общий для всех попыток: повтор после ошибки одной ленты не отправляет элементы заново
+        let seen = std::sync::Mutex::new(std::collections::HashSet::new());
+        (|| async {
+            fetch_merged_with_seen(&crawlers, sender.clone(), &seen)
+                .await
+                .map_err(|e| anyhow::anyhow!("RSS fetch failed: {}", e))
+        })
+            .retry(builder)
+            .sleep(tokio::time::sleep)
+            .notify(|err: &anyhow::Error, dur: Duration| {
+                info!("Retrying rss crawlers after {:?} due to error: {}", dur, err);
+            })
+            .await
+    }
+}
+
+/// Лимит элементов, отправляемых в worker с одной страницы истории:
+/// минимум из crawler.npalist.max_items_per_page и run.max_posts_per_run
+fn history_page_cap(config: &AppConfig) -> Option<usize> {
+    let page_cap = config.crawler.npalist.as_ref().and_then(|n| n.max_items_per_page);
+    let run_cap = config.run.as_ref().and_then(|r| r.max_posts_per_run);
+    match (page_cap, run_cap) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
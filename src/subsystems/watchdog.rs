@@ -0,0 +1,40 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bon::Builder;
+use tokio_graceful_shutdown::{FutureExt, SubsystemHandle};
+use tokio_graceful_shutdown::errors::CancelledByShutdown;
+use tracing::{info, warn};
+
+/// Описание элемента, который сейчас обрабатывает worker (для логов watchdog)
+pub type InProgress = Arc<Mutex<Option<String>>>;
+
+/// Ограничивает общее время запуска (run.max_duration_secs): по истечении
+/// запрашивает штатное завершение всех подсистем
+#[derive(Builder)]
+pub struct WatchdogSubsystem {
+    pub(crate) max_duration: Duration,
+    #[builder(default)]
+    pub(crate) in_progress: InProgress,
+}
+
+impl WatchdogSubsystem {
+    pub async fn run(self, subsys: SubsystemHandle) -> std::io::Result<()> {
+        info!(max_duration_secs = self.max_duration.as_secs(), "Starting Watchdog subsystem");
+
+        match tokio::time::sleep(self.max_duration).cancel_on_shutdown(&subsys).await {
+            Ok(()) => {
+                let in_progress = self.in_progress.lock().unwrap().clone();
+                warn!(
+                    max_duration_secs = self.max_duration.as_secs(),
+                    in_progress = in_progress.as_deref().unwrap_or("<idle>"),
+                    "run.max_duration_secs exceeded, requesting shutdown"
+                );
+                subsys.request_shutdown();
+            }
+            Err(CancelledByShutdown) => info!("Watchdog subsystem cancelled by shutdown"),
+        }
+
+        Ok(())
+    }
+}
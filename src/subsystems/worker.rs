@@ -1,17 +1,26 @@
-use std::sync::Arc;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use bon::Builder;
+use reqwest::Client;
+use serde::Serialize;
 use tokio::sync::mpsc;
 use tokio_graceful_shutdown::{FutureExt, SubsystemHandle};
 use tokio_graceful_shutdown::errors::CancelledByShutdown;
-use tracing::info;
+use tracing::{error, info};
 
-use crate::models::types::CrawlItem;
 use crate::services::summarizer::Summarizer;
 use crate::services::worker::Worker;
 use crate::traits::cache_manager::CacheManager;
 use crate::traits::telegram_api::TelegramApi;
 use crate::models::config::AppConfig;
+use crate::subsystems::scanner::ScanMessage;
+use crate::subsystems::health::Liveness;
+use crate::subsystems::watchdog::InProgress;
+
+/// Число проектов, опубликованных за запуск хотя бы в один канал, для итога run_with_options
+pub type PublishedProjects = Arc<AtomicUsize>;
 
 #[derive(Builder)]
 pub struct WorkerSubsystem {
@@ -20,12 +29,25 @@ pub struct WorkerSubsystem {
     pub(crate) telegram_api: Option<Arc<dyn TelegramApi>>,
     pub(crate) target_chat_id: Option<i64>,
     pub(crate) cache_manager: Arc<dyn CacheManager>,
-    pub(crate) receiver: mpsc::Receiver<CrawlItem>,
+    pub(crate) receiver: mpsc::Receiver<ScanMessage>,
+    /// Общий HTTP-клиент (секция http)
+    #[builder(default)]
+    pub(crate) client: Client,
+    #[builder(default)]
+    pub(crate) in_progress: InProgress,
+    #[builder(default)]
+    pub(crate) published_projects: PublishedProjects,
+    #[builder(default)]
+    pub(crate) dry_run: bool,
+    /// Работа и занятость элементом (GET /healthz)
+    #[builder(default)]
+    pub(crate) liveness: Liveness,
 }
 
 impl WorkerSubsystem {
     pub async fn run(self, subsys: SubsystemHandle) -> std::io::Result<()> {
         info!("Starting Worker subsystem");
+        let _running = self.liveness.running();
 
         let worker = Worker::builder()
             .config(self.config.clone())
@@ -33,6 +55,8 @@ impl WorkerSubsystem {
             .maybe_telegram_api(self.telegram_api.as_ref().map(Arc::clone))
             .maybe_target_chat_id(self.target_chat_id.clone())
             .cache_manager(Arc::clone(&self.cache_manager))
+            .client(self.client.clone())
+            .dry_run(self.dry_run)
             .build()
             .await?;
 
@@ -41,22 +65,76 @@ impl WorkerSubsystem {
             .run
             .as_ref()
             .and_then(|r| r.max_posts_per_run);
+        // crawler.poll_interval_secs: worker работает до завершения процесса, а run.max_posts_per_run
+        // ограничивает публикации одного цикла обхода; лишние элементы ждут следующего цикла
+        let continuous = self.config.crawler.poll_interval_secs.is_some();
+        let report_path = self.config.run.as_ref().and_then(|r| r.report_path.clone());
+        let report = Arc::new(Mutex::new(RunReport {
+            started_at: chrono::Utc::now().to_rfc3339(),
+            ..Default::default()
+        }));
+        let in_progress = Arc::clone(&self.in_progress);
+        let item_report = Arc::clone(&report);
 
         let fut = async move {
+            let report = item_report;
             let mut rx = self.receiver;
+            let in_progress = self.in_progress;
+            let published_projects = self.published_projects;
+            let dry_run = self.dry_run;
+            let liveness = self.liveness;
             let mut published_count = 0;
+            let mut cycle = 0;
 
             loop {
                 // Ожидаем сообщения из канала без таймаутов
                 match rx.recv().await {
-                    Some(item) => {
+                    Some(ScanMessage::CycleStarted(started)) => {
+                        cycle = started;
+                        if continuous {
+                            published_count = 0;
+                        }
+                    }
+                    Some(ScanMessage::Item(item)) => {
+                        if continuous && max_posts_per_run.is_some_and(|limit| published_count >= limit) {
+                            info!(cycle, title = %item.title, "max_posts_per_run reached for this cycle, item deferred to next cycle");
+                            continue;
+                        }
                         info!("received item from npa crawler: {}", item.title);
-                        let count = worker.process_item(item).await?;
+                        let item_id = item.project_id.clone().unwrap_or_else(|| item.url.clone());
+                        report.lock().unwrap().received += 1;
+                        *in_progress.lock().unwrap() = Some(format!("{} ({})", item.title, item.url));
+                        let busy = liveness.busy();
+                        let count = match worker.process_item(item).await {
+                            Ok(count) => count,
+                            Err(e) => {
+                                report.lock().unwrap().failed_items.push(item_id);
+                                return Err(e);
+                            }
+                        };
+                        drop(busy);
+                        *in_progress.lock().unwrap() = None;
                         published_count += count;
+                        if dry_run {
+                            // --dry-run: отрендеренные посты не считаются опубликованными
+                            if count > 0 {
+                                let mut report = report.lock().unwrap();
+                                report.dry_run_projects += count;
+                                report.dry_run_items.push(item_id);
+                            }
+                        } else {
+                            published_projects.fetch_add(count, Ordering::SeqCst);
+                            if count > 0 {
+                                let mut report = report.lock().unwrap();
+                                report.published_projects += count;
+                                report.published_items.push(item_id);
+                            }
+                        }
                         
-                        // Если задан лимит постов, завершаем после обработки
+                        // Если задан лимит публикаций (проектов, а не постов по каналам), завершаем после обработки
+                        // (в режиме опроса — ждем следующего цикла)
                         if let Some(limit) = max_posts_per_run {
-                            if published_count >= limit {
+                            if published_count >= limit && !continuous {
                                 break;
                             }
                         }
@@ -71,18 +149,64 @@ impl WorkerSubsystem {
             Ok::<(), std::io::Error>(())
         };
 
-        match fut.cancel_on_shutdown(&subsys).await {
+        let result = match fut.cancel_on_shutdown(&subsys).await {
             Ok(Ok(())) => {
                 info!("Worker subsystem finished");
                 // Запрашиваем завершение прочих подсистем
                 subsys.request_shutdown();
+                Ok(())
+            }
+            Ok(Err(e)) => Err(e),
+            Err(CancelledByShutdown) => {
+                info!("Worker subsystem cancelled by shutdown");
+                report.lock().unwrap().interrupted = true;
+                Ok(())
+            }
+        };
+
+        // Отчет пишется при любом завершении, в том числе по сигналу (в пределах окна штатного завершения)
+        if let Some(path) = report_path {
+            let mut report = report.lock().unwrap();
+            report.finished_at = Some(chrono::Utc::now().to_rfc3339());
+            report.in_progress = in_progress.lock().unwrap().clone();
+            match write_report(Path::new(&path), &report) {
+                Ok(()) => info!(path = %path, "run report written"),
+                Err(e) => error!(path = %path, error = %e, "failed to write run report"),
             }
-            Ok(Err(e)) => return Err(e),
-            Err(CancelledByShutdown) => info!("Worker subsystem cancelled by shutdown"),
         }
 
-        Ok(())
+        result
     }
 }
 
+/// Итоги запуска для run.report_path
+#[derive(Debug, Default, Serialize)]
+struct RunReport {
+    started_at: String,
+    finished_at: Option<String>,
+    /// Получено элементов от краулера
+    received: usize,
+    /// Опубликовано проектов (хотя бы в один канал; проект в нескольких каналах считается один раз)
+    published_projects: usize,
+    published_items: Vec<String>,
+    /// Отрендерено проектов в --dry-run (не опубликованы)
+    dry_run_projects: usize,
+    dry_run_items: Vec<String>,
+    failed_items: Vec<String>,
+    /// Элемент, обработка которого прервана завершением
+    in_progress: Option<String>,
+    /// Запуск остановлен запросом завершения (сигнал, watchdog), а не исчерпанием работы
+    interrupted: bool,
+}
 
+/// Записывает отчет через временный файл и rename, чтобы не оставить частично записанный JSON
+fn write_report(path: &Path, report: &RunReport) -> std::io::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(report)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, json)?;
+    std::fs::rename(&tmp, path)
+}
@@ -1,7 +1,10 @@
 use async_trait::async_trait;
 use crate::models::types::CacheMetadata;
 use crate::models::channel::PublisherChannel;
-use crate::models::types::{SummaryText, PostText, MetadataItem};
+use crate::models::types::{DocumentValidators, SummaryText, PostText, MetadataItem, ThreadProgress};
+
+/// Изменение manifest для `CacheManager::update_manifest`
+pub type ManifestUpdate = Box<dyn FnOnce(&mut crate::models::types::Manifest) + Send>;
 
 /// Trait для управления кэшем артефактов обработки
 #[async_trait]
@@ -50,6 +53,90 @@ pub trait CacheManager: Send + Sync {
         channel: PublisherChannel,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
 
+    /// Фиксирует успешную публикацию одной транзакцией: суммаризация, пост и статус канала
+    /// записываются вместе, без промежуточных состояний
+    async fn mark_published(
+        &self,
+        project_id: &str,
+        channel: PublisherChannel,
+        summary_text: Option<&str>,
+        post_text: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Сохраняет HTTP-валидаторы документа и (если переданы) метаданные краулера,
+    /// не трогая markdown и суммаризации
+    async fn update_document_state(
+        &self,
+        project_id: &str,
+        validators: Option<&DocumentValidators>,
+        crawl_metadata: &[MetadataItem],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Отмечает элемент как пропущенный без публикации (с причиной); такой элемент считается обработанным
+    async fn mark_skipped(
+        &self,
+        project_id: &str,
+        reason: &str,
+        crawl_metadata: &[crate::models::types::MetadataItem],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Отмечает попытку обработки, прерванную по run.item_timeout_secs; возвращает число таких попыток
+    async fn record_timeout(
+        &self,
+        project_id: &str,
+        crawl_metadata: &[crate::models::types::MetadataItem],
+    ) -> Result<u32, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Сохраняет результаты публикации по адресатам канала (адресат -> успех), заменяя прежние
+    async fn record_target_results(
+        &self,
+        project_id: &str,
+        channel: PublisherChannel,
+        results: &[(String, bool)],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Сохраняет опубликованные части цепочки статусов поста канала, заменяя прежние
+    async fn record_thread_progress(
+        &self,
+        project_id: &str,
+        channel: PublisherChannel,
+        progress: &ThreadProgress,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Сохраняет заголовок элемента из источника
+    async fn record_title(
+        &self,
+        project_id: &str,
+        title: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Удаляет суммаризации и посты каналов проекта, сохраняя документ, метаданные краулера
+    /// и статус публикации. Возвращает false, если проекта нет в кэше
+    async fn clear_summaries(&self, project_id: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Возвращает project_id всех проектов, для которых в кэше есть metadata.json
+    /// (в файловом кэше до run.cache_layout: v2 — и проектов в плоских файлах старого формата)
+    async fn list_project_ids(&self) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Удаляет проекты, созданные раньше `ttl` назад (по created_at) и полностью опубликованные
+    /// во все `enabled_channels` (или пропущенные). Возвращает число удаленных проектов
+    async fn prune_expired(
+        &self,
+        ttl: std::time::Duration,
+        enabled_channels: &[PublisherChannel],
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Ставит маркер in_progress на проект. Возвращает false, если проект уже обрабатывает
+    /// другой экземпляр (маркер моложе `ttl`); устаревший маркер перехватывается
+    async fn try_claim(
+        &self,
+        project_id: &str,
+        ttl: std::time::Duration,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Снимает маркер in_progress, поставленный этим экземпляром
+    async fn release_claim(&self, project_id: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
     /// Атомарно обновляет данные канала (суммаризацию, пост и статус публикации)
     async fn update_channel_data(
         &self,
@@ -129,8 +216,12 @@ pub trait CacheManager: Send + Sync {
     /// Сохраняет manifest
     async fn save_manifest(&self, manifest: &crate::models::types::Manifest) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
 
-    /// Обновляет min_published_project_id в manifest
-    async fn update_min_published_project_id(&self, min_id: u32) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    /// Читает, изменяет и записывает manifest как одну операцию, чтобы одновременные экземпляры
+    /// не теряли изменения друг друга. Возвращает записанный manifest
+    async fn update_manifest(&self, update: ManifestUpdate) -> Result<crate::models::types::Manifest, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Обновляет min_published_project_id источника `source_id` в manifest
+    async fn update_min_published_project_id(&self, source_id: &str, min_id: u32) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
 
     /// Атомарно обновляет все данные каналов для проекта
     async fn update_all_channels_data(
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use async_trait::async_trait;
 
 /// Defines the interface for a chat-based language model API (e.g., OpenAI, LocalAI).
@@ -11,6 +13,25 @@ use async_trait::async_trait;
 pub trait ChatApi: Send + Sync {
     /// Sends a prompt to a chat API and returns the assistant's response.
     async fn call_chat_api(&self, prompt: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Whether `call_chat_api_streaming` really streams the response. Callers drop the overall
+    /// request timeout only for streaming backends.
+    fn supports_streaming(&self) -> bool {
+        false
+    }
+
+    /// Streaming variant of `call_chat_api`: the response is accumulated from chunks and the call
+    /// fails only when no chunk arrives within `idle_timeout`.
+    ///
+    /// The default implementation makes a regular (non-streaming) request.
+    async fn call_chat_api_streaming(
+        &self,
+        prompt: &str,
+        idle_timeout: Duration,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let _ = idle_timeout;
+        self.call_chat_api(prompt).await
+    }
 }
 
 
@@ -5,6 +5,8 @@ use crate::models::types::CrawlItem;
 
 #[async_trait]
 pub trait Crawler: Send + Sync {
+    /// Стабильный идентификатор источника: ключ состояния в manifest.json и поле `source` в логах
+    fn source_id(&self) -> String;
     async fn fetch_stream(&self, sender: mpsc::Sender<CrawlItem>) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
 }
 
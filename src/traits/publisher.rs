@@ -3,7 +3,8 @@ use std::error::Error;
 
 #[async_trait]
 pub trait Publisher: Send + Sync {
-    fn name(&self) -> &str;
+    /// Stable publisher name for logs and metric labels
+    fn name(&self) -> &'static str;
     async fn publish(&self, title: &str, url: &str, text: &str) -> Result<(), Box<dyn Error + Send + Sync>>;
 }
 
@@ -8,6 +8,17 @@ use async_trait::async_trait;
 pub trait TelegramApi: Send + Sync {
     /// Sends a text message to a specified Telegram chat.
     async fn send_telegram_message(&self, chat_id: i64, text: String) -> Result<(), String>;
+
+    /// Sends a file as a document with the given caption to a specified Telegram chat.
+    /// `mime_type` is the content type of the uploaded file.
+    async fn send_telegram_document(
+        &self,
+        chat_id: i64,
+        file_name: String,
+        mime_type: String,
+        bytes: Vec<u8>,
+        caption: String,
+    ) -> Result<(), String>;
     
     /// Returns the client for this API instance
     fn client(&self) -> &reqwest::Client;
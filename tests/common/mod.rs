@@ -110,6 +110,18 @@ pub async fn mount_npalist_with_error(server: &MockServer) {
     server.register(mock).await;
 }
 
+#[allow(dead_code)]
+pub async fn mount_atom(server: &MockServer) {
+    let atom_xml = fs::read_to_string(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/mocks/atom.xml"),
+    )
+    .unwrap();
+    let mock = Mock::given(method("GET"))
+        .and(path("/feed.atom"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(atom_xml));
+    server.register(mock).await;
+}
+
 pub async fn mount_stages(server: &MockServer, stages_json: &str) {
     let mock = Mock::given(method("GET"))
         .and(path_regex(r"/api/public/PublicProjects/GetProjectStages/\d+"))
@@ -131,6 +143,21 @@ pub async fn mount_docx(server: &MockServer) {
     server.register(mock).await;
 }
 
+#[allow(dead_code)]
+pub async fn mount_pdf(server: &MockServer) {
+    let pdf_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/mocks/source.pdf");
+    let pdf_content = fs::read(&pdf_path).unwrap();
+
+    let mock = Mock::given(method("GET"))
+        .and(path_regex(r"/api/public/Files/GetFile"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "application/pdf")
+                .set_body_bytes(pdf_content)
+        );
+    server.register(mock).await;
+}
+
 #[allow(dead_code)]
 pub async fn mount_gemini_generate(server: &MockServer) {
     let response_body = fs::read_to_string(
@@ -0,0 +1,81 @@
+use luminis::run_with_config_path;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use std::fs;
+use std::path::PathBuf;
+use wiremock::matchers::{method, path_regex, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use crate::common::{mount_docx, mount_gemini_generate, mount_stages, read_mocks, render_config};
+
+/// Тест проверяет, что filter.max_age_days пропускает старый элемент (фиксируя его в кэше),
+/// а свежий элемент публикуется
+#[tokio::test]
+#[serial]
+async fn test_old_item_skipped_recent_published() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    // Первый проект (160532) становится "старым"
+    let npalist_xml = fs::read_to_string(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/mocks/npalist.xml"),
+    )
+    .unwrap()
+    .replace("2025-09-20T17:07:27.95Z", "2001-01-01T00:00:00Z");
+    Mock::given(method("GET"))
+        .and(path_regex(r"/api/npalist/"))
+        .and(query_param("offset", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(npalist_xml))
+        .mount(&server)
+        .await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let mut cfg_text = fs::read_to_string(cfg_file.path()).unwrap();
+    cfg_text.push_str("filter:\n  max_age_days: 3650\n");
+    fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(predicate::str::contains("https://regulation.gov.ru/projects/160531"));
+    output_file.assert(predicate::str::contains("projects/160532").not());
+
+    // Старый элемент записан в кэш как пропущенный
+    let meta: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(cache.child("160532/metadata.json").path()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(meta["skip_reason"].as_str().unwrap_or("").starts_with("older than 3650 days"), true);
+    assert_eq!(meta["published_channels"].as_array().map(|a| a.len()), Some(0));
+
+    let docx_for_old = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|req| req.url.path() == "/api/public/PublicProjects/GetProjectStages/160532")
+        .count();
+    assert_eq!(docx_for_old, 0, "old item must not be downloaded");
+}
@@ -0,0 +1,38 @@
+use luminis::models::config::{AppConfig, DEFAULT_POST_TEMPLATE, LogFormat, OutputConfig, RunConfig};
+use pretty_assertions::assert_eq;
+
+/// Тест проверяет, что AppConfig::default() проходит validate после включения одного канала,
+/// а без каналов validate сообщает, чего не хватает
+#[test]
+fn test_default_config_validates_with_one_channel() {
+    let cfg = AppConfig::default();
+    let err = cfg.validate().expect_err("default config has no enabled channel");
+    assert!(err.contains("no publishing channel is enabled"), "unexpected error: {}", err);
+
+    let cfg = AppConfig {
+        output: Some(OutputConfig { console_enabled: Some(true), ..Default::default() }),
+        ..AppConfig::default()
+    };
+    assert_eq!(cfg.validate(), Ok(()));
+    assert_eq!(cfg.run.as_ref().and_then(|r| r.post_template.as_deref()), Some(DEFAULT_POST_TEMPLATE));
+    assert_eq!(cfg.crawler.npalist.as_ref().map(|n| n.enabled), Some(Some(true)));
+}
+
+/// Тест проверяет, что builder задает только переданные секции: без run.post_template конфигурация невалидна
+#[test]
+fn test_builder_config_requires_post_template() {
+    let cfg = AppConfig::builder()
+        .output(OutputConfig { file_enabled: Some(true), console_enabled: Some(false), ..Default::default() })
+        .build();
+    let err = cfg.validate().expect_err("builder config has no post template");
+    assert!(err.contains("run.post_template"), "unexpected error: {}", err);
+}
+
+/// Тест проверяет разбор run.log_format: по умолчанию text, значение json принимается
+#[test]
+fn test_run_log_format_parses() {
+    let run: RunConfig = serde_yaml::from_str("log_format: json").unwrap();
+    assert_eq!(run.log_format, Some(LogFormat::Json));
+    assert_eq!(RunConfig::default().log_format.unwrap_or_default(), LogFormat::Text);
+    assert!(serde_yaml::from_str::<RunConfig>("log_format: xml").is_err());
+}
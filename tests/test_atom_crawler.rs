@@ -0,0 +1,82 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use luminis::crawlers::{AtomCrawler, crawl_to_vec};
+use luminis::models::channel::PublisherChannel;
+use luminis::services::cache_manager_impl::FileSystemCacheManager;
+use luminis::traits::cache_manager::CacheManager;
+use pretty_assertions::assert_eq;
+use wiremock::MockServer;
+
+mod common;
+
+use crate::common::mount_atom;
+
+/// Тест проверяет разбор ленты Atom: ссылки, project_id по regex, текст из summary/content и метку источника
+#[tokio::test]
+async fn test_atom_feed_entries() {
+    let server = MockServer::start().await;
+    mount_atom(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let cache_manager = Arc::new(
+        FileSystemCacheManager::builder()
+            .cache_dir(temp_dir.path().to_str().unwrap().to_string())
+            .build(),
+    );
+
+    let crawler = AtomCrawler::builder()
+        .url(format!("{}/feed.atom", server.uri()))
+        .project_id_re(regex::Regex::new(r"projects/(\d{5,})").unwrap())
+        .source_label("[atom]".to_string())
+        .timeout(Duration::from_secs(2))
+        .cache_manager(cache_manager)
+        .enabled_channels(vec![PublisherChannel::File])
+        .build()
+        .unwrap();
+
+    let items = crawl_to_vec(&crawler).await.unwrap();
+
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[0].project_id.as_deref(), Some("160532"));
+    assert_eq!(items[0].url, "https://regulation.gov.ru/projects/160532");
+    assert_eq!(items[0].body, "Тестовый проект\nВид: \"Проект федерального закона\"");
+    assert_eq!(items[0].source_label.as_deref(), Some("[atom]"));
+    assert!(items[0].publish_date().is_some());
+    assert_eq!(items[1].project_id.as_deref(), Some("160632"));
+    assert!(items[1].body.ends_with("Процедура: \"Раскрытие информации о подготовке проектов нормативных правовых актов\""));
+    // Записи без ссылки URL берется из <id>, project_id не найден
+    assert_eq!(items[2].url, "https://example.org/news/7");
+    assert_eq!(items[2].project_id, None);
+}
+
+/// Тест проверяет, что полностью опубликованные записи ленты не отправляются повторно
+#[tokio::test]
+async fn test_atom_skips_fully_published_entries() {
+    let server = MockServer::start().await;
+    mount_atom(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let cache_manager = Arc::new(
+        FileSystemCacheManager::builder()
+            .cache_dir(temp_dir.path().to_str().unwrap().to_string())
+            .build(),
+    );
+    cache_manager
+        .mark_published("160532", PublisherChannel::File, Some("summary"), "post")
+        .await
+        .unwrap();
+
+    let crawler = AtomCrawler::builder()
+        .url(format!("{}/feed.atom", server.uri()))
+        .project_id_re(regex::Regex::new(r"projects/(\d{5,})").unwrap())
+        .timeout(Duration::from_secs(2))
+        .cache_manager(cache_manager)
+        .enabled_channels(vec![PublisherChannel::File])
+        .build()
+        .unwrap();
+
+    let items = crawl_to_vec(&crawler).await.unwrap();
+    let ids: Vec<Option<&str>> = items.iter().map(|i| i.project_id.as_deref()).collect();
+    assert_eq!(ids, vec![Some("160632"), None]);
+}
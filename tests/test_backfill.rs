@@ -0,0 +1,120 @@
+use luminis::backfill;
+use luminis::models::channel::PublisherChannel;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use wiremock::MockServer;
+
+mod common;
+
+use crate::common::{mount_docx, mount_gemini_generate, mount_stages, read_mocks, render_config};
+
+/// Тест проверяет, что backfill публикует проект в файловый канал без обращения к списку НПА,
+/// а повторный backfill без --title публикует его снова с заголовком из кэша, хотя канал уже
+/// отмечен опубликованным
+#[tokio::test]
+#[serial]
+async fn test_backfill_publishes_project_to_requested_channel() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        true,  // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_path = cfg_file.path().to_str().unwrap();
+
+    let published = backfill(cfg_path, "160532", Some("Тестовый проект"), &[PublisherChannel::File]).await.unwrap();
+    assert_eq!(published, 1, "project must be published");
+    output_file.assert(predicate::str::contains("https://regulation.gov.ru/projects/160532"));
+    output_file.assert(predicate::str::contains("Тестовый проект"));
+
+    // Повторная публикация принудительная: файл создается заново, заголовок берется из кэша
+    std::fs::remove_file(output_file.path()).unwrap();
+    let published = backfill(cfg_path, "160532", None, &[PublisherChannel::File]).await.unwrap();
+    assert_eq!(published, 1, "backfill must re-post to an already published channel");
+    output_file.assert(predicate::str::contains("https://regulation.gov.ru/projects/160532"));
+    output_file.assert(predicate::str::contains("Тестовый проект"));
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(
+        requests.iter().any(|r| r.url.path().contains("/api/npalist/")),
+        false,
+        "backfill must bypass the crawler"
+    );
+}
+
+/// Тест проверяет, что backfill в выключенный канал завершается ошибкой до обработки проекта
+#[tokio::test]
+#[serial]
+async fn test_backfill_rejects_disabled_channel() {
+    let server = MockServer::start().await;
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &server.uri(),
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+
+    let err = backfill(cfg_file.path().to_str().unwrap(), "160532", None, &[PublisherChannel::Telegram])
+        .await
+        .expect_err("disabled channel must be rejected");
+    assert_eq!(err.to_string(), "backfill: channel telegram is not enabled in config");
+    assert_eq!(server.received_requests().await.unwrap().len(), 0);
+}
+
+/// Тест проверяет, что backfill без --title завершается ошибкой, если заголовка проекта нет в кэше:
+/// stages endpoint заголовок не возвращает
+#[tokio::test]
+#[serial]
+async fn test_backfill_requires_title_when_not_cached() {
+    let server = MockServer::start().await;
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &server.uri(),
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+
+    let err = backfill(cfg_file.path().to_str().unwrap(), "160532", None, &[PublisherChannel::File])
+        .await
+        .expect_err("backfill without a title must fail");
+    assert_eq!(
+        err.to_string(),
+        "backfill: title of project 160532 is not in cache, pass it with --title"
+    );
+    assert_eq!(server.received_requests().await.unwrap().len(), 0);
+    output_file.assert(predicate::path::missing());
+}
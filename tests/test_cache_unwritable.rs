@@ -0,0 +1,43 @@
+use luminis::run_with_config_path;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use pretty_assertions::assert_eq;
+use wiremock::MockServer;
+
+mod common;
+
+use crate::common::render_config;
+
+/// Тест проверяет, что недоступный для записи каталог кэша — понятная ошибка запуска
+/// до обращения к источнику. Каталог кэша вложен в обычный файл, поэтому запись
+/// невозможна даже под root (в отличие от прав 0555)
+#[tokio::test]
+#[serial]
+async fn test_unwritable_cache_dir_fails_startup() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let blocker = temp_dir.child("readonly");
+    blocker.write_str("not a directory").unwrap();
+    let cache = blocker.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+
+    let err = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .expect_err("unwritable cache dir must fail startup");
+    assert_eq!(err.to_string().contains("is not writable"), true, "unexpected error: {}", err);
+    assert_eq!(err.to_string().contains(cache.path().to_str().unwrap()), true, "error must name the cache dir: {}", err);
+    assert_eq!(server.received_requests().await.unwrap().is_empty(), true);
+}
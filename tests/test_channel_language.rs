@@ -0,0 +1,85 @@
+use luminis::run_with_config_path;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use wiremock::matchers::{body_string_contains, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use crate::common::{mount_docx, mount_npalist, mount_stages, read_mocks, render_config};
+
+/// Ответ Gemini с заданным текстом суммаризации
+fn gemini_response(text: &str) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(serde_json::json!({
+        "candidates": [{
+            "content": { "parts": [{ "text": text }], "role": "model" },
+            "finishReason": "STOP"
+        }],
+        "modelVersion": "gemini-2.0-flash"
+    }))
+}
+
+/// Тест проверяет, что каналы с разными channels.<name>.language получают суммаризации
+/// на своих языках: промпт каждого канала содержит свой язык и попадает в свой мок Gemini
+#[tokio::test]
+#[serial]
+async fn test_channels_get_summaries_in_their_languages() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    Mock::given(method("POST"))
+        .and(path("/v1beta/models/gemini-2.0-flash:generateContent"))
+        .and(body_string_contains("Язык ответа: en"))
+        .respond_with(gemini_response("Health insurance law amendments"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1beta/models/gemini-2.0-flash:generateContent"))
+        .and(body_string_contains("Язык ответа: ru"))
+        .respond_with(gemini_response("Поправки в закон об ОМС"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        true,  // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let mut cfg_text = std::fs::read_to_string(cfg_file.path()).unwrap();
+    cfg_text.push_str("channels:\n  file:\n    language: en\n  console:\n    language: ru\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(predicate::str::contains("Health insurance law amendments"));
+    output_file.assert(predicate::str::contains("Поправки в закон об ОМС").not());
+
+    let llm_calls = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|r| r.url.path().contains("generateContent"))
+        .count();
+    // Моки с expect(1) дополнительно проверяют при завершении, что каждый язык запрошен ровно один раз
+    assert_eq!(llm_calls, 2, "each channel must be summarized separately");
+}
@@ -11,7 +11,7 @@ mod common;
 
 use crate::common::{
     mount_docx, mount_mastodon, mount_npalist, mount_stages,
-    mount_telegram, read_mocks, mount_gemini_generate_with_limit,
+    mount_telegram, read_mocks, mount_gemini_generate, mount_gemini_generate_with_limit,
     prepopulate_channel_cache, render_config_with_channels, render_config_with_custom_limits,
 };
 
@@ -399,3 +399,286 @@ async fn test_channel_summarization_cache_reuse() {
     // Verify other mocks
     server.verify().await;
 }
+
+/// Тест проверяет, что channels.<name>.style передается в промпт суммаризации каждого канала
+#[tokio::test]
+#[serial]
+async fn test_channel_style_sent_to_llm() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config_with_channels(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        true,  // console_enabled
+        true,  // file_enabled
+    );
+    let mut cfg_text = std::fs::read_to_string(cfg_file.path()).unwrap();
+    cfg_text.push_str("channels:\n  console:\n    style: неформально, с эмодзи\n  file:\n    style: официально-деловой\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let received_requests = server.received_requests().await.unwrap();
+    let llm_bodies: Vec<String> = received_requests
+        .iter()
+        .filter(|req| req.url.path().contains("generateContent"))
+        .map(|req| {
+            // Тело запроса — JSON, приводим к тексту без экранирования
+            let v: serde_json::Value = serde_json::from_slice(&req.body).unwrap();
+            v.to_string()
+        })
+        .collect();
+
+    let casual: Vec<_> = llm_bodies.iter().filter(|b| b.contains("неформально, с эмодзи")).collect();
+    let formal: Vec<_> = llm_bodies.iter().filter(|b| b.contains("официально-деловой")).collect();
+    assert_eq!(casual.len(), 1, "console summary should use casual style");
+    assert_eq!(formal.len(), 1, "file summary should use formal style");
+    assert_eq!(casual[0].contains("официально-деловой"), false);
+    assert_eq!(formal[0].contains("неформально"), false);
+}
+
+/// Тест проверяет run.combine_identical_channels: каналы с одинаковым лимитом и стилем
+/// получают одну суммаризацию (один вызов LLM), а публикация идет в оба канала
+#[tokio::test]
+#[serial]
+async fn test_identical_channels_share_one_summary() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+    mount_telegram(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config_with_custom_limits(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        true,  // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        1000,  // telegram_max_chars
+        500,   // mastodon_max_chars
+        1000,  // console_max_chars
+        1000,  // file_max_chars
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("run:\n", "run:\n  combine_identical_channels: true\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let received_requests = server.received_requests().await.unwrap();
+    let llm_calls = received_requests
+        .iter()
+        .filter(|req| req.url.path().contains("generateContent"))
+        .count();
+    assert_eq!(llm_calls, 1, "identically configured channels must share one summary");
+    assert_eq!(
+        received_requests.iter().filter(|req| req.method == Method::POST && req.url.path().contains("sendMessage")).count(),
+        1
+    );
+    output_file.assert(predicate::str::contains("https://regulation.gov.ru/projects/160532"));
+}
+
+/// Тест проверяет, что для канала без своего лимита применяется channels.default_limit,
+/// а явные лимиты каналов не меняются
+#[test]
+fn test_channels_default_limit_applied_without_channel_limit() {
+    use luminis::models::channel::PublisherChannel;
+    use luminis::services::channels::ChannelManager;
+    use luminis::services::settings::load_config;
+
+    let cfg = common::render_config("http://localhost", "./post.txt", "./cache", true, false, true, false, true);
+    let text = std::fs::read_to_string(cfg.path()).unwrap();
+    // Убираем секцию telegram: у канала не остается своего лимита
+    let start = text.find("telegram:\n").unwrap();
+    let end = text.find("mastodon:\n").unwrap();
+    let text = format!("{}{}\nchannels:\n  default_limit: 777\n", &text[..start], &text[end..]);
+    std::fs::write(cfg.path(), text).unwrap();
+
+    let config = load_config(cfg.path()).unwrap();
+    let manager = ChannelManager::builder().config(&config).build();
+
+    assert_eq!(manager.get_channel_limit(PublisherChannel::Telegram), None);
+    assert_eq!(manager.channel_limit_or_default(PublisherChannel::Telegram), 777);
+    assert_eq!(manager.channel_limit_or_default(PublisherChannel::Mastodon), 495);
+}
+
+/// Тест проверяет, что смена llm.model делает кэшированные суммаризации и посты недействительными:
+/// суммаризация генерируется заново, а в метаданные записывается новая модель
+#[tokio::test]
+#[serial]
+async fn test_model_change_regenerates_cached_summary() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    // Кэш с суммаризацией и постом канала file, сделанными прежней моделью, без публикации
+    let metadata = serde_json::json!({
+        "project_id": "160532",
+        "docx_path": "",
+        "markdown_path": "",
+        "published_channels": [],
+        "created_at": chrono::Utc::now().to_rfc3339(),
+        "channel_summaries": { "File": "Суммаризация прежней модели" },
+        "channel_posts": { "File": "Пост прежней модели" },
+        "crawl_metadata": [],
+        "summary_model": "gemini-1.5-flash"
+    });
+    cache.child("160532").child("metadata.json").write_str(&serde_json::to_string_pretty(&metadata).unwrap()).unwrap();
+
+    let cfg_file = render_config_with_channels(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+    );
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let received = server.received_requests().await.unwrap();
+    assert!(
+        received.iter().any(|r| r.url.path().contains(":generateContent")),
+        "summary made by another model must be regenerated"
+    );
+    output_file.assert(predicate::str::contains("Поправки в закон об ОМС"));
+    output_file.assert(predicate::str::contains("прежней модели").not());
+
+    let saved: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(cache.path().join("160532").join("metadata.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(saved["summary_model"], "gemini-2.0-flash");
+    assert_eq!(saved["published_channels"], serde_json::json!(["File"]));
+}
+
+/// Тест проверяет, что summarizer.test_fixed_summary подставляет фиксированную суммаризацию
+/// в пост без единого запроса к LLM
+#[tokio::test]
+#[serial]
+async fn test_fixed_summary_published_without_llm() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config_with_channels(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+    );
+    let mut yaml = std::fs::read_to_string(cfg_file.path()).unwrap();
+    yaml.push_str("\nsummarizer:\n  test_fixed_summary: \"Фиксированная тестовая суммаризация\"\n");
+    std::fs::write(cfg_file.path(), yaml).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(predicate::str::contains("Фиксированная тестовая суммаризация"));
+    let received = server.received_requests().await.unwrap();
+    assert!(
+        !received.iter().any(|r| r.url.path().contains(":generateContent")),
+        "no LLM request expected with test_fixed_summary"
+    );
+}
+
+/// Тест проверяет, что summarizer.input_source: both передает в LLM поля элемента краулера,
+/// а за ними текст документа
+#[tokio::test]
+#[serial]
+async fn test_input_source_both_concatenates_body_and_document() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config_with_channels(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+    );
+    let mut yaml = std::fs::read_to_string(cfg_file.path()).unwrap();
+    yaml.push_str("\nsummarizer:\n  input_source: both\n");
+    std::fs::write(cfg_file.path(), yaml).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let received = server.received_requests().await.unwrap();
+    let request = received
+        .iter()
+        .find(|r| r.url.path().contains(":generateContent"))
+        .expect("LLM must be called");
+    let prompt = String::from_utf8_lossy(&request.body).to_string();
+    let body_pos = prompt.find("Ведомство: Минздрав России").expect("prompt must contain item body");
+    let document_pos = prompt
+        .find("Собрание законодательства Российской Федерации")
+        .expect("prompt must contain document text");
+    assert!(body_pos < document_pos, "item body must precede document text");
+}
@@ -0,0 +1,68 @@
+use luminis::models::types::content_hash;
+use luminis::run_with_config_path;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use crate::common::{mount_docx, mount_gemini_generate, mount_npalist, mount_stages, read_mocks, render_config};
+
+const FILE_ID: &str = r#""fileId":"b3d99703-8b7a-4f72-bc39-c144792e97fa""#;
+
+/// Тест проверяет, что при crawler.verify_checksum документ с несовпадающей контрольной суммой
+/// пропускается, а документ с верной суммой публикуется
+#[tokio::test]
+#[serial]
+async fn test_checksum_mismatch_skips_item() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+    let docx = fs::read(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/mocks/source.docx")).unwrap();
+
+    // Для 160532 источник публикует неверную сумму
+    let bad_stages = stages_json.replace(FILE_ID, &format!(r#"{},"sha256":"{}""#, FILE_ID, "0".repeat(64)));
+    Mock::given(method("GET"))
+        .and(path("/api/public/PublicProjects/GetProjectStages/160532"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(bad_stages))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    // Для остальных проектов — верную
+    let good_stages = stages_json.replace(FILE_ID, &format!(r#"{},"sha256":"{}""#, FILE_ID, content_hash(&docx)));
+    mount_stages(&server, &good_stages).await;
+    mount_npalist(&server).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = fs::read_to_string(cfg_file.path()).unwrap()
+        .replace("crawler:\n", "crawler:\n  verify_checksum: true\n");
+    fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(predicate::str::contains("https://regulation.gov.ru/projects/160531"));
+    output_file.assert(predicate::str::contains("projects/160532").not());
+    // Документ с несовпадающей суммой не попадает в кэш
+    cache.child("160532/extracted.md").assert(predicate::path::missing());
+}
@@ -0,0 +1,190 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use luminis::crawlers::{NpaListCrawler, crawl_to_vec};
+use luminis::models::channel::PublisherChannel;
+use luminis::models::types::Manifest;
+use luminis::services::cache_manager_impl::FileSystemCacheManager;
+use luminis::traits::cache_manager::CacheManager;
+use luminis::traits::crawler::Crawler;
+use pretty_assertions::assert_eq;
+use wiremock::MockServer;
+
+mod common;
+
+use crate::common::mount_npalist;
+
+/// Тест проверяет использование NpaListCrawler напрямую, без подсистем приложения
+#[tokio::test]
+async fn test_crawl_to_vec_against_mock() {
+    let server = MockServer::start().await;
+    mount_npalist(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let cache_manager = Arc::new(
+        FileSystemCacheManager::builder()
+            .cache_dir(temp_dir.path().to_str().unwrap().to_string())
+            .build(),
+    );
+
+    let crawler = NpaListCrawler::builder()
+        .url_template(format!("{}/api/npalist/?limit={{limit}}&offset={{offset}}&sort=desc", server.uri()))
+        .project_id_re(regex::Regex::new(r"(\d{5,})").unwrap())
+        .timeout(Duration::from_secs(2))
+        .cache_manager(cache_manager)
+        .poll_delay(Duration::from_secs(0))
+        .enabled_channels(vec![PublisherChannel::File])
+        .build()
+        .unwrap();
+
+    let items = crawl_to_vec(&crawler).await.unwrap();
+
+    assert_eq!(items.len(), 50);
+    assert_eq!(items[0].project_id.as_deref(), Some("160532"));
+    assert_eq!(items[0].url, "https://regulation.gov.ru/projects/160532");
+    assert_eq!(items[0].stage(), Some("Текст"));
+}
+
+/// Тест проверяет, что regex и шаблон URL проекта задаются отдельно для каждого источника
+#[tokio::test]
+async fn test_per_source_project_id_regex_and_url_template() {
+    let server = MockServer::start().await;
+    mount_npalist(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let crawler_for = |cache: &str, url_template: &str, re: &str, project_url_template: Option<&str>| {
+        NpaListCrawler::builder()
+            .url_template(url_template.to_string())
+            .project_id_re(regex::Regex::new(re).unwrap())
+            .maybe_project_url_template(project_url_template.map(str::to_string))
+            .timeout(Duration::from_secs(2))
+            .cache_manager(Arc::new(
+                FileSystemCacheManager::builder()
+                    .cache_dir(temp_dir.path().join(cache).to_string_lossy().to_string())
+                    .build(),
+            ))
+            .poll_delay(Duration::from_secs(0))
+            .enabled_channels(vec![PublisherChannel::File])
+            .build()
+            .unwrap()
+    };
+
+    // Источник с URL по умолчанию: id извлекается из https://regulation.gov.ru/projects/{id}
+    let regulation = crawler_for(
+        "regulation",
+        &format!("{}/api/npalist/?limit={{limit}}&offset={{offset}}&sort=desc", server.uri()),
+        r"regulation\.gov\.ru/projects/(\d{5,})",
+        None,
+    );
+    // Зеркало со своим шаблоном URL и более строгим regex
+    let mirror = crawler_for(
+        "mirror",
+        &format!("{}/mirror/api/npalist/?limit={{limit}}&offset={{offset}}&sort=desc", server.uri()),
+        r"^https://npa\.example\.org/doc/(16053[12])/view$",
+        Some("https://npa.example.org/doc/{project_id}/view"),
+    );
+
+    let regulation_items = crawl_to_vec(&regulation).await.unwrap();
+    let mirror_items = crawl_to_vec(&mirror).await.unwrap();
+
+    assert_eq!(regulation_items.len(), 50);
+    assert_eq!(regulation_items[0].project_id.as_deref(), Some("160532"));
+    assert_eq!(regulation_items[0].url, "https://regulation.gov.ru/projects/160532");
+
+    let mirror_ids: Vec<_> = mirror_items.iter().map(|i| i.project_id.as_deref().unwrap()).collect();
+    assert_eq!(mirror_ids, vec!["160532", "160531"]);
+    assert_eq!(mirror_items[0].url, "https://npa.example.org/doc/160532/view");
+}
+
+/// Тест проверяет, что метка источника (crawler.npalist.label) попадает в каждый элемент
+#[tokio::test]
+async fn test_source_label_propagated_to_items() {
+    let server = MockServer::start().await;
+    mount_npalist(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let crawler = NpaListCrawler::builder()
+        .url_template(format!("{}/api/npalist/?limit={{limit}}&offset={{offset}}&sort=desc", server.uri()))
+        .source_label("[Минздрав]".to_string())
+        .timeout(Duration::from_secs(2))
+        .cache_manager(Arc::new(
+            FileSystemCacheManager::builder()
+                .cache_dir(temp_dir.path().to_string_lossy().to_string())
+                .build(),
+        ))
+        .poll_delay(Duration::from_secs(0))
+        .enabled_channels(vec![PublisherChannel::File])
+        .build()
+        .unwrap();
+
+    let items = crawl_to_vec(&crawler).await.unwrap();
+
+    assert_eq!(items.is_empty(), false);
+    assert_eq!(items.iter().all(|i| i.source_label.as_deref() == Some("[Минздрав]")), true);
+}
+
+/// Тест проверяет, что краулеры разных источников имеют разные source_id
+/// и хранят состояние (offset истории, min_published_project_id) в manifest каждый под своим ключом
+#[tokio::test]
+async fn test_source_id_keys_manifest_per_crawler() {
+    let server = MockServer::start().await;
+    // Пустые страницы: новых элементов нет, обход истории сразу доходит до конца
+    wiremock::Mock::given(wiremock::matchers::method("GET"))
+        .and(wiremock::matchers::path_regex(r"/api/npalist/"))
+        .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(
+            r#"<projects offset="0" limit="50" sort="desc" total="0"></projects>"#,
+        ))
+        .mount(&server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let cache_manager = Arc::new(
+        FileSystemCacheManager::builder()
+            .cache_dir(temp_dir.path().to_string_lossy().to_string())
+            .build(),
+    );
+    let crawler_for = |url_template: String, label: Option<&str>| {
+        NpaListCrawler::builder()
+            .url_template(url_template)
+            .maybe_source_label(label.map(str::to_string))
+            .timeout(Duration::from_secs(2))
+            .cache_manager(cache_manager.clone())
+            .poll_delay(Duration::from_secs(0))
+            .enabled_channels(vec![PublisherChannel::File])
+            .build()
+            .unwrap()
+    };
+    let regulation_url = format!("{}/api/npalist/?limit={{limit}}&offset={{offset}}&sort=desc", server.uri());
+    let mirror_url = format!("{}/mirror/api/npalist/?limit={{limit}}&offset={{offset}}&sort=desc", server.uri());
+    let regulation = crawler_for(regulation_url.clone(), None);
+    let mirror = crawler_for(mirror_url.clone(), Some("[Зеркало]"));
+
+    // Ключ состояния — URL источника, а не отображаемая метка
+    assert_eq!(regulation.source_id(), regulation_url);
+    assert_eq!(mirror.source_id(), mirror_url);
+
+    // Оба источника остановились в истории на разных offset
+    let mut manifest = Manifest::new();
+    manifest.history_offsets.insert(regulation.source_id(), 100);
+    manifest.history_offsets.insert(mirror.source_id(), 150);
+    manifest.min_published_project_ids.insert(mirror.source_id(), 170000);
+    cache_manager.save_manifest(&manifest).await.unwrap();
+
+    crawl_to_vec(&regulation).await.unwrap();
+
+    // Обход основного источника продолжился со своего offset и закончил историю
+    let received = server.received_requests().await.unwrap();
+    let history_offsets: Vec<_> = received
+        .iter()
+        .filter(|r| r.url.path() == "/api/npalist/")
+        .filter_map(|r| r.url.query_pairs().find(|(k, _)| k == "offset").map(|(_, v)| v.into_owned()))
+        .collect();
+    assert_eq!(history_offsets, vec!["0", "100"]);
+    assert_eq!(received.iter().any(|r| r.url.path().starts_with("/mirror/")), false);
+
+    // Состояние зеркала не тронуто
+    let manifest = cache_manager.load_manifest().await.unwrap();
+    assert_eq!(manifest.history_offsets.get(&regulation.source_id()), None);
+    assert_eq!(manifest.history_offsets.get(&mirror.source_id()), Some(&150));
+    assert_eq!(manifest.min_published_for(&mirror.source_id()), Some(170000));
+}
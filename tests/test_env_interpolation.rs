@@ -0,0 +1,75 @@
+use luminis::services::settings::load_config;
+use pretty_assertions::assert_eq;
+use serial_test::serial;
+
+const CONFIG: &str = r#"llm:
+  model: test
+crawler:
+  interval_seconds: 1
+# токен берется из окружения: ${LUMINIS_TEST_UNSET_IN_COMMENT}
+telegram:
+  api_base_url: https://api.telegram.org
+  bot_token: "${LUMINIS_TEST_TG_TOKEN}"
+  target_chat_id: 1
+  enabled: true
+run:
+  post_template: "$${url} {{ summary }}"
+"#;
+
+fn write_config(text: &str) -> tempfile::NamedTempFile {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), text).unwrap();
+    file
+}
+
+/// Тест проверяет, что `${VAR}` заменяется значением переменной окружения,
+/// а `$${VAR}` и ссылки в комментариях остаются как есть
+#[test]
+#[serial]
+fn test_config_resolves_env_variables() {
+    unsafe { std::env::set_var("LUMINIS_TEST_TG_TOKEN", "123:secret") };
+    let cfg_file = write_config(CONFIG);
+
+    let cfg = load_config(cfg_file.path()).unwrap();
+    unsafe { std::env::remove_var("LUMINIS_TEST_TG_TOKEN") };
+
+    assert_eq!(cfg.telegram.as_ref().map(|t| t.bot_token.as_str()), Some("123:secret"));
+    assert_eq!(cfg.run.as_ref().and_then(|r| r.post_template.as_deref()), Some("${url} {{ summary }}"));
+}
+
+/// Тест проверяет, что ссылка на незаданную переменную окружения — ошибка загрузки с ее именем
+#[test]
+#[serial]
+fn test_config_with_unset_env_variable_fails() {
+    unsafe { std::env::remove_var("LUMINIS_TEST_TG_TOKEN") };
+    let cfg_file = write_config(CONFIG);
+
+    let err = load_config(cfg_file.path()).expect_err("unset variable must fail").to_string();
+    assert_eq!(
+        err,
+        "environment variable LUMINIS_TEST_TG_TOKEN referenced in config at telegram.bot_token is not set"
+    );
+}
+
+/// Тест проверяет, что значение переменной подставляется после разбора YAML: спецсимволы YAML
+/// в нем не добавляют ключей, а ссылка на число заполняет числовое поле
+#[test]
+#[serial]
+fn test_env_value_with_yaml_syntax_is_kept_verbatim() {
+    unsafe {
+        std::env::set_var("LUMINIS_TEST_TG_TOKEN", "123:se#cret\nenabled: false \"x\"");
+        std::env::set_var("LUMINIS_TEST_TG_CHAT", "-1001");
+    };
+    let cfg_file = write_config(&CONFIG.replace("target_chat_id: 1", "target_chat_id: ${LUMINIS_TEST_TG_CHAT}"));
+
+    let cfg = load_config(cfg_file.path()).unwrap();
+    unsafe {
+        std::env::remove_var("LUMINIS_TEST_TG_TOKEN");
+        std::env::remove_var("LUMINIS_TEST_TG_CHAT");
+    };
+
+    let telegram = cfg.telegram.as_ref().unwrap();
+    assert_eq!(telegram.bot_token, "123:se#cret\nenabled: false \"x\"");
+    assert_eq!(telegram.target_chat_id, -1001);
+    assert_eq!(telegram.enabled, true);
+}
@@ -0,0 +1,114 @@
+use luminis::run_with_config_path;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use std::fs;
+use wiremock::MockServer;
+
+mod common;
+
+use crate::common::{mount_docx, mount_gemini_generate, mount_npalist, mount_stages, read_mocks, render_config};
+
+/// Тест проверяет, что канал File с несколькими целями пишет один пост и в .txt, и в .jsonl
+#[tokio::test]
+#[serial]
+async fn test_file_channel_writes_text_and_jsonl_targets() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let txt_target = temp_dir.child("posts.txt");
+    let jsonl_target = temp_dir.child("posts.jsonl");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let targets = format!(
+        "output:\n  file_targets:\n    - path: {}\n    - path: {}\n      format: jsonl\n",
+        txt_target.path().to_str().unwrap(),
+        jsonl_target.path().to_str().unwrap(),
+    );
+    let cfg_text = fs::read_to_string(cfg_file.path()).unwrap().replacen("output:\n", &targets, 1);
+    fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    // file_path не используется, когда заданы file_targets
+    output_file.assert(predicate::path::missing());
+
+    txt_target.assert(predicate::str::starts_with("https://regulation.gov.ru/projects/160532\nПоправки в закон об ОМС"));
+
+    let jsonl = fs::read_to_string(jsonl_target.path()).unwrap();
+    let lines: Vec<&str> = jsonl.lines().collect();
+    assert_eq!(lines.len(), 1);
+    let record: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(record["url"], "https://regulation.gov.ru/projects/160532");
+    let txt = fs::read_to_string(txt_target.path()).unwrap();
+    assert_eq!(record["text"].as_str().unwrap(), txt.trim_end_matches('\n'));
+    assert_eq!(record["published_at"].is_string(), true);
+}
+
+/// Тест проверяет запись файла с UTF-8 BOM и переводами строк CRLF
+#[tokio::test]
+#[serial]
+async fn test_file_channel_writes_bom_and_crlf() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replacen("output:\n", "output:\n  file_bom: true\n  file_line_ending: crlf\n", 1);
+    fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let bytes = fs::read(output_file.path()).unwrap();
+    assert_eq!(&bytes[..3], b"\xEF\xBB\xBF", "file must start with UTF-8 BOM");
+
+    let text = String::from_utf8(bytes[3..].to_vec()).unwrap();
+    assert!(text.starts_with("https://regulation.gov.ru/projects/160532\r\nПоправки в закон об ОМС"));
+    assert!(text.ends_with("\r\n"));
+    // Все переводы строк — CRLF, одиночных LF нет
+    assert_eq!(text.matches('\n').count(), text.matches("\r\n").count());
+    assert!(text.matches("\r\n").count() > 1);
+}
@@ -0,0 +1,113 @@
+use luminis::run_with_config_path;
+use serial_test::serial;
+use wiremock::MockServer;
+use assert_fs::prelude::*;
+
+mod common;
+
+use crate::common::{mount_docx, mount_npalist, mount_stages, read_mocks, render_config};
+
+/// Опрашивает `url`, пока он не ответит `expected` (или не истечет ~5 секунд)
+async fn wait_for_status(client: &reqwest::Client, url: &str, expected: u16) -> Option<u16> {
+    let mut last = None;
+    for _ in 0..50 {
+        if let Ok(resp) = client.get(url).send().await {
+            last = Some(resp.status().as_u16());
+            if last == Some(expected) {
+                break;
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    last
+}
+
+/// Мокирует источники и LLM, отвечающий через `llm_delay`, и запускает приложение с секцией health
+/// (`health_extra` дописывается в секцию). Возвращает задачу запуска, адрес проб и каталог запуска
+async fn start_run(
+    server: &MockServer,
+    llm_delay: std::time::Duration,
+    health_extra: &str,
+) -> (tokio::task::JoinHandle<std::io::Result<luminis::models::types::RunOutcome>>, String, assert_fs::TempDir) {
+    let stages_json = read_mocks();
+    mount_npalist(server).await;
+    mount_stages(server, &stages_json).await;
+    mount_docx(server).await;
+    // Медленный LLM (в пределах run.summarization_timeout_secs) удерживает запуск, пока тест опрашивает пробы
+    let gemini_body = std::fs::read_to_string(
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/resources/mocks/body-v1beta-models-gemini-2.0-flash_generateContent-8OOhY.json"),
+    )
+    .unwrap();
+    wiremock::Mock::given(wiremock::matchers::method("POST"))
+        .and(wiremock::matchers::path("/v1beta/models/gemini-2.0-flash:generateContent"))
+        .respond_with(
+            wiremock::ResponseTemplate::new(200)
+                .insert_header("content-type", "application/json; charset=UTF-8")
+                .set_body_string(gemini_body)
+                .set_delay(llm_delay),
+        )
+        .mount(server)
+        .await;
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &server.uri(),
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let mut cfg_text = std::fs::read_to_string(cfg_file.path()).unwrap();
+    cfg_text.push_str(&format!("\nhealth:\n  bind: 127.0.0.1:{}\n{}", port, health_extra));
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let cfg_path = cfg_file.path().to_str().unwrap().to_string();
+    let run = tokio::spawn(async move { run_with_config_path(&cfg_path, None).await });
+    (run, format!("http://127.0.0.1:{}", port), temp_dir)
+}
+
+/// Тест проверяет секцию health: /healthz отвечает 200 во время работы,
+/// /readyz — 200 после первого успешного обхода, неизвестный путь — 404
+#[tokio::test]
+#[serial]
+async fn test_health_endpoints_report_liveness_and_readiness() {
+    let server = MockServer::start().await;
+    let (run, probe, _temp_dir) = start_run(&server, std::time::Duration::from_millis(1500), "").await;
+
+    let client = reqwest::Client::new();
+    assert_eq!(wait_for_status(&client, &format!("{}/healthz", probe), 200).await, Some(200));
+    assert_eq!(wait_for_status(&client, &format!("{}/readyz", probe), 200).await, Some(200));
+    assert_eq!(wait_for_status(&client, &format!("{}/metrics", probe), 404).await, Some(404));
+
+    run.await.unwrap().unwrap();
+
+    // После завершения запуска сервер проб остановлен
+    assert_eq!(client.get(format!("{}/healthz", probe)).send().await.is_err(), true);
+}
+
+/// Тест проверяет, что /healthz отвечает 503 с именем подсистемы, когда worker занят одним
+/// элементом дольше health.stall_timeout_secs
+#[tokio::test]
+#[serial]
+async fn test_healthz_reports_stalled_worker() {
+    let server = MockServer::start().await;
+    let (run, probe, _temp_dir) =
+        start_run(&server, std::time::Duration::from_millis(2500), "  stall_timeout_secs: 1\n").await;
+
+    let client = reqwest::Client::new();
+    let healthz = format!("{}/healthz", probe);
+    assert_eq!(wait_for_status(&client, &healthz, 503).await, Some(503));
+    let body = client.get(&healthz).send().await.unwrap().text().await.unwrap();
+    assert_eq!(body.contains("worker stalled"), true, "unexpected body: {}", body);
+
+    run.await.unwrap().unwrap();
+}
@@ -0,0 +1,76 @@
+use luminis::run_with_config_path;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use crate::common::{mount_docx, mount_gemini_generate, mount_stages, read_mocks, render_config};
+
+/// Тест проверяет, что http.user_agent из конфигурации отправляется в запросах краулера
+/// к списку НПА и в запросах stages
+#[tokio::test]
+#[serial]
+async fn test_configured_user_agent_is_sent_by_crawler() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    let npalist_xml = std::fs::read_to_string(
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/mocks/npalist.xml"),
+    )
+    .unwrap();
+    Mock::given(method("GET"))
+        .and(path("/api/npalist/"))
+        .and(header("user-agent", "luminis-test/1.0"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(npalist_xml))
+        .expect(1..)
+        .mount(&server)
+        .await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path()).unwrap()
+        + "\nhttp:\n  user_agent: luminis-test/1.0\n  timeout_secs: 30\n";
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(predicate::str::contains("https://regulation.gov.ru/projects/160532"));
+    let crawler_requests: Vec<_> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|r| r.url.path() == "/api/npalist/" || r.url.path().contains("GetProjectStages"))
+        .collect();
+    assert_eq!(crawler_requests.is_empty(), false);
+    for request in crawler_requests {
+        assert_eq!(
+            request.headers.get("user-agent").and_then(|v| v.to_str().ok()),
+            Some("luminis-test/1.0"),
+            "unexpected User-Agent on {}",
+            request.url
+        );
+    }
+}
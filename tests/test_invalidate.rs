@@ -0,0 +1,187 @@
+use luminis::invalidate_summaries;
+use luminis::models::channel::PublisherChannel;
+use luminis::services::cache_manager_impl::FileSystemCacheManager;
+use luminis::traits::cache_manager::CacheManager;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+
+mod common;
+
+use crate::common::render_config;
+
+/// Тест проверяет, что invalidate --summaries очищает суммаризации и посты только в диапазоне,
+/// а документы и статус публикации остаются
+#[tokio::test]
+async fn test_invalidate_summaries_keeps_documents() {
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cache_manager = FileSystemCacheManager::builder()
+        .cache_dir(cache.path().to_str().unwrap().to_string())
+        .build();
+    for pid in ["160530", "160531", "160532"] {
+        cache_manager
+            .save_artifacts(pid, Some(b"docx"), "extracted text", "", "", &[], &[])
+            .await
+            .unwrap();
+        cache_manager
+            .mark_published(pid, PublisherChannel::File, Some("old summary"), "old post")
+            .await
+            .unwrap();
+    }
+
+    let cfg_file = render_config(
+        "http://127.0.0.1:9",
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+
+    let cleared = invalidate_summaries(cfg_file.path().to_str().unwrap(), 160531, 160535)
+        .await
+        .unwrap();
+    assert_eq!(cleared, 2);
+
+    for pid in ["160531", "160532"] {
+        let meta = cache_manager.load_metadata(pid).await.unwrap().unwrap();
+        assert!(meta.channel_summaries.is_empty());
+        assert!(meta.channel_posts.is_empty());
+        assert_eq!(meta.published_channels, vec![PublisherChannel::File]);
+        assert!(!cache_manager.has_summary(pid).await.unwrap());
+        assert_eq!(cache_manager.load_cached_data(pid).await.unwrap().as_deref(), Some("extracted text"));
+        cache.child(format!("{}/source.docx", pid)).assert(predicate::path::is_file());
+    }
+
+    // Проект вне диапазона не изменяется
+    let untouched = cache_manager.load_metadata("160530").await.unwrap().unwrap();
+    assert_eq!(untouched.channel_summaries[&PublisherChannel::File].as_str(), "old summary");
+    assert_eq!(untouched.channel_posts[&PublisherChannel::File].as_str(), "old post");
+}
+
+/// Тест проверяет, что выборка по --since/--until затрагивает только проекты кэша
+/// с датой публикации в диапазоне (включительно)
+#[tokio::test]
+async fn test_invalidate_summaries_by_publish_date() {
+    use luminis::invalidate_summaries_matching;
+    use luminis::models::types::{CacheSelection, MetadataItem};
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cache_manager = FileSystemCacheManager::builder()
+        .cache_dir(cache.path().to_str().unwrap().to_string())
+        .build();
+    let projects = [
+        ("160530", Some("2024-12-31T23:59:00Z")),
+        ("160531", Some("2025-01-01T08:00:00Z")),
+        ("160532", Some("31.03.2025")),
+        ("160533", Some("2025-04-01")),
+        ("160534", None),
+    ];
+    for (pid, date) in projects {
+        let metadata: Vec<MetadataItem> = date.map(|d| MetadataItem::PublishDate(d.to_string())).into_iter().collect();
+        cache_manager
+            .save_artifacts(pid, Some(b"docx"), "extracted text", "", "", &[], &metadata)
+            .await
+            .unwrap();
+        cache_manager
+            .mark_published(pid, PublisherChannel::File, Some("old summary"), "old post")
+            .await
+            .unwrap();
+    }
+
+    let cfg_file = render_config(
+        "http://127.0.0.1:9",
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+
+    let selection = CacheSelection {
+        since: chrono::NaiveDate::from_ymd_opt(2025, 1, 1),
+        until: chrono::NaiveDate::from_ymd_opt(2025, 3, 31),
+        ..Default::default()
+    };
+    let cleared = invalidate_summaries_matching(cfg_file.path().to_str().unwrap(), &selection)
+        .await
+        .unwrap();
+    assert_eq!(cleared, 2);
+
+    for (pid, _) in projects {
+        let meta = cache_manager.load_metadata(pid).await.unwrap().unwrap();
+        let expected_cleared = pid == "160531" || pid == "160532";
+        assert_eq!(meta.channel_summaries.is_empty(), expected_cleared, "project {}", pid);
+        assert_eq!(meta.published_channels, vec![PublisherChannel::File]);
+    }
+}
+
+/// Тест проверяет invalidate --republish: опубликованный проект из диапазона суммаризируется
+/// и публикуется заново, хотя канал уже отмечен опубликованным
+#[tokio::test]
+#[serial_test::serial]
+async fn test_invalidate_republish_publishes_project_again() {
+    use crate::common::{mount_docx, mount_gemini_generate, mount_stages, read_mocks};
+    use luminis::models::types::CacheSelection;
+    use luminis::{backfill, invalidate_and_republish_matching};
+
+    let server = wiremock::MockServer::start().await;
+    let stages_json = read_mocks();
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &server.uri(),
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_path = cfg_file.path().to_str().unwrap();
+
+    assert_eq!(backfill(cfg_path, "160532", Some("Тестовый проект"), &[]).await.unwrap(), 1);
+    let summarize_calls = || async {
+        server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .filter(|r| r.url.path().ends_with(":generateContent"))
+            .count()
+    };
+    let calls_before = summarize_calls().await;
+    std::fs::remove_file(output_file.path()).unwrap();
+
+    let selection = CacheSelection { from: Some(160530), to: Some(160535), ..Default::default() };
+    let republished = invalidate_and_republish_matching(cfg_path, &selection).await.unwrap();
+    assert_eq!(republished, 1);
+
+    output_file.assert(predicate::str::contains("https://regulation.gov.ru/projects/160532"));
+    output_file.assert(predicate::str::contains("Тестовый проект"));
+    assert_eq!(summarize_calls().await > calls_before, true, "summary must be generated again");
+
+    let cache_manager = FileSystemCacheManager::builder()
+        .cache_dir(cache.path().to_str().unwrap().to_string())
+        .build();
+    let meta = cache_manager.load_metadata("160532").await.unwrap().unwrap();
+    assert_eq!(meta.published_channels, vec![PublisherChannel::File]);
+    assert_eq!(meta.channel_summaries.is_empty(), false);
+}
@@ -0,0 +1,74 @@
+use luminis::run_with_config_path;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use std::fs;
+use wiremock::MockServer;
+
+mod common;
+
+use crate::common::{mount_docx, mount_gemini_generate, mount_npalist, mount_stages, prepopulate_cache, read_mocks, render_config};
+
+/// Тест проверяет, что документ без ключевых слов run.keyword_filter не отправляется в Gemini
+/// и записывается в кэш как пропущенный, а документ с ключевым словом публикуется
+#[tokio::test]
+#[serial]
+async fn test_item_without_keywords_never_summarized() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+    // 160531 уже скачан: его текст содержит ключевое слово, текст DOCX для 160532 — нет
+    prepopulate_cache(cache.path().to_str().unwrap(), "160531", "");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = fs::read_to_string(cfg_file.path()).unwrap().replace(
+        "run:\n  max_posts_per_run: 1\n",
+        "run:\n  max_posts_per_run: 1\n  keyword_filter:\n    any_of: [\"MARKDOWN КОНТЕНТ\"]\n",
+    );
+    fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(predicate::str::contains("https://regulation.gov.ru/projects/160531"));
+    output_file.assert(predicate::str::contains("projects/160532").not());
+
+    let meta: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(cache.child("160532/metadata.json").path()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        meta["skip_reason"].as_str(),
+        Some("none of run.keyword_filter.any_of [\"MARKDOWN КОНТЕНТ\"] found")
+    );
+
+    let prompts_for_rejected = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|req| req.url.path().contains(":generateContent"))
+        .filter(|req| String::from_utf8_lossy(&req.body).contains("projects/160532"))
+        .count();
+    assert_eq!(prompts_for_rejected, 0, "rejected item must not reach the LLM");
+}
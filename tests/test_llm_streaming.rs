@@ -0,0 +1,106 @@
+use luminis::run_with_config_path;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use crate::common::{mount_docx, mount_npalist, mount_stages, read_mocks, render_config};
+
+/// Тест проверяет summarizer.stream: суммаризация собирается из частей SSE-ответа
+/// streamGenerateContent, обычный generateContent не вызывается
+#[tokio::test]
+#[serial]
+async fn test_streaming_summary_is_assembled_from_chunks() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+
+    let event = |text: &str| {
+        format!(
+            "data: {{\"candidates\": [{{\"content\": {{\"parts\": [{{\"text\": \"{}\"}}], \"role\": \"model\"}}}}]}}\r\n\r\n",
+            text
+        )
+    };
+    let sse_body = [event("Потоковая "), event("суммаризация "), event("проекта")].concat();
+    Mock::given(method("POST"))
+        .and(path("/v1beta/models/gemini-2.0-flash:streamGenerateContent"))
+        .and(query_param("alt", "sse"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_string(sse_body),
+        )
+        .expect(1..)
+        .mount(&server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path()).unwrap() + "\nsummarizer:\n  stream: true\n";
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(predicate::str::contains("Потоковая суммаризация проекта"));
+    let generate_calls = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|r| r.url.path().ends_with(":generateContent"))
+        .count();
+    assert_eq!(generate_calls, 0);
+}
+
+/// ChatApi без потокового ответа
+struct PlainChatApi;
+
+#[async_trait::async_trait]
+impl luminis::traits::chat_api::ChatApi for PlainChatApi {
+    async fn call_chat_api(&self, _prompt: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok("ответ".to_string())
+    }
+}
+
+/// Тест проверяет, что summarizer.stream у бэкенда без потокового API не отключает
+/// общий таймаут суммаризации
+#[test]
+fn test_stream_without_streaming_backend_keeps_overall_timeout() {
+    let cfg: luminis::models::config::AppConfig = serde_yaml::from_str(concat!(
+        "llm:\n  model: test\n",
+        "crawler:\n  interval_seconds: 1\n",
+        "summarizer:\n  stream: true\n",
+    ))
+    .unwrap();
+    let summarizer = luminis::services::summarizer::Summarizer::builder()
+        .chat_api(std::sync::Arc::new(PlainChatApi))
+        .hard_max_chars(600)
+        .sample_percent(1.0)
+        .max_retry_attempts(0)
+        .retry_delay_secs(0)
+        .build()
+        .with_config(&cfg);
+    assert_eq!(summarizer.is_streaming(), false);
+}
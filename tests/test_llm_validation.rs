@@ -0,0 +1,68 @@
+use luminis::run_with_config_path;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use std::time::Duration;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use crate::common::{mount_npalist, render_config};
+
+/// Тест проверяет, что при summarizer.validate_on_start и отказе LLM с 401
+/// запуск завершается понятной ошибкой до начала краулинга
+#[tokio::test]
+#[serial]
+async fn test_startup_fails_on_unauthorized_llm() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+
+    mount_npalist(&server).await;
+    Mock::given(method("POST"))
+        .and(path("/v1beta/models/gemini-2.0-flash:generateContent"))
+        .respond_with(ResponseTemplate::new(401).set_body_string(
+            r#"{"error":{"code":401,"message":"API key not valid","status":"UNAUTHENTICATED"}}"#,
+        ))
+        .expect(1..)
+        .mount(&server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let mut cfg_text = std::fs::read_to_string(cfg_file.path()).unwrap();
+    cfg_text.push_str("summarizer:\n  validate_on_start: true\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let result = tokio::time::timeout(
+        Duration::from_secs(30),
+        run_with_config_path(cfg_file.path().to_str().unwrap(), None),
+    )
+    .await
+    .expect("startup validation must fail fast");
+
+    let err = result.expect_err("startup must fail with unauthorized LLM");
+    assert!(
+        err.to_string().contains("LLM validation failed at startup"),
+        "unexpected error: {}",
+        err
+    );
+
+    // Краулинг не начинался
+    let received_requests = server.received_requests().await.unwrap();
+    assert!(!received_requests.iter().any(|r| r.url.path().starts_with("/api/npalist")));
+    output_file.assert(predicates::path::missing());
+
+    server.verify().await;
+}
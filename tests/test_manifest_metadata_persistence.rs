@@ -58,12 +58,17 @@ async fn test_manifest_and_metadata_persistence() {
     // Проверяем структуру manifest.json с помощью json-test
     let mut manifest_test = JsonTest::new(&manifest);
     manifest_test
-        .assert_path("$.min_published_project_id")
+        .assert_path("$.min_published_project_ids")
         .exists()
-        .is_number()
-        .is_greater_than(0);
-    
-    let min_published_id = manifest["min_published_project_id"].as_u64().unwrap();
+        .is_object();
+    
+    // min_published_project_id хранится по источникам; в тесте источник один
+    let min_published_id = manifest["min_published_project_ids"]
+        .as_object()
+        .and_then(|ids| ids.values().next())
+        .and_then(|id| id.as_u64())
+        .unwrap();
+    assert!(min_published_id > 0);
     println!("✅ manifest.json содержит min_published_project_id: {}", min_published_id);
     
     // Проверяем, что metadata.json создан для обработанных проектов
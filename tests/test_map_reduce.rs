@@ -0,0 +1,81 @@
+use async_trait::async_trait;
+use luminis::services::summarizer::{ChannelPrompt, Summarizer};
+use luminis::traits::chat_api::ChatApi;
+use pretty_assertions::assert_eq;
+use std::sync::{Arc, Mutex};
+
+/// ChatApi, запоминающий промпты и отвечающий номером вызова
+struct RecordingChatApi {
+    prompts: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl ChatApi for RecordingChatApi {
+    async fn call_chat_api(&self, prompt: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let mut prompts = self.prompts.lock().unwrap();
+        prompts.push(prompt.to_string());
+        Ok(format!("изложение {}", prompts.len()))
+    }
+}
+
+fn summarizer(api: Arc<RecordingChatApi>, strategy: &str) -> Summarizer {
+    let yaml = format!(
+        "llm:\n  model: test\ncrawler:\n  interval_seconds: 1\nrun:\n  input_sample_percent: 1.0\n  prompt_template: \"Итог в {{{{ limit }}}} символов: {{{{ body }}}}\"\nsummarizer:\n  strategy: {}\n  chunk_chars: 100\n",
+        strategy
+    );
+    let cfg: luminis::models::config::AppConfig = serde_yaml::from_str(&yaml).unwrap();
+    Summarizer::builder()
+        .chat_api(api)
+        .hard_max_chars(600)
+        .sample_percent(1.0)
+        .max_retry_attempts(0)
+        .retry_delay_secs(0)
+        .build()
+        .with_config(&cfg)
+}
+
+fn long_document() -> String {
+    (1..=5).map(|i| format!("Абзац {} {}", i, "текст ".repeat(10))).collect::<Vec<_>>().join("\n\n")
+}
+
+/// Тест проверяет, что при summarizer.strategy: map_reduce длинный документ излагается по частям,
+/// а итоговый вызов строится промптом run.prompt_template по изложениям частей
+#[tokio::test]
+async fn test_map_reduce_summarizes_chunks_then_reduces() {
+    let api = Arc::new(RecordingChatApi { prompts: Mutex::new(Vec::new()) });
+    let summarizer = summarizer(api.clone(), "map_reduce");
+
+    let summary = summarizer
+        .summarize_with_limit("Проект", &long_document(), "https://example.org/1", None, Some(300), ChannelPrompt::default())
+        .await
+        .unwrap();
+
+    let prompts = api.prompts.lock().unwrap().clone();
+    // 5 абзацев по ~70 символов при chunk_chars: 100 — по части на абзац и итоговый вызов
+    assert_eq!(prompts.len(), 6);
+    for (i, prompt) in prompts[..5].iter().enumerate() {
+        assert!(prompt.contains(&format!("части {} из 5", i + 1)), "map prompt: {}", prompt);
+        assert!(prompt.contains(&format!("Абзац {}", i + 1)));
+    }
+    let reduce = &prompts[5];
+    assert!(reduce.starts_with("Итог в 300 символов:"), "reduce prompt: {}", reduce);
+    assert!(reduce.contains("изложение 1") && reduce.contains("изложение 5"));
+    assert!(!reduce.contains("Абзац"));
+    assert_eq!(summary, "изложение 6");
+}
+
+/// Тест проверяет, что стратегия single по умолчанию делает один вызов LLM по всему документу
+#[tokio::test]
+async fn test_single_strategy_makes_one_call() {
+    let api = Arc::new(RecordingChatApi { prompts: Mutex::new(Vec::new()) });
+    let summarizer = summarizer(api.clone(), "single");
+
+    summarizer
+        .summarize_with_limit("Проект", &long_document(), "https://example.org/1", None, Some(300), ChannelPrompt::default())
+        .await
+        .unwrap();
+
+    let prompts = api.prompts.lock().unwrap().clone();
+    assert_eq!(prompts.len(), 1);
+    assert!(prompts[0].contains("Абзац 5"));
+}
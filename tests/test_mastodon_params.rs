@@ -4,6 +4,7 @@ use wiremock::MockServer;
 use urlencoding::decode;
 use assert_fs::fixture::PathChild;
 use pretty_assertions::assert_eq;
+use luminis::publishers::utils::{counted_chars, MASTODON_LINK_CHARS};
 
 mod common;
 
@@ -395,3 +396,372 @@ async fn test_mastodon_multiple_params() {
     // Verify that Mastodon was called with correct parameters
     server.verify().await;
 }
+
+/// Тест проверяет, что хост mastodon.base_url вне mastodon.allowed_hosts — ошибка конфигурации при запуске,
+/// до обращения к краулеру и LLM
+#[tokio::test]
+#[serial]
+async fn test_mastodon_disallowed_host_fails_validation() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let tf = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config_with_mastodon_params(
+        &base,
+        tf.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        true,  // mastodon_enabled
+        false, // telegram_enabled
+        true,  // console_enabled
+        false, // file_enabled
+        None,  // mastodon_visibility (default)
+        None,  // mastodon_language (default)
+        None,  // mastodon_sensitive (default)
+        None,  // mastodon_max_chars (default)
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("mastodon:\n", "mastodon:\n  allowed_hosts: [mastodon.social]\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let err = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .expect_err("disallowed mastodon host must fail startup");
+    assert_eq!(err.to_string().contains("mastodon.allowed_hosts"), true, "unexpected error: {}", err);
+    assert_eq!(server.received_requests().await.unwrap().is_empty(), true);
+}
+
+/// Тест проверяет, что channels.mastodon.retry.max_attempts задает число попыток публикации
+/// в Mastodon при ошибках инстанса
+#[tokio::test]
+#[serial]
+async fn test_mastodon_channel_retry_attempts() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+    wiremock::Mock::given(wiremock::matchers::method("POST"))
+        .and(wiremock::matchers::path("/api/v1/statuses"))
+        .respond_with(wiremock::ResponseTemplate::new(500))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let tf = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config_with_mastodon_params(
+        &base,
+        tf.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        true,  // mastodon_enabled
+        false, // telegram_enabled
+        true,  // console_enabled
+        false, // file_enabled
+        None,  // mastodon_visibility (default)
+        None,  // mastodon_language (default)
+        None,  // mastodon_sensitive (default)
+        None,  // mastodon_max_chars (default)
+    );
+    let mut cfg_text = std::fs::read_to_string(cfg_file.path()).unwrap();
+    cfg_text.push_str("\nchannels:\n  mastodon:\n    retry:\n      max_attempts: 3\n      backoff_secs: 0\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    // Консоль публикуется, поэтому запуск останавливается на первом элементе (max_posts_per_run: 1)
+    let statuses = server
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|r| r.url.path() == "/api/v1/statuses")
+        .count();
+    assert_eq!(statuses, 3);
+    server.verify().await;
+}
+
+/// Тест проверяет, что mastodon.api_flavor: pleroma добавляет к статусу content_type=text/plain,
+/// а остальные параметры запроса остаются в формате Mastodon API
+#[tokio::test]
+#[serial]
+async fn test_mastodon_pleroma_flavor_request_shape() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+    wiremock::Mock::given(wiremock::matchers::method("POST"))
+        .and(wiremock::matchers::path("/api/v1/statuses"))
+        .and(wiremock::matchers::body_string_contains("content_type=text%2Fplain"))
+        .respond_with(wiremock::ResponseTemplate::new(200).set_body_string("{\"id\":\"1\"}"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let tf = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config_with_mastodon_params(
+        &base,
+        tf.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        true,  // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        false, // file_enabled
+        Some("public"), // mastodon_visibility
+        None,  // mastodon_language (default)
+        None,  // mastodon_sensitive (default)
+        None,  // mastodon_max_chars (default)
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("  login_cli: false\n", "  login_cli: false\n  api_flavor: pleroma\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let received = server.received_requests().await.unwrap();
+    let status = received
+        .iter()
+        .find(|r| r.url.path() == "/api/v1/statuses")
+        .expect("status must be posted");
+    let body = String::from_utf8_lossy(&status.body).to_string();
+    let decoded = decode(&body).unwrap().to_string();
+    assert_eq!(decoded.contains("visibility=public"), true);
+    assert_eq!(decoded.contains("language=ru"), true);
+    assert_eq!(decoded.contains("status="), true);
+    server.verify().await;
+}
+
+/// Тест: длинная ссылка в начале шаблона засчитывается как 23 символа, и пост
+/// укладывается в mastodon.max_chars так, как его считает сервер
+#[tokio::test]
+#[serial]
+async fn test_mastodon_long_link_counts_as_link_chars() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+    wiremock::Mock::given(wiremock::matchers::method("POST"))
+        .and(wiremock::matchers::path("/api/v1/statuses"))
+        .respond_with(wiremock::ResponseTemplate::new(200).set_body_string("{\"id\":\"1\"}"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let tf = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let max_chars = 120;
+    let cfg_file = render_config_with_mastodon_params(
+        &base,
+        tf.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        true,  // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        false, // file_enabled
+        None,  // mastodon_visibility (default)
+        None,  // mastodon_language (default)
+        None,  // mastodon_sensitive (default)
+        Some(max_chars), // mastodon_max_chars
+    );
+    // Ссылка длиннее 23 символов стоит первой строкой поста
+    let long_link = format!("{}/projects/{}", base, "1".repeat(60));
+    let cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("    {{ url }}\n", &format!("    {}\n", long_link));
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let received = server.received_requests().await.unwrap();
+    let status_req = received
+        .iter()
+        .find(|r| r.url.path() == "/api/v1/statuses")
+        .expect("status must be posted");
+    let status = url::form_urlencoded::parse(&status_req.body)
+        .find(|(k, _)| k == "status")
+        .map(|(_, v)| v.into_owned())
+        .expect("status field");
+    // Ссылка не обрезана, а пост в пределах лимита с учетом веса ссылки
+    assert_eq!(status.starts_with(&long_link), true);
+    assert_eq!(counted_chars(&status, Some(MASTODON_LINK_CHARS)) <= max_chars, true);
+    // Фактическая длина больше лимита: текст не обрезан «на всякий случай» по полной длине ссылки
+    assert_eq!(status.chars().count() > max_chars, true);
+    server.verify().await;
+}
+
+/// Запуск с mastodon.attach_files, где первый проект списка ссылается на файл abc-123 с ответом
+/// `file_response`; сервер обрабатывает загруженное вложение асинхронно (202, затем готово).
+/// Возвращает media_ids[] опубликованного статуса и число загрузок /api/v2/media
+async fn run_with_attached_file(server: &MockServer, file_response: wiremock::ResponseTemplate) -> (Vec<String>, usize) {
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    // В первый проект списка добавлен файл параллельного этапа
+    let npalist_xml = std::fs::read_to_string(
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/mocks/npalist.xml"),
+    )
+    .unwrap()
+    .replacen(
+        "<project id=\"160532\">",
+        "<project id=\"160532\">\n    <parallelStageFile>abc-123</parallelStageFile>",
+        1,
+    );
+    wiremock::Mock::given(wiremock::matchers::method("GET"))
+        .and(wiremock::matchers::path_regex(r"/api/npalist/"))
+        .and(wiremock::matchers::query_param("offset", "0"))
+        .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(npalist_xml))
+        .mount(server)
+        .await;
+    wiremock::Mock::given(wiremock::matchers::method("GET"))
+        .and(wiremock::matchers::path("/api/public/Files/GetFile"))
+        .and(wiremock::matchers::query_param("fileId", "abc-123"))
+        .respond_with(file_response)
+        .with_priority(1)
+        .mount(server)
+        .await;
+    mount_stages(server, &stages_json).await;
+    mount_docx(server).await;
+    mount_gemini_generate(server).await;
+    wiremock::Mock::given(wiremock::matchers::method("POST"))
+        .and(wiremock::matchers::path("/api/v2/media"))
+        .respond_with(wiremock::ResponseTemplate::new(202).set_body_string("{\"id\":\"77\",\"url\":null}"))
+        .mount(server)
+        .await;
+    // Первый опрос: вложение еще обрабатывается, второй — готово
+    wiremock::Mock::given(wiremock::matchers::method("GET"))
+        .and(wiremock::matchers::path("/api/v1/media/77"))
+        .respond_with(wiremock::ResponseTemplate::new(206).set_body_string("{\"id\":\"77\",\"url\":null}"))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(server)
+        .await;
+    wiremock::Mock::given(wiremock::matchers::method("GET"))
+        .and(wiremock::matchers::path("/api/v1/media/77"))
+        .respond_with(
+            wiremock::ResponseTemplate::new(200)
+                .set_body_string(format!("{{\"id\":\"77\",\"url\":\"{}/media/77.png\"}}", base)),
+        )
+        .mount(server)
+        .await;
+    wiremock::Mock::given(wiremock::matchers::method("POST"))
+        .and(wiremock::matchers::path("/api/v1/statuses"))
+        .respond_with(wiremock::ResponseTemplate::new(200).set_body_string("{\"id\":\"1\"}"))
+        .expect(1)
+        .mount(server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let tf = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config_with_mastodon_params(
+        &base,
+        tf.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        true,  // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        false, // file_enabled
+        None,  // mastodon_visibility (default)
+        None,  // mastodon_language (default)
+        None,  // mastodon_sensitive (default)
+        None,  // mastodon_max_chars (default)
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("mastodon:\n", "mastodon:\n  attach_files: true\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let received = server.received_requests().await.unwrap();
+    let download = received
+        .iter()
+        .find(|r| r.url.path() == "/api/public/Files/GetFile" && r.url.query() == Some("fileId=abc-123"))
+        .expect("attachment must be downloaded");
+    assert_eq!(download.method.as_str(), "GET");
+    let status_req = received
+        .iter()
+        .find(|r| r.url.path() == "/api/v1/statuses")
+        .expect("status must be posted");
+    let media_ids = url::form_urlencoded::parse(&status_req.body)
+        .filter(|(k, _)| k == "media_ids[]")
+        .map(|(_, v)| v.into_owned())
+        .collect();
+    let uploads = received.iter().filter(|r| r.url.path() == "/api/v2/media").count();
+    server.verify().await;
+    (media_ids, uploads)
+}
+
+/// Тест проверяет mastodon.attach_files: изображение проекта скачивается, загружается
+/// через /api/v2/media с типом image/png, после готовности вложения его id передается в media_ids[] статуса
+#[tokio::test]
+#[serial]
+async fn test_mastodon_attaches_first_project_file() {
+    let server = MockServer::start().await;
+    let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+    // Файловый API не указывает тип: он определяется по сигнатуре
+    let file = wiremock::ResponseTemplate::new(200)
+        .insert_header("Content-Type", "application/octet-stream")
+        .set_body_bytes(png);
+
+    let (media_ids, uploads) = run_with_attached_file(&server, file).await;
+
+    assert_eq!(media_ids, vec!["77".to_string()]);
+    assert_eq!(uploads, 1);
+    let received = server.received_requests().await.unwrap();
+    let upload = received.iter().find(|r| r.url.path() == "/api/v2/media").unwrap();
+    assert_eq!(String::from_utf8_lossy(&upload.body).contains("Content-Type: image/png"), true);
+    let polls = received.iter().filter(|r| r.url.path() == "/api/v1/media/77").count();
+    assert_eq!(polls, 2);
+}
+
+/// Тест проверяет, что документ (DOCX) не загружается в Mastodon, который его не примет:
+/// статус публикуется без вложения
+#[tokio::test]
+#[serial]
+async fn test_mastodon_does_not_attach_documents() {
+    let server = MockServer::start().await;
+    let docx = std::fs::read(
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/mocks/source.docx"),
+    )
+    .unwrap();
+    let file = wiremock::ResponseTemplate::new(200)
+        .insert_header("Content-Type", "application/vnd.openxmlformats-officedocument.wordprocessingml.document")
+        .set_body_bytes(docx);
+
+    let (media_ids, uploads) = run_with_attached_file(&server, file).await;
+
+    assert_eq!(media_ids, Vec::<String>::new());
+    assert_eq!(uploads, 0);
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use luminis::models::config::AppConfig;
+use luminis::models::types::{CrawlItem, synthetic_project_id};
+use luminis::publishers::MastodonPublisher;
+use luminis::services::cache_manager_impl::FileSystemCacheManager;
+use luminis::services::summarizer::Summarizer;
+use luminis::services::worker::Worker;
+use luminis::traits::chat_api::ChatApi;
+use assert_fs::prelude::*;
+use pretty_assertions::assert_eq;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Суммаризация из трех абзацев: вместе длиннее mastodon.max_chars, по отдельности короче
+struct LongChatApi;
+
+#[async_trait]
+impl ChatApi for LongChatApi {
+    async fn call_chat_api(&self, _prompt: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(concat!(
+            "Первый абзац длинной суммаризации проекта.\n\n",
+            "Второй абзац с подробностями изменений.\n\n",
+            "Третий абзац с оценкой последствий.",
+        )
+        .to_string())
+    }
+}
+
+/// Worker с каналом Mastodon (max_chars 60, thread_long_posts) на мок-сервере `server`
+async fn thread_worker(server: &MockServer, cache: &assert_fs::fixture::ChildPath) -> Worker {
+    let cfg: AppConfig = serde_yaml::from_str(&format!(
+        concat!(
+            "llm:\n  model: test\n",
+            "crawler:\n  interval_seconds: 1\n",
+            "mastodon:\n  base_url: http://mastodon.invalid\n  access_token: \"\"\n  enabled: true\n",
+            "  max_chars: 60\n  thread_long_posts: true\n",
+            "output:\n  console_enabled: false\n",
+            "run:\n  require_project_id: false\n  processing_delay_secs: 0\n  cache_dir: {}\n",
+            "  post_template: \"{{{{ summary }}}}\"\n",
+        ),
+        cache.path().display(),
+    ))
+    .unwrap();
+
+    let summarizer = Arc::new(
+        Summarizer::builder()
+            .chat_api(Arc::new(LongChatApi))
+            .hard_max_chars(600)
+            .sample_percent(1.0)
+            .max_retry_attempts(0)
+            .retry_delay_secs(0)
+            .build()
+            .with_config(&cfg),
+    );
+    let mastodon = Arc::new(
+        MastodonPublisher::builder()
+            .client(reqwest::Client::new())
+            .base_url(server.uri())
+            .access_token("token".to_string())
+            .max_chars(60)
+            .thread_long_posts(true)
+            .build(),
+    );
+    Worker::builder()
+        .config(cfg)
+        .summarizer(summarizer)
+        .cache_manager(Arc::new(
+            FileSystemCacheManager::builder()
+                .cache_dir(cache.path().to_str().unwrap().to_string())
+                .build(),
+        ))
+        .mastodon_publisher(mastodon)
+        .build()
+        .await
+        .unwrap()
+}
+
+fn long_item() -> CrawlItem {
+    CrawlItem {
+        title: "Длинная новость".to_string(),
+        url: "https://example.org/news/long".to_string(),
+        body: "Текст длинной новости".to_string(),
+        project_id: None,
+        metadata: vec![],
+        source_label: None,
+        source_id: None,
+    }
+}
+
+/// Параметры запросов POST /api/v1/statuses, полученных сервером
+async fn received_statuses(server: &MockServer) -> Vec<HashMap<String, String>> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|r| r.url.path() == "/api/v1/statuses")
+        .map(|r| url::form_urlencoded::parse(&r.body).into_owned().collect())
+        .collect()
+}
+
+/// metadata.json элемента в кэше
+fn read_metadata(cache: &assert_fs::fixture::ChildPath, item: &CrawlItem) -> serde_json::Value {
+    serde_json::from_str(
+        &std::fs::read_to_string(cache.path().join(synthetic_project_id(&item.url)).join("metadata.json")).unwrap(),
+    )
+    .unwrap()
+}
+
+/// Монтирует ответы /api/v1/statuses по порядку; последний отвечает на все оставшиеся запросы
+async fn mount_statuses(server: &MockServer, responses: Vec<ResponseTemplate>) {
+    let last = responses.len() - 1;
+    for (i, response) in responses.into_iter().enumerate() {
+        let mock = Mock::given(method("POST")).and(path("/api/v1/statuses")).respond_with(response);
+        let mock = if i < last { mock.up_to_n_times(1) } else { mock };
+        mock.mount(server).await;
+    }
+}
+
+fn status(id: &str) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_string(format!(r#"{{"id":"{}"}}"#, id))
+}
+
+/// Тест проверяет mastodon.thread_long_posts: длинный пост публикуется цепочкой статусов,
+/// каждый следующий — ответ (in_reply_to_id) на id, возвращенный предыдущим запросом,
+/// а id статусов цепочки сохраняются в metadata.json
+#[tokio::test]
+async fn test_long_post_is_published_as_reply_chain() {
+    let server = MockServer::start().await;
+    mount_statuses(&server, vec![status("1001"), status("1002"), status("1003")]).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let cache = temp_dir.child("cache");
+    let worker = thread_worker(&server, &cache).await;
+
+    let item = long_item();
+    assert_eq!(worker.process_item(item.clone()).await.unwrap(), 1);
+
+    let statuses = received_statuses(&server).await;
+    assert_eq!(statuses.len(), 3);
+    assert_eq!(statuses[0]["status"], "Первый абзац длинной суммаризации проекта.");
+    assert_eq!(statuses[0].get("in_reply_to_id"), None);
+    assert_eq!(statuses[1]["status"], "Второй абзац с подробностями изменений.");
+    assert_eq!(statuses[1].get("in_reply_to_id").map(String::as_str), Some("1001"));
+    assert_eq!(statuses[2].get("in_reply_to_id").map(String::as_str), Some("1002"));
+
+    let metadata = read_metadata(&cache, &item);
+    assert_eq!(metadata["threads"]["Mastodon"]["status_ids"], serde_json::json!(["1001", "1002", "1003"]));
+}
+
+/// Тест проверяет, что повтор после ошибки на третьей части продолжает цепочку ответом
+/// на последний опубликованный статус, а не публикует первые части заново
+#[tokio::test]
+async fn test_failed_thread_is_resumed_on_retry() {
+    let server = MockServer::start().await;
+    mount_statuses(
+        &server,
+        vec![status("1001"), status("1002"), ResponseTemplate::new(500), status("1003")],
+    )
+    .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let cache = temp_dir.child("cache");
+    let worker = thread_worker(&server, &cache).await;
+
+    let item = long_item();
+    assert_eq!(worker.process_item(item.clone()).await.unwrap(), 0);
+    assert_eq!(
+        read_metadata(&cache, &item)["threads"]["Mastodon"]["status_ids"],
+        serde_json::json!(["1001", "1002"])
+    );
+
+    assert_eq!(worker.process_item(item.clone()).await.unwrap(), 1);
+
+    let statuses = received_statuses(&server).await;
+    assert_eq!(statuses.len(), 4);
+    assert_eq!(statuses[3]["status"], "Третий абзац с оценкой последствий.");
+    assert_eq!(statuses[3].get("in_reply_to_id").map(String::as_str), Some("1002"));
+    assert_eq!(
+        read_metadata(&cache, &item)["threads"]["Mastodon"]["status_ids"],
+        serde_json::json!(["1001", "1002", "1003"])
+    );
+}
@@ -0,0 +1,79 @@
+use luminis::publishers::matrix::MatrixPublisher;
+use luminis::publishers::utils::HttpRetryPolicy;
+use luminis::traits::publisher::Publisher;
+use pretty_assertions::assert_eq;
+use reqwest::Client;
+use wiremock::matchers::{header, method, path_regex};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn publisher(server: &MockServer, retry: HttpRetryPolicy) -> MatrixPublisher {
+    MatrixPublisher::builder()
+        .client(Client::new())
+        .homeserver_url(server.uri())
+        .access_token("TOKEN".to_string())
+        .room_id("!room:example.org".to_string())
+        .retry(retry)
+        .build()
+}
+
+/// Тест проверяет запрос m.room.message: адрес комнаты, токен, текст и HTML-вариант
+#[tokio::test]
+async fn test_matrix_sends_text_and_html_body() {
+    let server = MockServer::start().await;
+    Mock::given(method("PUT"))
+        .and(path_regex(r"^/_matrix/client/v3/rooms/%21room%3Aexample\.org/send/m\.room\.message/luminis-.+$"))
+        .and(header("authorization", "Bearer TOKEN"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"event_id":"$1"}"#))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    publisher(&server, HttpRetryPolicy::default())
+        .publish("Проект", "https://regulation.gov.ru/projects/160532", "https://regulation.gov.ru/projects/160532\nИтоги & выводы")
+        .await
+        .unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["msgtype"], "m.text");
+    assert_eq!(body["body"], "https://regulation.gov.ru/projects/160532\nИтоги & выводы");
+    assert_eq!(body["format"], "org.matrix.custom.html");
+    assert_eq!(
+        body["formatted_body"],
+        "<a href=\"https://regulation.gov.ru/projects/160532\">https://regulation.gov.ru/projects/160532</a><br>Итоги &amp; выводы"
+    );
+}
+
+/// Тест проверяет, что повтор после 5xx и повторная публикация того же поста проекта идут
+/// с тем же txnId, а другой пост — с новым
+#[tokio::test]
+async fn test_matrix_retry_reuses_transaction_id() {
+    let server = MockServer::start().await;
+    Mock::given(method("PUT"))
+        .respond_with(ResponseTemplate::new(502))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"event_id":"$1"}"#))
+        .mount(&server)
+        .await;
+
+    let retry = HttpRetryPolicy { max_attempts: 2, base_delay: std::time::Duration::from_millis(10), multiplier: 1.0 };
+    let publisher = publisher(&server, retry);
+    publisher.publish_project("160532", "первый").await.unwrap();
+    publisher.publish_project("160532", "первый").await.unwrap();
+    publisher.publish_project("160532", "второй").await.unwrap();
+
+    let paths: Vec<String> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|r| r.url.path().to_string())
+        .collect();
+    assert_eq!(paths.len(), 4);
+    assert_eq!(paths[0], paths[1], "retry must reuse the transaction id");
+    assert_eq!(paths[1], paths[2], "republishing the same post must reuse the transaction id");
+    assert_ne!(paths[2], paths[3], "another post gets its own transaction id");
+}
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use luminis::models::config::AppConfig;
+use luminis::services::chat_api_local::LocalChatApi;
+use luminis::services::summarizer::{estimate_tokens, ChannelPrompt, Summarizer};
+use pretty_assertions::assert_eq;
+use wiremock::MockServer;
+
+mod common;
+
+use crate::common::mount_gemini_generate;
+
+/// Тест проверяет summarizer.max_input_tokens_by_provider: исходный текст длиннее бюджета обрезается
+/// до вызова LLM с сохранением начала и конца, и промпт в запросе к модели укладывается в бюджет
+#[tokio::test]
+async fn test_oversized_source_is_truncated_to_token_budget() {
+    let server = MockServer::start().await;
+    mount_gemini_generate(&server).await;
+
+    let cfg: AppConfig = serde_yaml::from_str(&format!(
+        concat!(
+            "llm:\n  model: gemini-2.0-flash\n  provider: Gemini\n  base_url: {}/v1beta\n  api_key: TESTKEY\n",
+            "  max_retry_attempts: 0\n",
+            "crawler:\n  interval_seconds: 1\n",
+            "summarizer:\n  max_input_tokens: 100000\n  max_input_tokens_by_provider:\n    gemini: 1000\n",
+            "  input_tail_share: 0.25\n",
+            "  prompt_template: \"{{{{ text }}}}\"\n",
+            "run:\n  input_sample_percent: 1.0\n",
+        ),
+        server.uri()
+    ))
+    .unwrap();
+
+    let summarizer = Summarizer::builder()
+        .chat_api(Arc::new(LocalChatApi::from_config(&cfg.llm)))
+        .hard_max_chars(600)
+        .sample_percent(1.0)
+        .max_retry_attempts(0)
+        .retry_delay_secs(0)
+        .build()
+        .with_config(&cfg);
+
+    let source = format!("НАЧАЛО {} СЕРЕДИНА {} КОНЕЦ", "слово ".repeat(20000), "текст ".repeat(20000));
+    assert_eq!(estimate_tokens(&source) > 50000, true);
+
+    summarizer
+        .summarize_with_limit("Проект", &source, "https://example.org/1", None, Some(300), ChannelPrompt::default())
+        .await
+        .unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 1);
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    let sent = body.to_string();
+    let prompt = body
+        .pointer("/contents/0/parts/0/text")
+        .and_then(|v| v.as_str())
+        .unwrap_or_else(|| panic!("no prompt text in request: {}", sent));
+    assert_eq!(estimate_tokens(prompt) <= 1000, true, "prompt of {} tokens exceeds budget", estimate_tokens(prompt));
+    assert_eq!(prompt.starts_with("НАЧАЛО"), true);
+    assert_eq!(prompt.trim_end().ends_with("КОНЕЦ"), true);
+    assert_eq!(prompt.contains("СЕРЕДИНА"), false);
+}
@@ -0,0 +1,158 @@
+use luminis::run_with_config_path;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use std::fs;
+use wiremock::matchers::{method, path_regex, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use crate::common::{mount_docx, mount_gemini_generate, mount_npalist, mount_stages, read_mocks, render_config};
+
+/// Запускает публикацию в файл с дополнительной секцией templates и возвращает текст поста
+async fn publish_with_templates(templates_section: &str) -> String {
+    let server = MockServer::start().await;
+    mount_npalist(&server).await;
+    publish_from_server(server, templates_section).await
+}
+
+async fn publish_from_server(server: MockServer, templates_section: &str) -> String {
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let mut cfg_text = fs::read_to_string(cfg_file.path()).unwrap();
+    cfg_text.push_str(templates_section);
+    fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(predicate::str::contains("https://regulation.gov.ru/projects/160532"));
+    fs::read_to_string(output_file.path()).unwrap()
+}
+
+/// Тест проверяет, что поле из templates.metadata_deny не попадает в пост
+#[tokio::test]
+#[serial]
+async fn test_denied_metadata_field_absent_from_post() {
+    let post = publish_with_templates("templates:\n  metadata_deny: [responsible]\n").await;
+
+    assert!(!post.contains("Филиппов Олег Анатольевич"), "responsible must be hidden: {}", post);
+    assert!(!post.contains("Отв:"), "responsible must be hidden: {}", post);
+    assert!(post.contains("Дата:2025-09-20"), "other metadata must stay: {}", post);
+}
+
+/// Тест проверяет, что при templates.metadata_allow в пост попадают только перечисленные поля
+#[tokio::test]
+#[serial]
+async fn test_only_allowed_metadata_fields_in_post() {
+    let post = publish_with_templates("templates:\n  metadata_allow: [publish_date]\n").await;
+
+    assert!(post.contains("Дата:2025-09-20"), "allowed field must stay: {}", post);
+    assert!(!post.contains("Деп:"), "department is not allowed: {}", post);
+    assert!(!post.contains("Отв:"), "responsible is not allowed: {}", post);
+}
+
+/// Запускает публикацию, где у первого проекта в responsible указан e-mail
+async fn publish_with_responsible_email(templates_section: &str) -> String {
+    let server = MockServer::start().await;
+    let npalist_xml = fs::read_to_string(
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/mocks/npalist.xml"),
+    )
+    .unwrap()
+    .replacen(
+        "<responsible>Филиппов Олег Анатольевич</responsible>",
+        "<responsible>Филиппов Олег Анатольевич, filippovoa@minzdrav.gov.ru</responsible>",
+        1,
+    );
+    Mock::given(method("GET"))
+        .and(path_regex(r"/api/npalist/"))
+        .and(query_param("offset", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(npalist_xml))
+        .mount(&server)
+        .await;
+    publish_from_server(server, templates_section).await
+}
+
+/// Тест проверяет, что e-mail в метаданных маскируется в опубликованном посте по умолчанию
+#[tokio::test]
+#[serial]
+async fn test_email_in_responsible_is_masked() {
+    let post = publish_with_responsible_email("").await;
+
+    assert!(!post.contains("filippovoa@minzdrav.gov.ru"), "email must be masked: {}", post);
+    assert!(post.contains("Отв:Филиппов Олег Анатольевич, f***@minzdrav.gov.ru"), "masked email expected: {}", post);
+}
+
+/// Тест проверяет, что templates.redact_emails: false оставляет e-mail как есть
+#[tokio::test]
+#[serial]
+async fn test_email_kept_when_redaction_disabled() {
+    let post = publish_with_responsible_email("templates:\n  redact_emails: false\n").await;
+
+    assert!(post.contains("filippovoa@minzdrav.gov.ru"), "email must be kept: {}", post);
+}
+
+/// Тест проверяет, что project_id доступен в шаблоне поста строкой, числом и по templates.project_id_format
+#[tokio::test]
+#[serial]
+async fn test_project_id_string_numeric_and_formatted_forms() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let mut cfg_text = fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace(
+            "  post_template: |\n    {{ url }}\n",
+            "  post_template: |\n    ID:{{ project_id }}|{{ project_id_num + 1 }}|{{ project_id_formatted }}\n    {{ url }}\n",
+        );
+    cfg_text.push_str("templates:\n  project_id_format: \"№ {project_id_grouped}\"\n");
+    fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let post = fs::read_to_string(output_file.path()).unwrap();
+    assert!(post.contains("ID:160532|160533|№ 160 532"), "all project_id forms must render: {}", post);
+}
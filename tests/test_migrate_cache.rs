@@ -0,0 +1,87 @@
+use luminis::migrate_cache;
+use luminis::models::channel::PublisherChannel;
+use luminis::models::config::CacheLayout;
+use luminis::services::cache_manager_impl::{CacheMigrationReport, FileSystemCacheManager};
+use luminis::traits::cache_manager::CacheManager;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+
+/// Тест проверяет migrate-cache на фикстуре старого кэша: плоские файлы переносятся в каталоги
+/// проектов, проект с готовым metadata.json пропускается, а после переноса кэш читается
+/// с cache_layout v2 без обращения к плоским файлам
+#[tokio::test]
+async fn test_migrate_legacy_cache_fixture() {
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let cache = temp_dir.child("cache");
+    cache
+        .copy_from(std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/legacy_cache"), &["*"])
+        .unwrap();
+    // Проект уже в новом формате: его плоский файл не переносится и не удаляется
+    cache.child("180000/metadata.json").write_str(&serde_json::to_string(&serde_json::json!({
+        "project_id": "180000",
+        "docx_path": "",
+        "markdown_path": "",
+        "published_channels": [],
+        "created_at": "2025-01-01T00:00:00+00:00",
+        "channel_summaries": {},
+        "channel_posts": {},
+        "crawl_metadata": []
+    })).unwrap()).unwrap();
+    cache.child("180000_extracted.md").write_str("устаревшая копия").unwrap();
+    // Суммаризация без метаданных (каналы неизвестны) не теряется при переносе
+    cache.child("190000_summary.txt").write_str("Суммаризация 190000 без метаданных\n").unwrap();
+
+    let cfg_file = temp_dir.child("config.yaml");
+    cfg_file
+        .write_str(&format!(
+            concat!(
+                "llm:\n  model: test\n",
+                "crawler:\n  interval_seconds: 1\n",
+                "run:\n  cache_dir: {}\n  post_template: \"{{{{ url }}}}\"\n",
+            ),
+            cache.path().display()
+        ))
+        .unwrap();
+
+    let report = migrate_cache(cfg_file.path().to_str().unwrap(), true).await.unwrap();
+    assert_eq!(report, CacheMigrationReport { migrated: 3, skipped: 1, deleted_files: 5 });
+
+    for legacy in [
+        "160532_metadata.json",
+        "160532_extracted.md",
+        "160532_summary.txt",
+        "170001_extracted.md",
+        "190000_summary.txt",
+    ] {
+        cache.child(legacy).assert(predicate::path::missing());
+    }
+    cache.child("180000_extracted.md").assert(predicate::path::exists());
+    cache.child("160532/extracted.md").assert("Текст проекта 160532 из старого кэша\n");
+
+    let cm = FileSystemCacheManager::builder()
+        .cache_dir(cache.path().to_str().unwrap().to_string())
+        .layout(CacheLayout::V2)
+        .build();
+    let meta = cm.load_metadata("160532").await.unwrap().unwrap();
+    assert_eq!(meta.published_channels, vec![PublisherChannel::File]);
+    assert_eq!(
+        cm.load_channel_summary("160532", PublisherChannel::File).await.unwrap().map(|s| s.as_str().to_string()),
+        Some("Суммаризация 160532 из старого кэша\n".to_string())
+    );
+    assert_eq!(
+        cm.load_cached_data("170001").await.unwrap(),
+        Some("Текст проекта 170001 без метаданных\n".to_string())
+    );
+    for channel in [PublisherChannel::File, PublisherChannel::Telegram] {
+        assert_eq!(
+            cm.load_channel_summary("190000", channel).await.unwrap().map(|s| s.as_str().to_string()),
+            Some("Суммаризация 190000 без метаданных\n".to_string())
+        );
+    }
+    assert_eq!(cm.has_data("180000").await.unwrap(), false, "v2 layout must ignore legacy flat files");
+
+    // Повторный запуск ничего не переносит
+    let report = migrate_cache(cfg_file.path().to_str().unwrap(), true).await.unwrap();
+    assert_eq!(report, CacheMigrationReport { migrated: 0, skipped: 1, deleted_files: 0 });
+}
@@ -147,6 +147,212 @@ async fn fetch_docx_via_wiremock() {
       server.verify().await;
 }
 
+/// Тест проверяет crawler.force_document_type: DOCX, отданный с application/octet-stream,
+/// извлекается при force_document_type: docx; при явно неверном Content-Type режим auto
+/// определяет DOCX по сигнатуре, а старый .doc (OLE) отклоняется в обоих режимах
+#[tokio::test]
+#[serial]
+async fn force_document_type_docx_ignores_wrong_content_type() {
+    use luminis::models::config::DocumentType;
+
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let docx = std::fs::read(std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/mocks/source.docx")).unwrap();
+
+    let template = format!("{}/api/public/PublicProjects/GetProjectStages/{{project_id}}", base);
+    let fetcher = |document_type: DocumentType| {
+        DocxMarkdownFetcher::builder()
+            .file_id_url_template(template.clone())
+            .document_type(document_type)
+            .build()
+    };
+
+    mount_stages(&server, &read_mocks()).await;
+    wiremock::Mock::given(wiremock::matchers::method("GET"))
+        .and(wiremock::matchers::path_regex(r"/api/public/Files/GetFile"))
+        .respond_with(
+            wiremock::ResponseTemplate::new(200)
+                .insert_header("content-type", "application/octet-stream")
+                .set_body_bytes(docx.clone()),
+        )
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    let (_bytes, md) = fetcher(DocumentType::Docx).fetch_markdown("160532").await.unwrap().expect("docx must be fetched");
+    assert_eq!(md.trim().is_empty(), false, "Extracted markdown should not be empty");
+
+    wiremock::Mock::given(wiremock::matchers::method("GET"))
+        .and(wiremock::matchers::path_regex(r"/api/public/Files/GetFile"))
+        .respond_with(
+            wiremock::ResponseTemplate::new(200)
+                .insert_header("content-type", "text/plain")
+                .set_body_bytes(docx),
+        )
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    let (_bytes, md) = fetcher(DocumentType::Auto).fetch_markdown("160532").await.unwrap().expect("docx must be sniffed");
+    assert_eq!(md.trim().is_empty(), false, "Extracted markdown should not be empty");
+
+    let mut ole = vec![0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+    ole.extend_from_slice(&[0u8; 512]);
+    wiremock::Mock::given(wiremock::matchers::method("GET"))
+        .and(wiremock::matchers::path_regex(r"/api/public/Files/GetFile"))
+        .respond_with(
+            wiremock::ResponseTemplate::new(200)
+                .insert_header("content-type", "application/msword")
+                .set_body_bytes(ole),
+        )
+        .mount(&server)
+        .await;
+    for document_type in [DocumentType::Auto, DocumentType::Docx] {
+        let err = fetcher(document_type).fetch_markdown("160532").await.expect_err("OLE .doc must be rejected");
+        assert!(err.to_string().contains("OLE"), "unexpected error: {}", err);
+    }
+}
+
+/// Отвечает телом с задержкой и запоминает время прихода каждого запроса
+struct DelayedBody {
+    body: Vec<u8>,
+    delay: std::time::Duration,
+    arrivals: std::sync::Arc<std::sync::Mutex<Vec<std::time::Instant>>>,
+}
+
+impl wiremock::Respond for DelayedBody {
+    fn respond(&self, _request: &wiremock::Request) -> wiremock::ResponseTemplate {
+        self.arrivals.lock().unwrap().push(std::time::Instant::now());
+        wiremock::ResponseTemplate::new(200)
+            .set_body_bytes(self.body.clone())
+            .set_delay(self.delay)
+    }
+}
+
+/// Тест проверяет, что общий лимит crawler.fetch_concurrency ограничивает число одновременных
+/// скачиваний документа при нескольких проектах в работе
+#[tokio::test]
+#[serial]
+async fn fetch_concurrency_bounds_parallel_docx_downloads() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+
+    let stages_json = read_mocks();
+    mount_stages(&server, &stages_json).await;
+    let delay = std::time::Duration::from_millis(300);
+    let arrivals = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    wiremock::Mock::given(wiremock::matchers::method("GET"))
+        .and(wiremock::matchers::path_regex(r"/api/public/Files/GetFile"))
+        .respond_with(DelayedBody {
+            body: std::fs::read(std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/mocks/source.docx")).unwrap(),
+            delay,
+            arrivals: arrivals.clone(),
+        })
+        .mount(&server)
+        .await;
+
+    let template = format!("{}/api/public/PublicProjects/GetProjectStages/{{project_id}}", base);
+    let permits = std::sync::Arc::new(tokio::sync::Semaphore::new(2));
+    let fetchers: Vec<DocxMarkdownFetcher> = (0..5)
+        .map(|_| {
+            DocxMarkdownFetcher::builder()
+                .file_id_url_template(template.clone())
+                .fetch_permits(permits.clone())
+                .build()
+        })
+        .collect();
+    let results = futures_util::future::join_all(fetchers.iter().map(|f| f.fetch_markdown("160532"))).await;
+    assert!(results.iter().all(|r| matches!(r, Ok(Some(_)))));
+
+    let arrivals = arrivals.lock().unwrap().clone();
+    assert_eq!(arrivals.len(), 5);
+    let max_in_flight = max_in_flight(&arrivals, delay);
+    assert!(max_in_flight <= 2, "at most 2 concurrent downloads expected, got {}", max_in_flight);
+}
+
+/// Сколько запросов пришло, пока обрабатывался один (ответ задержан на `delay`)
+fn max_in_flight(arrivals: &[std::time::Instant], delay: std::time::Duration) -> usize {
+    let window = delay - std::time::Duration::from_millis(50);
+    arrivals
+        .iter()
+        .map(|start| arrivals.iter().filter(|t| **t >= *start && **t - *start < window).count())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Тест проверяет, что при общем кэше документов (cache.share_documents) документ,
+/// общий для двух проектов (одинаковый fileId), скачивается один раз
+#[tokio::test]
+#[serial]
+async fn shared_document_cache_downloads_shared_file_id_once() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+
+    let stages_json = read_mocks();
+    mount_stages(&server, &stages_json).await;
+    wiremock::Mock::given(wiremock::matchers::method("GET"))
+        .and(wiremock::matchers::path_regex(r"/api/public/Files/GetFile"))
+        .respond_with(
+            wiremock::ResponseTemplate::new(200)
+                .set_body_bytes(std::fs::read(std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/mocks/source.docx")).unwrap()),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let documents_dir = temp_dir.child("documents");
+    let template = format!("{}/api/public/PublicProjects/GetProjectStages/{{project_id}}", base);
+    let fetcher = DocxMarkdownFetcher::builder()
+        .file_id_url_template(template)
+        .document_cache_dir(documents_dir.path().to_path_buf())
+        .build();
+
+    let first = fetcher.fetch_markdown("160531").await.unwrap();
+    let second = fetcher.fetch_markdown("160532").await.unwrap();
+    let (first_bytes, first_md) = first.expect("first project should be fetched");
+    let (second_bytes, second_md) = second.expect("second project should be served from shared cache");
+    assert_eq!(first_bytes, second_bytes);
+    assert_eq!(first_md, second_md);
+    assert_eq!(std::fs::read_dir(documents_dir.path()).unwrap().count(), 1);
+
+    server.verify().await;
+}
+
+/// Тест проверяет, что общий лимит crawler.scan_concurrency ограничивает число одновременных
+/// запросов stages при поиске fileId для нескольких проектов
+#[tokio::test]
+#[serial]
+async fn scan_concurrency_bounds_parallel_stages_requests() {
+    use luminis::crawlers::FileIdScanner;
+
+    let server = MockServer::start().await;
+    let base = server.uri();
+
+    let delay = std::time::Duration::from_millis(300);
+    let arrivals = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    wiremock::Mock::given(wiremock::matchers::method("GET"))
+        .and(wiremock::matchers::path_regex(r"/api/public/PublicProjects/GetProjectStages/"))
+        .respond_with(DelayedBody {
+            body: read_mocks().into_bytes(),
+            delay,
+            arrivals: arrivals.clone(),
+        })
+        .mount(&server)
+        .await;
+
+    let permits = std::sync::Arc::new(tokio::sync::Semaphore::new(2));
+    let scanner = FileIdScanner::builder().permits(permits).build();
+    let urls: Vec<String> = (160528..160533)
+        .map(|id| format!("{}/api/public/PublicProjects/GetProjectStages/{}", base, id))
+        .collect();
+    let results = futures_util::future::join_all(urls.iter().map(|u| scanner.fetch_file_info(u))).await;
+    assert!(results.iter().all(|r| matches!(r, Ok(Some(_)))));
+
+    let arrivals = arrivals.lock().unwrap().clone();
+    assert_eq!(arrivals.len(), 5);
+    let max_in_flight = max_in_flight(&arrivals, delay);
+    assert!(max_in_flight <= 2, "at most 2 concurrent stages requests expected, got {}", max_in_flight);
+}
+
 #[tokio::test]
 #[serial]
 async fn test_gemini_api_client() {
@@ -192,3 +398,124 @@ async fn test_gemini_api_client() {
         .expect("gemini call ok");
     assert_eq!(resp.contains("Поправки в закон об ОМС"), true);
 }
+
+/// Тест проверяет параллельную публикацию каналов одного элемента (run.publish_concurrency_per_item):
+/// все три канала публикуются и фиксируются в кэше
+#[tokio::test]
+#[serial]
+async fn publish_channels_concurrently_and_record_all() {
+    use luminis::models::channel::PublisherChannel;
+    use luminis::services::cache_manager_impl::FileSystemCacheManager;
+    use luminis::traits::cache_manager::CacheManager;
+
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+    mount_telegram(&server).await;
+    mount_mastodon(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        true,  // mastodon_enabled
+        true,  // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path()).unwrap()
+        .replace("run:\n", "run:\n  publish_concurrency_per_item: 3\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(predicate::str::contains("https://regulation.gov.ru/projects/160532"));
+    let received_requests = server.received_requests().await.unwrap();
+    assert_eq!(received_requests.iter().filter(|r| r.url.path().contains("sendMessage")).count(), 1);
+    assert_eq!(received_requests.iter().filter(|r| r.url.path() == "/api/v1/statuses").count(), 1);
+
+    let cache_manager = FileSystemCacheManager::builder()
+        .cache_dir(cache.path().to_str().unwrap().to_string())
+        .build();
+    let meta = cache_manager.load_metadata("160532").await.unwrap().unwrap();
+    for channel in [PublisherChannel::Telegram, PublisherChannel::Mastodon, PublisherChannel::File] {
+        assert!(meta.published_channels.contains(&channel), "{} must be recorded", channel);
+        assert!(meta.channel_posts.contains_key(&channel), "{} post must be recorded", channel);
+    }
+    assert!(
+        cache_manager
+            .is_fully_published("160532", &[PublisherChannel::Telegram, PublisherChannel::Mastodon, PublisherChannel::File])
+            .await
+            .unwrap()
+    );
+
+    server.verify().await;
+}
+
+/// Тест проверяет channels.<name>.post_template: из одного элемента каналы получают разные посты,
+/// при этом контекст шаблонов (url, project_id, summary, метаданные) одинаковый
+#[tokio::test]
+#[serial]
+async fn per_channel_post_template_renders_different_posts() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+    mount_telegram(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        true,  // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let mut cfg_text = std::fs::read_to_string(cfg_file.path()).unwrap();
+    cfg_text.push_str(
+        "\nchannels:\n  telegram:\n    post_template: \"<b>{{ project_id }}</b> {{ url }} {{ department }}\"\n  file:\n    post_template: \"{{ project_id }} | {{ url }} | {{ department }}\"\n",
+    );
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(predicate::str::contains(
+        "160532 | https://regulation.gov.ru/projects/160532 | Минздрав России",
+    ));
+    output_file.assert(predicate::str::contains("<b>").not());
+
+    let received_requests = server.received_requests().await.unwrap();
+    let telegram_request = received_requests
+        .iter()
+        .find(|req| req.url.path().contains("sendMessage"))
+        .expect("telegram post must be sent");
+    let body: serde_json::Value = serde_json::from_slice(&telegram_request.body).unwrap();
+    assert_eq!(
+        body["text"],
+        "<b>160532</b> https://regulation.gov.ru/projects/160532 Минздрав России"
+    );
+}
@@ -1,4 +1,5 @@
-use luminis::{crawlers::Manifest, run_with_config_path};
+use luminis::{crawlers::Manifest, run_with_config_path, run_with_options};
+use luminis::models::config::RunOptions;
 use luminis::services::cache_manager_impl::FileSystemCacheManager;
 use luminis::traits::cache_manager::CacheManager;
 use serial_test::serial;
@@ -35,6 +36,7 @@ async fn test_npalist_offset0_reading() {
     // Предварительно создаем manifest.json с min_published_project_id=160533 (все элементы на offset=0 считаются новыми)
     let manifest = Manifest {
         min_published_project_id: Some(160533),
+        ..Default::default()
     };
     _cache_manager.save_manifest(&manifest).await.unwrap();
     
@@ -79,7 +81,7 @@ async fn test_npalist_offset0_reading() {
     
     // Проверяем, что manifest.json обновился после обработки новых элементов
     let updated_manifest = _cache_manager.load_manifest().await.unwrap();
-    assert!(updated_manifest.min_published_project_id.is_some(), "manifest should be updated with min_published_project_id");
+    assert!(!updated_manifest.min_published_project_ids.is_empty(), "manifest should be updated with min_published_project_id of the source");
     
     // Verify mocks were called
     server.verify().await;
@@ -524,6 +526,7 @@ async fn test_full_reading_cycle_with_manifest() {
     // Предварительно создаем manifest.json с min_published_project_id=160533 (все элементы на offset=0 считаются новыми)
     let manifest = Manifest {
         min_published_project_id: Some(160533),
+        ..Default::default()
     };
     _cache_manager.save_manifest(&manifest).await.unwrap();
     
@@ -600,7 +603,7 @@ async fn test_full_reading_cycle_with_manifest() {
     
     // Проверяем, что manifest.json обновился с правильными данными
     let updated_manifest = _cache_manager.load_manifest().await.unwrap();
-    assert_eq!(updated_manifest.min_published_project_id, Some(160531));
+    assert_eq!(updated_manifest.min_published_project_ids.values().copied().collect::<Vec<_>>(), vec![160531]);
     
     // Проверяем порядок запросов
     let received_requests = server.received_requests().await.unwrap();
@@ -619,4 +622,252 @@ async fn test_full_reading_cycle_with_manifest() {
     
     // Verify mocks were called
     server.verify().await;
-}
\ No newline at end of file
+}
+/// Тест проверяет, что CLI-переопределения offset/limit обходят offset, вычисленный по manifest
+#[tokio::test]
+#[serial]
+async fn test_offset_limit_overrides_bypass_manifest() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("post.txt");
+    let cache = temp_dir.child("cache");
+
+    // manifest указывает на другой диапазон — он должен быть проигнорирован
+    let cache_manager = FileSystemCacheManager::builder()
+        .cache_dir(cache.path().to_str().unwrap().to_string())
+        .build();
+    cache_manager
+        .save_manifest(&Manifest { min_published_project_id: Some(160533), ..Default::default() })
+        .await
+        .unwrap();
+
+    let npalist_xml = std::fs::read_to_string(
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/mocks/npalist_offset58.xml"),
+    )
+    .unwrap();
+    wiremock::Mock::given(wiremock::matchers::method("GET"))
+        .and(wiremock::matchers::path_regex(r"/api/npalist/"))
+        .and(wiremock::matchers::query_param("limit", "20"))
+        .and(wiremock::matchers::query_param("offset", "120"))
+        .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(npalist_xml))
+        .expect(1..)
+        .mount(&server)
+        .await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+
+    let options = RunOptions { offset: Some(120), limit: Some(20), ..Default::default() };
+    let result = run_with_options(cfg_file.path().to_str().unwrap(), None, options).await;
+    assert_eq!(result.is_ok(), true, "Run should succeed");
+
+    let received_requests = server.received_requests().await.unwrap();
+    let npalist_queries: Vec<String> = received_requests
+        .iter()
+        .filter(|req| req.url.path().starts_with("/api/npalist/"))
+        .map(|req| req.url.query().unwrap_or("").to_string())
+        .collect();
+    assert_eq!(npalist_queries.is_empty(), false);
+    for q in &npalist_queries {
+        assert_eq!(q.contains("offset=120") && q.contains("limit=20"), true, "unexpected npalist request: {}", q);
+    }
+
+    output_file.assert(predicate::str::is_empty().not());
+    server.verify().await;
+}
+
+/// Тест проверяет лимит элементов страницы истории: при run.max_posts_per_run=1 со страницы
+/// offset=50 в worker уходит один элемент, а manifest не сдвигается за неопубликованные
+#[tokio::test]
+#[serial]
+async fn test_history_page_cap_keeps_manifest_on_published_items() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("post.txt");
+    let cache = temp_dir.child("cache");
+
+    let cache_manager = FileSystemCacheManager::builder()
+        .cache_dir(cache.path().to_str().unwrap().to_string())
+        .build();
+    cache_manager
+        .save_manifest(&Manifest { min_published_project_id: Some(160533), ..Default::default() })
+        .await
+        .unwrap();
+
+    // Все элементы offset=0 уже опубликованы в канал File
+    let project_ids_offset0 = [
+        "160532", "160531", "160530", "160529", "160528", "160527", "160526", "160525", "160524", "160523",
+        "160521", "160520", "160519", "160518", "160517", "160516", "160515", "160514", "160513", "160512",
+        "160511", "160510", "160508", "160507", "160504", "160501", "160500", "160499", "160498", "160497",
+        "160496", "160495", "160494", "160493", "160492", "160491", "160490", "160489", "160488", "160487",
+        "160486", "160485", "160484", "160483", "160482", "160481", "160480", "160479", "160478", "160477"
+    ];
+    for project_id in &project_ids_offset0 {
+        let metadata = serde_json::json!({
+            "project_id": project_id,
+            "docx_path": format!("{}.docx", project_id),
+            "markdown_path": format!("{}.md", project_id),
+            "summary_path": null,
+            "post_path": null,
+            "published_channels": ["File"],
+            "created_at": chrono::Utc::now().to_rfc3339(),
+            "channel_summaries": {},
+            "channel_posts": {},
+            "crawl_metadata": []
+        });
+        cache.child(project_id).child("metadata.json").write_str(&serde_json::to_string_pretty(&metadata).unwrap()).unwrap();
+    }
+
+    // Страница истории offset=50: пять неопубликованных элементов (160475..160471)
+    mount_npalist_offset0(&server).await;
+    mount_npalist_offset50(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("  npalist:\n", "  npalist:\n    max_items_per_page: 3\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let result = run_with_config_path(cfg_file.path().to_str().unwrap(), None).await;
+    assert_eq!(result.is_ok(), true, "Run should succeed");
+
+    output_file.assert(predicate::str::contains("160475"));
+    assert_eq!(cache_manager.is_published_in_channel("160475", luminis::models::channel::PublisherChannel::File).await.unwrap(), true);
+    // Элементы сверх лимита не отправлялись в worker
+    for project_id in ["160474", "160473", "160472", "160471"] {
+        assert_eq!(cache.child(project_id).path().exists(), false, "{} must be left for the next run", project_id);
+    }
+
+    // manifest не указывает ниже последнего подтвержденно опубликованного элемента
+    let updated_manifest = cache_manager.load_manifest().await.unwrap();
+    let min_id = *updated_manifest.min_published_project_ids.values().next().expect("manifest must keep min_published_project_id");
+    assert!(min_id >= 160475, "manifest skipped unpublished items: {}", min_id);
+}
+
+/// Тест проверяет crawler.history_pages_per_run: первый запуск читает одну страницу истории
+/// и сохраняет offset следующей в manifest, второй запуск продолжает с сохраненного offset
+#[tokio::test]
+#[serial]
+async fn test_history_pages_per_run_resumes_from_saved_offset() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("post.txt");
+    let cache = temp_dir.child("cache");
+
+    let cache_manager = FileSystemCacheManager::builder()
+        .cache_dir(cache.path().to_str().unwrap().to_string())
+        .build();
+
+    // Все элементы offset=0 и страницы истории offset=50 уже опубликованы в канал File
+    let published_ids = [
+        "160532", "160531", "160530", "160529", "160528", "160527", "160526", "160525", "160524", "160523",
+        "160521", "160520", "160519", "160518", "160517", "160516", "160515", "160514", "160513", "160512",
+        "160511", "160510", "160508", "160507", "160504", "160501", "160500", "160499", "160498", "160497",
+        "160496", "160495", "160494", "160493", "160492", "160491", "160490", "160489", "160488", "160487",
+        "160486", "160485", "160484", "160483", "160482", "160481", "160480", "160479", "160478", "160477",
+        "160475", "160474"
+    ];
+    for project_id in &published_ids {
+        let metadata = serde_json::json!({
+            "project_id": project_id,
+            "docx_path": format!("{}.docx", project_id),
+            "markdown_path": format!("{}.md", project_id),
+            "published_channels": ["File"],
+            "created_at": chrono::Utc::now().to_rfc3339(),
+            "channel_summaries": {},
+            "channel_posts": {},
+            "crawl_metadata": []
+        });
+        cache.child(project_id).child("metadata.json").write_str(&serde_json::to_string_pretty(&metadata).unwrap()).unwrap();
+    }
+
+    mount_npalist_offset0(&server).await;
+    mount_npalist_offset50(&server).await;
+    // Страница offset=100 пустая: конец истории
+    wiremock::Mock::given(wiremock::matchers::method("GET"))
+        .and(wiremock::matchers::path_regex(r"/api/npalist/"))
+        .and(wiremock::matchers::query_param("offset", "100"))
+        .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(
+            r#"<projects offset="100" limit="50" sort="desc" total="119927"></projects>"#,
+        ))
+        .mount(&server)
+        .await;
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("crawler:\n", "crawler:\n  history_pages_per_run: 1\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let history_requests = |offset: &'static str| {
+        let server = &server;
+        async move {
+            server
+                .received_requests()
+                .await
+                .unwrap()
+                .iter()
+                .filter(|r| r.url.path().contains("/api/npalist/"))
+                .filter(|r| r.url.query_pairs().any(|(k, v)| k == "offset" && v == offset))
+                .count()
+        }
+    };
+
+    // Первый запуск: одна страница истории (offset=50), offset=100 сохранен на следующий запуск
+    let result = run_with_config_path(cfg_file.path().to_str().unwrap(), None).await;
+    assert_eq!(result.is_ok(), true, "First run should succeed");
+    assert_eq!(history_requests("50").await, 1);
+    assert_eq!(history_requests("100").await, 0, "history page limit must stop the first run");
+    let manifest = cache_manager.load_manifest().await.unwrap();
+    assert_eq!(manifest.history_offsets.values().copied().collect::<Vec<_>>(), vec![100]);
+
+    // Второй запуск продолжает с offset=100, а не с вычисленного по min_published_project_id
+    let result = run_with_config_path(cfg_file.path().to_str().unwrap(), None).await;
+    assert_eq!(result.is_ok(), true, "Second run should succeed");
+    assert_eq!(history_requests("50").await, 1, "second run must not rescan offset=50");
+    assert_eq!(history_requests("100").await, 1);
+
+    // Обход истории завершен: сохраненный offset удален
+    let manifest = cache_manager.load_manifest().await.unwrap();
+    assert_eq!(manifest.history_offsets.is_empty(), true);
+}
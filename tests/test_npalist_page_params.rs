@@ -0,0 +1,111 @@
+use luminis::models::config::{AppConfig, RunOptions};
+use luminis::run_with_config_path;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use pretty_assertions::assert_eq;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use crate::common::{mount_docx, mount_gemini_generate, mount_stages, read_mocks, render_config};
+
+/// Тест проверяет, что crawler.npalist.limit и crawler.npalist.sort подставляются в {limit} и {sort}
+/// URL списка: запрос уходит с limit=25 и sort=asc
+#[tokio::test]
+#[serial]
+async fn test_npalist_request_uses_configured_limit_and_sort() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    let npalist_xml = std::fs::read_to_string(
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/mocks/npalist.xml"),
+    )
+    .unwrap();
+    Mock::given(method("GET"))
+        .and(path("/api/npalist/"))
+        .and(query_param("limit", "25"))
+        .and(query_param("offset", "0"))
+        .and(query_param("sort", "asc"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(npalist_xml))
+        .expect(1..)
+        .mount(&server)
+        .await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("&sort=desc", "&sort={sort}")
+        .replace("    limit: 50\n", "    limit: 25\n    sort: asc\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let npalist_requests: Vec<_> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|r| r.url.path() == "/api/npalist/")
+        .collect();
+    assert_eq!(npalist_requests.is_empty(), false);
+    for request in npalist_requests {
+        assert_eq!(request.url.query().unwrap_or_default().contains("limit=25"), true, "unexpected query: {}", request.url);
+    }
+}
+
+/// Тест проверяет, что crawler.npalist.limit вне диапазона 1..=200 отклоняется при проверке конфигурации
+#[test]
+fn test_npalist_limit_out_of_range_fails_validation() {
+    let cfg_text = |limit: u32| {
+        format!(
+            concat!(
+                "llm:\n  model: test\n",
+                "crawler:\n  interval_seconds: 1\n  npalist:\n    url: http://localhost/api/npalist/?limit={{limit}}\n    limit: {}\n",
+                "output:\n  console_enabled: true\n",
+                "run:\n  post_template: \"{{{{ url }}}}\"\n",
+            ),
+            limit
+        )
+    };
+    for limit in [0, 201] {
+        let cfg: AppConfig = serde_yaml::from_str(&cfg_text(limit)).unwrap();
+        assert_eq!(
+            cfg.validate(),
+            Err(format!("crawler.npalist.limit must be between 1 and 200, got {}", limit))
+        );
+    }
+    let cfg: AppConfig = serde_yaml::from_str(&cfg_text(200)).unwrap();
+    assert_eq!(cfg.validate(), Ok(()));
+}
+
+/// Тест проверяет, что --limit из командной строки проверяется тем же диапазоном 1..=200
+#[test]
+fn test_cli_limit_out_of_range_fails_validation() {
+    for limit in [0, 201] {
+        let options = RunOptions { limit: Some(limit), ..RunOptions::default() };
+        assert_eq!(options.validate(), Err(format!("--limit must be between 1 and 200, got {}", limit)));
+    }
+    let options = RunOptions { limit: Some(200), ..RunOptions::default() };
+    assert_eq!(options.validate(), Ok(()));
+    assert_eq!(RunOptions::default().validate(), Ok(()));
+}
@@ -1,4 +1,6 @@
-use luminis::run_with_config_path;
+use luminis::models::config::RunOptions;
+use luminis::models::types::RunOutcome;
+use luminis::{run_with_config_path, run_with_options};
 use serial_test::serial;
 use wiremock::MockServer;
 use wiremock::http::Method;
@@ -879,3 +881,802 @@ async fn publish_telegram_and_console_from_npalist_with_cache() {
     // Verify mocks were called (stages/gemini skipped due to cache)
     server.verify().await;
 }
+
+/// Проверяет, что временная ошибка скачивания документа повторяется и элемент публикуется
+#[tokio::test]
+#[serial]
+async fn publish_after_transient_docx_fetch_failure() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    // Первое скачивание документа падает с 500, следующее — успешно
+    wiremock::Mock::given(wiremock::matchers::method("GET"))
+        .and(wiremock::matchers::path_regex(r"/api/public/Files/GetFile"))
+        .respond_with(wiremock::ResponseTemplate::new(500))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path()).unwrap()
+        .replace("crawler:\n", "crawler:\n  file_max_retry_attempts: 2\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(predicate::str::contains("https://regulation.gov.ru/projects/160532"));
+    output_file.assert(predicate::str::contains("Поправки в закон об ОМС"));
+
+    let received_requests = server.received_requests().await.unwrap();
+    let docx_requests = received_requests
+        .iter()
+        .filter(|req| req.url.path() == "/api/public/Files/GetFile")
+        .count();
+    assert!(docx_requests >= 2, "document fetch should be retried, got {} requests", docx_requests);
+
+    server.verify().await;
+}
+
+/// Проверяет, что при `crawler.npalist.sort: ""` источник, отвечающий 400 на `sort`, все равно читается
+#[tokio::test]
+#[serial]
+async fn publish_from_source_rejecting_sort_param() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+    let npalist_xml = std::fs::read_to_string(
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/mocks/npalist.xml"),
+    )
+    .unwrap();
+
+    // Источник не поддерживает sort: с ним отвечает 400, без него — списком проектов
+    wiremock::Mock::given(wiremock::matchers::method("GET"))
+        .and(wiremock::matchers::path_regex(r"/api/npalist/"))
+        .and(wiremock::matchers::query_param("sort", "desc"))
+        .respond_with(wiremock::ResponseTemplate::new(400).set_body_string("unsupported parameter: sort"))
+        .expect(0)
+        .mount(&server)
+        .await;
+    wiremock::Mock::given(wiremock::matchers::method("GET"))
+        .and(wiremock::matchers::path_regex(r"/api/npalist/"))
+        .and(wiremock::matchers::query_param("limit", "50"))
+        .and(wiremock::matchers::query_param("offset", "0"))
+        .and(wiremock::matchers::query_param_is_missing("sort"))
+        .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(npalist_xml))
+        .expect(1..)
+        .mount(&server)
+        .await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path()).unwrap()
+        .replace("    limit: 50\n", "    limit: 50\n    sort: \"\"\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(predicate::str::contains("https://regulation.gov.ru/projects/160532"));
+
+    server.verify().await;
+}
+
+/// Проверяет, что при telegram.send_document исходный документ уходит через sendDocument с постом в подписи
+#[tokio::test]
+#[serial]
+async fn publish_telegram_document_with_caption() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+    wiremock::Mock::given(wiremock::matchers::method("POST"))
+        .and(wiremock::matchers::path("/botTEST/sendDocument"))
+        .respond_with(wiremock::ResponseTemplate::new(200).set_body_string("{\"ok\":true}"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        true,  // telegram_enabled
+        false, // console_enabled
+        false, // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path()).unwrap()
+        .replace("telegram:\n", "telegram:\n  send_document: true\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let received_requests = server.received_requests().await.unwrap();
+    assert!(
+        !received_requests.iter().any(|req| req.url.path().contains("sendMessage")),
+        "post should be sent as document caption, not as a separate message"
+    );
+    let document_request = received_requests
+        .iter()
+        .find(|req| req.url.path() == "/botTEST/sendDocument")
+        .expect("sendDocument should be called");
+
+    let body = &document_request.body;
+    let body_str = String::from_utf8_lossy(body);
+    assert!(body_str.contains("name=\"chat_id\""));
+    assert!(body_str.contains("filename=\"160532.docx\""));
+    assert!(body_str.contains("Content-Type: application/vnd.openxmlformats-officedocument.wordprocessingml.document"));
+
+    // Подпись — пост целиком в пределах лимита подписи Telegram
+    let caption_start = body_str.find("name=\"caption\"\r\n\r\n").expect("caption part") + "name=\"caption\"\r\n\r\n".len();
+    let caption_len = body_str[caption_start..].find("\r\n--").unwrap();
+    let caption = &body_str[caption_start..caption_start + caption_len];
+    assert!(caption.contains("https://regulation.gov.ru/projects/160532"));
+    assert!(caption.contains("Поправки в закон об ОМС"));
+    assert!(caption.chars().count() <= 1024);
+
+    // Файл документа передается без изменений
+    let docx = std::fs::read(
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/mocks/source.docx"),
+    )
+    .unwrap();
+    assert!(body.windows(docx.len()).any(|w| w == docx.as_slice()), "document bytes should be uploaded");
+
+    server.verify().await;
+}
+
+/// Проверяет, что 503 от stages endpoint повторяется и элемент публикуется после получения fileId
+#[tokio::test]
+#[serial]
+async fn publish_after_transient_stages_failure() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    // Первый запрос stages падает с 503, следующий возвращает fileId
+    wiremock::Mock::given(wiremock::matchers::method("GET"))
+        .and(wiremock::matchers::path_regex(r"/api/public/PublicProjects/GetProjectStages/\d+"))
+        .respond_with(wiremock::ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path()).unwrap()
+        .replace("  file_id:\n", "  file_id:\n    max_retry_attempts: 2\n    timeout_secs: 2\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(predicate::str::contains("https://regulation.gov.ru/projects/160532"));
+
+    let received_requests = server.received_requests().await.unwrap();
+    let stages_requests = received_requests
+        .iter()
+        .filter(|req| req.url.path().ends_with("/GetProjectStages/160532"))
+        .count();
+    assert_eq!(stages_requests, 2, "stages request should be retried once");
+
+    server.verify().await;
+}
+
+/// Тест проверяет, что telegram.message_thread_id передается в sendMessage,
+/// чтобы пост попал в нужную тему группы-форума
+#[tokio::test]
+#[serial]
+async fn publish_telegram_to_forum_topic() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+    mount_telegram(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        true,  // telegram_enabled
+        false, // console_enabled
+        false, // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("telegram:\n", "telegram:\n  message_thread_id: 42\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let received_requests = server.received_requests().await.unwrap();
+    let telegram_requests: Vec<_> = received_requests
+        .iter()
+        .filter(|req| req.url.path().contains("sendMessage"))
+        .collect();
+    assert_eq!(telegram_requests.len(), 1, "Should have exactly one Telegram post");
+
+    let body: serde_json::Value = serde_json::from_slice(&telegram_requests[0].body).unwrap();
+    assert_eq!(body["message_thread_id"], 42);
+    assert_eq!(body["chat_id"], 1);
+}
+
+/// Тест проверяет channels.telegram.on_partial: один из двух чатов отвечает ошибкой.
+/// fail — канал не считается опубликованным, succeed_if_any — считается;
+/// результаты по чатам в обоих случаях сохраняются в metadata.json
+#[tokio::test]
+#[serial]
+async fn publish_telegram_partial_success_per_on_partial() {
+    for (on_partial, expect_published) in [("fail", false), ("succeed_if_any", true)] {
+        let server = MockServer::start().await;
+        let base = server.uri();
+        let stages_json = read_mocks();
+
+        mount_npalist(&server).await;
+        mount_stages(&server, &stages_json).await;
+        mount_docx(&server).await;
+        mount_gemini_generate(&server).await;
+        // Второй чат недоступен боту
+        wiremock::Mock::given(wiremock::matchers::method("POST"))
+            .and(wiremock::matchers::path_regex(r"/botTEST/sendMessage"))
+            .and(wiremock::matchers::body_partial_json(serde_json::json!({"chat_id": 2})))
+            .respond_with(wiremock::ResponseTemplate::new(403).set_body_string(
+                r#"{"ok":false,"error_code":403,"description":"Forbidden: bot was kicked from the group chat"}"#,
+            ))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        mount_telegram(&server).await;
+
+        let temp_dir = assert_fs::TempDir::new().unwrap();
+        let output_file = temp_dir.child("output.txt");
+        let cache = temp_dir.child("cache");
+
+        let cfg_file = render_config(
+            &base,
+            output_file.path().to_str().unwrap(),
+            cache.path().to_str().unwrap(),
+            false, // mastodon_enabled
+            true,  // telegram_enabled
+            false, // console_enabled
+            false, // file_enabled
+            true,  // npalist_enabled
+        );
+        let mut cfg_text = std::fs::read_to_string(cfg_file.path())
+            .unwrap()
+            .replace("telegram:\n", "telegram:\n  extra_chat_ids: [2]\n");
+        cfg_text.push_str(&format!("\nchannels:\n  telegram:\n    on_partial: {}\n", on_partial));
+        std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+        let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+            .await
+            .unwrap();
+
+        let received_requests = server.received_requests().await.unwrap();
+        let chat_ids: Vec<i64> = received_requests
+            .iter()
+            .filter(|req| req.url.path().contains("sendMessage"))
+            .map(|req| serde_json::from_slice::<serde_json::Value>(&req.body).unwrap()["chat_id"].as_i64().unwrap())
+            .collect();
+        assert_eq!(chat_ids.contains(&1) && chat_ids.contains(&2), true, "{}: both chats must be tried", on_partial);
+
+        let metadata: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(cache.path().join("160532").join("metadata.json")).unwrap(),
+        )
+        .unwrap();
+        let published = metadata["published_channels"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|c| c == "Telegram");
+        assert_eq!(published, expect_published, "{}: telegram published status", on_partial);
+        assert_eq!(metadata["target_results"]["Telegram"], serde_json::json!({"1": true, "2": false}));
+    }
+}
+
+/// Тест проверяет, что повторная публикация после частичной неудачи (on_partial: fail) отправляет
+/// пост только чатам, которые его еще не получили
+#[tokio::test]
+#[serial]
+async fn publish_telegram_retry_skips_delivered_chats() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+    wiremock::Mock::given(wiremock::matchers::method("POST"))
+        .and(wiremock::matchers::path_regex(r"/botTEST/sendMessage"))
+        .and(wiremock::matchers::body_partial_json(serde_json::json!({"chat_id": 2})))
+        .respond_with(wiremock::ResponseTemplate::new(403).set_body_string(
+            r#"{"ok":false,"error_code":403,"description":"Forbidden: bot was kicked from the group chat"}"#,
+        ))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_telegram(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        true,  // telegram_enabled
+        false, // console_enabled
+        false, // file_enabled
+        true,  // npalist_enabled
+    );
+    let mut cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("telegram:\n", "telegram:\n  extra_chat_ids: [2]\n");
+    cfg_text.push_str("\nchannels:\n  telegram:\n    on_partial: fail\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    for _ in 0..2 {
+        let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+            .await
+            .unwrap();
+    }
+
+    let chat_ids: Vec<i64> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|req| req.url.path().contains("sendMessage"))
+        .map(|req| serde_json::from_slice::<serde_json::Value>(&req.body).unwrap()["chat_id"].as_i64().unwrap())
+        .collect();
+    assert_eq!(chat_ids.iter().filter(|c| **c == 1).count(), 1, "chat 1 must receive the post once");
+    assert_eq!(chat_ids.iter().filter(|c| **c == 2).count() >= 2, true, "chat 2 must be retried");
+}
+
+/// Тест проверяет --dry-run: в каналы ничего не отправляется и ничего не отмечается опубликованным,
+/// но суммаризация и пост канала сохраняются в кэш
+#[tokio::test]
+#[serial]
+async fn dry_run_skips_publishing_but_caches_posts() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+    mount_telegram(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        true,  // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+
+    let options = RunOptions { dry_run: true, ..Default::default() };
+    let outcome = run_with_options(cfg_file.path().to_str().unwrap(), None, options)
+        .await
+        .unwrap();
+    assert_eq!(outcome, RunOutcome::NothingNew, "dry-run posts must not count as published");
+
+    let received_requests = server.received_requests().await.unwrap();
+    assert_eq!(received_requests.iter().any(|req| req.url.path().contains("sendMessage")), false);
+    assert_eq!(std::fs::read_to_string(output_file.path()).unwrap_or_default(), "");
+
+    let metadata: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(cache.path().join("160532").join("metadata.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(metadata["published_channels"], serde_json::json!([]));
+    assert_eq!(
+        metadata["channel_posts"]["Telegram"].as_str().is_some_and(|p| p.contains("https://regulation.gov.ru/projects/160532")),
+        true
+    );
+    assert_eq!(metadata["channel_summaries"]["File"].as_str().is_some_and(|s| s.contains("Поправки")), true);
+
+    if let Ok(manifest) = std::fs::read_to_string(cache.path().join("manifest.json")) {
+        let manifest: serde_json::Value = serde_json::from_str(&manifest).unwrap();
+        assert_eq!(manifest["min_published_project_ids"], serde_json::Value::Null, "dry-run must not move the manifest");
+    }
+}
+
+/// Тест проверяет run.publish_retry: Telegram дважды отвечает 503, третья попытка успешна —
+/// запрос повторяется с задержкой, всего ровно три запроса
+#[tokio::test]
+#[serial]
+async fn publish_retry_repeats_transient_telegram_errors() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+    wiremock::Mock::given(wiremock::matchers::method("POST"))
+        .and(wiremock::matchers::path_regex(r"/botTEST/sendMessage"))
+        .respond_with(wiremock::ResponseTemplate::new(503).set_body_string("Service Unavailable"))
+        .up_to_n_times(2)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_telegram(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        true,  // telegram_enabled
+        false, // console_enabled
+        false, // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("run:\n", "run:\n  publish_retry:\n    max_attempts: 3\n    base_delay_ms: 10\n    multiplier: 2.0\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let received_requests = server.received_requests().await.unwrap();
+    let telegram_requests = received_requests
+        .iter()
+        .filter(|req| req.url.path().contains("sendMessage"))
+        .count();
+    assert_eq!(telegram_requests, 3);
+}
+
+/// Тест проверяет telegram.parse_mode: MarkdownV2 передается в sendMessage, разметка шаблона
+/// сохраняется, а `.` и `-` в подставленной суммаризации экранируются
+#[tokio::test]
+#[serial]
+async fn telegram_markdown_v2_escapes_substituted_values() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_telegram(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        true,  // telegram_enabled
+        false, // console_enabled
+        false, // file_enabled
+        true,  // npalist_enabled
+    );
+    let mut cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("telegram:\n", "telegram:\n  parse_mode: MarkdownV2\n");
+    cfg_text.push_str("\nsummarizer:\n  test_fixed_summary: \"Срок обсуждения - 30 дней. Итог\"\n");
+    cfg_text.push_str("\nchannels:\n  telegram:\n    post_template: \"*Проект*\\n{{ summary }}\"\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let received_requests = server.received_requests().await.unwrap();
+    let message = received_requests
+        .iter()
+        .find(|req| req.url.path().contains("sendMessage"))
+        .expect("telegram must be called");
+    let body: serde_json::Value = serde_json::from_slice(&message.body).unwrap();
+    assert_eq!(body["parse_mode"], "MarkdownV2");
+    assert_eq!(body["text"], "*Проект*\nСрок обсуждения \\- 30 дней\\. Итог");
+}
+
+/// Тест проверяет telegram.split_long_messages: пост длиннее max_chars отправляется несколькими
+/// сообщениями по границам абзацев, по порядку, без повторов текста; первое начинается со ссылки
+#[tokio::test]
+#[serial]
+async fn telegram_splits_long_post_into_sequential_messages() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_telegram(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        true,  // telegram_enabled
+        false, // console_enabled
+        false, // file_enabled
+        true,  // npalist_enabled
+    );
+    let paragraphs = [
+        "Первый абзац: законопроект меняет порядок оплаты медицинской помощи по ОМС для застрахованных.",
+        "Второй абзац: страховые организации получат новые обязанности по информированию граждан.",
+        "Третий абзац: поправки вступят в силу с первого января следующего года после принятия.",
+    ];
+    let mut cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("  max_chars: 4096\n", "  max_chars: 200\n  split_long_messages: true\n");
+    cfg_text.push_str(&format!("\nsummarizer:\n  test_fixed_summary: \"{}\"\n", paragraphs.join("\\n\\n")));
+    cfg_text.push_str("\nchannels:\n  telegram:\n    post_template: \"{{ url }}\\n\\n{{ summary }}\"\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let received_requests = server.received_requests().await.unwrap();
+    let texts: Vec<String> = received_requests
+        .iter()
+        .filter(|req| req.url.path().contains("sendMessage"))
+        .map(|req| serde_json::from_slice::<serde_json::Value>(&req.body).unwrap()["text"].as_str().unwrap().to_string())
+        .collect();
+
+    assert_eq!(texts.len() >= 2, true, "long post must be split: {:?}", texts);
+    assert_eq!(texts[0].starts_with(&format!("{}/", base)) || texts[0].starts_with("http"), true, "first part keeps the URL: {:?}", texts);
+    assert_eq!(texts.iter().all(|t| t.chars().count() <= 200), true);
+    // Каждый абзац ровно в одной части, части идут по порядку
+    let positions: Vec<usize> = paragraphs
+        .iter()
+        .map(|p| {
+            let holders: Vec<usize> = (0..texts.len()).filter(|i| texts[*i].contains(p)).collect();
+            assert_eq!(holders.len(), 1, "paragraph must be sent once: {}", p);
+            holders[0]
+        })
+        .collect();
+    assert_eq!(positions.windows(2).all(|w| w[0] <= w[1]), true);
+}
+
+/// Тест проверяет, что ошибка отправки части длинного поста (telegram.split_long_messages)
+/// оставляет канал неопубликованным, а следующие части не отправляются
+#[tokio::test]
+#[serial]
+async fn telegram_failed_message_part_is_not_published() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    wiremock::Mock::given(wiremock::matchers::method("POST"))
+        .and(wiremock::matchers::path_regex(r"/botTEST/sendMessage"))
+        .and(wiremock::matchers::body_string_contains("Второй абзац"))
+        .respond_with(wiremock::ResponseTemplate::new(400).set_body_string(
+            r#"{"ok":false,"error_code":400,"description":"Bad Request"}"#,
+        ))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_telegram(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        true,  // telegram_enabled
+        false, // console_enabled
+        false, // file_enabled
+        true,  // npalist_enabled
+    );
+    let paragraphs = [
+        "Первый абзац: законопроект меняет порядок оплаты медицинской помощи по ОМС для застрахованных.",
+        "Второй абзац: страховые организации получат новые обязанности по информированию граждан.",
+        "Третий абзац: поправки вступят в силу с первого января следующего года после принятия.",
+    ];
+    let mut cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("  max_chars: 4096\n", "  max_chars: 120\n  split_long_messages: true\n");
+    cfg_text.push_str(&format!("\nsummarizer:\n  test_fixed_summary: \"{}\"\n", paragraphs.join("\\n\\n")));
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let received_requests = server.received_requests().await.unwrap();
+    let third_sent = received_requests
+        .iter()
+        .filter(|req| req.url.path().contains("sendMessage"))
+        .any(|req| String::from_utf8_lossy(&req.body).contains("Третий абзац"));
+    assert_eq!(third_sent, false, "parts after the failed one must not be sent");
+
+    let metadata: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(cache.path().join("160532").join("metadata.json")).unwrap(),
+    )
+    .unwrap();
+    let published = metadata["published_channels"].as_array().unwrap().iter().any(|c| c == "Telegram");
+    assert_eq!(published, false, "half-sent telegram post must not be marked published");
+}
+
+/// Тест проверяет content_hash опубликованных постов: проект доходит до worker повторно
+/// (Telegram в первом запуске не принял пост), уже опубликованный в File пост публикуется
+/// заново только если после правки шаблона он рендерится иначе
+#[tokio::test]
+#[serial]
+async fn changed_post_template_republishes_published_channel() {
+    for (template_changed, expected_file_posts) in [(false, 1), (true, 2)] {
+        let server = MockServer::start().await;
+        let base = server.uri();
+        let stages_json = read_mocks();
+
+        mount_npalist(&server).await;
+        mount_stages(&server, &stages_json).await;
+        mount_docx(&server).await;
+        mount_gemini_generate(&server).await;
+        // Первая отправка в Telegram отклоняется, во втором запуске канал принимает пост
+        wiremock::Mock::given(wiremock::matchers::method("POST"))
+            .and(wiremock::matchers::path_regex(r"/botTEST/sendMessage"))
+            .respond_with(wiremock::ResponseTemplate::new(400).set_body_string(
+                r#"{"ok":false,"error_code":400,"description":"Bad Request: chat not found"}"#,
+            ))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        mount_telegram(&server).await;
+
+        let temp_dir = assert_fs::TempDir::new().unwrap();
+        let output_file = temp_dir.child("output.txt");
+        let cache = temp_dir.child("cache");
+
+        let cfg_file = render_config(
+            &base,
+            output_file.path().to_str().unwrap(),
+            cache.path().to_str().unwrap(),
+            false, // mastodon_enabled
+            true,  // telegram_enabled
+            false, // console_enabled
+            true,  // file_enabled
+            true,  // npalist_enabled
+        );
+        let cfg_text = std::fs::read_to_string(cfg_file.path())
+            .unwrap()
+            .replace("  file_append: false\n", "  file_append: true\n");
+        std::fs::write(cfg_file.path(), &cfg_text).unwrap();
+
+        let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+            .await
+            .unwrap();
+
+        if template_changed {
+            std::fs::write(cfg_file.path(), cfg_text.replace("Метаданные: [", "Мета: [")).unwrap();
+        }
+        let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+            .await
+            .unwrap();
+
+        let output = std::fs::read_to_string(output_file.path()).unwrap();
+        assert_eq!(
+            output.matches("regulation.gov.ru/projects/160532").count(),
+            expected_file_posts,
+            "template_changed={}: {}",
+            template_changed,
+            output
+        );
+        assert_eq!(output.contains("Мета: ["), template_changed);
+
+        let metadata: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(cache.path().join("160532").join("metadata.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(metadata["content_hash"]["File"].is_string(), true);
+        assert_eq!(metadata["content_hash"]["Telegram"].is_string(), true);
+    }
+}
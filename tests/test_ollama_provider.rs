@@ -0,0 +1,70 @@
+use luminis::run_with_config_path;
+use serial_test::serial;
+use wiremock::MockServer;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+
+use crate::common::{mount_docx, mount_npalist, mount_stages, read_mocks, render_config};
+
+/// Тест проверяет суммаризацию через локальный Ollama: запрос в /api/generate
+/// с `stream: false` без ключа API, текст ответа берется из поля `response`
+#[tokio::test]
+#[serial]
+async fn test_ollama_provider_generates_summary_without_api_key() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    wiremock::Mock::given(wiremock::matchers::method("POST"))
+        .and(wiremock::matchers::path("/api/generate"))
+        .and(wiremock::matchers::body_partial_json(serde_json::json!({"model": "llama3.1", "stream": false})))
+        .respond_with(wiremock::ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "model": "llama3.1",
+            "response": "Поправки в закон об ОМС от локальной модели",
+            "done": true
+        })))
+        .mount(&server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("  model: gemini-2.0-flash\n", "  model: llama3.1\n")
+        .replace("  provider: Gemini\n", "  provider: Ollama\n")
+        .replace(&format!("  base_url: {}/v1beta\n", base), &format!("  base_url: {}\n", base))
+        .replace("  api_key: TESTKEY\n", "");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(predicate::str::contains("Поправки в закон об ОМС от локальной модели"));
+
+    let received = server.received_requests().await.unwrap();
+    let generate = received
+        .iter()
+        .find(|r| r.url.path() == "/api/generate")
+        .expect("ollama must be called");
+    assert_eq!(generate.headers.get("authorization").is_none(), true);
+    let body: serde_json::Value = serde_json::from_slice(&generate.body).unwrap();
+    assert_eq!(body["prompt"].as_str().is_some_and(|p| !p.is_empty()), true);
+}
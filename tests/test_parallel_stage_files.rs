@@ -0,0 +1,146 @@
+use luminis::run_with_config_path;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use crate::common::{mount_docx, mount_gemini_generate, mount_stages, read_mocks, render_config};
+
+/// Монтирует npalist с проектом 160532, у которого два файла параллельной стадии,
+/// а также stages, документ и ответ Gemini
+async fn mount_project_with_files(server: &MockServer) {
+    let npalist_xml = std::fs::read_to_string(
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/mocks/npalist.xml"),
+    )
+    .unwrap()
+    .replacen(
+        "<project id=\"160532\">",
+        "<project id=\"160532\">\n    <parallelStageFile>abc-123</parallelStageFile>\n    <parallelStageFile>def-456</parallelStageFile>",
+        1,
+    );
+    Mock::given(method("GET"))
+        .and(path("/api/npalist/"))
+        .and(query_param("offset", "0"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(npalist_xml))
+        .mount(server)
+        .await;
+    mount_stages(server, &read_mocks()).await;
+    mount_docx(server).await;
+    mount_gemini_generate(server).await;
+}
+
+/// Ответ HEAD Files endpoint для `file_id`, ожидаемый ровно `times` раз
+async fn mount_file_head(server: &MockServer, file_id: &str, response: ResponseTemplate, times: u64) {
+    Mock::given(method("HEAD"))
+        .and(path("/api/public/Files/GetFile"))
+        .and(query_param("fileId", file_id))
+        .respond_with(response)
+        .expect(times)
+        .mount(server)
+        .await;
+}
+
+fn named_file() -> ResponseTemplate {
+    ResponseTemplate::new(200).insert_header(
+        "Content-Disposition",
+        "attachment; filename=\"notes.docx\"; filename*=UTF-8''%D0%97%D0%B0%D0%BA%D0%BB%D1%8E%D1%87%D0%B5%D0%BD%D0%B8%D0%B5.docx",
+    )
+}
+
+/// Конфигурация с crawler.resolve_parallel_stage_files и шаблоном поста файла с переменными файлов
+fn write_config(base: &str, output_file: &assert_fs::fixture::ChildPath, cache: &assert_fs::fixture::ChildPath) -> tempfile::NamedTempFile {
+    let cfg_file = render_config(
+        base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replacen("crawler:\n", "crawler:\n  resolve_parallel_stage_files: true\n", 1)
+        + concat!(
+            "channels:\n  file:\n    post_template: |\n",
+            "      files={{ parallel_stage_files }}\n",
+            "      names={{ parallel_stage_file_names }}\n",
+            "      urls={{ parallel_stage_file_urls }}\n",
+        );
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+    cfg_file
+}
+
+/// Тест проверяет, что при crawler.resolve_parallel_stage_files fileId параллельной стадии
+/// дополняются именами из Files endpoint и URL скачивания, а неудачный запрос имени
+/// оставляет fileId и не мешает публикации
+#[tokio::test]
+#[serial]
+async fn test_parallel_stage_files_are_resolved_to_names_and_urls() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    mount_project_with_files(&server).await;
+    mount_file_head(&server, "abc-123", named_file(), 1).await;
+    mount_file_head(&server, "def-456", ResponseTemplate::new(500), 1).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+    let cfg_file = write_config(&base, &output_file, &cache);
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(predicate::str::contains("files=abc-123, def-456"));
+    output_file.assert(predicate::str::contains("names=Заключение.docx, def-456"));
+    output_file.assert(predicate::str::contains(format!(
+        "urls={base}/api/public/Files/GetFile?fileId=abc-123, {base}/api/public/Files/GetFile?fileId=def-456"
+    )));
+}
+
+/// Тест проверяет, что имя файла, найденное при прошлой обработке проекта (crawl_metadata в кэше),
+/// повторно не запрашивается, а fileId без имени запрашивается снова
+#[tokio::test]
+#[serial]
+async fn test_resolved_file_names_are_reused_from_cache() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    mount_project_with_files(&server).await;
+    mount_file_head(&server, "abc-123", named_file(), 0).await;
+    mount_file_head(&server, "def-456", named_file(), 1).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+    cache
+        .child("160532/metadata.json")
+        .write_str(
+            &serde_json::json!({
+                "project_id": "160532",
+                "docx_path": "",
+                "markdown_path": "",
+                "published_channels": [],
+                "created_at": "2025-01-01T00:00:00+00:00",
+                "channel_summaries": {},
+                "channel_posts": {},
+                "crawl_metadata": [
+                    { "ParallelStageFiles": ["abc-123", "def-456"] },
+                    { "ParallelStageFileNames": ["Прошлое имя.docx", "def-456"] }
+                ]
+            })
+            .to_string(),
+        )
+        .unwrap();
+    let cfg_file = write_config(&base, &output_file, &cache);
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(predicate::str::contains("names=Прошлое имя.docx, Заключение.docx"));
+}
@@ -0,0 +1,106 @@
+use luminis::run_with_config_path;
+use serial_test::serial;
+use wiremock::MockServer;
+use assert_fs::prelude::*;
+
+mod common;
+
+use crate::common::{mount_gemini_generate, mount_npalist, mount_pdf, mount_stages, read_mocks, render_config};
+
+/// Тест проверяет, что документ в формате PDF распознается по сигнатуре, его текст
+/// извлекается и попадает в промпт суммаризатора, а извлеченный текст кэшируется
+#[tokio::test]
+#[serial]
+async fn test_pdf_document_text_reaches_summarizer() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_pdf(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let received = server.received_requests().await.unwrap();
+    let generate = received
+        .iter()
+        .find(|r| r.url.path().contains("generateContent"))
+        .expect("summarizer must be called");
+    let body = String::from_utf8_lossy(&generate.body);
+    assert_eq!(body.contains("telemedicine"), true, "prompt must contain PDF text: {}", body);
+
+    let extracted = std::fs::read_to_string(cache.path().join("160532").join("extracted.md")).unwrap();
+    assert_eq!(extracted.contains("electronic consent records"), true);
+}
+
+/// Тест проверяет, что PDF при telegram.send_document отправляется как {id}.pdf с типом application/pdf
+#[tokio::test]
+#[serial]
+async fn test_pdf_document_is_sent_with_pdf_name_and_mime() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_pdf(&server).await;
+    mount_gemini_generate(&server).await;
+    wiremock::Mock::given(wiremock::matchers::method("POST"))
+        .and(wiremock::matchers::path("/botTEST/sendDocument"))
+        .respond_with(wiremock::ResponseTemplate::new(200).set_body_string("{\"ok\":true}"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        true,  // telegram_enabled
+        false, // console_enabled
+        false, // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path()).unwrap()
+        .replace("telegram:\n", "telegram:\n  send_document: true\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let received = server.received_requests().await.unwrap();
+    let document = received
+        .iter()
+        .find(|r| r.url.path() == "/botTEST/sendDocument")
+        .expect("sendDocument should be called");
+    let body = String::from_utf8_lossy(&document.body);
+    assert_eq!(body.contains("filename=\"160532.pdf\""), true, "{}", body);
+    assert_eq!(body.contains("Content-Type: application/pdf"), true);
+
+    server.verify().await;
+}
@@ -0,0 +1,65 @@
+use luminis::run_with_config_path;
+use serial_test::serial;
+use wiremock::MockServer;
+use assert_fs::prelude::*;
+
+mod common;
+
+use crate::common::{mount_docx, mount_gemini_generate, mount_npalist, mount_stages, read_mocks, render_config};
+
+/// Тест проверяет crawler.poll_interval_secs: краулер повторяет полный обход каждую секунду,
+/// а worker не завершается по run.max_posts_per_run и публикует следующий элемент во втором цикле.
+/// Запуск останавливает run.max_duration_secs
+#[tokio::test]
+#[serial]
+async fn test_continuous_polling_runs_repeated_crawl_cycles() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("crawler:\n", "crawler:\n  poll_interval_secs: 1\n")
+        .replace("run:\n", "run:\n  max_duration_secs: 5\n")
+        .replace("  file_append: false\n", "  file_append: true\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    tokio::time::timeout(
+        std::time::Duration::from_secs(20),
+        run_with_config_path(cfg_file.path().to_str().unwrap(), None),
+    )
+    .await
+    .expect("run must be stopped by watchdog")
+    .unwrap();
+
+    let received = server.received_requests().await.unwrap();
+    let npalist_requests = received
+        .iter()
+        .filter(|r| r.url.path().starts_with("/api/npalist/") && r.url.query().is_some_and(|q| q.contains("offset=0")))
+        .count();
+    assert_eq!(npalist_requests >= 2, true, "expected at least two crawl cycles, got {}", npalist_requests);
+
+    // По одному посту за цикл: первый проект в первом цикле, следующий — во втором
+    let output = std::fs::read_to_string(output_file.path()).unwrap();
+    assert_eq!(output.contains("regulation.gov.ru/projects/160532"), true);
+    assert_eq!(output.matches("regulation.gov.ru/projects/").count() >= 2, true, "{}", output);
+}
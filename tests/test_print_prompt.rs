@@ -0,0 +1,64 @@
+use serial_test::serial;
+use assert_fs::prelude::*;
+use pretty_assertions::assert_eq;
+use wiremock::MockServer;
+
+mod common;
+
+use crate::common::{mount_docx, mount_npalist, mount_stages, read_mocks, render_config};
+
+/// Тест проверяет режим --print-prompt: промпт с текстом документа печатается,
+/// LLM не вызывается и ничего не публикуется
+#[tokio::test]
+#[serial]
+async fn test_print_prompt_skips_llm_and_emits_prompt() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    // Gemini не мокается: вызов LLM в этом режиме недопустим
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_path = cfg_file.path().to_str().unwrap().to_string();
+
+    let output = tokio::task::spawn_blocking(move || {
+        std::process::Command::new(env!("CARGO_BIN_EXE_luminis"))
+            .args(["--config", &cfg_path, "--print-prompt"])
+            .output()
+            .unwrap()
+    })
+    .await
+    .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert_eq!(output.status.success(), true, "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(stdout.contains("===== PROMPT:"), true);
+    assert_eq!(stdout.contains("Уложить в 20000 символов ответа"), true, "prompt must be rendered with the channel limit");
+    assert_eq!(stdout.contains("Собрание законодательства Российской Федерации"), true, "prompt must contain document text");
+
+    let llm_calls = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|req| req.url.path().contains("generateContent"))
+        .count();
+    assert_eq!(llm_calls, 0);
+    assert_eq!(output_file.path().exists(), false, "nothing must be published");
+}
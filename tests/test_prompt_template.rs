@@ -0,0 +1,80 @@
+use luminis::models::config::AppConfig;
+use luminis::run_with_config_path;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use pretty_assertions::assert_eq;
+use wiremock::MockServer;
+
+mod common;
+
+use crate::common::{mount_docx, mount_gemini_generate, mount_npalist, mount_stages, read_mocks, render_config};
+
+/// Тест проверяет, что summarizer.prompt_template заменяет run.prompt_template и получает
+/// переменные title, url, text, max_chars и language
+#[tokio::test]
+#[serial]
+async fn test_custom_prompt_template_reaches_gemini() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let mut cfg_text = std::fs::read_to_string(cfg_file.path()).unwrap();
+    cfg_text.push_str(concat!(
+        "summarizer:\n  language: en\n  prompt_template: |\n",
+        "    CUSTOM-PROMPT-MARKER lang={{ language }} max={{ max_chars }} url={{ url }}\n",
+        "    {{ text }}\n",
+    ));
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    let gemini = requests
+        .iter()
+        .find(|r| r.url.path().contains("generateContent"))
+        .expect("Gemini must be called");
+    let body = String::from_utf8_lossy(&gemini.body);
+    assert_eq!(
+        body.contains("CUSTOM-PROMPT-MARKER lang=en max=20000 url=https://regulation.gov.ru/projects/160532"),
+        true,
+        "custom prompt must be sent: {}",
+        body
+    );
+    assert_eq!(body.contains("Собрание законодательства Российской Федерации"), true, "prompt must contain document text");
+    assert_eq!(body.contains("Оцени полезность проекта"), false, "run.prompt_template must be overridden");
+}
+
+/// Тест проверяет, что синтаксическая ошибка в шаблоне промпта обнаруживается при проверке конфигурации
+#[test]
+fn test_broken_prompt_template_fails_validation() {
+    let cfg: AppConfig = serde_yaml::from_str(concat!(
+        "llm:\n  model: test\ncrawler:\n  interval_seconds: 1\n",
+        "output:\n  console_enabled: true\n",
+        "run:\n  post_template: \"{{ url }}\"\n",
+        "summarizer:\n  prompt_template: \"Текст: {{ text \"\n",
+    ))
+    .unwrap();
+    let err = cfg.validate().expect_err("broken template must be rejected");
+    assert_eq!(err.starts_with("summarizer.prompt_template is not a valid Tera template"), true, "unexpected error: {}", err);
+}
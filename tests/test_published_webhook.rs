@@ -0,0 +1,91 @@
+use luminis::models::channel::PublisherChannel;
+use luminis::run_with_config_path;
+use luminis::services::cache_manager_impl::FileSystemCacheManager;
+use luminis::traits::cache_manager::CacheManager;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use crate::common::{mount_docx, mount_gemini_generate, mount_npalist, mount_stages, read_mocks, render_config};
+
+async fn run_with_webhook(server: &MockServer, webhook_status: u16) -> assert_fs::TempDir {
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(server).await;
+    mount_stages(server, &stages_json).await;
+    mount_docx(server).await;
+    mount_gemini_generate(server).await;
+    Mock::given(method("POST"))
+        .and(path("/hooks/published"))
+        .respond_with(ResponseTemplate::new(webhook_status))
+        .expect(1)
+        .mount(server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("run:\n", &format!("run:\n  on_published_webhook: {}/hooks/published\n", base));
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(predicate::str::contains("https://regulation.gov.ru/projects/160532"));
+    temp_dir
+}
+
+/// Тест проверяет, что после успешной публикации webhook получает JSON с project_id и каналами
+#[tokio::test]
+#[serial]
+async fn test_webhook_receives_published_payload() {
+    let server = MockServer::start().await;
+    let _temp_dir = run_with_webhook(&server, 200).await;
+
+    let received = server.received_requests().await.unwrap();
+    let hook = received
+        .iter()
+        .find(|r| r.url.path() == "/hooks/published")
+        .expect("webhook must be called");
+    let payload: serde_json::Value = serde_json::from_slice(&hook.body).unwrap();
+    assert_eq!(payload["project_id"], "160532");
+    assert_eq!(payload["url"], "https://regulation.gov.ru/projects/160532");
+    assert_eq!(payload["channels"], serde_json::json!(["file"]));
+
+    server.verify().await;
+}
+
+/// Тест проверяет, что ошибка webhook не отменяет публикацию и не прерывает запуск
+#[tokio::test]
+#[serial]
+async fn test_webhook_failure_does_not_fail_item() {
+    let server = MockServer::start().await;
+    let temp_dir = run_with_webhook(&server, 500).await;
+
+    let cache_manager = FileSystemCacheManager::builder()
+        .cache_dir(temp_dir.path().join("cache").to_string_lossy().to_string())
+        .build();
+    assert!(cache_manager.is_published_in_channel("160532", PublisherChannel::File).await.unwrap());
+
+    server.verify().await;
+}
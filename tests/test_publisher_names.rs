@@ -0,0 +1,54 @@
+use luminis::models::config::{FileFormat, LineEnding};
+use luminis::publishers::console::ConsolePublisher;
+use luminis::publishers::file::FilePublisher;
+use luminis::publishers::mastodon::MastodonPublisher;
+use luminis::publishers::matrix::MatrixPublisher;
+use luminis::publishers::slack::SlackPublisher;
+use luminis::publishers::webhook::WebhookPublisher;
+use luminis::publishers::telegram::RealTelegramApi;
+use luminis::traits::publisher::Publisher;
+use pretty_assertions::assert_eq;
+use reqwest::Client;
+
+/// Тест проверяет, что каждый publisher возвращает стабильное имя для логов и метрик
+#[test]
+fn test_publishers_report_expected_names() {
+    let telegram = RealTelegramApi::builder()
+        .client(Client::new())
+        .base_url("http://localhost".to_string())
+        .token("token".to_string())
+        .chat_id(1)
+        .build();
+    let mastodon = MastodonPublisher::builder()
+        .client(Client::new())
+        .base_url("http://localhost".to_string())
+        .access_token("token".to_string())
+        .build();
+    let matrix = MatrixPublisher::builder()
+        .client(Client::new())
+        .homeserver_url("http://localhost".to_string())
+        .access_token("token".to_string())
+        .room_id("!room:localhost".to_string())
+        .build();
+    let slack = SlackPublisher::builder()
+        .client(Client::new())
+        .webhook_url("http://localhost".to_string())
+        .build();
+    let webhook = WebhookPublisher::builder()
+        .client(Client::new())
+        .url("http://localhost".to_string())
+        .build();
+    let console = ConsolePublisher { max_chars: None };
+    let file = FilePublisher {
+        path: "./post.txt".to_string(),
+        max_chars: None,
+        append: false,
+        format: FileFormat::Text,
+        bom: false,
+        line_ending: LineEnding::Lf,
+    };
+
+    let publishers: Vec<&dyn Publisher> = vec![&telegram, &mastodon, &matrix, &slack, &webhook, &console, &file];
+    let names: Vec<&'static str> = publishers.iter().map(|p| p.name()).collect();
+    assert_eq!(names, vec!["telegram", "mastodon", "matrix", "slack", "webhook", "console", "file"]);
+}
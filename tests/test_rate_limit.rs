@@ -0,0 +1,146 @@
+use async_trait::async_trait;
+use luminis::services::rate_limiter::RateLimiter;
+use luminis::services::summarizer::{ChannelPrompt, Summarizer};
+use luminis::traits::chat_api::ChatApi;
+use pretty_assertions::assert_eq;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// ChatApi, считающий вызовы
+struct CountingChatApi {
+    calls: AtomicUsize,
+}
+
+#[async_trait]
+impl ChatApi for CountingChatApi {
+    async fn call_chat_api(&self, _prompt: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(format!("ответ {}", self.calls.fetch_add(1, Ordering::SeqCst) + 1))
+    }
+}
+
+/// Тест проверяет, что одновременные суммаризации ждут общий лимит summarizer.requests_per_minute:
+/// 4 вызова при 600 в минуту (интервал 100 мс) занимают не меньше 300 мс
+#[tokio::test]
+async fn test_concurrent_summarizations_share_rate_limit() {
+    let cfg: luminis::models::config::AppConfig = serde_yaml::from_str(
+        "llm:\n  model: test\ncrawler:\n  interval_seconds: 1\nsummarizer:\n  requests_per_minute: 600\n",
+    )
+    .unwrap();
+    let api = Arc::new(CountingChatApi { calls: AtomicUsize::new(0) });
+    let summarizer = Arc::new(
+        Summarizer::builder()
+            .chat_api(api.clone())
+            .hard_max_chars(600)
+            .sample_percent(1.0)
+            .max_retry_attempts(0)
+            .retry_delay_secs(0)
+            .build()
+            .with_config(&cfg),
+    );
+
+    let started = Instant::now();
+    let calls = (0..4).map(|i| {
+        let summarizer = Arc::clone(&summarizer);
+        async move {
+            summarizer
+                .summarize_with_limit("Проект", "текст документа", &format!("https://example.org/{}", i), None, Some(300), ChannelPrompt::default())
+                .await
+                .unwrap()
+        }
+    });
+    let summaries = futures_util::future::join_all(calls).await;
+
+    assert_eq!(summaries.len(), 4);
+    assert_eq!(api.calls.load(Ordering::SeqCst), 4);
+    assert!(started.elapsed() >= Duration::from_millis(300), "calls must be spaced by the limiter: {:?}", started.elapsed());
+}
+
+/// Тест проверяет, что клоны ограничителя делят одно ведро
+#[tokio::test]
+async fn test_rate_limiter_clones_share_bucket() {
+    let limiter = RateLimiter::per_minute(1200);
+    assert_eq!(limiter.interval(), Duration::from_millis(50));
+    let clone = limiter.clone();
+
+    let started = Instant::now();
+    limiter.acquire().await;
+    clone.acquire().await;
+    limiter.acquire().await;
+    assert!(started.elapsed() >= Duration::from_millis(100), "elapsed: {:?}", started.elapsed());
+}
+
+/// Тест проверяет, что отмененный ожидающий не занимает слот: после отмены следующий вызов
+/// получает токен через один интервал, а не через два
+#[tokio::test]
+async fn test_cancelled_waiter_releases_slot() {
+    let limiter = RateLimiter::per_minute(600);
+    let started = Instant::now();
+    limiter.acquire().await;
+
+    let cancelled = tokio::time::timeout(Duration::from_millis(10), limiter.acquire()).await;
+    assert!(cancelled.is_err(), "second acquire must still be waiting");
+
+    limiter.acquire().await;
+    let elapsed = started.elapsed();
+    assert!(elapsed >= Duration::from_millis(100), "elapsed: {:?}", elapsed);
+    assert!(elapsed < Duration::from_millis(190), "cancelled waiter must not keep its slot: {:?}", elapsed);
+}
+
+/// ChatApi, отвечающий через `delay`
+struct SlowChatApi {
+    delay: Duration,
+}
+
+#[async_trait]
+impl ChatApi for SlowChatApi {
+    async fn call_chat_api(&self, _prompt: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        tokio::time::sleep(self.delay).await;
+        Ok("ответ".to_string())
+    }
+}
+
+fn limited_summarizer(delay: Duration) -> Arc<Summarizer> {
+    let cfg: luminis::models::config::AppConfig = serde_yaml::from_str(concat!(
+        "llm:\n  model: test\ncrawler:\n  interval_seconds: 1\n",
+        "run:\n  summarization_timeout_secs: 1\n",
+        "summarizer:\n  requests_per_minute: 120\n",
+    ))
+    .unwrap();
+    Arc::new(
+        Summarizer::builder()
+            .chat_api(Arc::new(SlowChatApi { delay }))
+            .hard_max_chars(600)
+            .sample_percent(1.0)
+            .max_retry_attempts(0)
+            .retry_delay_secs(0)
+            .build()
+            .with_config(&cfg),
+    )
+}
+
+/// Тест проверяет, что ожидание summarizer.requests_per_minute не входит в run.summarization_timeout_secs:
+/// второй вызов ждет токен 500 мс и отвечает за 700 мс — вместе дольше таймаута в 1 с, но успешно,
+/// а вызов дольше таймаута по-прежнему прерывается
+#[tokio::test]
+async fn test_rate_limit_wait_is_outside_summarization_timeout() {
+    let summarizer = limited_summarizer(Duration::from_millis(700));
+    assert!(summarizer.times_out_calls());
+    let calls = (0..2).map(|i| {
+        let summarizer = Arc::clone(&summarizer);
+        async move {
+            summarizer
+                .summarize_with_limit("Проект", "текст документа", &format!("https://example.org/{}", i), None, Some(300), ChannelPrompt::default())
+                .await
+        }
+    });
+    for result in futures_util::future::join_all(calls).await {
+        assert_eq!(result.unwrap(), "ответ");
+    }
+
+    let err = limited_summarizer(Duration::from_millis(1500))
+        .summarize_with_limit("Проект", "текст документа", "https://example.org/slow", None, Some(300), ChannelPrompt::default())
+        .await
+        .expect_err("call longer than the timeout must fail");
+    assert_eq!(err.to_string(), "summarizer timeout");
+}
@@ -0,0 +1,136 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use luminis::crawlers::{RssCrawler, fetch_merged, fetch_merged_with_seen};
+use luminis::models::channel::PublisherChannel;
+use luminis::models::config::{CrawlerConfig, RssSources};
+use luminis::services::cache_manager_impl::FileSystemCacheManager;
+use luminis::traits::crawler::Crawler;
+use pretty_assertions::assert_eq;
+use tokio::sync::mpsc;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn rss_feed(items: &[(&str, &str)]) -> String {
+    let items: String = items
+        .iter()
+        .map(|(id, title)| {
+            format!(
+                "<item><guid isPermaLink=\"false\">{id}</guid><link>https://regulation.gov.ru/projects/{id}</link><title>{title}</title></item>"
+            )
+        })
+        .collect();
+    format!(r#"<?xml version="1.0" encoding="utf-8"?><rss version="2.0"><channel>{items}</channel></rss>"#)
+}
+
+async fn mount_feed(server: &MockServer, feed_path: &str, body: String) {
+    Mock::given(method("GET"))
+        .and(path(feed_path))
+        .respond_with(ResponseTemplate::new(200).set_body_string(body))
+        .mount(server)
+        .await;
+}
+
+/// Тест проверяет, что элемент из двух RSS-лент отправляется в worker один раз
+#[tokio::test]
+async fn test_two_rss_sources_deduplicate_by_project_id() {
+    let server = MockServer::start().await;
+    mount_feed(&server, "/rss/a", rss_feed(&[("160532", "Проект A"), ("160632", "Общий проект")])).await;
+    mount_feed(&server, "/rss/b", rss_feed(&[("160632", "Общий проект"), ("160628", "Проект B")])).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let cache_manager = Arc::new(
+        FileSystemCacheManager::builder()
+            .cache_dir(temp_dir.path().to_str().unwrap().to_string())
+            .build(),
+    );
+    let crawler_for = |feed_path: &str| {
+        RssCrawler::builder()
+            .url(format!("{}{}", server.uri(), feed_path))
+            .project_id_re(regex::Regex::new(r"(\d{5,})").unwrap())
+            .timeout(Duration::from_secs(2))
+            .cache_manager(cache_manager.clone())
+            .enabled_channels(vec![PublisherChannel::File])
+            .build()
+            .unwrap()
+    };
+    let a = crawler_for("/rss/a");
+    let b = crawler_for("/rss/b");
+    let crawlers: Vec<&dyn Crawler> = vec![&a, &b];
+
+    let (tx, mut rx) = mpsc::channel(16);
+    fetch_merged(&crawlers, tx).await.unwrap();
+    let mut ids = Vec::new();
+    while let Some(item) = rx.recv().await {
+        ids.push(item.project_id.unwrap());
+    }
+    ids.sort();
+
+    assert_eq!(ids, vec!["160532", "160628", "160632"]);
+}
+
+/// Тест проверяет, что повтор обхода после ошибки одной ленты не отправляет заново элементы,
+/// уже отправленные из других лент в прошлой попытке
+#[tokio::test]
+async fn test_retry_after_failed_feed_does_not_resend_items() {
+    let server = MockServer::start().await;
+    mount_feed(&server, "/rss/a", rss_feed(&[("160532", "Проект A")])).await;
+    Mock::given(method("GET"))
+        .and(path("/rss/b"))
+        .respond_with(ResponseTemplate::new(500))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    mount_feed(&server, "/rss/b", rss_feed(&[("160628", "Проект B")])).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let cache_manager = Arc::new(
+        FileSystemCacheManager::builder()
+            .cache_dir(temp_dir.path().to_str().unwrap().to_string())
+            .build(),
+    );
+    let crawler_for = |feed_path: &str| {
+        RssCrawler::builder()
+            .url(format!("{}{}", server.uri(), feed_path))
+            .project_id_re(regex::Regex::new(r"(\d{5,})").unwrap())
+            .timeout(Duration::from_secs(2))
+            .cache_manager(cache_manager.clone())
+            .enabled_channels(vec![PublisherChannel::File])
+            .build()
+            .unwrap()
+    };
+    let a = crawler_for("/rss/a");
+    let b = crawler_for("/rss/b");
+    let crawlers: Vec<&dyn Crawler> = vec![&a, &b];
+    let seen = Mutex::new(HashSet::new());
+
+    let (tx, mut rx) = mpsc::channel(16);
+    assert!(fetch_merged_with_seen(&crawlers, tx.clone(), &seen).await.is_err());
+    fetch_merged_with_seen(&crawlers, tx, &seen).await.unwrap();
+    let mut ids = Vec::new();
+    while let Some(item) = rx.recv().await {
+        ids.push(item.project_id.unwrap());
+    }
+
+    assert_eq!(ids, vec!["160532", "160628"]);
+}
+
+/// Тест проверяет обратную совместимость: crawler.rss задается одной записью или списком
+#[test]
+fn test_rss_config_single_or_list() {
+    let single: CrawlerConfig = serde_yaml::from_str(
+        "interval_seconds: 10\nrss:\n  url: https://regulation.gov.ru/api/public/Rss\n  regex: \"(\\\\d{5,})\"\n",
+    )
+    .unwrap();
+    let single = single.rss.unwrap();
+    assert!(matches!(single, RssSources::One(_)));
+    assert_eq!(single.enabled().len(), 1);
+
+    let list: CrawlerConfig = serde_yaml::from_str(
+        "interval_seconds: 10\nrss:\n  - url: https://a.example/rss\n  - url: https://b.example/rss\n    enabled: false\n  - url: https://c.example/rss\n",
+    )
+    .unwrap();
+    let urls: Vec<String> = list.rss.unwrap().enabled().iter().map(|r| r.url.clone()).collect();
+    assert_eq!(urls, vec!["https://a.example/rss", "https://c.example/rss"]);
+}
@@ -0,0 +1,94 @@
+use luminis::run_with_config_path;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use assert_fs::TempDir;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use std::fs;
+use wiremock::MockServer;
+
+mod common;
+
+use crate::common::{mount_docx, mount_gemini_generate, mount_npalist, mount_stages, read_mocks, render_config};
+
+/// Публикует в файл с секцией run.filters и возвращает каталог (output.txt, cache) и сервер
+async fn publish_with_filters(filters_section: &str) -> (TempDir, MockServer) {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("run:\n  max_posts_per_run: 1\n", &format!("run:\n  max_posts_per_run: 1\n{}", filters_section));
+    fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    (temp_dir, server)
+}
+
+/// Тест проверяет, что элемент ведомства вне run.filters.department_in пропускается до суммаризации,
+/// а элемент разрешенного ведомства публикуется
+#[tokio::test]
+#[serial]
+async fn test_excluded_department_skipped_allowed_published() {
+    let (temp_dir, server) = publish_with_filters(
+        "  filters:\n    department_in: [\"фас россии\"]\n    status_not_in: [\"Архив\"]\n",
+    )
+    .await;
+
+    // 160532 — Минздрав России, 160531 — ФАС России
+    let output_file = temp_dir.child("output.txt");
+    output_file.assert(predicate::str::contains("https://regulation.gov.ru/projects/160531"));
+    output_file.assert(predicate::str::contains("projects/160532").not());
+
+    let meta: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(temp_dir.child("cache/160532/metadata.json").path()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        meta["skip_reason"].as_str(),
+        Some("department \"Минздрав России\" is not in run.filters.department_in")
+    );
+
+    let stages_for_skipped = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|req| req.url.path() == "/api/public/PublicProjects/GetProjectStages/160532")
+        .count();
+    assert_eq!(stages_for_skipped, 0, "filtered item must not be downloaded");
+}
+
+/// Тест проверяет, что пустые фильтры пропускают все элементы
+#[tokio::test]
+#[serial]
+async fn test_empty_filters_allow_all() {
+    let (temp_dir, _server) = publish_with_filters("  filters:\n    department_in: []\n").await;
+
+    temp_dir
+        .child("output.txt")
+        .assert(predicate::str::contains("https://regulation.gov.ru/projects/160532"));
+}
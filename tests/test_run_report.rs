@@ -0,0 +1,180 @@
+use luminis::run_with_config_path;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use pretty_assertions::assert_eq;
+use std::time::Duration;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use crate::common::{mount_docx, mount_gemini_generate, mount_npalist, mount_stages, read_mocks, render_config};
+
+fn config_with_report(base: &str, temp_dir: &assert_fs::TempDir, extra_run: &str) -> tempfile::NamedTempFile {
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+    let report = temp_dir.child("report.json");
+
+    let cfg_file = render_config(
+        base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("summarization_timeout_secs: 3", "summarization_timeout_secs: 60")
+        .replace(
+            "run:\n",
+            &format!("run:\n  report_path: {}\n{}", report.path().to_str().unwrap(), extra_run),
+        );
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+    cfg_file
+}
+
+fn read_report(temp_dir: &assert_fs::TempDir) -> serde_json::Value {
+    let data = std::fs::read_to_string(temp_dir.child("report.json").path()).expect("run report must be written");
+    serde_json::from_str(&data).unwrap()
+}
+
+/// Тест проверяет, что отчет запуска пишется после обычного завершения
+#[tokio::test]
+#[serial]
+async fn test_report_written_after_completed_run() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &read_mocks()).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let cfg_file = config_with_report(&base, &temp_dir, "");
+
+    run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let report = read_report(&temp_dir);
+    assert_eq!(report["interrupted"], false);
+    assert_eq!(report["received"], 1);
+    assert_eq!(report["published_items"], serde_json::json!(["160532"]));
+    assert_eq!(report["in_progress"], serde_json::Value::Null);
+    assert!(report["finished_at"].is_string());
+}
+
+/// Тест проверяет, что проект, опубликованный в два канала (console и file), считается одной
+/// публикацией: в отчете и в лимите run.max_posts_per_run: 1
+#[tokio::test]
+#[serial]
+async fn test_report_counts_project_published_to_two_channels_once() {
+    use luminis::models::channel::PublisherChannel;
+    use luminis::services::cache_manager_impl::FileSystemCacheManager;
+    use luminis::traits::cache_manager::CacheManager;
+
+    let server = MockServer::start().await;
+    let base = server.uri();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &read_mocks()).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let cfg_file = config_with_report(&base, &temp_dir, "");
+    let cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("console_enabled: false", "console_enabled: true");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let outcome = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    assert_eq!(outcome, luminis::models::types::RunOutcome::Published(1));
+    let report = read_report(&temp_dir);
+    assert_eq!(report["published_projects"], 1);
+    assert_eq!(report["published_items"], serde_json::json!(["160532"]));
+
+    let cache_manager = FileSystemCacheManager::builder()
+        .cache_dir(temp_dir.child("cache").path().to_str().unwrap().to_string())
+        .build();
+    let meta = cache_manager.load_metadata("160532").await.unwrap().unwrap();
+    assert_eq!(meta.published_channels.contains(&PublisherChannel::Console), true);
+    assert_eq!(meta.published_channels.contains(&PublisherChannel::File), true);
+}
+
+/// Тест проверяет, что при завершении посреди обработки (watchdog запрашивает shutdown)
+/// частичный отчет все равно записывается и содержит прерванный элемент
+#[tokio::test]
+#[serial]
+async fn test_partial_report_written_on_shutdown() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &read_mocks()).await;
+    mount_docx(&server).await;
+    // Медленный LLM: элемент не успевает опубликоваться до завершения
+    Mock::given(method("POST"))
+        .and(path("/v1beta/models/gemini-2.0-flash:generateContent"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(60)))
+        .mount(&server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let cfg_file = config_with_report(&base, &temp_dir, "  max_duration_secs: 2\n");
+
+    tokio::time::timeout(
+        Duration::from_secs(20),
+        run_with_config_path(cfg_file.path().to_str().unwrap(), None),
+    )
+    .await
+    .expect("run must be stopped by shutdown")
+    .unwrap();
+
+    let report = read_report(&temp_dir);
+    assert_eq!(report["interrupted"], true);
+    assert_eq!(report["received"], 1);
+    assert_eq!(report["published_items"], serde_json::json!([]));
+    assert!(
+        report["in_progress"].as_str().unwrap_or_default().contains("https://regulation.gov.ru/projects/160532"),
+        "report must name the interrupted item: {}",
+        report
+    );
+}
+
+/// Тест проверяет, что запуск без новых элементов возвращает RunOutcome::NothingNew
+/// с кодом выхода --once NOTHING_NEW_EXIT_CODE, а запуск с публикацией — код 0
+#[tokio::test]
+#[serial]
+async fn test_run_outcome_nothing_new_exit_code() {
+    use luminis::models::types::{NOTHING_NEW_EXIT_CODE, RunOutcome};
+
+    let server = MockServer::start().await;
+    let base = server.uri();
+    Mock::given(method("GET"))
+        .and(wiremock::matchers::path_regex(r"/api/npalist/"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"<projects offset="0" limit="50" sort="desc" total="0"></projects>"#,
+        ))
+        .mount(&server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let cfg_file = config_with_report(&base, &temp_dir, "");
+
+    let outcome = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    assert_eq!(outcome, RunOutcome::NothingNew);
+    assert_eq!(outcome.exit_code(), NOTHING_NEW_EXIT_CODE);
+    assert_eq!(RunOutcome::from_published(1).exit_code(), 0);
+    assert_eq!(read_report(&temp_dir)["published_projects"], 0);
+}
@@ -0,0 +1,146 @@
+use luminis::publishers::slack::{SlackPublisher, escape_mrkdwn};
+use luminis::publishers::utils::HttpRetryPolicy;
+use luminis::run_with_config_path;
+use luminis::traits::publisher::Publisher;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use pretty_assertions::assert_eq;
+use reqwest::Client;
+use std::fs;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use crate::common::{mount_docx, mount_gemini_generate, mount_npalist, mount_stages, read_mocks, render_config};
+
+/// Тест проверяет, что пост публикуется в Slack webhook: JSON содержит суммаризацию и URL проекта,
+/// а с blocks_template — section с кнопкой-ссылкой
+#[tokio::test]
+#[serial]
+async fn test_slack_webhook_body_contains_summary_and_url() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+    Mock::given(method("POST"))
+        .and(path("/slack/webhook"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .mount(&server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        false, // file_enabled
+        true,  // npalist_enabled
+    );
+    let mut cfg_text = fs::read_to_string(cfg_file.path()).unwrap();
+    cfg_text.push_str(&format!(
+        concat!(
+            "slack:\n  webhook_url: {}/slack/webhook\n  enabled: true\n  blocks_template: |\n",
+            "    [{{\"type\": \"section\", \"text\": {{\"type\": \"mrkdwn\", \"text\": {{{{ text | json_encode() }}}}}},\n",
+            "      \"accessory\": {{\"type\": \"button\", \"text\": {{\"type\": \"plain_text\", \"text\": \"Открыть\"}}, \"url\": {{{{ url | json_encode() }}}}}}}}]\n",
+        ),
+        base
+    ));
+    fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    let slack_request = requests
+        .iter()
+        .find(|r| r.url.path() == "/slack/webhook")
+        .expect("slack webhook must be called");
+    let body: serde_json::Value = serde_json::from_slice(&slack_request.body).unwrap();
+    let text = body["text"].as_str().unwrap();
+    assert!(text.contains("https://regulation.gov.ru/projects/160532"), "text must contain URL: {}", text);
+    assert!(text.contains("Поправки в закон об ОМС"), "text must contain summary: {}", text);
+    assert_eq!(body["blocks"][0]["text"]["text"].as_str(), Some(text));
+    assert_eq!(body["blocks"][0]["accessory"]["url"], "https://regulation.gov.ru/projects/160532");
+}
+
+/// Тест проверяет, что после 429 запрос повторяется не раньше Retry-After
+#[tokio::test]
+async fn test_slack_waits_retry_after_on_429() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "1"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .mount(&server)
+        .await;
+
+    let publisher = SlackPublisher::builder()
+        .client(Client::new())
+        .webhook_url(format!("{}/hook", server.uri()))
+        .retry(HttpRetryPolicy { max_attempts: 2, base_delay: std::time::Duration::from_millis(10), multiplier: 1.0 })
+        .build();
+    let started = std::time::Instant::now();
+    publisher.publish("", "https://example.org", "текст").await.unwrap();
+
+    assert!(started.elapsed() >= std::time::Duration::from_secs(1), "Retry-After must be respected");
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    let body: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+    assert_eq!(body, serde_json::json!({ "text": "текст" }));
+}
+
+/// Тест проверяет, что без run.publish_retry (одна попытка) ответ 429 с Retry-After
+/// все равно повторяется один раз
+#[tokio::test]
+async fn test_slack_retries_429_once_by_default() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "1"))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .mount(&server)
+        .await;
+
+    let publisher = SlackPublisher::builder()
+        .client(Client::new())
+        .webhook_url(format!("{}/hook", server.uri()))
+        .build();
+    publisher.publish("", "https://example.org", "текст").await.unwrap();
+
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+}
+
+/// Тест проверяет экранирование mrkdwn: &, < и > не становятся разметкой Slack,
+/// а экранированный текст укладывается в max_chars
+#[test]
+fn test_slack_text_is_escaped_for_mrkdwn() {
+    assert_eq!(escape_mrkdwn("a < b && c > d"), "a &lt; b &amp;&amp; c &gt; d");
+
+    let publisher = SlackPublisher::builder()
+        .client(Client::new())
+        .webhook_url("http://localhost".to_string())
+        .max_chars(20)
+        .build();
+    let body = publisher.payload("", "https://example.org", "<!channel> R&D отдел").unwrap();
+    let text = body["text"].as_str().unwrap();
+    assert!(text.starts_with("&lt;!channel") && !text.contains('<'), "unexpected text: {}", text);
+    assert!(text.chars().count() <= 20, "text longer than max_chars: {}", text);
+}
@@ -0,0 +1,169 @@
+use luminis::models::types::content_hash;
+use luminis::run_with_config_path;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use std::fs;
+use std::path::PathBuf;
+use wiremock::MockServer;
+
+mod common;
+
+use crate::common::{mount_docx, mount_npalist, mount_stages, read_mocks, render_config_with_channels};
+
+/// Тест проверяет, что при неизменном документе и смене стадии публикуется
+/// короткий пост-обновление с кэшированной суммаризацией, без обращения к LLM
+#[tokio::test]
+#[serial]
+async fn test_stage_change_publishes_update_with_cached_summary() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    // Gemini намеренно не мокается: суммаризация должна браться из кэша
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    // Проект 160532 уже опубликован в файл на стадии "Оценка", документ тот же
+    let docx = fs::read(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/mocks/source.docx")).unwrap();
+    let metadata = serde_json::json!({
+        "project_id": "160532",
+        "docx_path": "",
+        "markdown_path": "",
+        "published_channels": ["File"],
+        "created_at": "2025-09-20T00:00:00+00:00",
+        "channel_summaries": { "File": "Кэшированная суммаризация" },
+        "channel_posts": { "File": "Старый пост" },
+        "crawl_metadata": [ { "Stage": "Оценка" } ],
+        "document_hash": content_hash(&docx),
+    });
+    cache.child("160532").create_dir_all().unwrap();
+    cache
+        .child("160532/metadata.json")
+        .write_str(&serde_json::to_string_pretty(&metadata).unwrap())
+        .unwrap();
+
+    let cfg_file = render_config_with_channels(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+    );
+    let mut cfg_text = fs::read_to_string(cfg_file.path()).unwrap();
+    cfg_text.push_str("templates:\n  update_post: \"Новая стадия: {{ stage }}\\n{{ url }}\\n{{ summary }}\"\n");
+    fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(
+        "Новая стадия: Текст\nhttps://regulation.gov.ru/projects/160532\nКэшированная суммаризация\n",
+    );
+
+    // Новая стадия сохранена в кэш, основной пост канала не перезаписан
+    let saved: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(cache.child("160532/metadata.json").path()).unwrap()).unwrap();
+    assert_eq!(saved["crawl_metadata"].to_string().contains("Текст"), true);
+    assert_eq!(saved["channel_posts"]["File"], "Старый пост");
+
+    let received_requests = server.received_requests().await.unwrap();
+    let llm_calls = received_requests
+        .iter()
+        .filter(|req| req.url.path().contains("generateContent"))
+        .count();
+    assert_eq!(llm_calls, 0, "LLM must not be called for a stage update");
+    output_file.assert(predicate::str::contains("Старый пост").not());
+}
+
+/// Тест проверяет crawler.head_before_get: HEAD возвращает тот же ETag, документ не скачивается,
+/// кэшированный markdown остается, а пост-обновление публикуется
+#[tokio::test]
+#[serial]
+async fn test_head_unchanged_skips_document_download() {
+    use wiremock::matchers::{method, path_regex};
+    use wiremock::{Mock, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    Mock::given(method("HEAD"))
+        .and(path_regex(r"/api/public/Files/GetFile"))
+        .respond_with(ResponseTemplate::new(200).insert_header("etag", "\"v1\""))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path_regex(r"/api/public/Files/GetFile"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    // Проект 160532 опубликован на стадии "Оценка"; при скачивании документ имел ETag "v1"
+    let metadata = serde_json::json!({
+        "project_id": "160532",
+        "docx_path": "",
+        "markdown_path": "",
+        "published_channels": ["File"],
+        "created_at": "2025-09-20T00:00:00+00:00",
+        "channel_summaries": { "File": "Кэшированная суммаризация" },
+        "channel_posts": { "File": "Старый пост" },
+        "crawl_metadata": [ { "Stage": "Оценка" } ],
+        "document_hash": "cached-hash",
+        "document_validators": { "etag": "\"v1\"", "last_modified": null, "content_length": null },
+    });
+    cache.child("160532").create_dir_all().unwrap();
+    cache
+        .child("160532/metadata.json")
+        .write_str(&serde_json::to_string_pretty(&metadata).unwrap())
+        .unwrap();
+    cache.child("160532/extracted.md").write_str("Кэшированный markdown").unwrap();
+
+    let cfg_file = render_config_with_channels(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+    );
+    let mut cfg_text = fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("crawler:\n", "crawler:\n  head_before_get: true\n");
+    cfg_text.push_str("templates:\n  update_post: \"Новая стадия: {{ stage }}\\n{{ url }}\\n{{ summary }}\"\n");
+    fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(
+        "Новая стадия: Текст\nhttps://regulation.gov.ru/projects/160532\nКэшированная суммаризация\n",
+    );
+    cache.child("160532/extracted.md").assert("Кэшированный markdown");
+
+    let saved: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(cache.child("160532/metadata.json").path()).unwrap()).unwrap();
+    assert_eq!(saved["crawl_metadata"].to_string().contains("Текст"), true);
+    assert_eq!(saved["document_validators"]["etag"], "\"v1\"");
+
+    server.verify().await;
+}
@@ -0,0 +1,122 @@
+use luminis::run_with_config_path;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use wiremock::matchers::{method, path_regex};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use crate::common::{mount_docx, mount_npalist, mount_stages, read_mocks, render_config, render_config_with_custom_limits};
+
+/// Тест проверяет, что при недоступном LLM и on_unavailable: fallback_template
+/// публикуется пост по метаданным без суммаризации
+#[tokio::test]
+#[serial]
+async fn test_dead_llm_publishes_fallback_template_post() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    // Gemini не мокается: любой вызов LLM завершается ошибкой
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let mut cfg_text = std::fs::read_to_string(cfg_file.path()).unwrap();
+    cfg_text.push_str(
+        "summarizer:\n  on_unavailable: fallback_template\ntemplates:\n  no_summary_post: \"[без суммаризации] {{ title }}\\n{{ url }}\\nОтв: {{ responsible }}\"\n",
+    );
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(
+        "[без суммаризации] О внесении изменений в Федеральный закон «Об обязательном медицинском страховании в Российской Федерации»\n\
+https://regulation.gov.ru/projects/160532\n\
+Отв: Филиппов Олег Анатольевич\n",
+    );
+    output_file.assert(predicate::str::contains("Рейтинг").not());
+}
+
+/// Тест проверяет, что при Gemini, всегда отвечающем 500, и summarizer.fallback: truncate_source
+/// в файл публикуется начало исходного текста, обрезанное по лимиту канала, а сам отрывок
+/// не кэшируется как суммаризация канала
+#[tokio::test]
+#[serial]
+async fn test_failing_llm_publishes_truncated_source() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    Mock::given(method("POST"))
+        .and(path_regex(r"generateContent$"))
+        .respond_with(ResponseTemplate::new(500).set_body_string("internal error"))
+        .mount(&server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config_with_custom_limits(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        4096,  // telegram_max_chars
+        495,   // mastodon_max_chars
+        10000, // console_max_chars
+        300,   // file_max_chars
+    );
+    let mut cfg_text = std::fs::read_to_string(cfg_file.path()).unwrap();
+    cfg_text.push_str("summarizer:\n  fallback: truncate_source\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    assert!(requests.iter().any(|r| r.url.path().ends_with("generateContent")), "LLM must be called");
+
+    // Пост собран из начала извлеченного текста документа
+    let markdown = std::fs::read_to_string(cache.child("160532/extracted.md").path()).unwrap();
+    let first_words = markdown.split_whitespace().take(5).collect::<Vec<_>>().join(" ");
+    output_file.assert(predicate::str::contains("https://regulation.gov.ru/projects/160532"));
+    output_file.assert(predicate::str::contains(first_words));
+    output_file.assert(predicate::str::contains("Поправки в закон об ОМС").not());
+    let post = std::fs::read_to_string(output_file.path()).unwrap();
+    assert!(post.chars().count() < markdown.chars().count(), "excerpt must be truncated to the channel limit");
+
+    let metadata: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(cache.child("160532/metadata.json").path()).unwrap(),
+    )
+    .unwrap();
+    assert!(
+        metadata["channel_summaries"].as_object().map_or(true, |m| m.is_empty()),
+        "source excerpt must not be cached as a channel summary"
+    );
+}
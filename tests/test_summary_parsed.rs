@@ -0,0 +1,93 @@
+use luminis::models::types::{SummaryParsed, SummaryRatings};
+use luminis::run_with_config_path;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use wiremock::MockServer;
+
+mod common;
+
+use crate::common::{mount_docx, mount_gemini_generate, mount_npalist, mount_stages, read_mocks, render_config};
+
+/// Текст ответа Gemini из фикстуры mount_gemini_generate
+fn gemini_fixture_text() -> String {
+    let body = std::fs::read_to_string(
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/resources/mocks/body-v1beta-models-gemini-2.0-flash_generateContent-8OOhY.json"),
+    )
+    .unwrap();
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    json["candidates"][0]["content"]["parts"][0]["text"].as_str().unwrap().to_string()
+}
+
+/// Тест проверяет разбор блока «Рейтинг» из ответа Gemini: оценки извлекаются,
+/// а текст суммаризации остается без заголовка и строк оценок
+#[test]
+fn test_summary_parsed_from_gemini_fixture() {
+    let parsed = SummaryParsed::parse(&gemini_fixture_text());
+
+    assert_eq!(
+        parsed.ratings,
+        SummaryRatings { usefulness: Some(5), repressiveness: Some(2), corruption: Some(6) }
+    );
+    assert_eq!(parsed.summary.starts_with("Поправки в закон об ОМС"), true);
+    assert_eq!(parsed.summary.ends_with("Финансирование мед.помощи в новых регионах."), true);
+    assert_eq!(parsed.summary.contains("Рейтинг"), false);
+
+    let without_rating = SummaryParsed::parse("Только текст");
+    assert_eq!(without_rating, SummaryParsed { summary: "Только текст".to_string(), ..Default::default() });
+}
+
+/// Тест проверяет, что оценки (без копии текста) сохраняются в metadata.json (summary_ratings) и доступны
+/// шаблону поста как переменные usefulness, repressiveness, corruption и summary_body
+#[tokio::test]
+#[serial]
+async fn test_summary_ratings_in_metadata_and_template() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path()).unwrap()
+        + concat!(
+            "channels:\n  file:\n    post_template: |\n",
+            "      rating={{ usefulness }}/{{ repressiveness }}/{{ corruption }}\n",
+            "      body={{ summary_body }}\n",
+        );
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    output_file.assert(predicate::str::contains("rating=5/2/6"));
+    output_file.assert(predicate::str::contains("Рейтинг").not());
+
+    let metadata: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(cache.child("160532/metadata.json").path()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        metadata["summary_ratings"]["File"],
+        serde_json::json!({ "usefulness": 5, "repressiveness": 2, "corruption": 6 })
+    );
+}
@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use luminis::models::types::{CrawlItem, synthetic_project_id};
+use luminis::services::cache_manager_impl::FileSystemCacheManager;
+use luminis::services::settings::load_config;
+use luminis::services::summarizer::Summarizer;
+use luminis::services::worker::Worker;
+use luminis::traits::cache_manager::CacheManager;
+use luminis::traits::chat_api::ChatApi;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+
+mod common;
+
+use crate::common::render_config;
+
+struct FixedChatApi;
+
+#[async_trait]
+impl ChatApi for FixedChatApi {
+    async fn call_chat_api(&self, _prompt: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok("Суммаризация новости без идентификатора".to_string())
+    }
+}
+
+/// Тест проверяет, что RSS-элемент без project_id публикуется под синтетическим id
+/// при run.require_project_id: false
+#[tokio::test]
+async fn test_item_without_project_id_published_under_synthetic_id() {
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        "http://127.0.0.1:9",
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path()).unwrap()
+        .replace("run:\n", "run:\n  require_project_id: false\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+    let cfg = load_config(cfg_file.path()).unwrap();
+
+    let summarizer = Arc::new(
+        Summarizer::builder()
+            .chat_api(Arc::new(FixedChatApi))
+            .hard_max_chars(600)
+            .sample_percent(1.0)
+            .max_retry_attempts(0)
+            .retry_delay_secs(0)
+            .build()
+            .with_config(&cfg),
+    );
+    let cache_manager = Arc::new(
+        FileSystemCacheManager::builder()
+            .cache_dir(cache.path().to_str().unwrap().to_string())
+            .build(),
+    );
+    let worker = Worker::builder()
+        .config(cfg)
+        .summarizer(summarizer)
+        .cache_manager(cache_manager.clone())
+        .build()
+        .await
+        .unwrap();
+
+    let url = "https://regulation.gov.ru/news/without-id";
+    let item = CrawlItem {
+        title: "Новость без идентификатора".to_string(),
+        url: url.to_string(),
+        body: "Текст новости из RSS".to_string(),
+        project_id: None,
+        metadata: vec![],
+        source_label: None,
+        source_id: None,
+    };
+
+    let published = worker.process_item(item).await.unwrap();
+
+    assert_eq!(published, 1);
+    output_file.assert(predicate::str::contains(url));
+    output_file.assert(predicate::str::contains("Суммаризация новости без идентификатора"));
+
+    let synthetic_id = synthetic_project_id(url);
+    cache.child(format!("{}/metadata.json", synthetic_id)).assert(predicate::path::is_file());
+    assert_eq!(
+        cache_manager.is_published_in_channel(&synthetic_id, luminis::models::channel::PublisherChannel::File).await.unwrap(),
+        true
+    );
+}
+
+/// Тест проверяет, что пост элемента несет метку своего источника ({{ source_label }}):
+/// элементы двух источников с разными метками публикуются каждый со своей
+#[tokio::test]
+async fn test_posts_carry_source_label() {
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        "http://127.0.0.1:9",
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path()).unwrap()
+        .replace("run:\n", "run:\n  require_project_id: false\n")
+        .replace("  post_template: |\n    {{ url }}\n", "  post_template: |\n    {{ source_label }} {{ url }}\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+    let cfg = load_config(cfg_file.path()).unwrap();
+
+    let summarizer = Arc::new(
+        Summarizer::builder()
+            .chat_api(Arc::new(FixedChatApi))
+            .hard_max_chars(600)
+            .sample_percent(1.0)
+            .max_retry_attempts(0)
+            .retry_delay_secs(0)
+            .build()
+            .with_config(&cfg),
+    );
+    let cache_manager = Arc::new(
+        FileSystemCacheManager::builder()
+            .cache_dir(cache.path().to_str().unwrap().to_string())
+            .build(),
+    );
+    let worker = Worker::builder()
+        .config(cfg)
+        .summarizer(summarizer)
+        .cache_manager(cache_manager)
+        .build()
+        .await
+        .unwrap();
+
+    for (label, url) in [
+        ("[Минздрав]", "https://minzdrav.example.org/news/1"),
+        ("[Минфин]", "https://minfin.example.org/news/2"),
+    ] {
+        let item = CrawlItem {
+            title: format!("Новость {}", label),
+            url: url.to_string(),
+            body: "Текст новости".to_string(),
+            project_id: None,
+            metadata: vec![],
+            source_label: Some(label.to_string()),
+            source_id: None,
+        };
+        assert_eq!(worker.process_item(item).await.unwrap(), 1);
+
+        // Файл перезаписывается каждым постом: проверяем пост сразу после публикации
+        output_file.assert(predicate::str::contains(format!("{} {}", label, url)));
+    }
+}
@@ -0,0 +1,138 @@
+use luminis::run_with_config_path;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use std::time::{Duration, Instant};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use crate::common::{mount_docx, mount_gemini_generate, mount_npalist, mount_stages, read_mocks, render_config};
+
+/// Тест проверяет, что run.max_duration_secs завершает зависший запуск:
+/// LLM отвечает дольше лимита, но процесс выходит в пределах заданного времени
+#[tokio::test]
+#[serial]
+async fn test_max_duration_stops_stuck_run() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    // Медленный LLM: ни один элемент не успевает опубликоваться
+    Mock::given(method("POST"))
+        .and(path("/v1beta/models/gemini-2.0-flash:generateContent"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(60)))
+        .mount(&server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("summarization_timeout_secs: 3", "summarization_timeout_secs: 60")
+        .replace("run:\n", "run:\n  max_duration_secs: 2\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let started = Instant::now();
+    let result = tokio::time::timeout(
+        Duration::from_secs(20),
+        run_with_config_path(cfg_file.path().to_str().unwrap(), None),
+    )
+    .await
+    .expect("run must be stopped by watchdog");
+    result.unwrap();
+
+    let elapsed = started.elapsed();
+    assert!(elapsed >= Duration::from_secs(2), "run finished too early: {:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(10), "watchdog did not stop the run in time: {:?}", elapsed);
+    output_file.assert(predicates::path::missing());
+}
+
+/// Тест проверяет, что run.item_timeout_secs ограничивает обработку одного элемента целиком:
+/// скачивание документа первого элемента зависает, таймаут записывается как попытка (элемент
+/// повторится в следующем цикле), а следующий элемент публикуется. После второго таймаута
+/// (item_timeout_max_attempts: 2) элемент записывается как пропущенный
+#[tokio::test]
+#[serial]
+async fn test_item_timeout_skips_hung_item_and_continues() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    // Для 160532 stages указывают на отдельный файл, скачивание которого зависает
+    Mock::given(method("GET"))
+        .and(path("/api/public/PublicProjects/GetProjectStages/160532"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            stages_json.replace("b3d99703-8b7a-4f72-bc39-c144792e97fa", "hung-file"),
+        ))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/api/public/Files/GetFile"))
+        .and(wiremock::matchers::query_param("fileId", "hung-file"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(60)))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        true,  // file_enabled
+        true,  // npalist_enabled
+    );
+    let cfg_text = std::fs::read_to_string(cfg_file.path())
+        .unwrap()
+        .replace("run:\n", "run:\n  item_timeout_secs: 2\n  item_timeout_max_attempts: 2\n");
+    std::fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    for attempt in 1..=2 {
+        let result = tokio::time::timeout(
+            Duration::from_secs(30),
+            run_with_config_path(cfg_file.path().to_str().unwrap(), None),
+        )
+        .await
+        .expect("hung item must not block the run");
+        result.unwrap();
+
+        output_file.assert(predicates::str::contains("https://regulation.gov.ru/projects/160531"));
+        let meta: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(cache.child("160532/metadata.json").path()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(meta["timeout_attempts"], attempt);
+        if attempt == 1 {
+            assert!(meta["skip_reason"].is_null(), "first timeout must leave the item retryable");
+            continue;
+        }
+        assert!(meta["skip_reason"].as_str().unwrap_or("").starts_with("item timeout"));
+    }
+}
@@ -0,0 +1,81 @@
+use luminis::publishers::webhook::sign_body;
+use luminis::run_with_config_path;
+use serial_test::serial;
+use assert_fs::prelude::*;
+use pretty_assertions::assert_eq;
+use std::fs;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use crate::common::{mount_docx, mount_gemini_generate, mount_npalist, mount_stages, read_mocks, render_config};
+
+/// Тест проверяет, что webhook получает JSON-событие с суммаризацией и метаданными,
+/// дополнительные заголовки и подпись HMAC-SHA256 тела запроса
+#[tokio::test]
+#[serial]
+async fn test_webhook_event_is_signed_and_structured() {
+    let server = MockServer::start().await;
+    let base = server.uri();
+    let stages_json = read_mocks();
+
+    mount_npalist(&server).await;
+    mount_stages(&server, &stages_json).await;
+    mount_docx(&server).await;
+    mount_gemini_generate(&server).await;
+    Mock::given(method("POST"))
+        .and(path("/hooks/luminis"))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg_file = render_config(
+        &base,
+        output_file.path().to_str().unwrap(),
+        cache.path().to_str().unwrap(),
+        false, // mastodon_enabled
+        false, // telegram_enabled
+        false, // console_enabled
+        false, // file_enabled
+        true,  // npalist_enabled
+    );
+    let mut cfg_text = fs::read_to_string(cfg_file.path()).unwrap();
+    cfg_text.push_str(&format!(
+        concat!(
+            "webhook:\n  url: {}/hooks/luminis\n  enabled: true\n  secret: s3cr3t\n",
+            "  headers:\n    Authorization: \"Bearer token\"\n",
+        ),
+        base
+    ));
+    fs::write(cfg_file.path(), cfg_text).unwrap();
+
+    let _ = run_with_config_path(cfg_file.path().to_str().unwrap(), None)
+        .await
+        .unwrap();
+
+    let requests = server.received_requests().await.unwrap();
+    let hook = requests
+        .iter()
+        .find(|r| r.url.path() == "/hooks/luminis")
+        .expect("webhook must be called");
+    assert_eq!(hook.headers.get("authorization").unwrap(), "Bearer token");
+    assert_eq!(hook.headers.get("content-type").unwrap(), "application/json");
+    assert_eq!(
+        hook.headers.get("x-luminis-signature").unwrap().to_str().unwrap(),
+        sign_body("s3cr3t", &hook.body)
+    );
+
+    let body: serde_json::Value = serde_json::from_slice(&hook.body).unwrap();
+    assert_eq!(body["project_id"], "160532");
+    assert_eq!(body["url"], "https://regulation.gov.ru/projects/160532");
+    assert_eq!(body["channel"], "webhook");
+    assert!(body["summary"].as_str().unwrap().contains("Поправки в закон об ОМС"), "summary: {}", body["summary"]);
+    assert_eq!(body["metadata"]["department"], "Минздрав России");
+    assert!(body["published_at"].as_str().is_some_and(|s| chrono::DateTime::parse_from_rfc3339(s).is_ok()));
+    assert!(body.get("post").is_none(), "rendered post must not be sent");
+}
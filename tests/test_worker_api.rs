@@ -0,0 +1,223 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use luminis::models::config::AppConfig;
+use luminis::models::types::CrawlItem;
+use luminis::models::config::{FileFormat, LineEnding};
+use luminis::publishers::{FilePublisher, MastodonPublisher, SlackPublisher};
+use luminis::services::cache_manager_impl::FileSystemCacheManager;
+use luminis::services::summarizer::Summarizer;
+use luminis::services::worker::Worker;
+use luminis::traits::chat_api::ChatApi;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+mod common;
+
+use crate::common::mount_mastodon;
+
+struct FixedChatApi;
+
+#[async_trait]
+impl ChatApi for FixedChatApi {
+    async fn call_chat_api(&self, _prompt: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok("Суммаризация встроенного запуска".to_string())
+    }
+}
+
+fn item(n: u32) -> CrawlItem {
+    CrawlItem {
+        title: format!("Новость {}", n),
+        url: format!("https://example.org/news/{}", n),
+        body: format!("Текст новости {}", n),
+        project_id: None,
+        metadata: vec![],
+        source_label: None,
+        source_id: None,
+    }
+}
+
+/// Суммаризатор с FixedChatApi и параметрами из `cfg`
+fn summarizer(cfg: &AppConfig) -> Arc<Summarizer> {
+    Arc::new(
+        Summarizer::builder()
+            .chat_api(Arc::new(FixedChatApi))
+            .hard_max_chars(600)
+            .sample_percent(1.0)
+            .max_retry_attempts(0)
+            .retry_delay_secs(0)
+            .build()
+            .with_config(cfg),
+    )
+}
+
+/// Минимальный AppConfig с каналом File (`file_path`), кэшем в `cache` и дополнительными секциями `extra`
+fn config(file_path: &std::path::Path, cache: &std::path::Path, extra: &str) -> AppConfig {
+    serde_yaml::from_str(&format!(
+        concat!(
+            "llm:\n  model: test\n",
+            "crawler:\n  interval_seconds: 1\n",
+            "{}",
+            "output:\n  console_enabled: false\n  file_enabled: true\n  file_path: {}\n  file_append: true\n",
+            "run:\n  require_project_id: false\n  processing_delay_secs: 0\n  cache_dir: {}\n",
+            "  post_template: \"{{{{ url }}}} {{{{ summary }}}}\"\n",
+        ),
+        extra,
+        file_path.display(),
+        cache.display(),
+    ))
+    .unwrap()
+}
+
+fn cache_manager(cache: &std::path::Path) -> Arc<FileSystemCacheManager> {
+    Arc::new(
+        FileSystemCacheManager::builder()
+            .cache_dir(cache.to_str().unwrap().to_string())
+            .build(),
+    )
+}
+
+/// Worker только с каналом File
+async fn file_worker(file_path: &std::path::Path, cache: &std::path::Path) -> Worker {
+    let cfg = config(file_path, cache, "");
+    Worker::builder()
+        .summarizer(summarizer(&cfg))
+        .config(cfg)
+        .cache_manager(cache_manager(cache))
+        .build()
+        .await
+        .unwrap()
+}
+
+/// Тест проверяет публичный API Worker: worker собирается напрямую из минимального AppConfig
+/// с готовым публикатором Mastodon (без токена в конфигурации) и обрабатывает элементы,
+/// переданные вручную, без краулера
+#[tokio::test]
+async fn test_worker_processes_hand_built_items() {
+    let server = MockServer::start().await;
+    mount_mastodon(&server).await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg = config(
+        output_file.path(),
+        cache.path(),
+        "mastodon:\n  base_url: http://mastodon.invalid\n  access_token: \"\"\n  enabled: true\n",
+    );
+    let mastodon = Arc::new(
+        MastodonPublisher::builder()
+            .client(reqwest::Client::new())
+            .base_url(server.uri())
+            .access_token("token".to_string())
+            .build(),
+    );
+    let worker = Worker::builder()
+        .summarizer(summarizer(&cfg))
+        .config(cfg)
+        .cache_manager(cache_manager(cache.path()))
+        .mastodon_publisher(mastodon)
+        .build()
+        .await
+        .unwrap();
+
+    assert_eq!(worker.process_item(item(1)).await.unwrap(), 1);
+    assert_eq!(worker.process_items([item(2), item(3)]).await.unwrap(), 2);
+    // Повторная обработка опубликованного элемента ничего не публикует
+    assert_eq!(worker.process_item(item(1)).await.unwrap(), 0);
+
+    for n in 1..=3 {
+        output_file.assert(predicate::str::contains(format!("https://example.org/news/{} Суммаризация встроенного запуска", n)));
+    }
+    let statuses = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|r| r.url.path() == "/api/v1/statuses")
+        .count();
+    assert_eq!(statuses, 3);
+}
+
+/// Тест проверяет, что неудачная публикация не считается опубликованной и не отмечает канал:
+/// путь file_path — каталог, запись в него невозможна
+#[tokio::test]
+async fn test_failed_publish_is_not_counted() {
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let cache = temp_dir.child("cache");
+    let worker = file_worker(temp_dir.path(), cache.path()).await;
+
+    assert_eq!(worker.process_item(item(1)).await.unwrap(), 0);
+    assert_eq!(worker.process_item(item(1)).await.unwrap(), 0);
+}
+
+/// Тест проверяет, что элемент, уже опубликованный во всех каналах, повторно не публикуется
+#[tokio::test]
+async fn test_already_published_item_is_skipped() {
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let output_file = temp_dir.child("output.txt");
+    let cache = temp_dir.child("cache");
+    let worker = file_worker(output_file.path(), cache.path()).await;
+
+    assert_eq!(worker.process_item(item(1)).await.unwrap(), 1);
+    assert_eq!(worker.process_item(item(1)).await.unwrap(), 0);
+
+    let posts = std::fs::read_to_string(output_file.path()).unwrap();
+    assert_eq!(posts.matches("https://example.org/news/1").count(), 1);
+}
+
+/// Тест проверяет, что готовые публикаторы Slack и File из builder используются вместо
+/// созданных по конфигурации: webhook_url и file_path из конфигурации не используются
+#[tokio::test]
+async fn test_worker_uses_injected_publishers() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/slack/hook"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let temp_dir = assert_fs::TempDir::new().unwrap();
+    let config_file = temp_dir.child("config_output.txt");
+    let injected_file = temp_dir.child("injected_output.txt");
+    let cache = temp_dir.child("cache");
+
+    let cfg = config(
+        config_file.path(),
+        cache.path(),
+        "slack:\n  webhook_url: http://slack.invalid/hook\n  enabled: true\n",
+    );
+    let slack = Arc::new(
+        SlackPublisher::builder()
+            .client(reqwest::Client::new())
+            .webhook_url(format!("{}/slack/hook", server.uri()))
+            .build(),
+    );
+    let file = Arc::new(FilePublisher {
+        path: injected_file.path().to_str().unwrap().to_string(),
+        max_chars: None,
+        append: false,
+        format: FileFormat::Text,
+        bom: false,
+        line_ending: LineEnding::Lf,
+    });
+    let worker = Worker::builder()
+        .summarizer(summarizer(&cfg))
+        .config(cfg)
+        .cache_manager(cache_manager(cache.path()))
+        .slack_publisher(slack)
+        .file_publisher(file)
+        .build()
+        .await
+        .unwrap();
+
+    assert_eq!(worker.process_item(item(1)).await.unwrap(), 1);
+
+    injected_file.assert(predicate::str::contains("https://example.org/news/1 Суммаризация встроенного запуска"));
+    config_file.assert(predicate::path::missing());
+}